@@ -0,0 +1,21 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use sharing::{Parameters, RabinInformationDispersal, ShamirSecretSharing, ShamirShare, Sharing};
+
+fuzz_target!(|input: (Parameters, Vec<ShamirShare>)| {
+    let (parameters, shares) = input;
+    let shamir = ShamirSecretSharing::new(parameters.n(), parameters.k(), rand::thread_rng());
+    let _ = shamir.reconstruct(shares.clone());
+
+    let rabin = RabinInformationDispersal::new(parameters.n(), parameters.k());
+    let _ = rabin.reconstruct(
+        shares
+            .into_iter()
+            .map(|s| sharing::RabinShare {
+                id: s.id,
+                length: s.body.len(),
+                body: s.body,
+            })
+            .collect(),
+    );
+});
@@ -0,0 +1,192 @@
+//! Share Archives
+//!
+//! Packs everything a custodian holds into a single tar archive: for every shared item
+//! its [`Manifest`] and the custodian's share in the [`ShareBytes`] encoding,
+//! stored as `<name>/manifest` and `<name>/share`.
+//! [`unpack`] checks every share against its manifest on the way back.
+//!
+//! ```rust
+//! use sharing::{archive, manifest::Manifest, share::ShamirShare};
+//! use sharing::{Parameters, Scheme, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let parameters = Parameters::new(3, 2).unwrap();
+//! let sets: Vec<_> = ["database", "signing-key"]
+//!     .iter()
+//!     .map(|name| {
+//!         let shares = sharer.share(name.as_bytes().to_vec()).unwrap();
+//!         let manifest = Manifest::new(Scheme::Shamir, parameters, &shares);
+//!         archive::Set { name: name.to_string(), manifest, shares }
+//!     })
+//!     .collect();
+//!
+//! // one archive per custodian, holding their share of every set
+//! let archives = archive::pack_custodians(&sets).unwrap();
+//! assert_eq!(archives.len(), 3);
+//!
+//! let first: Vec<archive::Entry<ShamirShare>> = archive::unpack(&archives[0].1[..]).unwrap();
+//! let third: Vec<archive::Entry<ShamirShare>> = archive::unpack(&archives[2].1[..]).unwrap();
+//! assert_eq!(first[1].name, "signing-key");
+//! let shares = vec![first[1].share.clone(), third[1].share.clone()];
+//! assert_eq!(sharer.reconstruct(shares).unwrap(), b"signing-key");
+//! ```
+use crate::{
+    error::SharingError,
+    manifest::Manifest,
+    share::{decode_error, ShareBytes, ShareId},
+};
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+};
+
+/// One shared item in a custodian's archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<S> {
+    pub name: String,
+    pub manifest: Manifest,
+    pub share: S,
+}
+
+/// A complete share set, to be split into one archive per custodian.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Set<S> {
+    pub name: String,
+    pub manifest: Manifest,
+    pub shares: Vec<S>,
+}
+
+/// Writes the entries as a tar archive into `out`.
+///
+/// Names must be non-empty and must not contain `/` or `\`, or be `.` or `..`.
+pub fn pack<S: ShareBytes, W: Write>(entries: &[Entry<S>], out: W) -> io::Result<W> {
+    let mut builder = tar::Builder::new(out);
+    for entry in entries {
+        if !is_valid_name(&entry.name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid entry name",
+            ));
+        }
+        append(
+            &mut builder,
+            &format!("{}/manifest", entry.name),
+            entry.manifest.to_string().as_bytes(),
+        )?;
+        append(
+            &mut builder,
+            &format!("{}/share", entry.name),
+            &entry.share.to_bytes(),
+        )?;
+    }
+    builder.into_inner()
+}
+
+/// Packs one archive per share id, each holding that custodian's share of every set.
+///
+/// Returns the id and archive bytes of every custodian, ordered by id.
+pub fn pack_custodians<S: ShareBytes + ShareId + Clone>(
+    sets: &[Set<S>],
+) -> io::Result<Vec<(u8, Vec<u8>)>> {
+    let mut custodians: BTreeMap<u8, Vec<Entry<S>>> = BTreeMap::new();
+    for set in sets {
+        for share in &set.shares {
+            custodians.entry(share.id()).or_default().push(Entry {
+                name: set.name.clone(),
+                manifest: set.manifest.clone(),
+                share: share.clone(),
+            });
+        }
+    }
+    custodians
+        .into_iter()
+        .map(|(id, entries)| Ok((id, pack(&entries, Vec::new())?)))
+        .collect()
+}
+
+/// Reads an archive written by [`pack`], in archive order.
+///
+/// Fails if an entry is missing its manifest or share,
+/// or with [`SharingError::CorruptShare`] if the share does not match the manifest.
+pub fn unpack<S: ShareBytes + ShareId, R: Read>(input: R) -> Result<Vec<Entry<S>>, SharingError> {
+    let mut files: Vec<Files> = Vec::new();
+    let mut archive = tar::Archive::new(input);
+    for file in archive.entries()? {
+        let mut file = file?;
+        let path = file.path()?.to_string_lossy().into_owned();
+        let mut parts = path.splitn(2, '/');
+        let (name, kind) = match (parts.next(), parts.next()) {
+            (Some(name), Some(kind)) if is_valid_name(name) => (name.to_string(), kind.to_string()),
+            _ => return Err(malformed()),
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let index = match files.iter().position(|f| f.name == name) {
+            Some(index) => index,
+            None => {
+                files.push(Files {
+                    name,
+                    manifest: None,
+                    share: None,
+                });
+                files.len() - 1
+            }
+        };
+        let slot = match kind.as_str() {
+            "manifest" => &mut files[index].manifest,
+            "share" => &mut files[index].share,
+            _ => return Err(malformed()),
+        };
+        if slot.replace(bytes).is_some() {
+            return Err(malformed());
+        }
+    }
+    files
+        .into_iter()
+        .map(
+            |Files {
+                 name,
+                 manifest,
+                 share,
+             }| {
+                let manifest: Manifest = String::from_utf8(manifest.ok_or_else(malformed)?)
+                    .map_err(|_| malformed())?
+                    .parse()
+                    .map_err(|_| SharingError::Malformed("manifest"))?;
+                let share = share.ok_or_else(malformed)?;
+                let share = S::from_bytes(&share).ok_or_else(|| decode_error(&share))?;
+                if !manifest.verify_share(&share) {
+                    return Err(SharingError::CorruptShare(share.id()));
+                }
+                Ok(Entry {
+                    name,
+                    manifest,
+                    share,
+                })
+            },
+        )
+        .collect()
+}
+
+/// The raw files of one entry while reading an archive.
+struct Files {
+    name: String,
+    manifest: Option<Vec<u8>>,
+    share: Option<Vec<u8>>,
+}
+
+fn append<W: Write>(builder: &mut tar::Builder<W>, path: &str, data: &[u8]) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o600);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+fn malformed() -> SharingError {
+    SharingError::Malformed("share archive")
+}
@@ -0,0 +1,187 @@
+//! ASCII Armor
+//!
+//! A PEM-like text envelope around a share in the [wire format](crate::wire), to paste into
+//! an email or a password manager. Headers say what the share is at a glance:
+//!
+//! ```text
+//! -----BEGIN SHAMIR SHARE-----
+//! Scheme: shamir
+//! Id: 3
+//! Threshold: 2
+//! Label: share 3 of 5, given to Alice
+//!
+//! pQNTAgMAAAAAAAAAAsr+AAAAHHNoYXJlIDMgb2YgNSwgZ2l2ZW4gdG8gQWxpY2X/
+//! /////////wovgSg=
+//! -----END SHAMIR SHARE-----
+//! ```
+//!
+//! The body is the base64 of the wire encoding, which is what counts: the `Scheme`, `Id` and
+//! `Threshold` headers must agree with it, the `Label` is the one in the share's [`Metadata`]
+//! and is not checked, other headers are ignored. So is text around the envelope.
+//!
+//! Available with the `armor` feature.
+//!
+//! [`Metadata`]: crate::metadata::Metadata
+//!
+//! ```rust
+//! use sharing::{armor::{self, Armor}, metadata::Metadata, wire::WireShare};
+//! use sharing::{ShamirSecretSharing, ShamirShare, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares: Vec<String> = sharer.share(b"secret".to_vec()).unwrap()
+//!     .into_iter()
+//!     .map(|share| {
+//!         let label = format!("share {} of 3", share.id);
+//!         WireShare::new(2, share).with_metadata(Metadata::new().with_label(&label)).to_armor()
+//!     })
+//!     .collect();
+//! assert!(shares[0].starts_with("-----BEGIN SHAMIR SHARE-----\nScheme: shamir\nId: 1\n"));
+//!
+//! let share = WireShare::<ShamirShare>::from_armor(&shares[1]).unwrap();
+//! assert_eq!(share.metadata.label.as_deref(), Some("share 2 of 3"));
+//!
+//! // or any scheme, for reconstruction
+//! let bytes: Vec<Vec<u8>> = shares.iter().map(|text| armor::decode(text).unwrap()).collect();
+//! assert_eq!(sharing::reconstruct(&bytes[1..]).unwrap(), b"secret");
+//! ```
+use crate::{
+    error::SharingError,
+    share::ShareBytes,
+    wire::{self, WireScheme, WireShare},
+    Scheme,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Characters of base64 per line of the body, as in PEM.
+const WIDTH: usize = 64;
+
+/// Shares with an armored text form.
+pub trait Armor: Sized {
+    fn to_armor(&self) -> String;
+
+    /// Fails with [`SharingError::Malformed`] if there is no envelope or the headers do not
+    /// match the share, and with [`SharingError::MismatchedParameters`] for another scheme.
+    fn from_armor(text: &str) -> Result<Self, SharingError>;
+}
+
+impl<S: WireScheme> Armor for WireShare<S> {
+    fn to_armor(&self) -> String {
+        let label = label(S::SCHEME);
+        let mut text = format!("-----BEGIN {}-----\n", label);
+        text.push_str(&format!("Scheme: {}\n", S::SCHEME));
+        text.push_str(&format!("Id: {}\n", self.share.id()));
+        text.push_str(&format!("Threshold: {}\n", self.k));
+        if let Some(name) = &self.metadata.label {
+            // a header is a single line
+            text.push_str(&format!("Label: {}\n", name.replace(['\r', '\n'], " ")));
+        }
+        text.push('\n');
+        for line in STANDARD.encode(self.to_bytes()).as_bytes().chunks(WIDTH) {
+            text.push_str(&String::from_utf8_lossy(line));
+            text.push('\n');
+        }
+        text.push_str(&format!("-----END {}-----\n", label));
+        text
+    }
+
+    fn from_armor(text: &str) -> Result<Self, SharingError> {
+        Self::decode(&decode(text)?)
+    }
+}
+
+/// The wire encoding of an armored share of any scheme, checked against its headers.
+pub fn decode(text: &str) -> Result<Vec<u8>, SharingError> {
+    const MALFORMED: SharingError = SharingError::Malformed("armored share");
+    let mut lines = text.lines().map(str::trim);
+    let scheme = lines
+        .by_ref()
+        .find_map(|line| {
+            let label = line.strip_prefix("-----BEGIN ")?.strip_suffix("-----")?;
+            [Scheme::Shamir, Scheme::Rabin, Scheme::Krawczyk]
+                .iter()
+                .copied()
+                .find(|scheme| self::label(*scheme) == label)
+        })
+        .ok_or(MALFORMED)?;
+    let end = format!("-----END {}-----", label(scheme));
+    let mut headers = Vec::new();
+    let mut body = String::new();
+    let mut closed = false;
+    for line in lines {
+        if line == end {
+            closed = true;
+            break;
+        }
+        match line.split_once(':') {
+            Some((key, value)) if body.is_empty() => headers.push((key.trim(), value.trim())),
+            _ => body.extend(line.split_whitespace()),
+        }
+    }
+    if !closed {
+        return Err(MALFORMED);
+    }
+    let bytes = STANDARD.decode(body).map_err(|_| MALFORMED)?;
+    let header = wire::header(&bytes)?;
+    if header.scheme != scheme {
+        return Err(SharingError::MismatchedParameters);
+    }
+    for (key, value) in headers {
+        let matches = match key.to_ascii_lowercase().as_str() {
+            "scheme" => value.eq_ignore_ascii_case(scheme.name()),
+            "id" => value.parse() == Ok(header.id),
+            "threshold" => value.parse() == Ok(header.k),
+            _ => true,
+        };
+        if !matches {
+            return Err(SharingError::Malformed("armor header"));
+        }
+    }
+    Ok(bytes)
+}
+
+fn label(scheme: Scheme) -> &'static str {
+    match scheme {
+        Scheme::Shamir => "SHAMIR SHARE",
+        Scheme::Rabin => "RABIN SHARE",
+        Scheme::Krawczyk => "KRAWCZYK SHARE",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{metadata::Metadata, RabinShare, ShamirShare};
+
+    #[test]
+    fn headers_and_envelope() {
+        let share = WireShare::new(
+            2,
+            ShamirShare {
+                id: 3,
+                body: vec![0xca, 0xfe],
+            },
+        )
+        .with_metadata(Metadata::new().with_label("share 3 of 5, given to Alice"));
+        let text = share.to_armor();
+        assert!(text.contains("\n/////////wovgSg=\n"));
+        assert_eq!(WireShare::from_armor(&text).unwrap(), share);
+
+        let wrapped = format!(
+            "Forwarded message:\n\n  {}\n-- \nBob",
+            text.replace('\n', "\n  ")
+        );
+        assert_eq!(WireShare::from_armor(&wrapped).unwrap(), share);
+
+        let edited = text.replace("Label: share 3", "Label: share 4");
+        assert_eq!(WireShare::from_armor(&edited).unwrap(), share);
+        for lie in ["Id: 4", "Threshold: 3", "Scheme: rabin"].iter() {
+            let key = lie.split(':').next().unwrap();
+            let line = text.lines().find(|l| l.starts_with(key)).unwrap();
+            assert!(WireShare::<ShamirShare>::from_armor(&text.replace(line, lie)).is_err());
+        }
+        assert!(WireShare::<RabinShare>::from_armor(&text).is_err());
+        assert!(WireShare::<ShamirShare>::from_armor(&text.replace("SHAMIR", "RABIN")).is_err());
+        let truncated: String = text.lines().take(7).collect::<Vec<_>>().join("\n");
+        assert!(WireShare::<ShamirShare>::from_armor(&truncated).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Async Adapters
+//!
+//! Data arriving as a [`Stream`] of chunks is shared chunk by chunk with [`share_streams`],
+//! which gives every holder a stream of their [`Indexed`] shares to send on its own connection.
+//! On the receiving side a [`Combine`] sink accepts the shares in any order, reconstructs
+//! every chunk once `k` of its shares arrived and forwards the data in order to another sink.
+//!
+//! ```rust
+//! use futures::{executor::block_on, stream, SinkExt, StreamExt};
+//! use sharing::{asynchronous::{share_streams, Combine}, ShamirSecretSharing, SharingError};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let chunks = stream::iter(vec![b"first".to_vec(), b"second".to_vec()]);
+//! let mut holders = share_streams(sharer, 3, chunks);
+//!
+//! let data = block_on(async {
+//!     let third: Vec<_> = holders.pop().unwrap().collect().await;
+//!     let first: Vec<_> = holders.remove(0).collect().await;
+//!
+//!     let combiner = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//!     let mut combine = Combine::new(combiner, 2, Vec::new());
+//!     for share in third.into_iter().chain(first) {
+//!         combine.send(share?).await?;
+//!     }
+//!     combine.close().await?;
+//!     Ok::<_, SharingError>(combine.into_inner())
+//! })
+//! .unwrap();
+//! assert_eq!(data, [b"first".to_vec(), b"second".to_vec()]);
+//! ```
+use crate::{error::SharingError, share::ShareId, Sharing};
+use futures::{
+    ready,
+    sink::Sink,
+    stream::Stream,
+    task::{Context, Poll, Waker},
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// A share of the chunk at `index` of the input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Indexed<S> {
+    pub index: u64,
+    pub share: S,
+}
+
+/// Shares every chunk of `input` into `n` shares, one stream per holder.
+///
+/// The streams share one buffer, so the input is only read once: a chunk is kept until
+/// every stream has taken its share, and a stream that is never polled buffers all of them.
+/// A chunk that does not share into `n` shares ends every stream with an error.
+pub fn share_streams<St, T>(sharer: T, n: u8, input: St) -> Vec<ShareStream<St, T>>
+where
+    St: Stream<Item = Vec<u8>> + Unpin,
+    T: Sharing,
+{
+    let state = Arc::new(Mutex::new(Split {
+        input,
+        sharer,
+        next: 0,
+        queues: (0..n).map(|_| VecDeque::new()).collect(),
+        wakers: vec![None; n as usize],
+        done: false,
+    }));
+    (0..n as usize)
+        .map(|holder| ShareStream {
+            state: state.clone(),
+            holder,
+        })
+        .collect()
+}
+
+struct Split<St, T: Sharing> {
+    input: St,
+    sharer: T,
+    next: u64,
+    queues: Vec<VecDeque<Result<Indexed<T::Share>, SharingError>>>,
+    wakers: Vec<Option<Waker>>,
+    done: bool,
+}
+
+impl<St, T: Sharing> Split<St, T> {
+    fn distribute(&mut self, data: Vec<u8>) {
+        let index = self.next;
+        self.next += 1;
+        match self.sharer.share(data).ok() {
+            Some(shares) if shares.len() == self.queues.len() => {
+                for (queue, share) in self.queues.iter_mut().zip(shares) {
+                    queue.push_back(Ok(Indexed { index, share }));
+                }
+            }
+            _ => {
+                for queue in &mut self.queues {
+                    queue.push_back(Err(SharingError::MismatchedParameters));
+                }
+                self.done = true;
+            }
+        }
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        for waker in self.wakers.iter_mut().filter_map(Option::take) {
+            waker.wake();
+        }
+    }
+}
+
+/// The shares of one holder, created by [`share_streams`].
+pub struct ShareStream<St, T: Sharing> {
+    state: Arc<Mutex<Split<St, T>>>,
+    holder: usize,
+}
+
+impl<St, T: Sharing> ShareStream<St, T> {
+    /// The position of this holder's shares in the output of [`Sharing::share`].
+    pub fn holder(&self) -> usize {
+        self.holder
+    }
+}
+
+impl<St, T> Stream for ShareStream<St, T>
+where
+    St: Stream<Item = Vec<u8>> + Unpin,
+    T: Sharing,
+{
+    type Item = Result<Indexed<T::Share>, SharingError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut guard = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = &mut *guard;
+        loop {
+            if let Some(item) = state.queues[self.holder].pop_front() {
+                return Poll::Ready(Some(item));
+            }
+            if state.done {
+                return Poll::Ready(None);
+            }
+            match Pin::new(&mut state.input).poll_next(cx) {
+                Poll::Ready(Some(data)) => state.distribute(data),
+                Poll::Ready(None) => {
+                    state.done = true;
+                    state.wake();
+                }
+                Poll::Pending => {
+                    state.wakers[self.holder] = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+/// Reconstructs [`Indexed`] shares chunk by chunk and sends the data to `Si` in order.
+///
+/// Shares of chunks that were already reconstructed and repeated ids are ignored.
+/// Closing fails if a chunk is still missing shares.
+pub struct Combine<T: Sharing, Si> {
+    sharer: T,
+    k: usize,
+    sink: Si,
+    next: u64,
+    pending: BTreeMap<u64, Vec<T::Share>>,
+    ready: VecDeque<Vec<u8>>,
+}
+
+impl<T: Sharing, Si> Combine<T, Si> {
+    /// Reconstructs every chunk with `sharer` once `k` of its shares arrived.
+    pub fn new(sharer: T, k: u8, sink: Si) -> Self {
+        Self {
+            sharer,
+            k: k as usize,
+            sink,
+            next: 0,
+            pending: BTreeMap::new(),
+            ready: VecDeque::new(),
+        }
+    }
+
+    /// The number of chunks reconstructed so far.
+    pub fn completed(&self) -> u64 {
+        self.next
+    }
+
+    pub fn get_ref(&self) -> &Si {
+        &self.sink
+    }
+
+    pub fn into_inner(self) -> Si {
+        self.sink
+    }
+}
+
+impl<T, Si> Combine<T, Si>
+where
+    T: Sharing,
+    Si: Sink<Vec<u8>> + Unpin,
+    SharingError: From<Si::Error>,
+{
+    fn drain(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), SharingError>> {
+        while !self.ready.is_empty() {
+            ready!(Pin::new(&mut self.sink).poll_ready(cx))?;
+            if let Some(data) = self.ready.pop_front() {
+                Pin::new(&mut self.sink).start_send(data)?;
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T, Si> Sink<Indexed<T::Share>> for Combine<T, Si>
+where
+    T: Sharing + Unpin,
+    T::Share: ShareId,
+    Si: Sink<Vec<u8>> + Unpin,
+    SharingError: From<Si::Error>,
+{
+    type Error = SharingError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().drain(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Indexed<T::Share>) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        if item.index < this.next {
+            return Ok(());
+        }
+        let shares = this.pending.entry(item.index).or_default();
+        if shares.iter().all(|share| share.id() != item.share.id()) {
+            shares.push(item.share);
+        }
+        while this
+            .pending
+            .get(&this.next)
+            .is_some_and(|shares| shares.len() >= this.k)
+        {
+            let shares = this.pending.remove(&this.next).unwrap_or_default();
+            let data = this.sharer.reconstruct(shares)?;
+            this.ready.push_back(data);
+            this.next += 1;
+        }
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        ready!(this.drain(cx))?;
+        Pin::new(&mut this.sink).poll_flush(cx).map_err(Into::into)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        ready!(this.drain(cx))?;
+        if !this.pending.is_empty() {
+            let found = this.pending.get(&this.next).map_or(0, Vec::len);
+            return Poll::Ready(Err(SharingError::InsufficientShares {
+                needed: this.k,
+                found,
+            }));
+        }
+        Pin::new(&mut this.sink).poll_close(cx).map_err(Into::into)
+    }
+}
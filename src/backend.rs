@@ -0,0 +1,323 @@
+//! GF(256) Backends
+//!
+//! The bulk kernel of sharing and reconstruction, adding a multiple of one slice to another,
+//! can run on several implementations of the field multiplication:
+//!
+//! - [`Backend::Simd`]: 16 entry product tables for both nibbles of every byte, looked up
+//!   in registers 32 bytes at a time with AVX2 or 16 with SSSE3 (`pshufb` on x86-64),
+//! - [`Backend::Table`]: log and exp table lookups in memory, a byte at a time,
+//! - [`Backend::Clmul`]: the CPU's carry-less multiply (PCLMULQDQ on x86-64),
+//!   with Barrett reduction instead of secret dependent table lookups,
+//! - [`Backend::Portable`]: shift and add without tables or branches on the data.
+//!
+//! [`Backend::detect`], also the [`Default`], picks the fastest one the running CPU supports,
+//! so a single binary uses the vector instructions where they are available and the tables
+//! elsewhere. It is used by every bulk path: Shamir sharing and reconstruction, Rabin
+//! dispersal and decoding and so Krawczyk, which is built from both.
+//!
+//! Only the tables make memory accesses that depend on the data. The carry-less and portable
+//! kernels avoid them too but work a byte at a time, they are for callers who want no such
+//! accesses on CPUs without the vector instructions.
+//!
+//! ```rust
+//! use sharing::{backend::Backend, math::Field};
+//!
+//! let field = Field::default();
+//! let src: Vec<u8> = (0..40).map(|i| i * 3).collect();
+//! for backend in [Backend::detect(), Backend::Simd, Backend::Table, Backend::Clmul, Backend::Portable] {
+//!     let mut dst = vec![0u8; 40];
+//!     backend.mul_add(&field, 0x53, &src, &mut dst);
+//!     assert_eq!(dst[39], field.mul(0x53, src[39]));
+//! }
+//! ```
+use crate::math::Field;
+
+/// The bytes of every coefficient the bulk loops hand to the kernels at once,
+/// so a block of all `k` of them stays in the cache.
+#[cfg(any(feature = "shamir", feature = "rabin"))]
+pub(crate) const STRIPE: usize = 4096;
+
+/// An implementation of GF(256) multiplication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Simd,
+    Table,
+    Clmul,
+    Portable,
+}
+
+impl Backend {
+    /// The fastest backend this CPU supports: [`Simd`](Backend::Simd) if it has the
+    /// vector instructions, [`Table`](Backend::Table) otherwise.
+    pub fn detect() -> Self {
+        if Backend::Simd.is_available() {
+            Backend::Simd
+        } else {
+            Backend::Table
+        }
+    }
+
+    /// Whether the CPU supports the backend,
+    /// only [`Simd`](Backend::Simd) and [`Clmul`](Backend::Clmul) may be missing.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Backend::Table | Backend::Portable => true,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Simd => std::is_x86_feature_detected!("ssse3"),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Clmul => std::is_x86_feature_detected!("pclmulqdq"),
+            #[cfg(not(target_arch = "x86_64"))]
+            Backend::Simd | Backend::Clmul => false,
+        }
+    }
+
+    /// `dst[i] += c · src[i]` for every `i` of the shorter slice.
+    ///
+    /// An unavailable backend falls back to [`Portable`](Backend::Portable).
+    pub fn mul_add(&self, field: &Field, c: u8, src: &[u8], dst: &mut [u8]) {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Backend::Simd if self.is_available() => {
+                let len = src.len().min(dst.len());
+                let tables = simd::Tables::new(field.polynomial() as u8, c);
+                if std::is_x86_feature_detected!("avx2") {
+                    // SAFETY: the CPU supports avx2
+                    unsafe { simd::mul_add_avx2(&tables, &src[..len], &mut dst[..len]) }
+                } else {
+                    // SAFETY: the CPU supports ssse3
+                    unsafe { simd::mul_add_ssse3(&tables, &src[..len], &mut dst[..len]) }
+                }
+            }
+            Backend::Table => {
+                for (d, s) in dst.iter_mut().zip(src) {
+                    *d ^= field.mul(c, *s);
+                }
+            }
+            #[cfg(target_arch = "x86_64")]
+            Backend::Clmul if self.is_available() => {
+                // SAFETY: the CPU supports pclmulqdq
+                unsafe { clmul::mul_add(field.polynomial(), c, src, dst) }
+            }
+            Backend::Simd | Backend::Clmul | Backend::Portable => {
+                let reduction = field.polynomial() as u8;
+                for (d, s) in dst.iter_mut().zip(src) {
+                    *d ^= mul_portable(reduction, c, *s);
+                }
+            }
+        }
+    }
+
+    /// Adds the polynomials with coefficients `columns[0] + columns[1]·x + …`, every column
+    /// `dst.len()` bytes long and holding one coefficient of each polynomial, evaluated at `x`.
+    #[cfg(any(feature = "shamir", feature = "rabin"))]
+    pub(crate) fn evaluate_add(&self, field: &Field, columns: &[u8], x: u8, dst: &mut [u8]) {
+        let mut power = 1;
+        for column in columns.chunks(dst.len().max(1)) {
+            self.mul_add(field, power, column, dst);
+            power = field.mul(power, x);
+        }
+    }
+}
+
+impl Default for Backend {
+    /// See [`detect`](Backend::detect).
+    fn default() -> Self {
+        Backend::detect()
+    }
+}
+
+/// Shift and add multiplication, `reduction` being the polynomial without `x⁸`.
+fn mul_portable(reduction: u8, mut a: u8, mut b: u8) -> u8 {
+    let mut res = 0;
+    for _ in 0..8 {
+        res ^= a & (b & 1).wrapping_neg();
+        a = (a << 1) ^ (reduction & (a >> 7).wrapping_neg());
+        b >>= 1;
+    }
+    res
+}
+
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use super::mul_portable;
+    use std::arch::x86_64::{
+        __m128i, __m256i, _mm256_and_si256, _mm256_broadcastsi128_si256, _mm256_loadu_si256,
+        _mm256_set1_epi8, _mm256_shuffle_epi8, _mm256_srli_epi64, _mm256_storeu_si256,
+        _mm256_xor_si256, _mm_and_si128, _mm_loadu_si128, _mm_set1_epi8, _mm_shuffle_epi8,
+        _mm_srli_epi64, _mm_storeu_si128, _mm_xor_si128,
+    };
+
+    /// The products of `c` with every low nibble and every high nibble.
+    pub(super) struct Tables {
+        low: [u8; 16],
+        high: [u8; 16],
+    }
+
+    impl Tables {
+        pub(super) fn new(reduction: u8, c: u8) -> Self {
+            let mut tables = Tables {
+                low: [0; 16],
+                high: [0; 16],
+            };
+            for i in 0..16u8 {
+                tables.low[i as usize] = mul_portable(reduction, c, i);
+                tables.high[i as usize] = mul_portable(reduction, c, i << 4);
+            }
+            tables
+        }
+    }
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn mul_16(low: __m128i, high: __m128i, src: __m128i) -> __m128i {
+        let mask = _mm_set1_epi8(0x0f);
+        _mm_xor_si128(
+            _mm_shuffle_epi8(low, _mm_and_si128(src, mask)),
+            _mm_shuffle_epi8(high, _mm_and_si128(_mm_srli_epi64(src, 4), mask)),
+        )
+    }
+
+    /// `dst += c · src` for slices of the same length.
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn mul_add_ssse3(tables: &Tables, src: &[u8], dst: &mut [u8]) {
+        let low = _mm_loadu_si128(tables.low.as_ptr() as *const __m128i);
+        let high = _mm_loadu_si128(tables.high.as_ptr() as *const __m128i);
+        let mut chunks = dst.chunks_exact_mut(16);
+        for (d, s) in (&mut chunks).zip(src.chunks_exact(16)) {
+            let product = mul_16(low, high, _mm_loadu_si128(s.as_ptr() as *const __m128i));
+            let sum = _mm_xor_si128(_mm_loadu_si128(d.as_ptr() as *const __m128i), product);
+            _mm_storeu_si128(d.as_mut_ptr() as *mut __m128i, sum);
+        }
+        // the tail goes through the registers as well, so no lookup touches memory
+        let tail = chunks.into_remainder();
+        if !tail.is_empty() {
+            let mut buf = [0u8; 16];
+            buf[..tail.len()].copy_from_slice(&src[src.len() - tail.len()..]);
+            let product = mul_16(low, high, _mm_loadu_si128(buf.as_ptr() as *const __m128i));
+            _mm_storeu_si128(buf.as_mut_ptr() as *mut __m128i, product);
+            for (d, p) in tail.iter_mut().zip(&buf) {
+                *d ^= p;
+            }
+        }
+    }
+
+    /// `dst += c · src` for slices of the same length.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn mul_add_avx2(tables: &Tables, src: &[u8], dst: &mut [u8]) {
+        let low =
+            _mm256_broadcastsi128_si256(_mm_loadu_si128(tables.low.as_ptr() as *const __m128i));
+        let high =
+            _mm256_broadcastsi128_si256(_mm_loadu_si128(tables.high.as_ptr() as *const __m128i));
+        let mask = _mm256_set1_epi8(0x0f);
+        let mut chunks = dst.chunks_exact_mut(32);
+        for (d, s) in (&mut chunks).zip(src.chunks_exact(32)) {
+            let s = _mm256_loadu_si256(s.as_ptr() as *const __m256i);
+            let product = _mm256_xor_si256(
+                _mm256_shuffle_epi8(low, _mm256_and_si256(s, mask)),
+                _mm256_shuffle_epi8(high, _mm256_and_si256(_mm256_srli_epi64(s, 4), mask)),
+            );
+            let sum = _mm256_xor_si256(_mm256_loadu_si256(d.as_ptr() as *const __m256i), product);
+            _mm256_storeu_si256(d.as_mut_ptr() as *mut __m256i, sum);
+        }
+        let tail = chunks.into_remainder();
+        let start = src.len() - tail.len();
+        mul_add_ssse3(tables, &src[start..], tail);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod clmul {
+    use std::arch::x86_64::{_mm_clmulepi64_si128, _mm_cvtsi128_si64, _mm_cvtsi64_si128};
+
+    /// `x¹⁶ / polynomial`, the Barrett constant.
+    fn barrett(polynomial: u16) -> u64 {
+        let (mut remainder, mut quotient) = (1u32 << 16, 0u64);
+        for shift in (0..=8).rev() {
+            if remainder & (1 << (shift + 8)) != 0 {
+                remainder ^= (polynomial as u32) << shift;
+                quotient |= 1 << shift;
+            }
+        }
+        quotient
+    }
+
+    #[target_feature(enable = "pclmulqdq")]
+    unsafe fn clmul(a: u64, b: u64) -> u64 {
+        _mm_cvtsi128_si64(_mm_clmulepi64_si128(
+            _mm_cvtsi64_si128(a as i64),
+            _mm_cvtsi64_si128(b as i64),
+            0,
+        )) as u64
+    }
+
+    #[target_feature(enable = "pclmulqdq")]
+    pub(super) unsafe fn mul_add(polynomial: u16, c: u8, src: &[u8], dst: &mut [u8]) {
+        let mu = barrett(polynomial);
+        for (d, s) in dst.iter_mut().zip(src) {
+            let product = clmul(*s as u64, c as u64);
+            let quotient = clmul(product >> 8, mu) >> 8;
+            *d ^= (product ^ clmul(quotient, polynomial as u64)) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_backend_matches_the_tables() {
+        for polynomial in [Field::DEFAULT_POLYNOMIAL, Field::AES_POLYNOMIAL] {
+            let field = Field::new(polynomial).unwrap();
+            let src: Vec<u8> = (0..=255).collect();
+            for backend in [Backend::Simd, Backend::Clmul, Backend::Portable] {
+                for c in 0..=255 {
+                    // lengths with a partial vector at the end
+                    for len in [256, 63, 15] {
+                        let mut expected = vec![0x5a; len];
+                        Backend::Table.mul_add(&field, c, &src, &mut expected);
+                        let mut dst = vec![0x5a; len];
+                        backend.mul_add(&field, c, &src, &mut dst);
+                        assert_eq!(dst, expected, "{:?} {:#x} c = {}", backend, polynomial, c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "shamir", feature = "rabin"))]
+    fn every_backend_shares_alike() {
+        use crate::{RabinInformationDispersal, ShamirSecretSharing, Sharing};
+        use rand::rngs::mock::StepRng;
+
+        // more than one stripe, ending in a partial one
+        let data: Vec<u8> = (0..10_001u32).map(|i| (i * 7 % 251) as u8).collect();
+        let backends = [
+            Backend::Simd,
+            Backend::Table,
+            Backend::Clmul,
+            Backend::Portable,
+        ];
+        let shamir =
+            |backend| ShamirSecretSharing::new(5, 3, StepRng::new(3, 7)).with_backend(backend);
+        let rabin = |backend| RabinInformationDispersal::new(5, 3).with_backend(backend);
+        let expected = (
+            shamir(Backend::Table).share(data.clone()).unwrap(),
+            rabin(Backend::Table).share(data.clone()).unwrap(),
+        );
+        for backend in backends {
+            let shares = shamir(backend).share(data.clone()).unwrap();
+            assert_eq!(shares, expected.0, "{:?}", backend);
+            assert_eq!(
+                shamir(backend).reconstruct(shares[2..].to_vec()).unwrap(),
+                data
+            );
+            let shares = rabin(backend).share(data.clone()).unwrap();
+            assert_eq!(shares, expected.1, "{:?}", backend);
+            assert_eq!(
+                rabin(backend).reconstruct(shares[2..].to_vec()).unwrap(),
+                data
+            );
+        }
+    }
+}
@@ -0,0 +1,443 @@
+//! CBOR
+//!
+//! Deterministic CBOR ([RFC 8949] §4.2.1) for every share type, e.g. to carry shares in COSE
+//! or CWT based systems. A share is a tagged map with small integer keys:
+//!
+//! ```text
+//! 1936220530({0: type, 1: id, ...fields})
+//! ```
+//!
+//! | Key | Field | Value |
+//! |---|---|---|
+//! | 0 | type | 0 Shamir, 1 Rabin, 2 Krawczyk, 3 Krawczyk key, 4 Krawczyk data, 5 Krawczyk batch |
+//! | 1 | id | unsigned |
+//! | 2 | body | byte string |
+//! | 3 | length | unsigned |
+//! | 4 | key | byte string |
+//! | 5 | compression | unsigned: 0 none, 1 LZ4, 2 Zstandard |
+//! | 6 | associated data | byte string |
+//! | 7 | digest | byte string of 32 bytes |
+//! | 8 | files | array of maps `{2: body, 3: length, 7: digest}` |
+//!
+//! Every type writes all of its fields, in the order of their keys:
+//!
+//! | Type | Keys |
+//! |---|---|
+//! | [`ShamirShare`] | 0, 1, 2 |
+//! | [`RabinShare`] | 0, 1, 2, 3 |
+//! | [`KrawczykShare`] | 0, 1, 2, 3, 4, 5, 6, 7 |
+//! | [`KrawczykKeyShare`] | 0, 1, 4, 7 |
+//! | [`KrawczykDataShare`] | 0, 1, 2, 3, 5, 6 |
+//! | [`KrawczykBatchShare`] | 0, 1, 4, 5, 8 |
+//!
+//! This layout does not change between releases, new fields get new keys. Decoding is strict:
+//! only the canonical encoding of a share is accepted, so equal shares have equal bytes.
+//! The tag, `"shar"` as a big endian integer, is not registered with IANA.
+//!
+//! Available with the `cbor` feature.
+//!
+//! [RFC 8949]: https://www.rfc-editor.org/rfc/rfc8949
+//!
+//! ```rust
+//! use sharing::{cbor::Cbor, ShamirShare};
+//!
+//! let share = ShamirShare { id: 2, body: vec![0xca, 0xfe] };
+//! let bytes = share.to_cbor();
+//! assert_eq!(bytes, [0xda, 0x73, 0x68, 0x61, 0x72, 0xa3, 0, 0, 1, 2, 2, 0x42, 0xca, 0xfe]);
+//! assert_eq!(ShamirShare::from_cbor(&bytes).unwrap(), share);
+//! ```
+use crate::{
+    compression::Compression,
+    error::SharingError,
+    share::{
+        BatchFile, KrawczykBatchShare, KrawczykDataShare, KrawczykKeyShare, KrawczykShare,
+        RabinShare, ShamirShare,
+    },
+    strict::{Limits, StrictCheck},
+};
+use std::convert::TryFrom;
+
+/// The CBOR tag of every share.
+pub const TAG: u64 = 0x7368_6172;
+
+const TYPE: u64 = 0;
+const ID: u64 = 1;
+const BODY: u64 = 2;
+const LENGTH: u64 = 3;
+const KEY: u64 = 4;
+const COMPRESSION: u64 = 5;
+const ASSOCIATED_DATA: u64 = 6;
+const DIGEST: u64 = 7;
+const FILES: u64 = 8;
+
+const UNSIGNED: u8 = 0;
+const BYTES: u8 = 2;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAGGED: u8 = 6;
+
+/// Share types with a deterministic CBOR encoding.
+pub trait Cbor: Sized {
+    fn to_cbor(&self) -> Vec<u8>;
+
+    /// Fails with [`SharingError::Malformed`] on anything but the canonical encoding of a
+    /// share of this type.
+    fn from_cbor(bytes: &[u8]) -> Result<Self, SharingError>;
+    /// Decodes like [`from_cbor`](Cbor::from_cbor), then checks the share against `limits`.
+    fn from_cbor_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError>
+    where
+        Self: StrictCheck,
+    {
+        limits.size(bytes)?;
+        let share = Self::from_cbor(bytes)?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl Cbor for ShamirShare {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut w = Writer::share(0, self.id, 3);
+        w.field_bytes(BODY, &self.body);
+        w.0
+    }
+
+    fn from_cbor(bytes: &[u8]) -> Result<Self, SharingError> {
+        let mut r = Reader::share(bytes, 0, 3)?;
+        let share = ShamirShare {
+            id: r.id()?,
+            body: r.field_bytes(BODY)?,
+        };
+        r.finish(share)
+    }
+}
+
+impl Cbor for RabinShare {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut w = Writer::share(1, self.id, 4);
+        w.field_bytes(BODY, &self.body);
+        w.field_uint(LENGTH, self.length as u64);
+        w.0
+    }
+
+    fn from_cbor(bytes: &[u8]) -> Result<Self, SharingError> {
+        let mut r = Reader::share(bytes, 1, 4)?;
+        let share = RabinShare {
+            id: r.id()?,
+            body: r.field_bytes(BODY)?,
+            length: r.field_length()?,
+        };
+        r.finish(share)
+    }
+}
+
+impl Cbor for KrawczykShare {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut w = Writer::share(2, self.id, 8);
+        w.field_bytes(BODY, &self.body);
+        w.field_uint(LENGTH, self.length as u64);
+        w.field_bytes(KEY, &self.key);
+        w.field_uint(COMPRESSION, self.compression.to_byte().into());
+        w.field_bytes(ASSOCIATED_DATA, &self.associated_data);
+        w.field_bytes(DIGEST, &self.digest);
+        w.0
+    }
+
+    fn from_cbor(bytes: &[u8]) -> Result<Self, SharingError> {
+        let mut r = Reader::share(bytes, 2, 8)?;
+        let share = KrawczykShare {
+            id: r.id()?,
+            body: r.field_bytes(BODY)?,
+            length: r.field_length()?,
+            key: r.field_bytes(KEY)?,
+            compression: r.field_compression()?,
+            associated_data: r.field_bytes(ASSOCIATED_DATA)?,
+            digest: r.field_digest()?,
+        };
+        r.finish(share)
+    }
+}
+
+impl Cbor for KrawczykKeyShare {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut w = Writer::share(3, self.id, 4);
+        w.field_bytes(KEY, &self.key);
+        w.field_bytes(DIGEST, &self.digest);
+        w.0
+    }
+
+    fn from_cbor(bytes: &[u8]) -> Result<Self, SharingError> {
+        let mut r = Reader::share(bytes, 3, 4)?;
+        let share = KrawczykKeyShare {
+            id: r.id()?,
+            key: r.field_bytes(KEY)?,
+            digest: r.field_digest()?,
+        };
+        r.finish(share)
+    }
+}
+
+impl Cbor for KrawczykDataShare {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut w = Writer::share(4, self.id, 6);
+        w.field_bytes(BODY, &self.body);
+        w.field_uint(LENGTH, self.length as u64);
+        w.field_uint(COMPRESSION, self.compression.to_byte().into());
+        w.field_bytes(ASSOCIATED_DATA, &self.associated_data);
+        w.0
+    }
+
+    fn from_cbor(bytes: &[u8]) -> Result<Self, SharingError> {
+        let mut r = Reader::share(bytes, 4, 6)?;
+        let share = KrawczykDataShare {
+            id: r.id()?,
+            body: r.field_bytes(BODY)?,
+            length: r.field_length()?,
+            compression: r.field_compression()?,
+            associated_data: r.field_bytes(ASSOCIATED_DATA)?,
+        };
+        r.finish(share)
+    }
+}
+
+impl Cbor for KrawczykBatchShare {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut w = Writer::share(5, self.id, 5);
+        w.field_bytes(KEY, &self.key);
+        w.field_uint(COMPRESSION, self.compression.to_byte().into());
+        w.uint(FILES);
+        w.head(ARRAY, self.files.len() as u64);
+        for file in &self.files {
+            w.head(MAP, 3);
+            w.field_bytes(BODY, &file.body);
+            w.field_uint(LENGTH, file.length as u64);
+            w.field_bytes(DIGEST, &file.digest);
+        }
+        w.0
+    }
+
+    fn from_cbor(bytes: &[u8]) -> Result<Self, SharingError> {
+        let mut r = Reader::share(bytes, 5, 5)?;
+        let id = r.id()?;
+        let key = r.field_bytes(KEY)?;
+        let compression = r.field_compression()?;
+        r.key(FILES)?;
+        let count = r.expect(ARRAY)?;
+        let mut files = Vec::new();
+        for _ in 0..count {
+            if r.expect(MAP)? != 3 {
+                return Err(MALFORMED);
+            }
+            files.push(BatchFile {
+                body: r.field_bytes(BODY)?,
+                length: r.field_length()?,
+                digest: r.field_digest()?,
+            });
+        }
+        let share = KrawczykBatchShare {
+            id,
+            key,
+            compression,
+            files,
+        };
+        r.finish(share)
+    }
+}
+
+const MALFORMED: SharingError = SharingError::Malformed("cbor share");
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    /// The tag, the map header and the type and id fields.
+    fn share(kind: u64, id: u8, fields: u64) -> Self {
+        let mut w = Writer(Vec::new());
+        w.head(TAGGED, TAG);
+        w.head(MAP, fields);
+        w.field_uint(TYPE, kind);
+        w.field_uint(ID, id.into());
+        w
+    }
+
+    /// The shortest head for the value.
+    fn head(&mut self, major: u8, value: u64) {
+        let major = major << 5;
+        match value {
+            0..=23 => self.0.push(major | value as u8),
+            24..=0xff => self.0.extend_from_slice(&[major | 24, value as u8]),
+            0x100..=0xffff => {
+                self.0.push(major | 25);
+                self.0.extend_from_slice(&(value as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                self.0.push(major | 26);
+                self.0.extend_from_slice(&(value as u32).to_be_bytes());
+            }
+            _ => {
+                self.0.push(major | 27);
+                self.0.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+    }
+
+    fn uint(&mut self, value: u64) {
+        self.head(UNSIGNED, value);
+    }
+
+    fn field_uint(&mut self, key: u64, value: u64) {
+        self.uint(key);
+        self.uint(value);
+    }
+
+    fn field_bytes(&mut self, key: u64, bytes: &[u8]) {
+        self.uint(key);
+        self.head(BYTES, bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Checks the tag, the number of fields and the type.
+    fn share(bytes: &'a [u8], kind: u64, fields: u64) -> Result<Self, SharingError> {
+        let mut r = Reader(bytes);
+        if r.expect(TAGGED)? != TAG || r.expect(MAP)? != fields {
+            return Err(MALFORMED);
+        }
+        r.key(TYPE)?;
+        if r.expect(UNSIGNED)? != kind {
+            return Err(SharingError::Malformed("cbor share type"));
+        }
+        Ok(r)
+    }
+
+    fn finish<T>(self, share: T) -> Result<T, SharingError> {
+        if self.0.is_empty() {
+            Ok(share)
+        } else {
+            Err(MALFORMED)
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SharingError> {
+        if self.0.len() < n {
+            return Err(MALFORMED);
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    /// The value of the next head, which must have this major type and be the shortest.
+    fn expect(&mut self, major: u8) -> Result<u64, SharingError> {
+        let initial = *self.take(1)?.first().ok_or(MALFORMED)?;
+        if initial >> 5 != major {
+            return Err(MALFORMED);
+        }
+        let (value, min) = match initial & 0x1f {
+            info @ 0..=23 => return Ok(info.into()),
+            24 => (self.take(1)?[0].into(), 24),
+            25 => (
+                u16::from_be_bytes([self.take(1)?[0], self.take(1)?[0]]).into(),
+                0x100,
+            ),
+            26 => {
+                let mut buf = [0u8; 4];
+                buf.copy_from_slice(self.take(4)?);
+                (u32::from_be_bytes(buf).into(), 0x1_0000)
+            }
+            27 => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(self.take(8)?);
+                (u64::from_be_bytes(buf), 0x1_0000_0000)
+            }
+            _ => return Err(MALFORMED),
+        };
+        if value < min {
+            return Err(MALFORMED);
+        }
+        Ok(value)
+    }
+
+    fn key(&mut self, key: u64) -> Result<(), SharingError> {
+        if self.expect(UNSIGNED)? == key {
+            Ok(())
+        } else {
+            Err(MALFORMED)
+        }
+    }
+
+    fn id(&mut self) -> Result<u8, SharingError> {
+        self.key(ID)?;
+        u8::try_from(self.expect(UNSIGNED)?).map_err(|_| MALFORMED)
+    }
+
+    fn field_length(&mut self) -> Result<usize, SharingError> {
+        self.key(LENGTH)?;
+        usize::try_from(self.expect(UNSIGNED)?).map_err(|_| MALFORMED)
+    }
+
+    fn field_compression(&mut self) -> Result<Compression, SharingError> {
+        self.key(COMPRESSION)?;
+        u8::try_from(self.expect(UNSIGNED)?)
+            .ok()
+            .and_then(Compression::from_byte)
+            .ok_or(MALFORMED)
+    }
+
+    fn field_bytes(&mut self, key: u64) -> Result<Vec<u8>, SharingError> {
+        self.key(key)?;
+        let length = usize::try_from(self.expect(BYTES)?).map_err(|_| MALFORMED)?;
+        Ok(self.take(length)?.to_vec())
+    }
+
+    fn field_digest(&mut self) -> Result<[u8; 32], SharingError> {
+        let bytes = self.field_bytes(DIGEST)?;
+        <[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| MALFORMED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let share = RabinShare {
+            id: 1,
+            length: 300,
+            body: vec![7],
+        };
+        let bytes = share.to_cbor();
+        assert_eq!(
+            bytes[5..],
+            [0xa4, 0, 1, 1, 1, 2, 0x41, 7, 3, 0x19, 0x01, 0x2c]
+        );
+        assert_eq!(RabinShare::from_cbor(&bytes).unwrap(), share);
+
+        let batch = KrawczykBatchShare {
+            id: 4,
+            key: vec![1, 2],
+            compression: Compression::None,
+            files: vec![BatchFile {
+                length: 3,
+                digest: [9; 32],
+                body: vec![5; 30],
+            }],
+        };
+        assert_eq!(
+            KrawczykBatchShare::from_cbor(&batch.to_cbor()).unwrap(),
+            batch
+        );
+
+        // another type, a longer head than needed, trailing bytes
+        assert!(ShamirShare::from_cbor(&bytes).is_err());
+        let mut long = bytes.clone();
+        long.splice(13..14, [0x18, 0x03]);
+        assert!(RabinShare::from_cbor(&long).is_err());
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(RabinShare::from_cbor(&trailing).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Content Defined Chunking
+//!
+//! Splits data at content defined boundaries (FastCDC style, with a gear rolling hash and
+//! normalized chunking), so an insertion or deletion only changes the chunks around it.
+//! Every chunk is shared on its own, and sharing a new version of a file reuses the shares
+//! of all chunks that did not change. The chunk index can be stored in the
+//! [`Manifest`](crate::manifest::Manifest) with
+//! [`with_chunks`](crate::manifest::Manifest::with_chunks).
+//!
+//! The index holds the SHA-256 of every plaintext chunk, so only publish it where
+//! confirming guesses of a chunk's contents is acceptable.
+//!
+//! ```rust
+//! use sharing::{cdc::{self, Chunker}, manifest::Manifest, Parameters, Scheme};
+//! use sharing::{ShamirSecretSharing, ShamirShare};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let chunker = Chunker::new(256, 1024, 4096).unwrap();
+//! let mut data: Vec<u8> = (0..50_000u32)
+//!     .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+//!     .collect();
+//!
+//! let first = cdc::share(&sharer, &chunker, &data, None).unwrap();
+//! data.splice(20_000..20_000, b"inserted".iter().copied());
+//! let second = cdc::share(&sharer, &chunker, &data, Some(&first)).unwrap();
+//!
+//! // most chunks kept their shares
+//! let reused = second.index.iter().filter(|c| first.shares.contains_key(&c.digest)).count();
+//! assert!(reused + 3 >= second.index.len());
+//! assert_eq!(second.shares[&second.index[0].digest], first.shares[&first.index[0].digest]);
+//!
+//! let rec = cdc::reconstruct(&sharer, &second.index, &second.shares).unwrap();
+//! assert_eq!(rec, data);
+//!
+//! let parameters = Parameters::new(3, 2).unwrap();
+//! let manifest = Manifest::new(Scheme::Shamir, parameters, &[] as &[ShamirShare])
+//!     .with_chunks(&second.index);
+//! assert_eq!(manifest.to_string().parse::<Manifest>().unwrap().chunks(), &second.index[..]);
+//! ```
+use crate::Sharing;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// 256 pseudo random words for the gear hash, from SplitMix64.
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// The minimum, average and maximum chunk size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chunker {
+    min: usize,
+    avg: usize,
+    max: usize,
+}
+
+impl Default for Chunker {
+    /// 2 KiB, 8 KiB and 64 KiB.
+    fn default() -> Self {
+        Self {
+            min: 2 * 1024,
+            avg: 8 * 1024,
+            max: 64 * 1024,
+        }
+    }
+}
+
+impl Chunker {
+    /// `None` unless `0 < min <= avg <= max` and `avg` is a power of two of at least 4.
+    pub fn new(min: usize, avg: usize, max: usize) -> Option<Self> {
+        if min == 0 || min > avg || avg > max || !avg.is_power_of_two() || avg < 4 {
+            return None;
+        }
+        Some(Self { min, avg, max })
+    }
+
+    /// The length of the chunk at the start of `data`.
+    fn cut(&self, data: &[u8]) -> usize {
+        if data.len() <= self.min {
+            return data.len();
+        }
+        let end = data.len().min(self.max);
+        let normal = self.avg.min(end);
+        let bits = self.avg.trailing_zeros();
+        // harder to match before the average size, easier after it
+        let strict = !0u64 << (64 - (bits + 1));
+        let loose = !0u64 << (64 - (bits - 1));
+        let mut hash = 0u64;
+        for (i, byte) in data.iter().enumerate().take(end).skip(self.min) {
+            hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+            let mask = if i < normal { strict } else { loose };
+            if hash & mask == 0 {
+                return i + 1;
+            }
+        }
+        end
+    }
+
+    /// The chunks of `data`, in order.
+    pub fn chunks<'a>(&self, mut data: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut chunks = Vec::new();
+        while !data.is_empty() {
+            let (chunk, rest) = data.split_at(self.cut(data));
+            chunks.push(chunk);
+            data = rest;
+        }
+        chunks
+    }
+}
+
+/// One entry of the chunk index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chunk {
+    pub length: usize,
+    /// SHA-256 of the chunk.
+    pub digest: [u8; 32],
+}
+
+/// The chunk index of some data and the shares of every distinct chunk.
+#[derive(Debug, Clone)]
+pub struct ChunkedShares<S> {
+    pub index: Vec<Chunk>,
+    pub shares: BTreeMap<[u8; 32], Vec<S>>,
+}
+
+/// Chunks and shares `data`, reusing the shares of chunks already in `previous`.
+///
+/// `None` if sharing a chunk fails.
+pub fn share<T: Sharing>(
+    sharer: &T,
+    chunker: &Chunker,
+    data: &[u8],
+    previous: Option<&ChunkedShares<T::Share>>,
+) -> Option<ChunkedShares<T::Share>> {
+    let mut index = Vec::new();
+    let mut shares = BTreeMap::new();
+    for chunk in chunker.chunks(data) {
+        let digest: [u8; 32] = Sha256::digest(chunk).into();
+        index.push(Chunk {
+            length: chunk.len(),
+            digest,
+        });
+        if shares.contains_key(&digest) {
+            continue;
+        }
+        let chunk_shares = match previous.and_then(|p| p.shares.get(&digest)) {
+            Some(existing) => existing.clone(),
+            None => sharer.share(chunk.to_vec()).ok()?,
+        };
+        shares.insert(digest, chunk_shares);
+    }
+    Some(ChunkedShares { index, shares })
+}
+
+/// Reconstructs every chunk of the index and checks it against its digest.
+pub fn reconstruct<T: Sharing>(
+    sharer: &T,
+    index: &[Chunk],
+    shares: &BTreeMap<[u8; 32], Vec<T::Share>>,
+) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(index.iter().map(|c| c.length).sum());
+    for chunk in index {
+        let rec = sharer
+            .reconstruct(shares.get(&chunk.digest)?.clone())
+            .ok()?;
+        if rec.len() != chunk.length || Sha256::digest(&rec)[..] != chunk.digest[..] {
+            return None;
+        }
+        data.extend_from_slice(&rec);
+    }
+    Some(data)
+}
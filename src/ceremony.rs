@@ -0,0 +1,148 @@
+//! Dealer Ceremony
+//!
+//! The dealer side of a key ceremony as a typed state machine:
+//! [`Generated`] → [`Distributed`] → [`Finalized`]. Every transition consumes the previous
+//! state, so shares are handed out exactly once and a ceremony cannot be finalized before
+//! every holder confirmed the share they received with an [`echo`] of it.
+//!
+//! ```rust
+//! use sharing::{ceremony::{self, Generated}, ShamirSecretSharing, SharingError};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let generated = Generated::new(&sharer, vec![1, 2, 3]).unwrap();
+//! let (mut distributed, shares, openings) = generated.distribute();
+//!
+//! // every holder echoes the share and opening they received
+//! let echo = |i: usize| ceremony::echo(&shares[i], &openings[i]);
+//! distributed.acknowledge(shares[0].id, echo(0)).unwrap();
+//! distributed.acknowledge(shares[2].id, echo(2)).unwrap();
+//! assert!(matches!(
+//!     distributed.acknowledge(2, [0; 32]),
+//!     Err(SharingError::CorruptShare(2))
+//! ));
+//! assert_eq!(distributed.pending(), vec![2]);
+//!
+//! let mut distributed = distributed.finalize().unwrap_err();
+//! distributed.acknowledge(shares[1].id, echo(1)).unwrap();
+//! let finalized = distributed.finalize().unwrap();
+//! assert_eq!(finalized.commitments().len(), 3);
+//! ```
+use crate::{
+    commitment::{commit_with, Commitment, Opening},
+    error::SharingError,
+    share::ShamirShare,
+    ShamirSecretSharing,
+};
+use rand::Rng;
+use std::collections::BTreeSet;
+
+/// What a holder sends back to confirm the share and opening they received.
+pub fn echo(share: &ShamirShare, opening: &Opening) -> [u8; 32] {
+    commit_with(share, opening).digest
+}
+
+/// The shares are dealt but not yet handed out.
+#[derive(Debug)]
+pub struct Generated {
+    shares: Vec<ShamirShare>,
+    openings: Vec<Opening>,
+    commitments: Vec<Commitment>,
+}
+
+impl Generated {
+    /// Shares `secret` and commits to every share.
+    pub fn new<R: Rng>(
+        sharer: &ShamirSecretSharing<R>,
+        secret: Vec<u8>,
+    ) -> Result<Self, SharingError> {
+        let shares = sharer.try_share(secret)?;
+        let (openings, commitments) = sharer.commit(&shares)?;
+        Ok(Self {
+            shares,
+            openings,
+            commitments,
+        })
+    }
+
+    pub fn commitments(&self) -> &[Commitment] {
+        &self.commitments
+    }
+
+    /// Hands out the shares with their openings, the dealer keeps only the commitments.
+    pub fn distribute(self) -> (Distributed, Vec<ShamirShare>, Vec<Opening>) {
+        let distributed = Distributed {
+            commitments: self.commitments,
+            acknowledged: BTreeSet::new(),
+        };
+        (distributed, self.shares, self.openings)
+    }
+}
+
+/// The shares are handed out and acknowledgments are being collected.
+#[derive(Debug, Clone)]
+pub struct Distributed {
+    commitments: Vec<Commitment>,
+    acknowledged: BTreeSet<u8>,
+}
+
+impl Distributed {
+    pub fn commitments(&self) -> &[Commitment] {
+        &self.commitments
+    }
+
+    /// Records that holder `id` received the share matching `echo`.
+    ///
+    /// Fails with [`SharingError::CorruptShare`] if the echo does not match the commitment
+    /// and [`SharingError::MismatchedParameters`] if no share with this id was dealt.
+    pub fn acknowledge(&mut self, id: u8, echo: [u8; 32]) -> Result<(), SharingError> {
+        let commitment = self
+            .commitments
+            .iter()
+            .find(|c| c.id == id)
+            .ok_or(SharingError::MismatchedParameters)?;
+        if commitment.digest != echo {
+            return Err(SharingError::CorruptShare(id));
+        }
+        self.acknowledged.insert(id);
+        Ok(())
+    }
+
+    /// The ids that have not acknowledged yet.
+    pub fn pending(&self) -> Vec<u8> {
+        self.commitments
+            .iter()
+            .map(|c| c.id)
+            .filter(|id| !self.acknowledged.contains(id))
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pending().is_empty()
+    }
+
+    /// Completes the ceremony once every holder acknowledged, gives the state back otherwise.
+    pub fn finalize(self) -> Result<Finalized, Self> {
+        if !self.is_complete() {
+            return Err(self);
+        }
+        Ok(Finalized {
+            commitments: self.commitments,
+        })
+    }
+}
+
+/// Every holder confirmed their share, the commitments can be published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finalized {
+    commitments: Vec<Commitment>,
+}
+
+impl Finalized {
+    pub fn commitments(&self) -> &[Commitment] {
+        &self.commitments
+    }
+
+    pub fn into_commitments(self) -> Vec<Commitment> {
+        self.commitments
+    }
+}
@@ -0,0 +1,148 @@
+//! Share Commitments
+//!
+//! A dealer can publish a hash commitment for every share it hands out.
+//! Holders later check that the share they were given (or the one they get back)
+//! is the one that was committed to.
+//!
+//! Every commitment hashes a random salt with the share, which the dealer hands to the holder
+//! along with the share as its [`Opening`] and does not publish. Without the salt the
+//! commitments reveal nothing about the shares, not even for short secrets, whose share
+//! bodies could otherwise be found by trying every value.
+//!
+//! They also tell genuine shares apart from decoys stored among them, see [`separate`].
+//!
+//! ```rust
+//! use sharing::{commitment::verify_against_commitments, ShamirSecretSharing};
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//!
+//! let (shares, openings, commitments) =
+//!     sharer.share_with_commitments(vec![1, 2, 3, 4, 5]).unwrap();
+//! assert!(shares
+//!     .iter()
+//!     .zip(&openings)
+//!     .all(|(s, o)| verify_against_commitments(s, o, &commitments)));
+//!
+//! let mut swapped = shares[0].clone();
+//! swapped.body[0] ^= 1;
+//! assert!(!verify_against_commitments(&swapped, &openings[0], &commitments));
+//! ```
+use crate::{error::SharingError, secret::try_fill, share::ShamirShare};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+const DOMAIN: &[u8] = b"sharing/commitment/v1";
+
+/// Bytes of salt in every commitment.
+pub const SALT_LEN: usize = 16;
+
+/// A SHA-256 commitment to a single share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Commitment {
+    pub id: u8,
+    pub digest: [u8; 32],
+}
+
+/// The salt of the commitment to the share with the same id, kept by its holder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Opening {
+    pub id: u8,
+    pub salt: [u8; SALT_LEN],
+}
+
+/// Shares with the openings to hand out with them and the commitments to publish.
+pub type CommittedShares = (Vec<ShamirShare>, Vec<Opening>, Vec<Commitment>);
+
+/// Commits to a share with a fresh salt from `rng`.
+pub fn commit<R: Rng + ?Sized>(
+    share: &ShamirShare,
+    rng: &mut R,
+) -> Result<(Commitment, Opening), SharingError> {
+    let mut salt = [0u8; SALT_LEN];
+    try_fill(rng, &mut salt)?;
+    let opening = Opening { id: share.id, salt };
+    Ok((commit_with(share, &opening), opening))
+}
+
+/// The commitment to a share under the salt of `opening`.
+pub fn commit_with(share: &ShamirShare, opening: &Opening) -> Commitment {
+    let mut hasher = Sha256::new();
+    hasher.update(DOMAIN);
+    hasher.update(opening.salt);
+    hasher.update([share.id]);
+    hasher.update((share.body.len() as u64).to_be_bytes());
+    hasher.update(&share.body);
+    Commitment {
+        id: share.id,
+        digest: hasher.finalize().into(),
+    }
+}
+
+/// Checks that the share, opened with its salt, matches the published commitment with the
+/// same id.
+pub fn verify_against_commitments(
+    share: &ShamirShare,
+    opening: &Opening,
+    commitments: &[Commitment],
+) -> bool {
+    if opening.id != share.id {
+        return false;
+    }
+    let expected = commit_with(share, opening);
+    commitments
+        .iter()
+        .filter(|c| c.id == share.id)
+        .any(|c| c.digest == expected.digest)
+}
+
+/// Like [`verify_against_commitments`], with the opening of the same id among `openings`.
+pub(crate) fn verify_opened(
+    share: &ShamirShare,
+    openings: &[Opening],
+    commitments: &[Commitment],
+) -> bool {
+    openings
+        .iter()
+        .filter(|opening| opening.id == share.id)
+        .any(|opening| verify_against_commitments(share, opening, commitments))
+}
+
+/// Splits shares into those matching a commitment and the rest, e.g. the
+/// [`decoys`](crate::ShamirSecretSharing::decoys) stored among them.
+pub fn separate(
+    shares: Vec<ShamirShare>,
+    openings: &[Opening],
+    commitments: &[Commitment],
+) -> (Vec<ShamirShare>, Vec<ShamirShare>) {
+    shares
+        .into_iter()
+        .partition(|share| verify_opened(share, openings, commitments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hiding() {
+        let share = ShamirShare {
+            id: 1,
+            body: vec![7],
+        };
+        let mut rng = rand::thread_rng();
+        let (first, opening) = commit(&share, &mut rng).unwrap();
+        let (second, _) = commit(&share, &mut rng).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(commit_with(&share, &opening), first);
+
+        let other = Opening { id: 2, ..opening };
+        assert!(verify_against_commitments(
+            &share,
+            &opening,
+            &[second, first]
+        ));
+        assert!(!verify_against_commitments(&share, &other, &[first]));
+    }
+}
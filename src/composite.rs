@@ -0,0 +1,158 @@
+//! Layered Sharing
+//!
+//! A [`Composite`] applies one scheme to the shares of another: the inner scheme shares
+//! the data, then the outer scheme shares every encoded inner share. Holder `j` receives
+//! the `j`-th outer share of every inner share, bundled in one [`CompositeShare`].
+//! Reconstruction unwinds the layers, first every inner share, then the data.
+//!
+//! For example Shamir over each Rabin share, where no single holder learns anything
+//! about the dispersed pieces:
+//!
+//! ```rust
+//! use sharing::{composite::Composite, RabinInformationDispersal, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = Composite::new(
+//!     ShamirSecretSharing::new(3, 2, rand::thread_rng()),
+//!     RabinInformationDispersal::new(4, 2),
+//! );
+//! let shares = sharer.share(b"layered".to_vec()).unwrap();
+//! assert_eq!((shares.len(), shares[0].parts.len()), (3, 4));
+//!
+//! assert_eq!(sharer.reconstruct(shares[1..].to_vec()).unwrap(), b"layered");
+//! assert!(sharer.reconstruct(shares[..1].to_vec()).is_err());
+//! ```
+use crate::{
+    error::SharingError,
+    share::{decode_error, Share, ShareBytes, ShareId},
+    Sharing,
+};
+
+/// Shares the data with `inner`, then every inner share with `outer`.
+pub struct Composite<O, I> {
+    outer: O,
+    inner: I,
+}
+
+impl<O: Sharing, I: Sharing> Composite<O, I> {
+    pub fn new(outer: O, inner: I) -> Self {
+        Self { outer, inner }
+    }
+
+    pub fn outer(&self) -> &O {
+        &self.outer
+    }
+
+    pub fn inner(&self) -> &I {
+        &self.inner
+    }
+}
+
+/// One holder's outer shares, one for every inner share in order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompositeShare<S> {
+    pub parts: Vec<S>,
+}
+
+impl<S: Share> Share for CompositeShare<S> {
+    /// The size of the outer shares, the same for every part.
+    fn size(&self) -> usize {
+        self.parts.first().map_or(0, Share::size)
+    }
+
+    fn with_size(size: usize) -> Self {
+        Self {
+            parts: vec![S::with_size(size)],
+        }
+    }
+}
+
+impl<S: ShareId> ShareId for CompositeShare<S> {
+    /// The holder's outer id, shared by all parts.
+    fn id(&self) -> u8 {
+        self.parts.first().map_or(0, ShareId::id)
+    }
+}
+
+impl<O, I> Sharing for Composite<O, I>
+where
+    O: Sharing,
+    I: Sharing,
+    I::Share: ShareBytes,
+{
+    type Share = CompositeShare<O::Share>;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError> {
+        let inner = self.inner.share(data)?;
+        let mut holders: Vec<Self::Share> = Vec::new();
+        for share in &inner {
+            let outer = self.outer.share(share.to_bytes())?;
+            if holders.is_empty() {
+                holders = outer
+                    .iter()
+                    .map(|_| CompositeShare {
+                        parts: Vec::with_capacity(inner.len()),
+                    })
+                    .collect();
+            }
+            if outer.len() != holders.len() {
+                return Err(SharingError::MismatchedParameters);
+            }
+            for (holder, part) in holders.iter_mut().zip(outer) {
+                holder.parts.push(part);
+            }
+        }
+        Ok(holders)
+    }
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        self.inner.reconstruct(self.inner_shares(shares)?)
+    }
+
+    #[cfg(feature = "secure-memory")]
+    fn reconstruct_secure_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut crate::secure::SecretBuffer,
+    ) -> Result<(), SharingError> {
+        let inner = self.inner_shares(shares.to_vec())?;
+        self.inner.reconstruct_secure_into(&inner, out)
+    }
+}
+
+impl<O, I> Composite<O, I>
+where
+    O: Sharing,
+    I: Sharing,
+    I::Share: ShareBytes,
+{
+    /// Reconstructs the inner shares from each layer of outer shares.
+    fn inner_shares(
+        &self,
+        shares: Vec<CompositeShare<O::Share>>,
+    ) -> Result<Vec<I::Share>, SharingError> {
+        let count = shares
+            .first()
+            .ok_or(SharingError::EmptyShareSet)?
+            .parts
+            .len();
+        if shares.iter().any(|share| share.parts.len() != count) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let mut layers: Vec<Vec<O::Share>> = (0..count)
+            .map(|_| Vec::with_capacity(shares.len()))
+            .collect();
+        for share in shares {
+            for (layer, part) in layers.iter_mut().zip(share.parts) {
+                layer.push(part);
+            }
+        }
+        layers
+            .into_iter()
+            .map(|layer| {
+                let bytes = self.outer.reconstruct(layer)?;
+                I::Share::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,151 @@
+//! Compression
+//!
+//! [`KrawczykSecretSharing`](crate::KrawczykSecretSharing) can compress the data before
+//! encrypting and dispersing it, which shrinks every share for compressible data.
+//! The algorithm is recorded in each share, the codecs are behind the `lz4` and `zstd` features.
+//!
+//! Compression leaks information about the data through the share length,
+//! so only enable it when the length of the secret is not sensitive.
+//!
+//! ```rust
+//! # #[cfg(feature = "lz4")] {
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! use sharing::{compression::Compression, KrawczykSecretSharing, Sharing};
+//!
+//! let data = vec![7u8; 4096];
+//! let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(5, 3, rand::thread_rng())
+//!     .with_compression(Compression::Lz4);
+//!
+//! let shares = sharer.share(data.clone()).unwrap();
+//! assert!(shares[0].body.len() < 4096 / 3);
+//! assert_eq!(sharer.reconstruct(shares[..3].to_vec()).unwrap(), data);
+//! # }
+//! ```
+
+/// The compression applied to the data before it was encrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Compression {
+    #[default]
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl Compression {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Lz4 => "lz4",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Whether the codec was compiled in.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Compression::None => true,
+            Compression::Lz4 => cfg!(feature = "lz4"),
+            Compression::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    pub(crate) fn to_byte(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Lz4 => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    pub(crate) fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Lz4),
+            2 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Authenticated along with the ciphertext: the compression byte followed by `extra`,
+    /// empty when both are unset so such shares match those of earlier versions.
+    #[cfg(feature = "krawczyk")]
+    pub(crate) fn associated_data(self, extra: &[u8]) -> Vec<u8> {
+        if self == Compression::None && extra.is_empty() {
+            return Vec::new();
+        }
+        let mut out = Vec::with_capacity(1 + extra.len());
+        out.push(self.to_byte());
+        out.extend_from_slice(extra);
+        out
+    }
+
+    #[cfg(feature = "krawczyk")]
+    pub(crate) fn compress(self, data: Vec<u8>) -> Option<Vec<u8>> {
+        match self {
+            Compression::None => Some(data),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => Some(lz4_flex::compress_prepend_size(&data)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::bulk::compress(&data, 0).ok(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// `None` if the codec is not available or the data is corrupt.
+    #[cfg(feature = "krawczyk")]
+    pub(crate) fn decompress(self, data: Vec<u8>) -> Option<Vec<u8>> {
+        match self {
+            Compression::None => Some(data),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(&data).ok(),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                let capacity = zstd::zstd_safe::get_frame_content_size(&data).ok()??;
+                zstd::bulk::decompress(&data, std::convert::TryFrom::try_from(capacity).ok()?).ok()
+            }
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Compression {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "lz4" => Ok(Compression::Lz4),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "krawczyk"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let data = b"abcabcabcabcabcabcabcabcabcabc".to_vec();
+        for compression in [Compression::None, Compression::Lz4, Compression::Zstd].iter() {
+            if !compression.is_available() {
+                assert_eq!(compression.compress(data.clone()), None);
+                continue;
+            }
+            let compressed = compression.compress(data.clone()).unwrap();
+            assert_eq!(compression.decompress(compressed), Some(data.clone()));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Extendable Dealings
+//!
+//! A [`Dealing`] retains the random polynomials of a Shamir sharing, so the dealer can mint
+//! shares at new ids later, e.g. for a holder who joins after the fact, without
+//! re-issuing or invalidating the shares already handed out.
+//!
+//! The polynomials reveal the secret, so a retained dealing must be protected like it
+//! and dropped once no more shares will be needed; with the `secure-memory` feature it is held
+//! in locked memory and wiped.
+//! Dealings from [`share_deterministic`](crate::ShamirSecretSharing::share_deterministic)
+//! can instead be extended from the key with
+//! [`share_for_subset`](crate::ShamirSecretSharing::share_for_subset).
+//!
+//! ```rust
+//! use sharing::{ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let dealing = sharer.dealing(vec![1, 2, 3]).unwrap();
+//! let shares = dealing.shares(&[1, 2, 3]).unwrap();
+//!
+//! // a fourth holder joins later
+//! let minted = dealing.shares(&[4]).unwrap();
+//! let rec = sharer.reconstruct(vec![shares[0].clone(), minted[0].clone()]).unwrap();
+//! assert_eq!(rec, vec![1, 2, 3]);
+//! assert_eq!(dealing.shares(&[2]).unwrap(), vec![shares[1].clone()]);
+//! ```
+use crate::{
+    math::Field,
+    secret::{wipe, SecretBytes},
+    share::{valid_ids, ShamirShare},
+};
+use std::fmt;
+
+/// The retained polynomials of a Shamir sharing, created by
+/// [`ShamirSecretSharing::dealing`](crate::ShamirSecretSharing::dealing).
+#[derive(Clone)]
+pub struct Dealing {
+    field: Field,
+    k: u8,
+    /// `k` coefficients per secret byte, the byte itself first.
+    coefficients: SecretBytes,
+}
+
+impl Dealing {
+    pub(crate) fn new(field: Field, k: u8, coefficients: SecretBytes) -> Self {
+        Self {
+            field,
+            k,
+            coefficients,
+        }
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// The length of the shared data.
+    pub fn len(&self) -> usize {
+        self.coefficients.len() / self.k as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// The shares at `ids`, the same every time for the same id.
+    ///
+    /// `None` if an id is zero or repeated.
+    pub fn shares(&self, ids: &[u8]) -> Option<Vec<ShamirShare>> {
+        if !valid_ids(ids) {
+            return None;
+        }
+        Some(
+            ids.iter()
+                .map(|id| ShamirShare {
+                    id: *id,
+                    body: self
+                        .coefficients
+                        .chunks(self.k as usize)
+                        .map(|polynomial| self.field.evaluate(polynomial, *id))
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Debug for Dealing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dealing")
+            .field("field", &self.field)
+            .field("k", &self.k)
+            .field("coefficients", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Drop for Dealing {
+    fn drop(&mut self) {
+        wipe(&mut self.coefficients);
+    }
+}
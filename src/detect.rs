@@ -0,0 +1,246 @@
+//! Scheme Detection
+//!
+//! Shares in the [`ShareBytes`] encoding name their scheme, so a recovery tool can
+//! reconstruct them with [`reconstruct_any`] without knowing how they were dealt.
+//!
+//! The threshold is not part of the encoding and is recovered as follows:
+//!
+//! - Shamir: all given shares are interpolated, which is exact for any `k` up to
+//!   their number, so pass every available share.
+//! - Rabin: every `k` whose column count matches the body length is tried, smallest first,
+//!   and accepted once the other shares agree with the result; with exactly `k` shares
+//!   there is nothing left to check against.
+//! - Krawczyk: as Rabin, but every candidate is checked against the ciphertext digest and tag.
+//!   The cipher is not recorded either, so use [`reconstruct_any_with`].
+//!
+//! ```rust
+//! use sharing::{reconstruct_any, share::ShareBytes, RabinInformationDispersal};
+//! use sharing::{ShamirSecretSharing, Sharing};
+//!
+//! let shamir = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let shares: Vec<Vec<u8>> = shamir.share(b"one".to_vec()).unwrap()
+//!     .iter().map(ShareBytes::to_bytes).collect();
+//! assert_eq!(reconstruct_any(&shares[1..]).unwrap(), b"one");
+//!
+//! let rabin = RabinInformationDispersal::new(5, 3);
+//! let shares: Vec<Vec<u8>> = rabin.share(b"and another".to_vec()).unwrap()
+//!     .iter().map(ShareBytes::to_bytes).collect();
+//! assert_eq!(reconstruct_any(&shares).unwrap(), b"and another");
+//! ```
+//!
+//! Shares in the [wire format](crate::wire) record their threshold too, [`reconstruct`] reads
+//! both from them instead.
+use crate::{
+    error::SharingError,
+    ids::RabinInformationDispersal,
+    roles::Combiner,
+    share::{decode_error, valid_ids, RabinShare, ShamirShare, ShareBytes, ShareId},
+    wire::{self, WireScheme, WireShare},
+    Scheme, Sharing,
+};
+#[cfg(feature = "krawczyk")]
+use crate::{share::KrawczykShare, KrawczykSecretSharing};
+#[cfg(feature = "krawczyk")]
+use aead::{AeadInPlace, KeyInit};
+
+/// The scheme named by the first byte after the version, the same for every share.
+pub fn detect<S: AsRef<[u8]>>(shares: &[S]) -> Result<Scheme, SharingError> {
+    let mut schemes = shares.iter().map(|bytes| match bytes.as_ref() {
+        [_, b'S', ..] => Ok(Scheme::Shamir),
+        [_, b'R', ..] => Ok(Scheme::Rabin),
+        [_, b'K', ..] => Ok(Scheme::Krawczyk),
+        bytes => Err(decode_error(bytes)),
+    });
+    let scheme = schemes.next().ok_or(SharingError::InsufficientShares {
+        needed: 1,
+        found: 0,
+    })??;
+    for other in schemes {
+        if other? != scheme {
+            return Err(SharingError::MismatchedParameters);
+        }
+    }
+    Ok(scheme)
+}
+
+/// Detects the scheme and threshold of Shamir or Rabin shares and reconstructs them.
+pub fn reconstruct_any<S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<u8>, SharingError> {
+    match detect(shares)? {
+        Scheme::Shamir => shamir(decode(shares)?),
+        Scheme::Rabin => rabin(decode(shares)?),
+        Scheme::Krawczyk => Err(SharingError::MismatchedParameters),
+    }
+}
+
+/// Like [`reconstruct_any`], and reconstructs Krawczyk shares encrypted with `C`.
+///
+/// ```rust
+/// use chacha20poly1305::ChaCha20Poly1305;
+/// use sharing::{reconstruct_any_with, share::ShareBytes, KrawczykSecretSharing, Sharing};
+///
+/// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(4, 2, rand::thread_rng());
+/// let shares: Vec<Vec<u8>> = sharer.share(b"archive".to_vec()).unwrap()
+///     .iter().map(ShareBytes::to_bytes).collect();
+/// let rec = reconstruct_any_with::<ChaCha20Poly1305, _>(&shares[1..3]).unwrap();
+/// assert_eq!(rec, b"archive");
+/// ```
+#[cfg(feature = "krawczyk")]
+pub fn reconstruct_any_with<C, S>(shares: &[S]) -> Result<Vec<u8>, SharingError>
+where
+    C: AeadInPlace + KeyInit,
+    S: AsRef<[u8]>,
+{
+    match detect(shares)? {
+        Scheme::Krawczyk => krawczyk::<C>(decode(shares)?),
+        _ => reconstruct_any(shares),
+    }
+}
+
+/// Reconstructs shares in the [wire format](crate::wire) with the scheme and threshold they
+/// were dealt with, read from the shares themselves. Unlike [`reconstruct_any`] nothing is
+/// guessed, and fewer shares than the threshold fail with
+/// [`SharingError::InsufficientShares`].
+///
+/// ```rust
+/// use sharing::{share::ShareBytes, wire::WireShare, ShamirSecretSharing, Sharing};
+///
+/// let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+/// let shares: Vec<Vec<u8>> = sharer.share(b"parameters".to_vec()).unwrap()
+///     .into_iter().map(|share| WireShare::new(3, share).to_bytes()).collect();
+///
+/// // years later, without knowing n or k
+/// assert_eq!(sharing::reconstruct(&shares[2..]).unwrap(), b"parameters");
+/// assert!(sharing::reconstruct(&shares[3..]).is_err());
+/// ```
+pub fn reconstruct<S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<u8>, SharingError> {
+    let (scheme, k) = parameters(shares)?;
+    match scheme {
+        Scheme::Shamir => Combiner::new(k).reconstruct(decode_wire::<ShamirShare, _>(shares)?),
+        Scheme::Rabin => {
+            let shares = decode_wire::<RabinShare, _>(shares)?;
+            RabinInformationDispersal::new(shares.len() as u8, k).reconstruct(shares)
+        }
+        Scheme::Krawczyk => Err(SharingError::MismatchedParameters),
+    }
+}
+
+/// Like [`reconstruct`], and reconstructs Krawczyk shares encrypted with `C`.
+///
+/// ```rust
+/// use chacha20poly1305::ChaCha20Poly1305;
+/// use sharing::{reconstruct_with, share::ShareBytes, wire::WireShare};
+/// use sharing::{KrawczykSecretSharing, RabinInformationDispersal, Sharing};
+///
+/// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(4, 3, rand::thread_rng());
+/// let shares: Vec<Vec<u8>> = sharer.share(b"archive".to_vec()).unwrap()
+///     .into_iter().map(|share| WireShare::new(3, share).to_bytes()).collect();
+/// let rec = reconstruct_with::<ChaCha20Poly1305, _>(&shares[1..]).unwrap();
+/// assert_eq!(rec, b"archive");
+///
+/// let rabin = RabinInformationDispersal::new(4, 2);
+/// let shares: Vec<Vec<u8>> = rabin.share(b"index".to_vec()).unwrap()
+///     .into_iter().map(|share| WireShare::new(2, share).to_bytes()).collect();
+/// let rec = reconstruct_with::<ChaCha20Poly1305, _>(&shares[2..]).unwrap();
+/// assert_eq!(rec, b"index");
+/// ```
+#[cfg(feature = "krawczyk")]
+pub fn reconstruct_with<C, S>(shares: &[S]) -> Result<Vec<u8>, SharingError>
+where
+    C: AeadInPlace + KeyInit,
+    S: AsRef<[u8]>,
+{
+    match parameters(shares)? {
+        (Scheme::Krawczyk, k) => {
+            let shares = decode_wire::<KrawczykShare, _>(shares)?;
+            KrawczykSecretSharing::<C, _>::new(shares.len() as u8, k, rand::rngs::OsRng)
+                .reconstruct(shares)
+        }
+        _ => reconstruct(shares),
+    }
+}
+
+/// The scheme and threshold in the headers, which must all agree.
+fn parameters<S: AsRef<[u8]>>(shares: &[S]) -> Result<(Scheme, u8), SharingError> {
+    let mut headers = shares.iter().map(|bytes| wire::header(bytes.as_ref()));
+    let first = headers.next().ok_or(SharingError::EmptyShareSet)??;
+    for header in headers {
+        let header = header?;
+        if (header.scheme, header.k) != (first.scheme, first.k) {
+            return Err(SharingError::MismatchedParameters);
+        }
+    }
+    if shares.len() < usize::from(first.k) {
+        return Err(SharingError::InsufficientShares {
+            needed: first.k.into(),
+            found: shares.len(),
+        });
+    }
+    Ok((first.scheme, first.k))
+}
+
+fn decode_wire<T: WireScheme, S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<T>, SharingError> {
+    let shares = shares
+        .iter()
+        .map(|bytes| WireShare::<T>::decode(bytes.as_ref()).map(|wire| wire.share))
+        .collect::<Result<Vec<T>, _>>()?;
+    let ids: Vec<u8> = shares.iter().map(ShareId::id).collect();
+    if !valid_ids(&ids) {
+        return Err(SharingError::MismatchedParameters);
+    }
+    Ok(shares)
+}
+
+fn decode<T: ShareBytes + ShareId, S: AsRef<[u8]>>(shares: &[S]) -> Result<Vec<T>, SharingError> {
+    let shares = shares
+        .iter()
+        .map(|bytes| T::from_bytes(bytes.as_ref()).ok_or_else(|| decode_error(bytes.as_ref())))
+        .collect::<Result<Vec<T>, _>>()?;
+    let ids: Vec<u8> = shares.iter().map(ShareId::id).collect();
+    if !valid_ids(&ids) {
+        return Err(SharingError::MismatchedParameters);
+    }
+    Ok(shares)
+}
+
+fn shamir(shares: Vec<ShamirShare>) -> Result<Vec<u8>, SharingError> {
+    let count = shares.len();
+    Combiner::new(count as u8).reconstruct(shares)
+}
+
+/// The thresholds up to `count` that disperse `length` bytes into `columns` per share.
+fn candidates(length: usize, columns: usize, count: usize) -> impl Iterator<Item = u8> {
+    (1..=count as u8).filter(move |k| length.div_ceil(*k as usize) == columns)
+}
+
+fn rabin(shares: Vec<RabinShare>) -> Result<Vec<u8>, SharingError> {
+    let first = &shares[0];
+    let (length, columns, count) = (first.length, first.body.len(), shares.len());
+    let ids: Vec<u8> = shares.iter().map(|share| share.id).collect();
+    for k in candidates(length, columns, count) {
+        let sharer = RabinInformationDispersal::new(count as u8, k);
+        let data = match sharer.reconstruct(shares.clone()) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+        let consistent = sharer
+            .share_at(data.clone(), &ids)
+            .is_ok_and(|dispersed| dispersed == shares);
+        if consistent {
+            return Ok(data);
+        }
+    }
+    Err(SharingError::ReconstructionFailed)
+}
+
+#[cfg(feature = "krawczyk")]
+fn krawczyk<C: AeadInPlace + KeyInit>(shares: Vec<KrawczykShare>) -> Result<Vec<u8>, SharingError> {
+    let first = &shares[0];
+    let (length, columns, count) = (first.length, first.body.len(), shares.len());
+    candidates(length, columns, count)
+        .find_map(|k| {
+            KrawczykSecretSharing::<C, _>::new(count as u8, k, rand::rngs::OsRng)
+                .reconstruct(shares.clone())
+                .ok()
+        })
+        .ok_or(SharingError::ReconstructionFailed)
+}
@@ -0,0 +1,181 @@
+//! Share Discovery
+//!
+//! Recovers share sets from a directory full of backup files: [`scan`] walks the directory,
+//! reads every [`Manifest`] and every file in the [`ShareBytes`] encoding,
+//! and groups the shares by the manifest listing their digest.
+//! Each set reports whether it has enough shares and can reconstruct once it does.
+//!
+//! ```rust
+//! use sharing::{discovery, manifest::Manifest, share::ShareBytes};
+//! use sharing::{Parameters, Scheme, ShamirSecretSharing, Sharing};
+//!
+//! let dir = std::env::temp_dir().join(format!("sharing-discovery-doc-{}", std::process::id()));
+//! std::fs::create_dir_all(dir.join("nested")).unwrap();
+//!
+//! let sharer = ShamirSecretSharing::new(4, 3, rand::thread_rng());
+//! let shares = sharer.share(b"backup".to_vec()).unwrap();
+//! let manifest = Manifest::new(Scheme::Shamir, Parameters::new(4, 3).unwrap(), &shares);
+//! std::fs::write(dir.join("backup.manifest"), manifest.to_string()).unwrap();
+//! for share in &shares[..2] {
+//!     std::fs::write(dir.join(format!("backup.{}", share.id)), share.to_bytes()).unwrap();
+//! }
+//!
+//! let scan = discovery::scan(&dir).unwrap();
+//! assert!(!scan.sets[0].is_complete());
+//! assert_eq!(scan.sets[0].missing(), vec![3, 4]);
+//!
+//! std::fs::write(dir.join("nested/backup.4"), shares[3].to_bytes()).unwrap();
+//! let scan = discovery::scan(&dir).unwrap();
+//! assert!(scan.sets[0].is_complete());
+//! assert_eq!(scan.sets[0].reconstruct(&sharer).unwrap(), b"backup");
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use crate::{
+    error::SharingError,
+    manifest::Manifest,
+    share::{decode_error, KrawczykShare, RabinShare, ShamirShare, ShareBytes, ShareId},
+    Sharing,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Everything [`scan`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scan {
+    /// One entry per manifest, complete or not.
+    pub sets: Vec<DiscoveredSet>,
+    /// Share files not listed in any manifest.
+    pub unmatched: Vec<PathBuf>,
+}
+
+impl Scan {
+    /// The sets with at least `k` shares.
+    pub fn complete(&self) -> impl Iterator<Item = &DiscoveredSet> {
+        self.sets.iter().filter(|s| s.is_complete())
+    }
+}
+
+/// A share set described by a manifest and the share files found for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredSet {
+    pub manifest: Manifest,
+    pub manifest_path: PathBuf,
+    /// The id and path of every share found, one per id.
+    pub shares: Vec<(u8, PathBuf)>,
+}
+
+impl DiscoveredSet {
+    pub fn is_complete(&self) -> bool {
+        self.shares.len() >= self.manifest.parameters().k() as usize
+    }
+
+    /// The ids listed in the manifest that were not found.
+    pub fn missing(&self) -> Vec<u8> {
+        self.manifest
+            .shares()
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| self.shares.iter().all(|(found, _)| found != id))
+            .collect()
+    }
+
+    /// Reads the shares again, checks them against the manifest and reconstructs,
+    /// also checking the payload digest if the manifest has one.
+    ///
+    /// Shares that no longer match the manifest are left out, if too few remain
+    /// the error names the first of them.
+    pub fn reconstruct<T: Sharing>(&self, sharer: &T) -> Result<Vec<u8>, SharingError>
+    where
+        T::Share: ShareBytes + ShareId,
+    {
+        let mut shares = Vec::with_capacity(self.shares.len());
+        let mut corrupt = None;
+        for (id, path) in &self.shares {
+            let bytes = fs::read(path)?;
+            let share = T::Share::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))?;
+            if self.manifest.verify_share(&share) {
+                shares.push(share);
+            } else {
+                corrupt = corrupt.or(Some(*id));
+            }
+        }
+        let data = sharer.reconstruct(shares).map_err(|error| match corrupt {
+            Some(id) => SharingError::CorruptShare(id),
+            None => error,
+        })?;
+        if self.manifest.payload().is_some() && !self.manifest.verify_payload(&data) {
+            return Err(SharingError::ReconstructionFailed);
+        }
+        Ok(data)
+    }
+}
+
+/// Walks `dir` and its subdirectories and groups the share files by manifest.
+///
+/// Files that are neither manifests nor shares are ignored.
+pub fn scan(dir: &Path) -> io::Result<Scan> {
+    let mut manifests = Vec::new();
+    let mut shares = Vec::new();
+    walk(dir, &mut |path| {
+        let bytes = fs::read(path)?;
+        if let Some(manifest) = std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|text| text.parse::<Manifest>().ok())
+        {
+            manifests.push((path.to_path_buf(), manifest));
+        } else if let Some(id) = share_id(&bytes) {
+            let digest: [u8; 32] = Sha256::digest(&bytes).into();
+            shares.push((path.to_path_buf(), id, digest));
+        }
+        Ok(())
+    })?;
+    manifests.sort_by(|a, b| a.0.cmp(&b.0));
+    shares.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut sets: Vec<DiscoveredSet> = manifests
+        .into_iter()
+        .map(|(manifest_path, manifest)| DiscoveredSet {
+            manifest,
+            manifest_path,
+            shares: Vec::new(),
+        })
+        .collect();
+    let mut unmatched = Vec::new();
+    for (path, id, digest) in shares {
+        let set = sets
+            .iter_mut()
+            .find(|set| set.manifest.shares().contains(&(id, digest)));
+        match set {
+            Some(set) if set.shares.iter().any(|(found, _)| *found == id) => {}
+            Some(set) => set.shares.push((id, path)),
+            None => unmatched.push(path),
+        }
+    }
+    for set in &mut sets {
+        set.shares.sort_by_key(|(id, _)| *id);
+    }
+    Ok(Scan { sets, unmatched })
+}
+
+fn walk(dir: &Path, visit: &mut dyn FnMut(&Path) -> io::Result<()>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            walk(&path, visit)?;
+        } else {
+            visit(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// The id of a share in any of the [`ShareBytes`] encodings.
+fn share_id(bytes: &[u8]) -> Option<u8> {
+    ShamirShare::from_bytes(bytes)
+        .map(|s| s.id())
+        .or_else(|| RabinShare::from_bytes(bytes).map(|s| s.id()))
+        .or_else(|| KrawczykShare::from_bytes(bytes).map(|s| s.id()))
+}
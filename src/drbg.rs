@@ -0,0 +1,150 @@
+//! Deterministic Coefficients
+//!
+//! [`HmacDrbg`] is HMAC-DRBG with SHA-256 (NIST SP 800-90A) as a [`RngCore`].
+//! [`ShamirSecretSharing::share_deterministic`] seeds it from a key, the secret and a context,
+//! so the same inputs always give the same shares, e.g. to recreate a lost backup share
+//! or to let an auditor check a dealer's output.
+//!
+//! The key has to be secret and high entropy: anyone who knows it can recompute the
+//! coefficients from the secret and test guesses of the secret against a single share.
+//! Use a different context for every sharing of the same secret.
+//!
+//! [`ShamirSecretSharing::share_deterministic`]: crate::ShamirSecretSharing::share_deterministic
+//!
+//! ```rust
+//! use sharing::{drbg::HmacDrbg, ShamirSecretSharing, Sharing};
+//! use rand::RngCore;
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let key = [7u8; 32];
+//!
+//! let bodies = |context: &[u8]| -> Vec<Vec<u8>> {
+//!     let shares = sharer.share_deterministic(vec![1, 2, 3], &key, context).unwrap();
+//!     shares.into_iter().map(|share| share.body).collect()
+//! };
+//! assert_eq!(bodies(b"backup/2026"), bodies(b"backup/2026"));
+//! assert_ne!(bodies(b"backup/2026"), bodies(b"backup/2027"));
+//!
+//! let shares = sharer.share_deterministic(vec![1, 2, 3], &key, b"backup/2026").unwrap();
+//! assert_eq!(sharer.reconstruct(shares[2..].to_vec()).unwrap(), vec![1, 2, 3]);
+//!
+//! let mut drbg = HmacDrbg::new(&key, b"secret", b"context");
+//! assert_ne!(drbg.next_u64(), drbg.next_u64());
+//! ```
+use crate::secret::wipe;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+const BLOCK: usize = 64;
+const DOMAIN: &[u8] = b"sharing/drbg/v1";
+
+/// HMAC-DRBG with SHA-256, without reseeding.
+#[derive(Clone)]
+pub struct HmacDrbg {
+    k: [u8; 32],
+    v: [u8; 32],
+}
+
+impl HmacDrbg {
+    /// Instantiates the generator from `key`, `secret` and `context`.
+    ///
+    /// Every input is length prefixed in the seed material, so moving bytes
+    /// from one input to the next gives a different stream.
+    pub fn new(key: &[u8], secret: &[u8], context: &[u8]) -> Self {
+        let mut drbg = Self {
+            k: [0; 32],
+            v: [1; 32],
+        };
+        let mut seed =
+            Vec::with_capacity(DOMAIN.len() + key.len() + secret.len() + context.len() + 12);
+        seed.extend_from_slice(DOMAIN);
+        for input in [key, secret, context] {
+            seed.extend_from_slice(&(input.len() as u32).to_be_bytes());
+            seed.extend_from_slice(input);
+        }
+        drbg.update(&seed);
+        wipe(&mut seed);
+        drbg
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.k = hmac(&self.k, &[&self.v, &[0], data]);
+        self.v = hmac(&self.k, &[&self.v]);
+        if !data.is_empty() {
+            self.k = hmac(&self.k, &[&self.v, &[1], data]);
+            self.v = hmac(&self.k, &[&self.v]);
+        }
+    }
+}
+
+impl RngCore for HmacDrbg {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(32) {
+            self.v = hmac(&self.k, &[&self.v]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[]);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for HmacDrbg {}
+
+impl std::fmt::Debug for HmacDrbg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HmacDrbg")
+    }
+}
+
+impl Drop for HmacDrbg {
+    fn drop(&mut self) {
+        wipe(&mut self.k);
+        wipe(&mut self.v);
+    }
+}
+
+/// HMAC-SHA256 over the concatenation of `parts`, for keys of at most one block.
+pub(crate) fn hmac(key: &[u8; 32], parts: &[&[u8]]) -> [u8; 32] {
+    let mut pad = [0u8; BLOCK];
+    pad[..32].copy_from_slice(key);
+    let mut inner = Sha256::new();
+    inner.update(pad.map(|b| b ^ 0x36));
+    for part in parts {
+        inner.update(part);
+    }
+    let mut outer = Sha256::new();
+    outer.update(pad.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    wipe(&mut pad);
+    outer.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_sha256_rfc4231() {
+        // test case 2 with the key zero padded to 32 bytes, which HMAC does anyway
+        let mut key = [0u8; 32];
+        key[..4].copy_from_slice(b"Jefe");
+        let mac = hmac(&key, &[b"what do ya want ", b"for nothing?"]);
+        assert_eq!(mac[..8], [0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Text Encodings
+//!
+//! Checksummed text forms of the [`ShareBytes`] encoding, for shares that are copied by hand,
+//! e.g. from a paper backup. A typo is caught when the share is parsed instead of silently
+//! reconstructing a wrong secret.
+//!
+//! - Bech32m ([BIP-350]) with the human readable prefix `share`, e.g. `share1qdfsx...`.
+//!   The checksum detects any error in up to four characters of a short share; for shares
+//!   longer than 89 characters any error is still missed with a chance of only 2<sup>-30</sup>.
+//! - URL-safe base64 of the bytes followed by their CRC-32C, behind the prefix `share:`.
+//!   Shorter, but detects fewer kinds of typos.
+//!
+//! Available with the `encoding` feature.
+//!
+//! [BIP-350]: https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+//!
+//! ```rust
+//! use sharing::{encoding::Encoding, ShamirSecretSharing, ShamirShare, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares = sharer.share(b"seed".to_vec()).unwrap();
+//!
+//! let text = shares[0].to_bech32();
+//! assert!(text.starts_with("share1"));
+//! assert_eq!(ShamirShare::from_bech32(&text.to_uppercase()).unwrap(), shares[0]);
+//!
+//! // a single mistyped character
+//! let mut typo = text.into_bytes();
+//! typo[10] = if typo[10] == b'q' { b'p' } else { b'q' };
+//! assert!(ShamirShare::from_bech32(std::str::from_utf8(&typo).unwrap()).is_err());
+//!
+//! let text = shares[1].to_base64();
+//! assert_eq!(ShamirShare::from_base64(&text).unwrap(), shares[1]);
+//! ```
+use crate::{
+    error::SharingError,
+    share::{crc32c, decode_error, ShareBytes},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+/// The human readable prefix of Bech32m shares.
+pub const HRP: &str = "share";
+
+/// The prefix of base64 shares.
+pub const BASE64_PREFIX: &str = "share:";
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32M: u32 = 0x2bc8_30a3;
+
+/// Checksummed text forms of any share with a [`ShareBytes`] encoding.
+pub trait Encoding: ShareBytes {
+    fn to_bech32(&self) -> String {
+        bech32_encode(HRP, &self.to_bytes())
+    }
+
+    /// Accepts all lower or all upper case, fails with [`SharingError::Malformed`]
+    /// on a wrong checksum.
+    fn from_bech32(s: &str) -> Result<Self, SharingError> {
+        let bytes = bech32_decode(HRP, s.trim())?;
+        Self::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))
+    }
+
+    fn to_base64(&self) -> String {
+        let mut bytes = self.to_bytes();
+        let crc = crc32c(&bytes);
+        bytes.extend_from_slice(&crc.to_be_bytes());
+        format!("{}{}", BASE64_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    /// Fails with [`SharingError::Malformed`] on a wrong checksum.
+    fn from_base64(s: &str) -> Result<Self, SharingError> {
+        let encoded = s
+            .trim()
+            .strip_prefix(BASE64_PREFIX)
+            .ok_or(SharingError::Malformed("base64 share prefix"))?;
+        let mut bytes = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|_| SharingError::Malformed("base64 share"))?;
+        if bytes.len() < 4 {
+            return Err(SharingError::Malformed("base64 share"));
+        }
+        let crc = bytes.split_off(bytes.len() - 4);
+        if crc[..] != crc32c(&bytes).to_be_bytes()[..] {
+            return Err(SharingError::Malformed("base64 share checksum"));
+        }
+        Self::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))
+    }
+}
+
+impl<T: ShareBytes> Encoding for T {}
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    values.fold(1, |chk, value| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(value);
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, g)| chk ^ g)
+    })
+}
+
+fn expand_hrp(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes()
+        .map(|c| c >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|c| c & 31))
+}
+
+/// Regroups bits, padding the last group with zeros; `None` if decoding leaves nonzero padding.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let (mut acc, mut bits) = (0u32, 0u32);
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    let max = (1 << to) - 1;
+    for value in data {
+        acc = (acc << from) | u32::from(*value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+fn bech32_encode(hrp: &str, bytes: &[u8]) -> String {
+    let data = convert_bits(bytes, 8, 5, true).unwrap_or_default();
+    let checksum = polymod(expand_hrp(hrp).chain(data.iter().copied()).chain([0; 6])) ^ BECH32M;
+    let mut out = String::with_capacity(hrp.len() + 7 + data.len());
+    out.push_str(hrp);
+    out.push('1');
+    let symbols = data
+        .iter()
+        .copied()
+        .chain((0..6).rev().map(|i| ((checksum >> (5 * i)) & 31) as u8));
+    out.extend(symbols.map(|d| char::from(CHARSET[usize::from(d)])));
+    out
+}
+
+fn bech32_decode(hrp: &str, s: &str) -> Result<Vec<u8>, SharingError> {
+    if s.bytes().any(|c| c.is_ascii_lowercase()) && s.bytes().any(|c| c.is_ascii_uppercase()) {
+        return Err(SharingError::Malformed("bech32 mixed case"));
+    }
+    let s = s.to_ascii_lowercase();
+    let (prefix, data) = s
+        .rsplit_once('1')
+        .ok_or(SharingError::Malformed("bech32 share"))?;
+    if prefix != hrp {
+        return Err(SharingError::Malformed("bech32 prefix"));
+    }
+    let data = data
+        .bytes()
+        .map(|c| CHARSET.iter().position(|d| *d == c).map(|p| p as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(SharingError::Malformed("bech32 character"))?;
+    if data.len() < 6 {
+        return Err(SharingError::Malformed("bech32 share"));
+    }
+    if polymod(expand_hrp(hrp).chain(data.iter().copied())) != BECH32M {
+        return Err(SharingError::Malformed("bech32 checksum"));
+    }
+    convert_bits(&data[..data.len() - 6], 5, 8, false)
+        .ok_or(SharingError::Malformed("bech32 padding"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference_values() {
+        // BIP-350 test vector
+        assert!(bech32_decode("a", "a1lqfn3a").is_ok());
+        assert!(bech32_decode("a", "a1lqfn3q").is_err());
+        // the CRC-32C check value
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+    }
+}
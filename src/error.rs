@@ -0,0 +1,180 @@
+//! Errors
+//!
+//! [`Sharing`](crate::Sharing) and the file, discovery and archive APIs report failures
+//! as a [`SharingError`], so callers can tell missing shares apart from corrupt or foreign ones
+//! and a failing random number generator from invalid input.
+//!
+//! ```rust
+//! # #[cfg(feature = "krawczyk")] {
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! use sharing::{files, KrawczykSecretSharing, SharingError};
+//!
+//! let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+//! match files::reconstruct_file(&sharer, &[]) {
+//!     Err(SharingError::InsufficientShares { needed, found }) => assert_eq!((needed, found), (2, 0)),
+//!     other => panic!("unexpected {:?}", other),
+//! }
+//! # }
+//! ```
+use std::{convert::Infallible, error::Error, fmt, io};
+
+/// Why reading, checking or reconstructing shares failed.
+#[derive(Debug)]
+pub enum SharingError {
+    /// Fewer shares than the threshold were available.
+    InsufficientShares {
+        needed: usize,
+        found: usize,
+    },
+    /// Fewer participants than required signed the manifest.
+    InsufficientSignatures {
+        needed: usize,
+        found: usize,
+    },
+    /// The share with this id does not match its manifest or digest.
+    CorruptShare(u8),
+    /// More than one of the shares has this id.
+    DuplicateShareId(u8),
+    /// The threshold or number of shares cannot be dealt, e.g. `k` is zero or above `n`.
+    InvalidParameters,
+    /// The shares do not belong together or do not fit the sharer's parameters.
+    MismatchedParameters,
+    /// No shares were given at all.
+    EmptyShareSet,
+    /// The share with this id has size `found` where the first share has `expected`.
+    BodyLengthMismatch {
+        id: u8,
+        expected: usize,
+        found: usize,
+    },
+    /// Encoded with a format version this build does not read.
+    UnsupportedVersion(u8),
+    /// Bytes that are not a share or record in the expected encoding.
+    Malformed(&'static str),
+    /// Enough shares were given, but they do not reconstruct or authenticate.
+    ReconstructionFailed,
+    /// The random number generator failed, e.g. a hardware source that is not ready.
+    Rng(Box<dyn Error + Send + Sync>),
+    Io(io::Error),
+}
+
+#[cfg(any(feature = "shamir", feature = "rabin"))]
+impl SharingError {
+    /// The error for a failed reconstruction from `found` shares with threshold `needed`.
+    pub(crate) fn reconstruction(needed: u8, found: usize) -> Self {
+        if found < needed as usize {
+            SharingError::InsufficientShares {
+                needed: needed as usize,
+                found,
+            }
+        } else {
+            SharingError::ReconstructionFailed
+        }
+    }
+}
+
+impl fmt::Display for SharingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SharingError::InsufficientShares { needed, found } => {
+                write!(f, "{} shares needed, {} found", needed, found)
+            }
+            SharingError::InsufficientSignatures { needed, found } => {
+                write!(f, "{} signatures needed, {} found", needed, found)
+            }
+            SharingError::CorruptShare(id) => write!(f, "share {} is corrupt", id),
+            SharingError::DuplicateShareId(id) => write!(f, "share {} is given twice", id),
+            SharingError::InvalidParameters => f.write_str("invalid sharing parameters"),
+            SharingError::MismatchedParameters => f.write_str("mismatched sharing parameters"),
+            SharingError::EmptyShareSet => f.write_str("no shares given"),
+            SharingError::BodyLengthMismatch {
+                id,
+                expected,
+                found,
+            } => write!(f, "share {} has size {}, expected {}", id, found, expected),
+            SharingError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {}", version)
+            }
+            SharingError::Malformed(what) => write!(f, "malformed {}", what),
+            SharingError::ReconstructionFailed => f.write_str("shares do not reconstruct"),
+            SharingError::Rng(error) => write!(f, "random number generator failed: {}", error),
+            SharingError::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for SharingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SharingError::Rng(error) => Some(error.as_ref()),
+            SharingError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SharingError {
+    fn from(error: io::Error) -> Self {
+        SharingError::Io(error)
+    }
+}
+
+impl From<Infallible> for SharingError {
+    fn from(error: Infallible) -> Self {
+        match error {}
+    }
+}
+
+/// Error returned when a line-based text form, a [manifest](crate::manifest),
+/// [transcript](crate::transcript) or test vector, cannot be parsed.
+#[cfg(feature = "shamir")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTextError {
+    line: usize,
+    field: &'static str,
+}
+
+#[cfg(feature = "shamir")]
+impl ParseTextError {
+    pub(crate) fn new(line: usize, field: &'static str) -> Self {
+        Self { line, field }
+    }
+
+    /// The error for a field that is missing altogether.
+    pub(crate) fn missing(field: &'static str) -> Self {
+        Self::new(0, field)
+    }
+
+    /// Moves the error down by `lines`, for a form parsed in blocks.
+    #[cfg(feature = "testvectors")]
+    pub(crate) fn offset(mut self, lines: usize) -> Self {
+        if self.line > 0 {
+            self.line += lines;
+        }
+        self
+    }
+
+    /// The 1-based line of the error, 0 if a field is missing.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The key of the field that is missing or invalid.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+}
+
+#[cfg(feature = "shamir")]
+impl fmt::Display for ParseTextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "missing or invalid `{}`", self.field)
+        } else {
+            write!(f, "invalid `{}` on line {}", self.field, self.line)
+        }
+    }
+}
+
+#[cfg(feature = "shamir")]
+impl Error for ParseTextError {}
@@ -0,0 +1,124 @@
+//! Size Estimates
+//!
+//! Reports how large the shares of a scheme will be before any data is processed,
+//! so tools can show the cost of a sharing and choose between the schemes.
+//! Sizes are those of the [`ShareBytes`](crate::share::ShareBytes) encoding.
+//!
+//! ```rust
+//! use sharing::estimate;
+//!
+//! let shamir = estimate::shamir(5, 3, 1_000_000).unwrap();
+//! let rabin = estimate::rabin(5, 3, 1_000_000).unwrap();
+//! assert_eq!(shamir.share_size, 1_000_003);
+//! assert_eq!(rabin.share_size, 333_345);
+//! assert!(rabin.total_size < shamir.total_size);
+//! assert!(shamir.expansion() > 4.9);
+//!
+//! # #[cfg(feature = "krawczyk")] {
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! let krawczyk = estimate::krawczyk::<ChaCha20Poly1305>(5, 3, 1_000_000).unwrap();
+//! assert_eq!(krawczyk.share_size, 52 + 32 + 333_339);
+//! # }
+//!
+//! assert!(estimate::shamir(3, 5, 10).is_none());
+//! ```
+#[cfg(feature = "krawczyk")]
+use aead::{generic_array::typenum::Unsigned, AeadInPlace, KeyInit};
+
+use crate::Scheme;
+
+/// The size of the shares of a sharing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShareSizeReport {
+    pub scheme: Scheme,
+    pub n: u8,
+    pub k: u8,
+    pub data_len: usize,
+    /// The encoded size of a single share.
+    pub share_size: usize,
+    /// The encoded size of all `n` shares.
+    pub total_size: usize,
+}
+
+impl ShareSizeReport {
+    fn new(scheme: Scheme, n: u8, k: u8, data_len: usize, share_size: usize) -> Option<Self> {
+        if k < 1 || k > n {
+            return None;
+        }
+        Some(Self {
+            scheme,
+            n,
+            k,
+            data_len,
+            share_size,
+            total_size: share_size * n as usize,
+        })
+    }
+
+    /// The bytes stored beyond the data itself.
+    pub fn overhead(&self) -> usize {
+        self.total_size.saturating_sub(self.data_len)
+    }
+
+    /// The total size relative to the data, infinite for empty data.
+    pub fn expansion(&self) -> f64 {
+        self.total_size as f64 / self.data_len as f64
+    }
+
+    /// The bytes that have to be gathered to reconstruct.
+    pub fn reconstruct_size(&self) -> usize {
+        self.share_size * self.k as usize
+    }
+}
+
+/// Every [`ShamirShare`](crate::ShamirShare) is as large as the data.
+pub fn shamir(n: u8, k: u8, data_len: usize) -> Option<ShareSizeReport> {
+    ShareSizeReport::new(Scheme::Shamir, n, k, data_len, 3 + data_len)
+}
+
+/// Every [`RabinShare`](crate::RabinShare) holds a `k`th of the data.
+pub fn rabin(n: u8, k: u8, data_len: usize) -> Option<ShareSizeReport> {
+    let body = data_len.div_ceil(k.max(1) as usize);
+    ShareSizeReport::new(Scheme::Rabin, n, k, data_len, 11 + body)
+}
+
+/// Every [`KrawczykShare`](crate::KrawczykShare) holds a key share and a `k`th of the ciphertext,
+/// without compression and associated data.
+#[cfg(feature = "krawczyk")]
+pub fn krawczyk<C: AeadInPlace + KeyInit>(
+    n: u8,
+    k: u8,
+    data_len: usize,
+) -> Option<ShareSizeReport> {
+    let body = (data_len + C::TagSize::USIZE).div_ceil(k.max(1) as usize);
+    let share_size = 52 + C::KeySize::USIZE + body;
+    ShareSizeReport::new(Scheme::Krawczyk, n, k, data_len, share_size)
+}
+
+#[cfg(all(test, feature = "krawczyk"))]
+mod tests {
+    use crate::{share::ShareBytes, Sharing};
+    use chacha20poly1305::ChaCha20Poly1305;
+
+    #[test]
+    fn estimates_match_encoded_shares() {
+        let data = vec![3u8; 1001];
+        let shamir = crate::ShamirSecretSharing::new(4, 3, rand::thread_rng());
+        let rabin = crate::RabinInformationDispersal::new(4, 3);
+        let krawczyk =
+            crate::KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(4, 3, rand::thread_rng());
+        let sizes = [
+            shamir.share(data.clone()).unwrap()[0].to_bytes().len(),
+            rabin.share(data.clone()).unwrap()[0].to_bytes().len(),
+            krawczyk.share(data.clone()).unwrap()[0].to_bytes().len(),
+        ];
+        let estimates = [
+            super::shamir(4, 3, data.len()).unwrap().share_size,
+            super::rabin(4, 3, data.len()).unwrap().share_size,
+            super::krawczyk::<ChaCha20Poly1305>(4, 3, data.len())
+                .unwrap()
+                .share_size,
+        ];
+        assert_eq!(sizes, estimates);
+    }
+}
@@ -0,0 +1,98 @@
+//! Executors
+//!
+//! The parallel paths of the crate, e.g. `files::share_files_on` and
+//! `Combiner::reconstruct_on`, hand their work to an [`Executor`] instead of spawning
+//! threads themselves, so an async runtime's blocking pool or a custom thread pool
+//! can drive them.
+//!
+//! Provided are [`Sequential`], which runs everything on the calling thread, [`Threads`],
+//! which runs every task on a scoped OS thread, and with the `rayon` feature `Rayon`.
+//!
+//! ```rust
+//! use sharing::executor::{self, Sequential, Threads};
+//!
+//! let squares = executor::map(&Threads, (1..=4u32).collect(), |x| x * x);
+//! assert_eq!(squares, Some(vec![1, 4, 9, 16]));
+//! assert_eq!(executor::map(&Sequential, vec!["a", "bc"], str::len), Some(vec![1, 2]));
+//! ```
+use std::thread;
+
+/// A unit of work borrowed from the caller's stack.
+pub type Task<'scope> = Box<dyn FnOnce() + Send + 'scope>;
+
+/// Runs a batch of tasks, possibly in parallel.
+pub trait Executor: Sync {
+    /// Runs every task and returns once all have finished.
+    ///
+    /// Tasks may borrow from the caller, as none outlives the call.
+    fn scope<'scope>(&self, tasks: Vec<Task<'scope>>);
+}
+
+/// Runs the tasks one after the other on the calling thread.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Sequential;
+
+impl Executor for Sequential {
+    fn scope<'scope>(&self, tasks: Vec<Task<'scope>>) {
+        for task in tasks {
+            task();
+        }
+    }
+}
+
+/// Runs every task on its own scoped OS thread.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Threads;
+
+impl Executor for Threads {
+    fn scope<'scope>(&self, tasks: Vec<Task<'scope>>) {
+        thread::scope(|scope| {
+            for task in tasks {
+                scope.spawn(task);
+            }
+        });
+    }
+}
+
+/// Runs the tasks on the global rayon thread pool.
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rayon;
+
+#[cfg(feature = "rayon")]
+impl Executor for Rayon {
+    fn scope<'scope>(&self, tasks: Vec<Task<'scope>>) {
+        rayon::scope(|scope| {
+            for task in tasks {
+                scope.spawn(move |_| task());
+            }
+        });
+    }
+}
+
+impl<E: Executor + ?Sized> Executor for &E {
+    fn scope<'scope>(&self, tasks: Vec<Task<'scope>>) {
+        (**self).scope(tasks)
+    }
+}
+
+/// Applies `f` to every item as its own task, the results in input order.
+///
+/// `None` if the executor did not run every task.
+pub fn map<E, T, U, F>(executor: &E, items: Vec<T>, f: F) -> Option<Vec<U>>
+where
+    E: Executor + ?Sized,
+    T: Send,
+    U: Send,
+    F: Fn(T) -> U + Sync,
+{
+    let mut results: Vec<Option<U>> = (0..items.len()).map(|_| None).collect();
+    let f = &f;
+    let tasks = items
+        .into_iter()
+        .zip(results.iter_mut())
+        .map(|(item, result)| Box::new(move || *result = Some(f(item))) as Task<'_>)
+        .collect();
+    executor.scope(tasks);
+    results.into_iter().collect()
+}
@@ -0,0 +1,171 @@
+//! Share Combinators
+//!
+//! [`SharingExt`] adds combinators to the shares returned by [`Sharing::share`](crate::Sharing),
+//! for the per-share processing that usually follows: attaching a label, pairing every share
+//! with its holder and encrypting every share to its holder.
+//!
+//! ```rust
+//! use sharing::{ext::SharingExt, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let holders = ["alice", "bob", "carol"];
+//! let labelled = sharer
+//!     .share(b"root password".to_vec())
+//!     .unwrap()
+//!     .zip_metadata(holders)
+//!     .unwrap()
+//!     .tag_each("db-prod");
+//!
+//! let (share, holder) = &labelled[1].share;
+//! assert_eq!((labelled[1].tag, *holder, share.id), ("db-prod", "bob", 2));
+//!
+//! // any other per-share step
+//! let ids = labelled.map_shares(|tagged| tagged.share.0.id);
+//! assert_eq!(ids, vec![1, 2, 3]);
+//! ```
+use crate::error::SharingError;
+#[cfg(feature = "krawczyk")]
+use {
+    crate::{
+        secret::try_fill,
+        share::{decode_error, ShareBytes, ShareId},
+    },
+    aead::{AeadInPlace, Key, KeyInit, Nonce},
+    rand::Rng,
+};
+
+/// A share with a tag attached to it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tagged<T, S> {
+    pub tag: T,
+    pub share: S,
+}
+
+/// A share encrypted to its holder with [`SharingExt::encrypt_each`].
+#[cfg(feature = "krawczyk")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sealed {
+    /// The id of the encrypted share, authenticated with it.
+    pub id: u8,
+    pub nonce: Vec<u8>,
+    /// The encoded share and the tag.
+    pub ciphertext: Vec<u8>,
+}
+
+#[cfg(feature = "krawczyk")]
+impl Sealed {
+    /// Decrypts the share with the holder's key.
+    pub fn open<C, S>(&self, key: &Key<C>) -> Result<S, SharingError>
+    where
+        C: AeadInPlace + KeyInit,
+        S: ShareBytes,
+    {
+        if self.nonce.len() != Nonce::<C>::default().len() {
+            return Err(SharingError::Malformed("nonce"));
+        }
+        let mut bytes = self.ciphertext.clone();
+        C::new(key)
+            .decrypt_in_place(Nonce::<C>::from_slice(&self.nonce), &[self.id], &mut bytes)
+            .map_err(|_| SharingError::CorruptShare(self.id))?;
+        S::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))
+    }
+}
+
+/// Combinators over the shares of one sharing.
+pub trait SharingExt<S>: Sized {
+    /// Transforms every share.
+    fn map_shares<U, F: FnMut(S) -> U>(self, f: F) -> Vec<U>;
+
+    /// Attaches the same tag to every share, e.g. the name of the secret.
+    fn tag_each<T: Clone>(self, tag: T) -> Vec<Tagged<T, S>>;
+
+    /// Pairs the shares with one item of metadata each, e.g. their holders,
+    /// failing unless the numbers match.
+    fn zip_metadata<M, I: IntoIterator<Item = M>>(
+        self,
+        metadata: I,
+    ) -> Result<Vec<(S, M)>, SharingError>;
+
+    /// Encrypts every share with the key of the recipient at the same position,
+    /// failing unless the numbers match.
+    #[cfg(feature = "krawczyk")]
+    fn encrypt_each<C, R>(
+        self,
+        recipient_keys: &[Key<C>],
+        rng: &mut R,
+    ) -> Result<Vec<Sealed>, SharingError>
+    where
+        C: AeadInPlace + KeyInit,
+        R: Rng + ?Sized,
+        S: ShareBytes + ShareId;
+}
+
+impl<S> SharingExt<S> for Vec<S> {
+    fn map_shares<U, F: FnMut(S) -> U>(self, f: F) -> Vec<U> {
+        self.into_iter().map(f).collect()
+    }
+
+    fn tag_each<T: Clone>(self, tag: T) -> Vec<Tagged<T, S>> {
+        self.map_shares(|share| Tagged {
+            tag: tag.clone(),
+            share,
+        })
+    }
+
+    fn zip_metadata<M, I: IntoIterator<Item = M>>(
+        self,
+        metadata: I,
+    ) -> Result<Vec<(S, M)>, SharingError> {
+        let metadata: Vec<M> = metadata.into_iter().collect();
+        if metadata.len() != self.len() {
+            return Err(SharingError::MismatchedParameters);
+        }
+        Ok(self.into_iter().zip(metadata).collect())
+    }
+
+    /// ```rust
+    /// use chacha20poly1305::{ChaCha20Poly1305, Key};
+    /// use sharing::{ext::SharingExt, ShamirSecretSharing, ShamirShare, Sharing};
+    ///
+    /// let keys = [Key::from([1; 32]), Key::from([2; 32]), Key::from([3; 32])];
+    /// let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+    /// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+    /// let sealed = shares
+    ///     .clone()
+    ///     .encrypt_each::<ChaCha20Poly1305, _>(&keys, &mut rand::thread_rng())
+    ///     .unwrap();
+    ///
+    /// let opened: ShamirShare = sealed[2].open::<ChaCha20Poly1305, _>(&keys[2]).unwrap();
+    /// assert_eq!(opened, shares[2]);
+    /// assert!(sealed[2].open::<ChaCha20Poly1305, ShamirShare>(&keys[0]).is_err());
+    /// ```
+    #[cfg(feature = "krawczyk")]
+    fn encrypt_each<C, R>(
+        self,
+        recipient_keys: &[Key<C>],
+        rng: &mut R,
+    ) -> Result<Vec<Sealed>, SharingError>
+    where
+        C: AeadInPlace + KeyInit,
+        R: Rng + ?Sized,
+        S: ShareBytes + ShareId,
+    {
+        self.zip_metadata(recipient_keys)?
+            .into_iter()
+            .map(|(share, key)| {
+                let id = share.id();
+                let mut nonce = Nonce::<C>::default();
+                try_fill(rng, &mut nonce)?;
+                let mut ciphertext = share.to_bytes();
+                C::new(key)
+                    .encrypt_in_place(&nonce, &[id], &mut ciphertext)
+                    .map_err(|_| SharingError::Malformed("share too long"))?;
+                Ok(Sealed {
+                    id,
+                    nonce: nonce.to_vec(),
+                    ciphertext,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,620 @@
+//! File Sharding
+//!
+//! Shares whole files with [`KrawczykSecretSharing`] and writes one file per share,
+//! in the [`ShareBytes`] encoding, named `<input file name>.<id>.share`.
+//! [`share_files`] processes many files with a bounded pool of worker threads,
+//! or on any [`Executor`] with [`share_files_on`], and reports where every share went together with the SHA-256 of every input.
+//!
+//! ```rust
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! use sharing::{files, KrawczykSecretSharing};
+//!
+//! let dir = std::env::temp_dir().join(format!("sharing-files-doc-{}", std::process::id()));
+//! std::fs::create_dir_all(&dir).unwrap();
+//! let inputs: Vec<_> = (0..4)
+//!     .map(|i| {
+//!         let path = dir.join(format!("file-{}.txt", i));
+//!         std::fs::write(&path, format!("contents {}", i)).unwrap();
+//!         path
+//!     })
+//!     .collect();
+//!
+//! let sharer = || KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+//! let report = files::share_files(sharer, &inputs, &dir.join("shares"), 2);
+//! assert!(report.is_success());
+//!
+//! let shared = report.files[1].result.as_ref().unwrap();
+//! let paths: Vec<_> = shared.shares[1..].iter().map(|(_, path)| path.as_path()).collect();
+//! assert_eq!(files::reconstruct_file(&sharer(), &paths).unwrap(), b"contents 1");
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use crate::{
+    error::SharingError,
+    executor::{Executor, Task, Threads},
+    share::{
+        decode_error, parse_hex, Hex, KrawczykFrameShare, KrawczykShare, ShamirShare, ShareBytes,
+    },
+    KrawczykSecretSharing, Sharing,
+};
+use aead::{AeadInPlace, KeyInit};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::{
+    convert::TryFrom,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    sync::Mutex,
+};
+
+/// The outcome of sharing a batch of files.
+#[derive(Debug)]
+pub struct Report {
+    /// One entry per input, in input order.
+    pub files: Vec<FileReport>,
+}
+
+impl Report {
+    pub fn is_success(&self) -> bool {
+        self.files.iter().all(|f| f.result.is_ok())
+    }
+}
+
+/// The outcome of sharing a single file.
+#[derive(Debug)]
+pub struct FileReport {
+    pub input: PathBuf,
+    pub result: Result<SharedFile, SharingError>,
+}
+
+/// Where the shares of a file were written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedFile {
+    /// SHA-256 of the input file.
+    pub digest: [u8; 32],
+    /// The id and path of every share.
+    pub shares: Vec<(u8, PathBuf)>,
+}
+
+/// Shares `input` and writes its shares into `output`, creating the directory if needed.
+pub fn share_file<C: AeadInPlace + KeyInit, R: Rng>(
+    sharer: &KrawczykSecretSharing<C, R>,
+    input: &Path,
+    output: &Path,
+) -> Result<SharedFile, SharingError> {
+    let data = fs::read(input)?;
+    let digest = Sha256::digest(&data).into();
+    let shares = sharer.share(data)?;
+    let name = input
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "input has no file name"))?;
+    fs::create_dir_all(output)?;
+    let shares = shares
+        .iter()
+        .map(|share| {
+            let mut file_name = name.to_os_string();
+            file_name.push(format!(".{}.share", share.id));
+            let path = output.join(file_name);
+            fs::write(&path, share.to_bytes())?;
+            Ok((share.id, path))
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(SharedFile { digest, shares })
+}
+
+/// Reads share files written by [`share_file`] and reconstructs the original file contents.
+pub fn reconstruct_file<C: AeadInPlace + KeyInit, R: Rng>(
+    sharer: &KrawczykSecretSharing<C, R>,
+    shares: &[&Path],
+) -> Result<Vec<u8>, SharingError> {
+    let shares = shares
+        .iter()
+        .map(|path| {
+            let bytes = fs::read(path)?;
+            KrawczykShare::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    sharer.reconstruct(shares)
+}
+
+/// Shares every input into `output` with at most `workers` threads.
+///
+/// Every worker builds its own sharer with `sharer`, so no RNG is shared between threads.
+/// A failing file does not stop the others, its error is part of the report.
+/// Inputs need distinct file names, as all shares go into the same directory.
+pub fn share_files<C, R, F>(sharer: F, inputs: &[PathBuf], output: &Path, workers: usize) -> Report
+where
+    C: AeadInPlace + KeyInit,
+    R: Rng,
+    F: Fn() -> KrawczykSecretSharing<C, R> + Sync,
+{
+    share_files_on(&Threads, sharer, inputs, output, workers)
+}
+
+/// Like [`share_files`], but runs the `workers` on `executor` instead of OS threads.
+pub fn share_files_on<E, C, R, F>(
+    executor: &E,
+    sharer: F,
+    inputs: &[PathBuf],
+    output: &Path,
+    workers: usize,
+) -> Report
+where
+    E: Executor + ?Sized,
+    C: AeadInPlace + KeyInit,
+    R: Rng,
+    F: Fn() -> KrawczykSecretSharing<C, R> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..inputs.len()).map(|_| None).collect::<Vec<_>>());
+    let worker = || {
+        let sharer = sharer();
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let input = match inputs.get(index) {
+                Some(input) => input,
+                None => break,
+            };
+            let result = share_file(&sharer, input, output);
+            results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+        }
+    };
+    let worker = &worker;
+    executor.scope(
+        (0..workers.clamp(1, inputs.len().max(1)))
+            .map(|_| Box::new(worker) as Task<'_>)
+            .collect(),
+    );
+    let results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    Report {
+        files: inputs
+            .iter()
+            .zip(results)
+            .map(|(input, result)| FileReport {
+                input: input.clone(),
+                result: result.unwrap_or_else(|| {
+                    Err(io::Error::other("not processed by the executor").into())
+                }),
+            })
+            .collect(),
+    }
+}
+
+/// Where the shares of a file shared with [`share_file_resumable`] were written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamedFile {
+    /// The id and path of every key share, `<input file name>.<id>.key`.
+    pub keys: Vec<(u8, PathBuf)>,
+    /// The id and path of every frame file, `<input file name>.<id>.frames`.
+    pub frames: Vec<(u8, PathBuf)>,
+}
+
+/// Shares a file of any size frame by frame with the [`stream`](crate::stream) construction.
+///
+/// Progress is recorded in `<input file name>.checkpoint` after every frame,
+/// so calling this again after an interruption resumes at the last completed frame,
+/// in a new [session](KrawczykSecretSharing::resume_encryptor) counted in the checkpoint.
+/// The checkpoint is removed once the final frame is written.
+///
+/// The checkpoint also holds the length and SHA-256 of the input, resuming fails with
+/// [`SharingError::MismatchedParameters`] if the input changed in between, as its frames
+/// would not continue the ones already written.
+pub fn share_file_resumable<C: AeadInPlace + KeyInit, R: Rng>(
+    sharer: &KrawczykSecretSharing<C, R>,
+    input: &Path,
+    output: &Path,
+    frame_size: usize,
+) -> Result<StreamedFile, SharingError> {
+    share_frames(sharer, input, output, frame_size, None)
+}
+
+/// Reconstructs a file shared with [`share_file_resumable`] into `out`,
+/// from `k` key shares and the frame files of the same holders.
+pub fn reconstruct_file_resumable<C: AeadInPlace + KeyInit, R: Rng, W: Write>(
+    sharer: &KrawczykSecretSharing<C, R>,
+    keys: &[&Path],
+    frames: &[&Path],
+    mut out: W,
+) -> Result<(), SharingError> {
+    let keys = keys
+        .iter()
+        .map(|path| {
+            let bytes = fs::read(path)?;
+            ShamirShare::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut decryptor = sharer.decryptor(keys)?;
+    let mut frames = frames
+        .iter()
+        .map(|path| Ok(BufReader::new(File::open(path)?)))
+        .collect::<io::Result<Vec<_>>>()?;
+    while !decryptor.is_complete() {
+        let shares = frames
+            .iter_mut()
+            .map(read_frame)
+            .collect::<Result<Vec<_>, _>>()?;
+        let frame = decryptor.decrypt_frame(shares)?;
+        out.write_all(&frame)?;
+    }
+    Ok(out.flush()?)
+}
+
+/// Shares the frames of `input`, returning [`io::ErrorKind::Interrupted`] after `limit` frames.
+fn share_frames<C: AeadInPlace + KeyInit, R: Rng>(
+    sharer: &KrawczykSecretSharing<C, R>,
+    input: &Path,
+    output: &Path,
+    frame_size: usize,
+    limit: Option<u32>,
+) -> Result<StreamedFile, SharingError> {
+    if frame_size == 0 {
+        return Err(SharingError::MismatchedParameters);
+    }
+    let name = input
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "input has no file name"))?;
+    let path = |suffix: String| {
+        let mut file_name = name.to_os_string();
+        file_name.push(suffix);
+        output.join(file_name)
+    };
+    let checkpoint_path = path(".checkpoint".to_string());
+    fs::create_dir_all(output)?;
+    let mut input = File::open(input)?;
+    let (length, digest) = digest_file(&mut input)?;
+
+    let (mut encryptor, mut checkpoint) = if checkpoint_path.exists() {
+        let mut checkpoint: Checkpoint = fs::read_to_string(&checkpoint_path)?.parse()?;
+        if checkpoint.frame_size != frame_size || checkpoint.input != (length, digest) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let keys = checkpoint
+            .lengths
+            .iter()
+            .map(|(id, length)| {
+                let frames = OpenOptions::new()
+                    .write(true)
+                    .open(path(format!(".{}.frames", id)))?;
+                // drop a partially written frame
+                frames.set_len(*length)?;
+                let bytes = fs::read(path(format!(".{}.key", id)))?;
+                ShamirShare::from_bytes(&bytes).ok_or_else(|| decode_error(&bytes))
+            })
+            .collect::<Result<Vec<_>, SharingError>>()?;
+        checkpoint.session = checkpoint
+            .session
+            .checked_add(1)
+            .ok_or(SharingError::InvalidParameters)?;
+        // the frame after the checkpoint may have been sealed already, never in this session
+        checkpoint.write(&checkpoint_path)?;
+        let encryptor = sharer.resume_encryptor(keys, checkpoint.session, checkpoint.frames)?;
+        (encryptor, checkpoint)
+    } else {
+        let (encryptor, keys) = sharer.encryptor()?;
+        for key in &keys {
+            fs::write(path(format!(".{}.key", key.id)), key.to_bytes())?;
+            File::create(path(format!(".{}.frames", key.id)))?;
+        }
+        let checkpoint = Checkpoint {
+            frame_size,
+            input: (length, digest),
+            session: 0,
+            frames: 0,
+            lengths: keys.iter().map(|key| (key.id, 0)).collect(),
+        };
+        checkpoint.write(&checkpoint_path)?;
+        (encryptor, checkpoint)
+    };
+
+    let mut files = checkpoint
+        .lengths
+        .iter()
+        .map(|(id, _)| {
+            OpenOptions::new()
+                .append(true)
+                .open(path(format!(".{}.frames", id)))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    input.seek(SeekFrom::Start(
+        checkpoint.frames as u64 * frame_size as u64,
+    ))?;
+
+    let interrupted = |written| {
+        if limit == Some(written) {
+            Err(SharingError::Io(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "frame limit reached",
+            )))
+        } else {
+            Ok(())
+        }
+    };
+    let mut written = 0;
+    let mut chunk = read_chunk(&mut input, frame_size)?;
+    let mut next = read_chunk(&mut input, frame_size)?;
+    // a short or empty chunk is always followed by an empty one, which ends the stream
+    while !next.is_empty() {
+        interrupted(written)?;
+        let shares = encryptor.encrypt_frame(chunk)?;
+        append_frames(&mut files, &mut checkpoint.lengths, shares)?;
+        checkpoint.frames += 1;
+        checkpoint.write(&checkpoint_path)?;
+        written += 1;
+        chunk = next;
+        next = read_chunk(&mut input, frame_size)?;
+    }
+    interrupted(written)?;
+    let shares = encryptor.encrypt_last(chunk)?;
+    append_frames(&mut files, &mut checkpoint.lengths, shares)?;
+    fs::remove_file(&checkpoint_path)?;
+
+    let ids = checkpoint.lengths.iter().map(|(id, _)| *id);
+    Ok(StreamedFile {
+        keys: ids
+            .clone()
+            .map(|id| (id, path(format!(".{}.key", id))))
+            .collect(),
+        frames: ids
+            .map(|id| (id, path(format!(".{}.frames", id))))
+            .collect(),
+    })
+}
+
+/// Appends a frame share to the file of every holder.
+fn append_frames(
+    files: &mut [File],
+    lengths: &mut [(u8, u64)],
+    shares: Vec<KrawczykFrameShare>,
+) -> io::Result<()> {
+    for ((file, (_, length)), share) in files.iter_mut().zip(lengths.iter_mut()).zip(shares) {
+        let record = frame_to_bytes(&share);
+        file.write_all(&record)?;
+        file.sync_data()?;
+        *length += record.len() as u64;
+    }
+    Ok(())
+}
+
+/// The length and SHA-256 of the whole input.
+fn digest_file(input: &mut File) -> io::Result<(u64, [u8; 32])> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut length = 0;
+    loop {
+        match input.read(&mut buf)? {
+            0 => return Ok((length, hasher.finalize().into())),
+            read => {
+                hasher.update(&buf[..read]);
+                length += read as u64;
+            }
+        }
+    }
+}
+
+/// Reads up to `size` bytes, fewer only at the end of the input.
+fn read_chunk(input: &mut File, size: usize) -> io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(size);
+    input.take(size as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// `id || session (u32) || index (u32) || last || length (u64) || body length (u32) || body`,
+/// integers big endian
+fn frame_to_bytes(share: &KrawczykFrameShare) -> Vec<u8> {
+    let mut out = Vec::with_capacity(22 + share.body.len());
+    out.push(share.id);
+    out.extend_from_slice(&share.session.to_be_bytes());
+    out.extend_from_slice(&share.index.to_be_bytes());
+    out.push(share.last as u8);
+    out.extend_from_slice(&(share.length as u64).to_be_bytes());
+    out.extend_from_slice(&(share.body.len() as u32).to_be_bytes());
+    out.extend_from_slice(&share.body);
+    out
+}
+
+fn read_frame<B: BufRead>(reader: &mut B) -> Result<KrawczykFrameShare, SharingError> {
+    let mut header = [0u8; 22];
+    reader.read_exact(&mut header)?;
+    let mut session = [0u8; 4];
+    session.copy_from_slice(&header[1..5]);
+    let mut index = [0u8; 4];
+    index.copy_from_slice(&header[5..9]);
+    let mut length = [0u8; 8];
+    length.copy_from_slice(&header[10..18]);
+    let mut body_length = [0u8; 4];
+    body_length.copy_from_slice(&header[18..22]);
+    // the body grows with the bytes actually read, so a forged length allocates nothing
+    let body_length = u64::from(u32::from_be_bytes(body_length));
+    let mut body = Vec::new();
+    reader.by_ref().take(body_length).read_to_end(&mut body)?;
+    if body.len() as u64 != body_length {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(KrawczykFrameShare {
+        id: header[0],
+        session: u32::from_be_bytes(session),
+        index: u32::from_be_bytes(index),
+        last: match header[9] {
+            0 => false,
+            1 => true,
+            _ => return Err(malformed("frame")),
+        },
+        length: usize::try_from(u64::from_be_bytes(length)).map_err(|_| malformed("frame"))?,
+        body,
+    })
+}
+
+const CHECKPOINT_VERSION: u32 = 2;
+
+/// Progress of [`share_file_resumable`], in the same `key = value` format as the test vectors.
+struct Checkpoint {
+    frame_size: usize,
+    /// The length and SHA-256 of the input being shared.
+    input: (u64, [u8; 32]),
+    /// The session of the last run, each resumption starts the next.
+    session: u32,
+    /// Frames completed by every holder.
+    frames: u32,
+    /// The id of every holder and the length of its frame file after `frames` frames.
+    lengths: Vec<(u8, u64)>,
+}
+
+impl Checkpoint {
+    /// Replaces the checkpoint atomically, so an interruption never leaves half of it.
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let mut text = format!(
+            "version = {}\nframe_size = {}\ninput = {} {}\nsession = {}\nframes = {}\n",
+            CHECKPOINT_VERSION,
+            self.frame_size,
+            self.input.0,
+            Hex(&self.input.1),
+            self.session,
+            self.frames
+        );
+        for (id, length) in &self.lengths {
+            text.push_str(&format!("share = {} {}\n", id, length));
+        }
+        let tmp = path.with_extension("checkpoint.tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(tmp, path)
+    }
+}
+
+impl FromStr for Checkpoint {
+    type Err = SharingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut version = None;
+        let mut frame_size = None;
+        let mut input = None;
+        let mut session = None;
+        let mut frames = None;
+        let mut lengths = Vec::new();
+        for line in s.lines().filter(|l| !l.trim().is_empty()) {
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().ok_or_else(|| malformed("checkpoint"))?.trim();
+            match key {
+                "version" => version = value.parse::<u32>().ok(),
+                "frame_size" => frame_size = value.parse().ok(),
+                "input" => {
+                    let mut fields = value.split_whitespace();
+                    input = match (fields.next(), fields.next(), fields.next()) {
+                        (Some(length), Some(digest), None) => length.parse().ok().zip(
+                            parse_hex(digest)
+                                .ok()
+                                .and_then(|d| <[u8; 32]>::try_from(d).ok()),
+                        ),
+                        _ => return Err(malformed("checkpoint")),
+                    }
+                }
+                "session" => session = value.parse().ok(),
+                "frames" => frames = value.parse().ok(),
+                "share" => {
+                    let mut fields = value.split_whitespace().map(str::parse::<u64>);
+                    match (fields.next(), fields.next(), fields.next()) {
+                        (Some(Ok(id)), Some(Ok(length)), None) if id <= u8::MAX as u64 => {
+                            lengths.push((id as u8, length))
+                        }
+                        _ => return Err(malformed("checkpoint")),
+                    }
+                }
+                _ => return Err(malformed("checkpoint")),
+            }
+        }
+        if version != Some(CHECKPOINT_VERSION) || lengths.is_empty() {
+            return Err(malformed("checkpoint"));
+        }
+        Ok(Checkpoint {
+            frame_size: frame_size.ok_or_else(|| malformed("checkpoint"))?,
+            input: input.ok_or_else(|| malformed("checkpoint"))?,
+            session: session.ok_or_else(|| malformed("checkpoint"))?,
+            frames: frames.ok_or_else(|| malformed("checkpoint"))?,
+            lengths,
+        })
+    }
+}
+
+fn malformed(what: &'static str) -> SharingError {
+    SharingError::Malformed(what)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chacha20poly1305::ChaCha20Poly1305;
+
+    #[test]
+    fn resume_after_interruption() {
+        let dir = std::env::temp_dir().join(format!("sharing-resume-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.bin");
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        fs::write(&input, &data).unwrap();
+        let output = dir.join("shares");
+        let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(4, 2, rand::thread_rng());
+
+        let interrupted = share_frames(&sharer, &input, &output, 1000, Some(3)).unwrap_err();
+        assert!(
+            matches!(interrupted, SharingError::Io(ref e) if e.kind() == io::ErrorKind::Interrupted)
+        );
+        assert!(output.join("input.bin.checkpoint").exists());
+        // every resumption seals in a new session
+        share_frames(&sharer, &input, &output, 1000, Some(2)).unwrap_err();
+        let checkpoint = output.join("input.bin.checkpoint");
+        let checkpoint: Checkpoint = fs::read_to_string(checkpoint).unwrap().parse().unwrap();
+        assert_eq!((checkpoint.session, checkpoint.frames), (1, 5));
+
+        let shared = share_file_resumable(&sharer, &input, &output, 1000).unwrap();
+        assert!(!output.join("input.bin.checkpoint").exists());
+
+        let keys: Vec<_> = shared.keys[2..].iter().map(|(_, p)| p.as_path()).collect();
+        let frames: Vec<_> = shared.frames[2..]
+            .iter()
+            .map(|(_, p)| p.as_path())
+            .collect();
+        let mut rec = Vec::new();
+        reconstruct_file_resumable(&sharer, &keys, &frames, &mut rec).unwrap();
+        assert_eq!(rec, data);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn resume_rejects_a_changed_input() {
+        let dir = std::env::temp_dir().join(format!("sharing-changed-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.bin");
+        fs::write(&input, vec![1u8; 5000]).unwrap();
+        let output = dir.join("shares");
+        let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+
+        share_frames(&sharer, &input, &output, 1000, Some(2)).unwrap_err();
+        fs::write(&input, vec![2u8; 5000]).unwrap();
+        assert!(matches!(
+            share_file_resumable(&sharer, &input, &output, 1000),
+            Err(SharingError::MismatchedParameters)
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn forged_frame_length_is_not_allocated() {
+        let share = KrawczykFrameShare {
+            id: 1,
+            session: 0,
+            index: 0,
+            last: true,
+            length: 3,
+            body: vec![1, 2, 3],
+        };
+        let mut record = frame_to_bytes(&share);
+        assert_eq!(read_frame(&mut &record[..]).unwrap(), share);
+        record[18..22].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            read_frame(&mut &record[..]),
+            Err(SharingError::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+//! Fixed Capacity Shares
+//!
+//! Shamir sharing of small secrets, e.g. device keys in firmware, without any heap allocation.
+//! A [`FixedShare`] stores its body inline in a [`heapless::Vec`] of capacity `N`, and
+//! [`share`] and [`reconstruct`] only use the stack and the static field tables.
+//!
+//! The shares are the same as those of [`ShamirSecretSharing`](crate::ShamirSecretSharing)
+//! with the default field and ids `1..=n`, so both can be mixed.
+//!
+//! ```rust
+//! use sharing::{fixed::{self, FixedShare}, ShamirSecretSharing, ShamirShare, Sharing};
+//!
+//! let key = [0x42u8; 32];
+//! let shares: heapless::Vec<FixedShare<32>, 5> =
+//!     fixed::share(&key, 5, 3, &mut rand::thread_rng()).unwrap();
+//!
+//! let recovered = fixed::reconstruct(&shares[1..4], 3).unwrap();
+//! assert_eq!(recovered[..], key[..]);
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let shares: Vec<ShamirShare> = shares.into_iter().map(ShamirShare::from).collect();
+//! assert_eq!(sharer.reconstruct(shares[2..].to_vec()).unwrap(), key.to_vec());
+//! ```
+use crate::{math, secret::wipe, share::ShamirShare};
+use rand::RngCore;
+use std::convert::TryFrom;
+
+/// A Shamir share of at most `N` bytes, stored inline.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedShare<const N: usize> {
+    pub id: u8,
+    pub body: heapless::Vec<u8, N>,
+}
+
+/// Shares `secret` into `n` shares with threshold `k` and ids `1..=n`.
+///
+/// `None` unless `1 <= k <= n`, `n <= M` and the secret fits into `N` bytes,
+/// or if the random number generator fails.
+pub fn share<R: RngCore, const N: usize, const M: usize>(
+    secret: &[u8],
+    n: u8,
+    k: u8,
+    rng: &mut R,
+) -> Option<heapless::Vec<FixedShare<N>, M>> {
+    if k < 1 || k > n || n as usize > M || secret.len() > N {
+        return None;
+    }
+    let mut shares = heapless::Vec::new();
+    for id in 1..=n {
+        shares
+            .push(FixedShare {
+                id,
+                body: heapless::Vec::new(),
+            })
+            .ok()?;
+    }
+    let mut coefficients = [0u8; 254];
+    let coefficients = &mut coefficients[..k as usize - 1];
+    for byte in secret {
+        if rng.try_fill_bytes(coefficients).is_err() {
+            wipe(coefficients);
+            return None;
+        }
+        for share in shares.iter_mut() {
+            let value = math::encode_byte(*byte, coefficients, share.id);
+            share.body.push(value).ok()?;
+        }
+    }
+    wipe(coefficients);
+    Some(shares)
+}
+
+/// Reconstructs the secret from the first `k` shares.
+///
+/// `None` if there are fewer than `k`, their ids are not distinct or their lengths differ.
+pub fn reconstruct<const N: usize>(
+    shares: &[FixedShare<N>],
+    k: u8,
+) -> Option<heapless::Vec<u8, N>> {
+    if k < 1 || shares.len() < k as usize {
+        return None;
+    }
+    let shares = &shares[..k as usize];
+    let size = shares[0].body.len();
+    if shares.iter().any(|share| share.body.len() != size) {
+        return None;
+    }
+    let mut points = [(0u8, 0u8); 255];
+    let points = &mut points[..k as usize];
+    let mut secret = heapless::Vec::new();
+    for i in 0..size {
+        for (point, share) in points.iter_mut().zip(shares) {
+            *point = (share.id, share.body[i]);
+        }
+        secret.push(math::interpolate_byte(points)?).ok()?;
+    }
+    for point in points.iter_mut() {
+        *point = (0, 0);
+    }
+    Some(secret)
+}
+
+impl<const N: usize> From<FixedShare<N>> for ShamirShare {
+    fn from(share: FixedShare<N>) -> Self {
+        Self {
+            id: share.id,
+            body: share.body.to_vec(),
+        }
+    }
+}
+
+/// Fails with the share if its body is longer than `N`.
+impl<const N: usize> TryFrom<ShamirShare> for FixedShare<N> {
+    type Error = ShamirShare;
+
+    fn try_from(share: ShamirShare) -> Result<Self, Self::Error> {
+        match heapless::Vec::from_slice(&share.body) {
+            Ok(body) => Ok(Self { id: share.id, body }),
+            Err(()) => Err(share),
+        }
+    }
+}
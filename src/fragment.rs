@@ -0,0 +1,238 @@
+//! Fragmentation
+//!
+//! Splits a serialized share into numbered fragments of a bounded size, e.g. for QR codes,
+//! SMS or NFC tags, and puts them back together in any order with a [`Reassembler`].
+//!
+//! Every fragment is `version || tag (u32) || index (u16) || count (u16) || payload`,
+//! integers big endian. The tag is a checksum of the whole share, so fragments of different
+//! shares are not mixed up and a reassembled share is checked before it is returned.
+//!
+//! ```rust
+//! use sharing::{fragment::{self, Reassembler}, share::ShareBytes, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares = sharer.share(vec![7u8; 5000]).unwrap();
+//! let bytes = shares[0].to_bytes();
+//!
+//! let fragments = fragment::fragment(&bytes, 2048).unwrap();
+//! assert_eq!(fragments.len(), 3);
+//!
+//! let mut reassembler = Reassembler::new();
+//! for fragment in fragments.iter().rev() {
+//!     reassembler.add(fragment::Fragment::from_bytes(&fragment.to_bytes()).unwrap()).unwrap();
+//! }
+//! assert!(reassembler.is_complete());
+//! assert_eq!(reassembler.finish().unwrap(), bytes);
+//! ```
+use std::{convert::TryFrom, error::Error, fmt};
+
+const VERSION: u8 = 1;
+const HEADER: usize = 9;
+
+/// One numbered piece of a fragmented share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub tag: u32,
+    /// Position of the fragment, starting at zero.
+    pub index: u16,
+    /// Total number of fragments.
+    pub count: u16,
+    pub payload: Vec<u8>,
+}
+
+impl Fragment {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER + self.payload.len());
+        out.push(VERSION);
+        out.extend_from_slice(&self.tag.to_be_bytes());
+        out.extend_from_slice(&self.index.to_be_bytes());
+        out.extend_from_slice(&self.count.to_be_bytes());
+        out.extend_from_slice(&self.payload);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [VERSION, t0, t1, t2, t3, i0, i1, c0, c1, payload @ ..] => {
+                let fragment = Self {
+                    tag: u32::from_be_bytes([*t0, *t1, *t2, *t3]),
+                    index: u16::from_be_bytes([*i0, *i1]),
+                    count: u16::from_be_bytes([*c0, *c1]),
+                    payload: payload.to_vec(),
+                };
+                if fragment.index < fragment.count {
+                    Some(fragment)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Splits `data` into fragments whose encoding is at most `max_size` bytes.
+///
+/// `None` if `max_size` leaves no room for a payload or more than `u16::MAX` fragments are needed.
+/// Empty data gives a single empty fragment.
+pub fn fragment(data: &[u8], max_size: usize) -> Option<Vec<Fragment>> {
+    let size = max_size.checked_sub(HEADER).filter(|s| *s > 0)?;
+    let count = u16::try_from(data.len().div_ceil(size).max(1)).ok()?;
+    let tag = checksum(data);
+    let mut fragments: Vec<Fragment> = data
+        .chunks(size)
+        .enumerate()
+        .map(|(index, payload)| Fragment {
+            tag,
+            index: index as u16,
+            count,
+            payload: payload.to_vec(),
+        })
+        .collect();
+    if fragments.is_empty() {
+        fragments.push(Fragment {
+            tag,
+            index: 0,
+            count,
+            payload: Vec::new(),
+        });
+    }
+    Some(fragments)
+}
+
+/// Collects the fragments of one share in any order.
+#[derive(Debug, Clone, Default)]
+pub struct Reassembler {
+    tag: Option<u32>,
+    pieces: Vec<Option<Vec<u8>>>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a fragment, the first one decides which share is being reassembled.
+    pub fn add(&mut self, fragment: Fragment) -> Result<(), FragmentError> {
+        match self.tag {
+            None => {
+                self.tag = Some(fragment.tag);
+                self.pieces = vec![None; fragment.count as usize];
+            }
+            Some(tag) if tag != fragment.tag || self.pieces.len() != fragment.count as usize => {
+                return Err(FragmentError::Foreign);
+            }
+            Some(_) => {}
+        }
+        let piece = self
+            .pieces
+            .get_mut(fragment.index as usize)
+            .ok_or(FragmentError::Foreign)?;
+        if piece.is_some() {
+            return Err(FragmentError::Duplicate(fragment.index));
+        }
+        *piece = Some(fragment.payload);
+        Ok(())
+    }
+
+    /// The indices still missing, all of them before the first fragment is known.
+    pub fn missing(&self) -> Vec<u16> {
+        self.pieces
+            .iter()
+            .enumerate()
+            .filter(|(_, piece)| piece.is_none())
+            .map(|(index, _)| index as u16)
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.tag.is_some() && self.pieces.iter().all(Option::is_some)
+    }
+
+    /// Joins the fragments and checks the result against the tag.
+    pub fn finish(self) -> Result<Vec<u8>, FragmentError> {
+        let tag = self.tag.ok_or_else(|| FragmentError::Missing(Vec::new()))?;
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return Err(FragmentError::Missing(missing));
+        }
+        let data: Vec<u8> = self.pieces.into_iter().flatten().flatten().collect();
+        if checksum(&data) != tag {
+            return Err(FragmentError::Checksum);
+        }
+        Ok(data)
+    }
+}
+
+/// Why a fragment was rejected or a share could not be reassembled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FragmentError {
+    /// The fragment with this index was already added.
+    Duplicate(u16),
+    /// These fragments are still missing, empty if no fragment was added at all.
+    Missing(Vec<u16>),
+    /// The fragment belongs to another share.
+    Foreign,
+    /// The reassembled share does not match its tag.
+    Checksum,
+}
+
+impl fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FragmentError::Duplicate(index) => write!(f, "duplicate fragment {}", index),
+            FragmentError::Missing(missing) if missing.is_empty() => f.write_str("no fragments"),
+            FragmentError::Missing(missing) => write!(f, "{} fragments missing", missing.len()),
+            FragmentError::Foreign => f.write_str("fragment of another share"),
+            FragmentError::Checksum => f.write_str("reassembled share does not match its tag"),
+        }
+    }
+}
+
+impl Error for FragmentError {}
+
+/// 32 bit FNV-1a, to tell shares apart and catch mangled fragments, not to resist forgery.
+fn checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash: u32, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_duplicate_and_foreign() {
+        let data: Vec<u8> = (0..100).collect();
+        let fragments = fragment(&data, HEADER + 30).unwrap();
+        assert_eq!(fragments.len(), 4);
+
+        let mut reassembler = Reassembler::new();
+        reassembler.add(fragments[2].clone()).unwrap();
+        assert_eq!(
+            reassembler.add(fragments[2].clone()),
+            Err(FragmentError::Duplicate(2))
+        );
+        let other = fragment(b"other", HEADER + 30).unwrap();
+        assert_eq!(
+            reassembler.add(other[0].clone()),
+            Err(FragmentError::Foreign)
+        );
+        assert_eq!(reassembler.missing(), vec![0, 1, 3]);
+        assert_eq!(
+            reassembler.clone().finish(),
+            Err(FragmentError::Missing(vec![0, 1, 3]))
+        );
+
+        let mut tampered = fragments[0].clone();
+        tampered.payload[0] ^= 1;
+        for fragment in [tampered, fragments[1].clone(), fragments[3].clone()] {
+            reassembler.add(fragment).unwrap();
+        }
+        assert_eq!(reassembler.finish(), Err(FragmentError::Checksum));
+
+        assert!(fragment(&data, HEADER).is_none());
+        assert_eq!(fragment(&[], 100).unwrap()[0].count, 1);
+    }
+}
@@ -0,0 +1,145 @@
+//! Two level Shamir sharing in the AES field with a digest share, as used by SLIP-39 and SSKR.
+//!
+//! A secret is split with itself at x = 255 and, from a threshold of two, a share at x = 254
+//! holding 4 bytes of HMAC-SHA256 of the secret keyed by the rest of that share. Recovery
+//! checks the digest, so shares of different backups are caught. A backup splits its secret
+//! among groups and the share of each group among its members.
+use crate::{error::SharingError, math::Field, secret::try_fill};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+const SECRET_INDEX: u8 = 255;
+const DIGEST_INDEX: u8 = 254;
+const DIGEST_LEN: usize = 4;
+
+/// A member share as far as recovery is concerned.
+pub(crate) struct Member<'a> {
+    pub(crate) group_index: u8,
+    pub(crate) member_index: u8,
+    pub(crate) member_threshold: u8,
+    pub(crate) value: &'a [u8],
+}
+
+/// Recovers the secret from the members of at least `group_threshold` complete groups.
+/// Shares of incomplete groups, and groups or members beyond the thresholds, are ignored.
+/// Fails with [`SharingError::InvalidParameters`] on a zero threshold and
+/// [`SharingError::Malformed`] on a value too short to hold a digest.
+pub(crate) fn combine(
+    group_threshold: u8,
+    members: &[Member<'_>],
+) -> Result<Vec<u8>, SharingError> {
+    if group_threshold == 0 {
+        return Err(SharingError::InvalidParameters);
+    }
+    let mut groups: BTreeMap<u8, Vec<&Member<'_>>> = BTreeMap::new();
+    for member in members {
+        if member.member_threshold == 0 {
+            return Err(SharingError::InvalidParameters);
+        }
+        if member.value.len() < DIGEST_LEN {
+            return Err(SharingError::Malformed("member share"));
+        }
+        let group = groups.entry(member.group_index).or_default();
+        if group.iter().any(|m| {
+            m.member_threshold != member.member_threshold || m.value.len() != member.value.len()
+        }) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        if group.iter().any(|m| m.member_index == member.member_index) {
+            return Err(SharingError::DuplicateShareId(member.member_index));
+        }
+        group.push(member);
+    }
+
+    let needed = usize::from(group_threshold);
+    let mut group_secrets = Vec::with_capacity(needed);
+    for (group_index, members) in &groups {
+        let threshold = usize::from(members[0].member_threshold);
+        if members.len() >= threshold && group_secrets.len() < needed {
+            let points: Vec<(u8, &[u8])> = members[..threshold]
+                .iter()
+                .map(|m| (m.member_index, m.value))
+                .collect();
+            group_secrets.push((*group_index, recover(&points)?));
+        }
+    }
+    if group_secrets.len() < needed {
+        return Err(SharingError::InsufficientShares {
+            needed,
+            found: group_secrets.len(),
+        });
+    }
+    let points: Vec<(u8, &[u8])> = group_secrets.iter().map(|(i, s)| (*i, &s[..])).collect();
+    recover(&points)
+}
+
+fn digest(random: &[u8], secret: &[u8]) -> Result<[u8; DIGEST_LEN], SharingError> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(random).map_err(|_| SharingError::InvalidParameters)?;
+    mac.update(secret);
+    let mut digest = [0u8; DIGEST_LEN];
+    digest.copy_from_slice(&mac.finalize().into_bytes()[..DIGEST_LEN]);
+    Ok(digest)
+}
+
+/// The values of the polynomial through `points` at `x`, in the AES field.
+fn interpolate(points: &[(u8, &[u8])], x: u8) -> Result<Vec<u8>, SharingError> {
+    let (_, first) = points.first().ok_or(SharingError::EmptyShareSet)?;
+    let xs: Vec<u8> = points.iter().map(|(x, _)| *x).collect();
+    let coefficients = Field::AES
+        .lagrange_coefficients(&xs, x)
+        .ok_or(SharingError::DuplicateShareId(x))?;
+    let mut out = vec![0u8; first.len()];
+    for ((_, values), c) in points.iter().zip(coefficients) {
+        for (o, v) in out.iter_mut().zip(values.iter()) {
+            *o ^= Field::AES.mul(c, *v);
+        }
+    }
+    Ok(out)
+}
+
+/// Splits `secret` into `count` values with the secret at x = 255 and a digest of it at 254.
+pub(crate) fn split<G: Rng + ?Sized>(
+    threshold: u8,
+    count: u8,
+    secret: &[u8],
+    rng: &mut G,
+) -> Result<Vec<(u8, Vec<u8>)>, SharingError> {
+    if threshold == 1 {
+        return Ok((0..count).map(|i| (i, secret.to_vec())).collect());
+    }
+    let mut shares = Vec::with_capacity(usize::from(count));
+    for i in 0..threshold - 2 {
+        let mut value = vec![0u8; secret.len()];
+        try_fill(rng, &mut value)?;
+        shares.push((i, value));
+    }
+    let mut digest_share = vec![0u8; secret.len()];
+    try_fill(rng, &mut digest_share[DIGEST_LEN..])?;
+    let digest = digest(&digest_share[DIGEST_LEN..], secret)?;
+    digest_share[..DIGEST_LEN].copy_from_slice(&digest);
+
+    let mut base: Vec<(u8, &[u8])> = shares.iter().map(|(i, v)| (*i, &v[..])).collect();
+    base.push((DIGEST_INDEX, &digest_share));
+    base.push((SECRET_INDEX, secret));
+    let rest = (threshold - 2..count)
+        .map(|i| Ok((i, interpolate(&base, i)?)))
+        .collect::<Result<Vec<_>, SharingError>>()?;
+    shares.extend(rest);
+    Ok(shares)
+}
+
+/// Recovers the secret from `threshold` values and checks its digest.
+pub(crate) fn recover(points: &[(u8, &[u8])]) -> Result<Vec<u8>, SharingError> {
+    if let [(_, value)] = points {
+        return Ok(value.to_vec());
+    }
+    let secret = interpolate(points, SECRET_INDEX)?;
+    let digest_share = interpolate(points, DIGEST_INDEX)?;
+    if digest(&digest_share[DIGEST_LEN..], &secret)? != digest_share[..DIGEST_LEN] {
+        return Err(SharingError::ReconstructionFailed);
+    }
+    Ok(secret)
+}
@@ -0,0 +1,232 @@
+//! HTTP Distribution
+//!
+//! A [`Distributor`] delivers shares to custodians over HTTP, one endpoint per custodian:
+//! the n-th share is `POST`ed to the n-th endpoint in the [`ShareBytes`] encoding,
+//! and [`Distributor::fetch`] `GET`s them back from the same URLs for reconstruction.
+//! Failed requests are retried according to a [`Policy`].
+//!
+//! Only plain `http://` is compiled in, run a TLS terminating proxy for remote custodians.
+//!
+//! ```rust,no_run
+//! use sharing::{http::Distributor, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let distributor = Distributor::new(vec![
+//!     "http://custodian-a.local/shares/backup".to_string(),
+//!     "http://custodian-b.local/shares/backup".to_string(),
+//!     "http://custodian-c.local/shares/backup".to_string(),
+//! ]);
+//!
+//! let shares = sharer.share(b"secret".to_vec()).unwrap();
+//! for result in distributor.distribute(&shares) {
+//!     result.unwrap();
+//! }
+//!
+//! let fetched = distributor.fetch();
+//! assert_eq!(sharer.reconstruct(fetched).unwrap(), b"secret");
+//! ```
+use crate::share::ShareBytes;
+use std::{
+    io::{self, Read},
+    thread,
+    time::Duration,
+};
+
+/// Timeout and retry behaviour of a [`Distributor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    /// Limit for a single request, including connecting.
+    pub timeout: Duration,
+    /// Additional attempts after a failed request.
+    pub retries: u32,
+    /// Wait before the first retry, doubled for every further one.
+    pub backoff: Duration,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            retries: 3,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Sends shares to and fetches them from a fixed list of custodian endpoints.
+pub struct Distributor {
+    endpoints: Vec<String>,
+    policy: Policy,
+    agent: ureq::Agent,
+}
+
+impl Distributor {
+    pub fn new(endpoints: Vec<String>) -> Self {
+        Self::with_policy(endpoints, Policy::default())
+    }
+
+    pub fn with_policy(endpoints: Vec<String>, policy: Policy) -> Self {
+        Self {
+            endpoints,
+            policy,
+            agent: ureq::AgentBuilder::new().timeout(policy.timeout).build(),
+        }
+    }
+
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// `POST`s every share to its endpoint, returning the outcome per endpoint.
+    ///
+    /// Shares without an endpoint and endpoints without a share are reported as errors.
+    pub fn distribute<S: ShareBytes>(&self, shares: &[S]) -> Vec<io::Result<()>> {
+        (0..self.endpoints.len().max(shares.len()))
+            .map(|i| match (self.endpoints.get(i), shares.get(i)) {
+                (Some(endpoint), Some(share)) => {
+                    let bytes = share.to_bytes();
+                    self.retry(|| {
+                        self.agent
+                            .post(endpoint)
+                            .set("Content-Type", "application/octet-stream")
+                            .send_bytes(&bytes)
+                            .map(drop)
+                            .map_err(Box::new)
+                    })
+                }
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "number of shares and endpoints differ",
+                )),
+            })
+            .collect()
+    }
+
+    /// `GET`s the share from one endpoint.
+    pub fn fetch_from<S: ShareBytes>(&self, endpoint: &str) -> io::Result<S> {
+        let response = self.retry(|| self.agent.get(endpoint).call().map_err(Box::new))?;
+        let mut bytes = Vec::new();
+        response.into_reader().read_to_end(&mut bytes)?;
+        S::from_bytes(&bytes)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed share"))
+    }
+
+    /// The shares of every endpoint that answered, unreachable custodians are skipped.
+    pub fn fetch<S: ShareBytes>(&self) -> Vec<S> {
+        self.endpoints
+            .iter()
+            .filter_map(|endpoint| self.fetch_from(endpoint).ok())
+            .collect()
+    }
+
+    /// Runs `request` until it succeeds, fails permanently or the retries are used up.
+    ///
+    /// Transport errors and 5xx responses are retried, other statuses are not.
+    fn retry<T>(&self, mut request: impl FnMut() -> Result<T, Box<ureq::Error>>) -> io::Result<T> {
+        let mut backoff = self.policy.backoff;
+        let mut attempt = 0;
+        loop {
+            let error = match request() {
+                Ok(value) => return Ok(value),
+                Err(error) => match *error {
+                    ureq::Error::Status(status, _) if status < 500 => {
+                        return Err(io::Error::other(format!("endpoint answered {}", status)))
+                    }
+                    error => error,
+                },
+            };
+            if attempt >= self.policy.retries {
+                return Err(io::Error::other(error));
+            }
+            attempt += 1;
+            thread::sleep(backoff);
+            backoff = backoff.saturating_mul(2);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "shamir"))]
+mod tests {
+    use super::*;
+    use crate::{ShamirSecretSharing, Sharing};
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+    };
+
+    /// Stores the last `POST` body and serves it on `GET`, failing the first `failures` requests.
+    fn custodian(failures: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/share", listener.local_addr().unwrap());
+        let stored = Arc::new(Mutex::new(Vec::new()));
+        thread::spawn(move || {
+            for (i, stream) in listener.incoming().enumerate() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.to_ascii_lowercase().strip_prefix("content-length:")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0u8; length];
+                reader.read_exact(&mut body).unwrap();
+                let response = if i < failures {
+                    Vec::new()
+                } else if request.starts_with("POST") {
+                    *stored.lock().unwrap() = body;
+                    Vec::new()
+                } else {
+                    stored.lock().unwrap().clone()
+                };
+                let status = if i < failures {
+                    "503 Unavailable"
+                } else {
+                    "200 OK"
+                };
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    response.len()
+                )
+                .unwrap();
+                stream.write_all(&response).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn distribute_and_fetch_with_retries() {
+        let policy = Policy {
+            timeout: Duration::from_secs(5),
+            retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+        let endpoints = vec![custodian(0), custodian(2), custodian(0)];
+        let distributor = Distributor::with_policy(endpoints, policy);
+        let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+        let shares = sharer.share(b"secret".to_vec()).unwrap();
+
+        assert!(distributor.distribute(&shares).iter().all(Result::is_ok));
+        assert_eq!(
+            sharer.reconstruct(distributor.fetch()).unwrap(),
+            b"secret".to_vec()
+        );
+        assert!(distributor.distribute(&shares[..2])[2].is_err());
+    }
+}
@@ -1,172 +1,434 @@
 //! Information Dispersal Algorithms
+#[cfg(feature = "secure-memory")]
+use crate::secure::SecretBuffer;
+#[cfg(feature = "krawczyk")]
+use crate::share::KrawczykShare;
 use crate::{
-    share::{RabinShare, ShareVec},
-    Sharing,
+    backend::{Backend, STRIPE},
+    error::SharingError,
+    math::Field,
+    matrix::Matrix,
+    share::{check_ids, decode_error, dedup_shares, RabinShare, Share, ShareBytes, ShareId},
+    Parameters, Sharing,
+};
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::Path,
 };
-use gf::{Field, GF};
-
 
 /// # Rabin Information Dispersal
-/// 
+///
 /// ```rust
 /// use sharing::{RabinInformationDispersal, Sharing};
-/// 
+///
 /// let data = [1, 2, 3, 4, 5].to_vec();
-/// 
+///
 /// let sharer = RabinInformationDispersal::new(5, 3);
 ///
 /// let shares = sharer.share(data.clone()).unwrap();
 /// // You only need 3 out of the 5 shares to reconstruct
-/// let rec = sharer.recontruct(shares[1..=3].to_vec()).unwrap();
+/// let rec = sharer.reconstruct(shares[1..=3].to_vec()).unwrap();
 ///
 /// assert_eq!(data, rec);
 /// ```
+#[derive(Debug, Clone)]
 pub struct RabinInformationDispersal {
     n: u8,
     k: u8,
+    field: Field,
+    backend: Backend,
 }
 
 impl RabinInformationDispersal {
+    /// The parameters are checked when dispersing, see [`try_new`](Self::try_new)
+    /// to check them up front.
     pub fn new(n: u8, k: u8) -> Self {
-        Self { n, k }
+        Self {
+            n,
+            k,
+            field: Field::default(),
+            backend: Backend::detect(),
+        }
     }
-}
 
-impl Sharing for RabinInformationDispersal {
-    type Share = RabinShare;
-    fn share(&self, data: Vec<u8>) -> Option<Vec<Self::Share>> {
-        let length = data.len();
-        Some(
-            (1..=self.n)
-                .map(|x| {
-                    let gx = GF(x);
-                    RabinShare {
-                        id: x,
-                        length,
-                        body: data
-                            .chunks(self.k as usize)
-                            .map(|chunk| {
-                                chunk
-                                    .into_iter()
-                                    .rev()
-                                    .fold(GF::zero(), |res, b| GF(*b) + gx * res)
-                                    .into()
-                            })
-                            .collect(),
-                    }
-                })
-                .collect(),
-        )
-    }
-
-    fn recontruct(&self, shares: Vec<Self::Share>) -> Option<Vec<u8>> {
-        if shares.len() < self.k as usize {
-            return None;
-        }
-        let xvalues = shares.iter().map(|x| x.id).collect();
-        let decoder = generate_decoder(self.k as usize, xvalues);
-        let mut secret = vec![0u8; shares.size()];
-        for i in 0..shares[0].body.len() {
-            for j in 0..self.k as usize {
-                let index = (i * self.k as usize) + j;
-                if index >= shares.size() { continue; }
-                secret[index] = (0..self.k as usize)
-                    .map(|x| GF(decoder[j][x]) * GF(shares[x].body[i]))
-                    .sum::<GF<u8>>()
-                    .into();
-            }
-        }
-        Some(secret)
+    /// Like [`new`](Self::new), but fails with [`SharingError::InvalidParameters`]
+    /// unless `1 <= k <= n`.
+    pub fn try_new(n: u8, k: u8) -> Result<Self, SharingError> {
+        Parameters::new(n, k).ok_or(SharingError::InvalidParameters)?;
+        Ok(Self::new(n, k))
     }
-}
 
-fn generate_decoder(size: usize, values: Vec<u8>) -> Vec<Vec<u8>> {
-    inverse(
-        (0..size)
-            .map(|i| (0..size).map(|j| GF(values[i]).pow(j).into()).collect())
-            .collect(),
-    )
-}
+    /// Use a different GF(256) reduction polynomial, e.g. to interoperate with other implementations.
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.field = field;
+        self
+    }
 
-fn two_mut<T>(sl: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
-    let (smaller, lagger) = if i < j { (i, j) } else { (j, i) };
-    let (smsl, lgsl) = sl.split_at_mut(lagger);
-    if i == smaller {
-        (&mut smsl[smaller], &mut lgsl[0])
-    } else {
-        (&mut lgsl[0], &mut smsl[smaller])
+    pub fn field(&self) -> &Field {
+        &self.field
     }
-}
 
-fn inverse(matrix: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
-    let size = matrix.len();
-    let mut res = generate_identity(size);
-    let mut tmp = matrix.clone();
+    /// The multiplication used for the data, [`Backend::detect`] by default.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
 
-    for i in 0..size {
-        // if tmp[i][i] == 0 && !find_and_swap_nonzero_in_row(i, size, &mut tmp, &mut res) {
-        //   size = size - 1;
-        // }
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
 
-        let inv = GF(tmp[i][i]).inverse().into();
-        normalize_row(&mut tmp[i][..], &mut res[i][..], inv);
+    /// Disperses the data to the given ids instead of `1..=n`, failing like
+    /// [`ShamirSecretSharing::share_with_ids`](crate::ShamirSecretSharing::share_with_ids)
+    /// for ids that cannot be used.
+    ///
+    /// ```rust
+    /// use sharing::{RabinInformationDispersal, Sharing, SharingError};
+    ///
+    /// let sharer = RabinInformationDispersal::new(3, 2);
+    /// let shares = sharer.share_with_ids(vec![1, 2, 3, 4], &[4, 8]).unwrap();
+    /// assert_eq!(sharer.reconstruct(shares).unwrap(), vec![1, 2, 3, 4]);
+    /// assert!(matches!(
+    ///     sharer.share_with_ids(vec![1, 2, 3, 4], &[4]),
+    ///     Err(SharingError::MismatchedParameters)
+    /// ));
+    /// ```
+    pub fn share_with_ids(
+        &self,
+        data: Vec<u8>,
+        ids: &[u8],
+    ) -> Result<Vec<RabinShare>, SharingError> {
+        check_ids(ids)?;
+        if ids.len() < self.k as usize {
+            return Err(SharingError::MismatchedParameters);
+        }
+        self.share_at(data, ids)
+    }
+
+    pub(crate) fn share_at(
+        &self,
+        data: Vec<u8>,
+        ids: &[u8],
+    ) -> Result<Vec<RabinShare>, SharingError> {
+        let mut out = Vec::with_capacity(ids.len());
+        self.share_at_into(&data, ids, &mut out)?;
+        Ok(out)
+    }
+
+    /// Like `share_at`, but overwrites the shares in `out`, reusing their bodies.
+    pub(crate) fn share_at_into(
+        &self,
+        data: &[u8],
+        ids: &[u8],
+        out: &mut Vec<RabinShare>,
+    ) -> Result<(), SharingError> {
+        if self.k < 1 {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let k = self.k as usize;
+        let columns = data.len().div_ceil(k);
+        out.truncate(ids.len());
+        out.resize_with(ids.len(), || RabinShare::with_size(0));
+        for (share, x) in out.iter_mut().zip(ids) {
+            share.id = *x;
+            share.length = data.len();
+            share.body.clear();
+            share.body.resize(columns, 0);
+        }
+
+        // every chunk of `k` bytes is a polynomial, transposed to one column per power
+        let mut stripe = vec![0u8; k * STRIPE.min(columns)];
+        let mut start = 0;
+        while start < columns {
+            let len = STRIPE.min(columns - start);
+            for (i, chunk) in data[start * k..].chunks(k).take(len).enumerate() {
+                for j in 0..k {
+                    stripe[j * len + i] = chunk.get(j).copied().unwrap_or(0);
+                }
+            }
+            for share in out.iter_mut() {
+                self.backend.evaluate_add(
+                    &self.field,
+                    &stripe[..k * len],
+                    share.id,
+                    &mut share.body[start..start + len],
+                );
+            }
+            start += len;
+        }
+        Ok(())
+    }
 
-        for j in 0..size {
-            if j == i {
-                continue;
+    /// Reconstructs from share files in the [`ShareBytes`] encoding and writes the data to `out`.
+    ///
+    /// The files are streamed block by block, so memory use does not depend on their size.
+    /// Returns the number of bytes written, the first `k` files are read.
+    ///
+    /// Fails with [`SharingError::DuplicateShareId`] if two of them hold the same share id.
+    ///
+    /// ```rust
+    /// use sharing::{share::ShareBytes, RabinInformationDispersal, Sharing, SharingError};
+    ///
+    /// let dir = std::env::temp_dir().join(format!("sharing-paths-doc-{}", std::process::id()));
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    /// let sharer = RabinInformationDispersal::new(5, 3);
+    /// let paths: Vec<_> = sharer
+    ///     .share(data.clone())
+    ///     .unwrap()
+    ///     .iter()
+    ///     .map(|share| {
+    ///         let path = dir.join(format!("{}.share", share.id));
+    ///         std::fs::write(&path, share.to_bytes()).unwrap();
+    ///         path
+    ///     })
+    ///     .collect();
+    ///
+    /// let mut rec = Vec::new();
+    /// sharer.reconstruct_from_paths(&paths[2..], &mut rec).unwrap();
+    /// assert_eq!(rec, data);
+    ///
+    /// let twice = [&paths[0], &paths[1], &paths[0]];
+    /// assert!(matches!(
+    ///     sharer.reconstruct_from_paths(&twice, Vec::new()),
+    ///     Err(SharingError::DuplicateShareId(1))
+    /// ));
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn reconstruct_from_paths<W: Write>(
+        &self,
+        paths: &[impl AsRef<Path>],
+        mut out: W,
+    ) -> Result<u64, SharingError> {
+        let k = self.k as usize;
+        if k < 1 {
+            return Err(SharingError::MismatchedParameters);
+        }
+        if paths.len() < k {
+            return Err(SharingError::reconstruction(self.k, paths.len()));
+        }
+        let mut length = None;
+        let mut ids = Vec::with_capacity(k);
+        let mut readers = Vec::with_capacity(k);
+        for path in &paths[..k] {
+            let mut reader = BufReader::new(File::open(path.as_ref())?);
+            let mut header = [0u8; 11];
+            reader.read_exact(&mut header)?;
+            let share = RabinShare::from_bytes(&header).ok_or_else(|| decode_error(&header))?;
+            if ids.contains(&share.id) {
+                return Err(SharingError::DuplicateShareId(share.id));
             }
-            let coeff = tmp[j][i];
-            if coeff == 0 {
-                continue;
+            if length.is_some_and(|l| l != share.length) {
+                return Err(SharingError::MismatchedParameters);
             }
+            length = Some(share.length);
+            ids.push(share.id);
+            readers.push(reader);
+        }
+        let decoder = Matrix::vandermonde_in(&self.field, &ids, k)
+            .inverse_in(&self.field)
+            .ok_or(SharingError::MismatchedParameters)?;
 
-            let (tmpi, tmpj) = two_mut(&mut tmp[..], i, j);
-            let (resi, resj) = two_mut(&mut res[..], i, j);
-            mult_and_subtract(&mut tmpj[..], &mut tmpi[..], coeff);
-            mult_and_subtract(&mut resj[..], &mut resi[..], coeff);
+        let mut remaining =
+            u64::try_from(length.unwrap_or(0)).map_err(|_| SharingError::Malformed("share"))?;
+        let total = remaining;
+        let mut columns = vec![vec![0u8; STRIPE]; k];
+        let mut stripe = vec![0u8; STRIPE];
+        let mut block = vec![0u8; STRIPE * k];
+        while remaining > 0 {
+            let count = (STRIPE as u64).min(remaining.div_ceil(k as u64)) as usize;
+            for (reader, column) in readers.iter_mut().zip(columns.iter_mut()) {
+                reader.read_exact(&mut column[..count])?;
+            }
+            let take = (count * k).min(usize::try_from(remaining).unwrap_or(usize::MAX));
+            let bodies: Vec<&[u8]> = columns.iter().map(|column| &column[..count]).collect();
+            self.decode_stripe(&decoder, &bodies, &mut stripe[..count], &mut block[..take])?;
+            out.write_all(&block[..take])?;
+            remaining -= take as u64;
         }
+        out.flush()?;
+        Ok(total)
     }
+}
 
-    // we could assert here that tmp is now an identity matrix
+impl Sharing for RabinInformationDispersal {
+    type Share = RabinShare;
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError> {
+        if self.k < 1 || self.k > self.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        self.share_at(data, &(1..=self.n).collect::<Vec<_>>())
+    }
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct_ref(&shares)
+    }
+
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        self.decode(&dedup_shares(shares.iter().collect())?)
+    }
+
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<Self::Share>) -> Result<(), SharingError> {
+        if self.k < 1 || self.k > self.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        self.share_at_into(&data, &(1..=self.n).collect::<Vec<_>>(), out)
+    }
 
-    return res;
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        self.decode_into(&dedup_shares(shares.iter().collect())?, out)
+    }
+
+    #[cfg(feature = "secure-memory")]
+    fn reconstruct_secure_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut SecretBuffer,
+    ) -> Result<(), SharingError> {
+        self.decode_with(&dedup_shares(shares.iter().collect())?, |size| {
+            *out = SecretBuffer::new(size);
+            out
+        })
+    }
 }
 
-fn mult_and_subtract(row: &mut [u8], normalized: &[u8], coeff: u8) {
-    for i in 0..row.len() {
-        row[i] = (GF(row[i]) - GF(normalized[i]) * GF(coeff)).into();
+/// The dispersed part of a share, which [`RabinInformationDispersal::decode`] reads in place.
+pub(crate) trait Dispersed: ShareId {
+    /// The length of the dispersed data.
+    fn length(&self) -> usize;
+
+    fn body(&self) -> &[u8];
+}
+
+impl Dispersed for RabinShare {
+    fn length(&self) -> usize {
+        self.length
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
     }
 }
 
-fn normalize_row(tmp_row: &mut [u8], res_row: &mut [u8], element: u8) {
-    for i in 0..tmp_row.len() {
-        tmp_row[i] = (GF(tmp_row[i]) * GF(element)).into();
-        res_row[i] = (GF(res_row[i]) * GF(element)).into();
+#[cfg(feature = "krawczyk")]
+impl Dispersed for KrawczykShare {
+    fn length(&self) -> usize {
+        self.length
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
     }
 }
 
-// fn find_and_swap_nonzero_in_row(
-//     i: usize,
-//     num_rows: usize,
-//     tmp: &mut Vec<Vec<u8>>,
-//     res: &mut Vec<Vec<u8>>,
-// ) -> bool {
-//     for j in i + 1..num_rows {
-//         if tmp[j][i] != 0 {
-//             swap_rows(tmp, i, j);
-//             swap_rows(res, i, j);
-//             return true;
-//         }
-//     }
-//     false
-// }
-
-// fn swap_rows(matrix: &mut Vec<Vec<u8>>, first: usize, second: usize) {
-//     matrix.swap(first, second);
-// }
-
-fn generate_identity(size: usize) -> Vec<Vec<u8>> {
-    (0..size)
-        .map(|i| (0..size).map(|j| if i == j { 1 } else { 0 }).collect())
-        .collect()
+impl RabinInformationDispersal {
+    /// Recovers the dispersed data from shares with distinct ids.
+    pub(crate) fn decode<S: Dispersed>(&self, shares: &[&S]) -> Result<Vec<u8>, SharingError> {
+        let mut secret = Vec::new();
+        self.decode_into(shares, &mut secret)?;
+        Ok(secret)
+    }
+
+    /// Like `decode`, but writes the data to `secret`, reusing its capacity.
+    pub(crate) fn decode_into<S: Dispersed>(
+        &self,
+        shares: &[&S],
+        secret: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        self.decode_with(shares, |size| {
+            secret.clear();
+            secret.resize(size, 0);
+            secret
+        })
+    }
+
+    /// Decodes the shares into the zeroed bytes `out` returns for the size of the data.
+    fn decode_with<'a, S: Dispersed>(
+        &self,
+        shares: &[&S],
+        out: impl FnOnce(usize) -> &'a mut [u8],
+    ) -> Result<(), SharingError> {
+        if self.k < 1 {
+            return Err(SharingError::InvalidParameters);
+        }
+        if shares.len() < self.k as usize {
+            return Err(SharingError::reconstruction(self.k, shares.len()));
+        }
+        let size = shares[0].length();
+        if let Some(share) = shares.iter().find(|s| s.length() != size) {
+            return Err(SharingError::BodyLengthMismatch {
+                id: share.id(),
+                expected: size,
+                found: share.length(),
+            });
+        }
+        // every body holds one column per `k` bytes, which also bounds the allocation
+        let columns = size.div_ceil(self.k as usize);
+        if let Some(share) = shares.iter().find(|s| s.body().len() != columns) {
+            return Err(SharingError::BodyLengthMismatch {
+                id: share.id(),
+                expected: columns,
+                found: share.body().len(),
+            });
+        }
+        let xvalues: Vec<u8> = shares
+            .iter()
+            .take(self.k as usize)
+            .map(|x| x.id())
+            .collect();
+        let decoder = Matrix::vandermonde_in(&self.field, &xvalues, self.k as usize)
+            .inverse_in(&self.field)
+            .ok_or(SharingError::ReconstructionFailed)?;
+        let k = self.k as usize;
+        let secret = out(size);
+        let mut stripe = vec![0u8; STRIPE.min(columns)];
+        let mut start = 0;
+        while start < columns {
+            let len = STRIPE.min(columns - start);
+            let bodies: Vec<&[u8]> = shares[..k]
+                .iter()
+                .map(|share| &share.body()[start..start + len])
+                .collect();
+            let end = ((start + len) * k).min(size);
+            self.decode_stripe(
+                &decoder,
+                &bodies,
+                &mut stripe[..len],
+                &mut secret[start * k..end],
+            )?;
+            start += len;
+        }
+        Ok(())
+    }
+
+    /// Decodes the columns `bodies` of `k` shares into the interleaved bytes `out`,
+    /// which may end early, using `stripe` as scratch for one byte of every column.
+    fn decode_stripe(
+        &self,
+        decoder: &Matrix,
+        bodies: &[&[u8]],
+        stripe: &mut [u8],
+        out: &mut [u8],
+    ) -> Result<(), SharingError> {
+        let k = self.k as usize;
+        for j in 0..k {
+            let row = decoder.row(j).ok_or(SharingError::ReconstructionFailed)?;
+            stripe.fill(0);
+            for (c, body) in row.iter().zip(bodies) {
+                self.backend.mul_add(&self.field, *c, body, stripe);
+            }
+            for (i, byte) in stripe.iter().enumerate() {
+                if let Some(d) = out.get_mut(i * k + j) {
+                    *d = *byte;
+                }
+            }
+        }
+        Ok(())
+    }
 }
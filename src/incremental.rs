@@ -0,0 +1,119 @@
+//! Incremental Reconstruction
+//!
+//! Shares of a secret often arrive one at a time, over days and from different custodians.
+//! An [`Incremental`] collects them for any scheme, checks every share as it arrives
+//! against the ones collected before, and reports how many are still missing.
+//! For Shamir shares with a manifest or commitments see the `recovery` module,
+//! which checks more.
+//!
+//! ```rust
+//! use sharing::{incremental::{Incremental, Progress}, RabinInformationDispersal, Sharing};
+//!
+//! let sharer = RabinInformationDispersal::new(5, 3);
+//! let shares = sharer.share(b"arrives slowly".to_vec()).unwrap();
+//!
+//! let mut incremental = Incremental::new(sharer, 3);
+//! assert_eq!(incremental.add_share(shares[4].clone()).unwrap(), Progress::Missing(2));
+//! // the same share again changes nothing
+//! assert_eq!(incremental.add_share(shares[4].clone()).unwrap(), Progress::Missing(2));
+//!
+//! let mut truncated = shares[0].clone();
+//! truncated.length -= 3;
+//! assert!(incremental.add_share(truncated).is_err());
+//!
+//! incremental.add_share(shares[0].clone()).unwrap();
+//! assert_eq!(incremental.add_share(shares[2].clone()).unwrap(), Progress::Complete);
+//! assert_eq!(incremental.finish().unwrap(), b"arrives slowly");
+//! ```
+use crate::{
+    error::SharingError,
+    share::{Share, ShareId},
+    Sharing,
+};
+
+/// How far a collection of shares got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Progress {
+    /// This many more shares are needed.
+    Missing(usize),
+    /// The threshold is met, the secret can be reconstructed.
+    Complete,
+}
+
+impl Progress {
+    pub(crate) fn of(k: usize, collected: usize) -> Self {
+        match k.saturating_sub(collected) {
+            0 => Progress::Complete,
+            missing => Progress::Missing(missing),
+        }
+    }
+}
+
+/// Collects the shares of one secret until `k` of them reconstruct it with the sharer.
+pub struct Incremental<T: Sharing> {
+    sharer: T,
+    k: usize,
+    shares: Vec<T::Share>,
+}
+
+impl<T: Sharing> Incremental<T>
+where
+    T::Share: ShareId + PartialEq,
+{
+    pub fn new(sharer: T, k: u8) -> Self {
+        Self {
+            sharer,
+            k: k as usize,
+            shares: Vec::new(),
+        }
+    }
+
+    pub fn sharer(&self) -> &T {
+        &self.sharer
+    }
+
+    /// Adds a share, or fails without changing the collected shares.
+    ///
+    /// Adding a share again is harmless. Fails with [`SharingError::DuplicateShareId`]
+    /// for a different share under a collected id and with
+    /// [`SharingError::BodyLengthMismatch`] if its size differs from the collected shares.
+    pub fn add_share(&mut self, share: T::Share) -> Result<Progress, SharingError> {
+        if let Some(collected) = self.shares.iter().find(|s| s.id() == share.id()) {
+            if *collected != share {
+                return Err(SharingError::DuplicateShareId(share.id()));
+            }
+            return Ok(self.progress());
+        }
+        if let Some(first) = self.shares.first() {
+            if first.size() != share.size() {
+                return Err(SharingError::BodyLengthMismatch {
+                    id: share.id(),
+                    expected: first.size(),
+                    found: share.size(),
+                });
+            }
+        }
+        self.shares.push(share);
+        Ok(self.progress())
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress::of(self.k, self.shares.len())
+    }
+
+    /// The ids of the collected shares, in the order they arrived.
+    pub fn collected(&self) -> Vec<u8> {
+        self.shares.iter().map(ShareId::id).collect()
+    }
+
+    /// Reconstructs the secret from the collected shares.
+    pub fn finish(self) -> Result<Vec<u8>, SharingError> {
+        if self.shares.len() < self.k {
+            return Err(SharingError::InsufficientShares {
+                needed: self.k,
+                found: self.shares.len(),
+            });
+        }
+        self.sharer.reconstruct(self.shares)
+    }
+}
@@ -0,0 +1,160 @@
+//! Interoperability
+//!
+//! Converts Shamir shares to and from the byte layouts of two other Rust crates,
+//! so projects can migrate between them or combine shares dealt by either:
+//!
+//! - `vsss-rs`, whose GF(256) shares are the identifier byte followed by the value,
+//! - `shamirsecretsharing`, whose `hazmat` key shares are the x coordinate followed by
+//!   the 32 byte y value.
+//!
+//! Both evaluate share `i` at `x = i` with the secret in the constant term, like this crate,
+//! but in the AES field, so deal and reconstruct with [`compatible`] or [`Field::AES`].
+//!
+//! The `gfsplit` and `gfcombine` tools of libgfshare share in the default field of this crate
+//! instead, and write share `i` to a file named after the secret with the suffix `.i`, padded
+//! to three digits, holding only the value. [`to_gfshare_name`] and [`from_gfshare`] convert
+//! between those files and [`ShamirShare`]s. For the tools of `ssss`, see the `ssss` module
+//! of the feature of that name.
+//!
+//! ```rust
+//! use sharing::{interop, Sharing};
+//!
+//! let sharer = interop::compatible(3, 2, rand::thread_rng());
+//! let shares = sharer.share(vec![7; 32]).unwrap();
+//!
+//! // hand a share to a vsss-rs or shamirsecretsharing based tool ...
+//! let keyshare = interop::to_keyshare(&shares[0]).unwrap();
+//! let vsss = interop::to_vsss(&shares[2]);
+//!
+//! // ... and combine what comes back
+//! let shares = vec![
+//!     interop::from_keyshare(&keyshare).unwrap(),
+//!     interop::from_vsss(&vsss).unwrap(),
+//! ];
+//! assert_eq!(sharer.reconstruct(shares).unwrap(), vec![7; 32]);
+//! ```
+use crate::{error::SharingError, math::Field, secret::ShamirSecretSharing, share::ShamirShare};
+use rand::Rng;
+
+/// The size of a `shamirsecretsharing` key share: the x coordinate and 32 bytes.
+pub const KEYSHARE_SIZE: usize = 33;
+
+/// A sharer in the field and layout of both crates.
+pub fn compatible<R: Rng>(n: u8, k: u8, rng: R) -> ShamirSecretSharing<R> {
+    ShamirSecretSharing::new(n, k, rng).with_field(Field::AES)
+}
+
+/// The share as a `vsss-rs` GF(256) share.
+pub fn to_vsss(share: &ShamirShare) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + share.body.len());
+    bytes.push(share.id);
+    bytes.extend_from_slice(&share.body);
+    bytes
+}
+
+/// A `vsss-rs` GF(256) share, which must have a nonzero identifier.
+pub fn from_vsss(bytes: &[u8]) -> Result<ShamirShare, SharingError> {
+    match bytes {
+        [0, ..] => Err(SharingError::Malformed("share id")),
+        [id, body @ ..] => Ok(ShamirShare {
+            id: *id,
+            body: body.to_vec(),
+        }),
+        [] => Err(SharingError::Malformed("share")),
+    }
+}
+
+/// The share as a `shamirsecretsharing` key share, which only holds 32 byte keys.
+pub fn to_keyshare(share: &ShamirShare) -> Result<[u8; KEYSHARE_SIZE], SharingError> {
+    if share.body.len() != KEYSHARE_SIZE - 1 {
+        return Err(SharingError::MismatchedParameters);
+    }
+    let mut keyshare = [0u8; KEYSHARE_SIZE];
+    keyshare[0] = share.id;
+    keyshare[1..].copy_from_slice(&share.body);
+    Ok(keyshare)
+}
+
+/// A `shamirsecretsharing` key share.
+pub fn from_keyshare(keyshare: &[u8; KEYSHARE_SIZE]) -> Result<ShamirShare, SharingError> {
+    from_vsss(keyshare)
+}
+
+/// The name `gfsplit` gives the file of this share of `stem`, e.g. `secret.txt.042`.
+/// The body is the whole content of the file.
+pub fn to_gfshare_name(stem: &str, share: &ShamirShare) -> String {
+    format!("{}.{:03}", stem, share.id)
+}
+
+/// The share in a `gfsplit` file, from its name and content.
+pub fn from_gfshare(name: &str, contents: &[u8]) -> Result<ShamirShare, SharingError> {
+    let suffix = name
+        .rsplit_once('.')
+        .map(|(_, suffix)| suffix)
+        .filter(|suffix| suffix.len() == 3 && suffix.bytes().all(|b| b.is_ascii_digit()))
+        .ok_or(SharingError::Malformed("gfshare file name"))?;
+    match suffix.parse::<u8>() {
+        Ok(id) if id > 0 => Ok(ShamirShare {
+            id,
+            body: contents.to_vec(),
+        }),
+        _ => Err(SharingError::Malformed("share id")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{roles::Combiner, Sharing};
+
+    // y = 0x53 + 0xca·x in the AES field, where 2·0xca = 0x8f
+    const LINE: [[u8; 2]; 3] = [[1, 0x99], [2, 0xdc], [3, 0x53 ^ 0xca ^ 0x8f]];
+
+    #[test]
+    fn known_shares_reconstruct() {
+        let combiner = Combiner::new(2).with_field(Field::AES);
+        for pair in [[0, 1], [0, 2], [1, 2]] {
+            let shares = pair.iter().map(|i| from_vsss(&LINE[*i]).unwrap()).collect();
+            assert_eq!(combiner.reconstruct(shares).unwrap(), vec![0x53]);
+        }
+    }
+
+    #[test]
+    fn layouts_round_trip() {
+        let sharer = compatible(4, 3, rand::thread_rng());
+        let shares = sharer.share(vec![0xa5; 32]).unwrap();
+        for share in &shares {
+            assert_eq!(&from_vsss(&to_vsss(share)).unwrap(), share);
+            assert_eq!(&from_keyshare(&to_keyshare(share).unwrap()).unwrap(), share);
+        }
+        let short = sharer.share(vec![1; 16]).unwrap();
+        assert!(to_keyshare(&short[0]).is_err());
+        assert!(from_vsss(&[0, 1, 2]).is_err());
+        assert!(from_vsss(&[]).is_err());
+    }
+
+    #[test]
+    fn gfshare_files() {
+        let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+        let shares = sharer.share(b"hunter2".to_vec()).unwrap();
+        let files: Vec<(String, Vec<u8>)> = shares
+            .iter()
+            .map(|share| (to_gfshare_name("pw.txt", share), share.body.clone()))
+            .collect();
+        assert_eq!(files[1].0, "pw.txt.002");
+        let shares = files[1..]
+            .iter()
+            .map(|(name, contents)| from_gfshare(name, contents).unwrap())
+            .collect();
+        assert_eq!(sharer.reconstruct(shares).unwrap(), b"hunter2");
+        for name in ["pw.txt", "pw.txt.000", "pw.txt.256", "pw.txt.2"].iter() {
+            assert!(from_gfshare(name, b"x").is_err());
+        }
+    }
+
+    #[test]
+    fn other_fields_do_not_combine() {
+        let shares = LINE.iter().map(|s| from_vsss(s).unwrap()).collect();
+        assert_ne!(Combiner::new(2).reconstruct(shares).unwrap(), vec![0x53]);
+    }
+}
@@ -0,0 +1,271 @@
+//! JSON Documents
+//!
+//! A share as a JSON object with a fixed, versioned layout, for storing shares in document
+//! databases. Unlike the `serde` derives, which follow the Rust types, this layout is a
+//! promise: a document written today is read by every later version of this crate.
+//!
+//! ```json
+//! {
+//!   "comment": "in the blue envelope",
+//!   "created": "2023-11-14T22:13:20Z",
+//!   "id": 3,
+//!   "label": "share 3 of 5, given to Alice",
+//!   "payload": "yv4=",
+//!   "scheme": "shamir",
+//!   "threshold": 2,
+//!   "version": 1
+//! }
+//! ```
+//!
+//! | field       | type    | contents                                                     |
+//! |-------------|---------|--------------------------------------------------------------|
+//! | `version`   | integer | the layout version, [`JSON_VERSION`]                         |
+//! | `scheme`    | string  | `shamir`, `rabin` or `krawczyk`                              |
+//! | `threshold` | integer | the shares needed to reconstruct, `k`                        |
+//! | `id`        | integer | the id of the share, 1 to 255                                |
+//! | `payload`   | string  | base64 of the share's binary encoding after its id, as in the [wire format](crate::wire) |
+//! | `label`, `created`, `comment` | string | the [`Metadata`], omitted when absent        |
+//!
+//! Keys are written sorted, without whitespace. Reading ignores fields it does not know, so a
+//! later version may add fields without changing `version`, and a document with a higher
+//! `version` fails with [`SharingError::UnsupportedVersion`].
+//!
+//! Available with the `json` feature.
+//!
+//! [`Metadata`]: crate::metadata::Metadata
+//!
+//! ```rust
+//! use sharing::{json::Json, metadata::Metadata, wire::WireShare, ShamirShare};
+//!
+//! let share = WireShare::new(2, ShamirShare { id: 3, body: vec![0xca, 0xfe] })
+//!     .with_metadata(Metadata::new().with_label("Alice"));
+//! let text = share.to_json();
+//! assert_eq!(
+//!     text,
+//!     r#"{"id":3,"label":"Alice","payload":"yv4=","scheme":"shamir","threshold":2,"version":1}"#
+//! );
+//! assert_eq!(WireShare::<ShamirShare>::from_json(&text).unwrap(), share);
+//!
+//! // fields added by the application are kept out of the way
+//! let stored = r#"{"_id":"f00","version":1,"scheme":"shamir","threshold":2,"id":3,"payload":"yv4="}"#;
+//! assert_eq!(WireShare::<ShamirShare>::from_json(stored).unwrap().share.body, [0xca, 0xfe]);
+//! ```
+use crate::{
+    error::SharingError,
+    metadata::Metadata,
+    strict::{Limits, StrictCheck},
+    wire::{self, WireScheme, WireShare},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{Map, Value};
+use std::convert::TryFrom;
+
+/// The layout version written by this build.
+pub const JSON_VERSION: u8 = 1;
+
+const MALFORMED: SharingError = SharingError::Malformed("json share");
+
+/// Shares with a JSON document form.
+pub trait Json: Sized {
+    fn to_json_value(&self) -> Value;
+
+    /// Fails with [`SharingError::Malformed`] if a field is missing or has the wrong type,
+    /// and with [`SharingError::MismatchedParameters`] for another scheme.
+    fn from_json_value(value: &Value) -> Result<Self, SharingError>;
+
+    fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    fn from_json(text: &str) -> Result<Self, SharingError> {
+        Self::from_json_value(&serde_json::from_str(text).map_err(|_| MALFORMED)?)
+    }
+
+    /// Parses like [`from_json`](Json::from_json), then checks the share against `limits`.
+    fn from_json_strict(text: &str, limits: &Limits) -> Result<Self, SharingError>
+    where
+        Self: StrictCheck,
+    {
+        limits.size(text.as_bytes())?;
+        let share = Self::from_json(text)?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl<S: WireScheme> Json for WireShare<S> {
+    fn to_json_value(&self) -> Value {
+        // inserted sorted, so the order is the same with serde_json's `preserve_order`
+        let mut object = Map::new();
+        let metadata = &self.metadata;
+        if let Some(comment) = &metadata.comment {
+            object.insert("comment".into(), comment.as_str().into());
+        }
+        if let Some(created) = &metadata.created {
+            object.insert("created".into(), created.as_str().into());
+        }
+        object.insert("id".into(), self.share.id().into());
+        if let Some(label) = &metadata.label {
+            object.insert("label".into(), label.as_str().into());
+        }
+        let payload = STANDARD.encode(wire::payload(&self.share));
+        object.insert("payload".into(), payload.into());
+        object.insert("scheme".into(), S::SCHEME.name().into());
+        object.insert("threshold".into(), self.k.into());
+        object.insert("version".into(), JSON_VERSION.into());
+        Value::Object(object)
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, SharingError> {
+        let object = value.as_object().ok_or(MALFORMED)?;
+        let integer = |key| {
+            object
+                .get(key)
+                .and_then(Value::as_u64)
+                .and_then(|n| u8::try_from(n).ok())
+                .ok_or(MALFORMED)
+        };
+        match object.get("version").and_then(Value::as_u64) {
+            Some(1) => {}
+            Some(version) => {
+                let version = u8::try_from(version).unwrap_or(u8::MAX);
+                return Err(SharingError::UnsupportedVersion(version));
+            }
+            None => return Err(MALFORMED),
+        }
+        let scheme = object
+            .get("scheme")
+            .and_then(Value::as_str)
+            .ok_or(MALFORMED)?;
+        if scheme != S::SCHEME.name() {
+            return Err(match scheme.parse::<crate::Scheme>() {
+                Ok(_) => SharingError::MismatchedParameters,
+                Err(()) => MALFORMED,
+            });
+        }
+        let (k, id) = (integer("threshold")?, integer("id")?);
+        if k == 0 {
+            return Err(MALFORMED);
+        }
+        let payload = object
+            .get("payload")
+            .and_then(Value::as_str)
+            .ok_or(MALFORMED)?;
+        let payload = STANDARD.decode(payload).map_err(|_| MALFORMED)?;
+        let text = |key| match object.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(text)) => Ok(Some(text.clone())),
+            Some(_) => Err(MALFORMED),
+        };
+        let metadata = Metadata {
+            label: text("label")?,
+            created: text("created")?,
+            comment: text("comment")?,
+        };
+        Ok(Self {
+            k,
+            share: wire::from_payload(id, &payload)?,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RabinShare, ShamirShare};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    // changing these breaks documents already stored, see the module documentation
+    #[test]
+    fn locked_layout() {
+        let share = WireShare::new(
+            2,
+            ShamirShare {
+                id: 3,
+                body: vec![0xca, 0xfe],
+            },
+        )
+        .with_metadata(
+            Metadata::new()
+                .with_label("share 3 of 5, given to Alice")
+                .created_at(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+                .with_comment("in the blue envelope"),
+        );
+        let text = serde_json::to_string_pretty(&share.to_json_value()).unwrap();
+        let expected = r#"{
+  "comment": "in the blue envelope",
+  "created": "2023-11-14T22:13:20Z",
+  "id": 3,
+  "label": "share 3 of 5, given to Alice",
+  "payload": "yv4=",
+  "scheme": "shamir",
+  "threshold": 2,
+  "version": 1
+}"#;
+        assert_eq!(text, expected);
+        assert_eq!(WireShare::from_json(expected).unwrap(), share);
+
+        let rabin = WireShare::new(
+            3,
+            RabinShare {
+                id: 1,
+                length: 5,
+                body: vec![7, 8],
+            },
+        );
+        assert_eq!(
+            rabin.to_json(),
+            r#"{"id":1,"payload":"AAAAAAAAAAUHCA==","scheme":"rabin","threshold":3,"version":1}"#
+        );
+    }
+
+    #[test]
+    fn reading() {
+        let read = |text: &str| WireShare::<ShamirShare>::from_json(text);
+        let document = |fields: &str| {
+            format!(
+                r#"{{"version":1,"scheme":"shamir","threshold":2,"id":3,"payload":"yv4="{}}}"#,
+                fields
+            )
+        };
+        assert!(read(&document("")).unwrap().metadata.is_empty());
+        let limits = Limits::default().with_parameters(crate::Parameters::new(5, 3).unwrap());
+        assert!(matches!(
+            WireShare::<ShamirShare>::from_json_strict(&document(""), &limits),
+            Err(SharingError::MismatchedParameters)
+        ));
+        let extended = read(&document(r#","label":null,"checksum":"x","tags":[1]"#)).unwrap();
+        assert_eq!(extended, read(&document("")).unwrap());
+
+        let newer = document("").replace(r#""version":1"#, r#""version":2"#);
+        assert!(matches!(
+            read(&newer),
+            Err(SharingError::UnsupportedVersion(2))
+        ));
+        let rabin = document("").replace("shamir", "rabin");
+        assert!(matches!(
+            read(&rabin),
+            Err(SharingError::MismatchedParameters)
+        ));
+        for broken in [
+            document("").replace(r#""version":1,"#, ""),
+            document("").replace("shamir", "shamir2"),
+            document("").replace(r#""threshold":2"#, r#""threshold":0"#),
+            document("").replace(r#""id":3"#, r#""id":256"#),
+            document("").replace(r#""id":3"#, r#""id":"3""#),
+            document("").replace("yv4=", "yv4"),
+            document(r#","label":7"#),
+            "[]".to_string(),
+            "{".to_string(),
+        ]
+        .iter()
+        {
+            assert!(
+                matches!(read(broken), Err(SharingError::Malformed(_))),
+                "{}",
+                broken
+            );
+        }
+    }
+}
@@ -9,29 +9,374 @@
 //!
 //! let shares = sharer.share(data.clone()).unwrap();
 //! // You only need 3 out of the 5 shares to reconstruct
-//! let rec = sharer.recontruct(shares[1..=3].to_vec()).unwrap();
+//! let rec = sharer.reconstruct(shares[1..=3].to_vec()).unwrap();
 //!
 //! assert_eq!(data, rec);
 //! ```
+//!
+//! ## Features
+//!
+//! Every scheme can be enabled on its own, all are enabled by default:
+//!
+//! - `shamir`: [`ShamirSecretSharing`] and the schemes built on it
+//! - `rabin`: [`RabinInformationDispersal`]
+//! - `krawczyk`: [`KrawczykSecretSharing`], enables `shamir` and `rabin`
+//!
+//! Optional extras:
+//!
+//! - `aes`: [`KrawczykSecretSharing`] with hardware accelerated AES-256-GCM
+//! - `xchacha`: [`KrawczykSecretSharing`] with XChaCha20-Poly1305
+//! - `passphrase`: Krawczyk keys derived from a passphrase with Argon2
+//! - `qr`: printing and scanning shares as QR codes
+//! - `lz4`, `zstd`: compression before Krawczyk encryption, see [`compression`]
+//! - `archive`: one tar archive of shares and manifests per custodian
+//! - `http`: delivering shares to custodian endpoints
+//! - `heapless`: allocation free Shamir sharing of small secrets
+//! - `keyfile`: sharing only the secret material of PKCS#8, OpenSSH and age key files
+//! - `mnemonic`: Shamir shares as words of a BIP-39 word list
+//! - `slip39`: SLIP-39 mnemonic shares compatible with Trezor wallets
+//! - `sskr`: SSKR shares as URs and ByteWords, compatible with Gordian Seed Tool
+//! - `ssss`: shares of the `ssss-split` and `ssss-combine` tools
+//! - `encoding`: checksummed Bech32m and base64 text forms of shares
+//! - `armor`: PEM-like armored wire shares with readable headers
+//! - `serde`: `Serialize` and `Deserialize` for all share types, bytes as base64 in
+//!   human readable formats
+//! - `cbor`: deterministic CBOR for all share types, with a stable layout
+//! - `postcard`: compact postcard serialization of all share types, readable on `no_std`
+//! - `json`: wire shares as JSON documents with a stable, versioned layout
+//! - `signatures`: ed25519 participant signatures over share manifests
+//! - `futures`: `Stream` and `Sink` adapters for shares in async code
+//! - `rayon`: an [`executor`] for the parallel paths on the rayon thread pool
+//! - `secure-memory`: locked memory for reconstructed secrets, keys and polynomial coefficients,
+//!   and wiping of key material
 
+// reachable panics are bugs, see `tests::no_panic_on_any_input`
+#![cfg_attr(
+    not(test),
+    deny(clippy::panic, clippy::unwrap_used, clippy::expect_used)
+)]
+
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "armor")]
+pub mod armor;
+#[cfg(feature = "futures")]
+pub mod asynchronous;
+pub mod backend;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "shamir")]
+pub mod cdc;
+#[cfg(feature = "shamir")]
+pub mod ceremony;
+#[cfg(feature = "shamir")]
+pub mod commitment;
+pub mod composite;
+pub mod compression;
+#[cfg(feature = "shamir")]
+pub mod dealing;
+#[cfg(all(feature = "shamir", feature = "rabin"))]
+pub mod detect;
+#[cfg(feature = "shamir")]
+pub mod discovery;
+#[cfg(feature = "shamir")]
+pub mod drbg;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod error;
+pub mod estimate;
+pub mod executor;
+pub mod ext;
+#[cfg(feature = "krawczyk")]
+pub mod files;
+#[cfg(feature = "heapless")]
+pub mod fixed;
+pub mod fragment;
+#[cfg(any(feature = "slip39", feature = "sskr"))]
+mod grouped;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "rabin")]
 pub mod ids;
+pub mod incremental;
+#[cfg(feature = "shamir")]
+pub mod interop;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "keyfile")]
+pub mod keyfile;
+#[cfg(feature = "shamir")]
+pub mod manifest;
+pub mod math;
+pub mod matrix;
+#[cfg(feature = "shamir")]
+pub mod merkle;
+pub mod metadata;
+pub mod metrics;
+pub mod migration;
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+#[cfg(feature = "shamir")]
+pub mod mpc;
+pub mod paper;
+#[cfg(feature = "shamir")]
+pub mod placement;
+#[cfg(feature = "shamir")]
+pub mod policy;
+#[cfg(feature = "postcard")]
+pub mod postcard;
+#[cfg(feature = "shamir")]
+pub mod profile;
+#[cfg(feature = "qr")]
+pub mod qr;
+#[cfg(feature = "shamir")]
+pub mod recovery;
+#[cfg(feature = "shamir")]
+pub mod roles;
+#[cfg(feature = "shamir")]
 pub mod secret;
+#[cfg(feature = "secure-memory")]
+pub mod secure;
+#[cfg(feature = "serde")]
+mod serde_base64;
+#[cfg(feature = "shamir")]
+pub mod set;
+pub mod share;
+#[cfg(feature = "signatures")]
+pub mod signatures;
+#[cfg(feature = "slip39")]
+pub mod slip39;
+pub mod split;
+#[cfg(feature = "sskr")]
+pub mod sskr;
+#[cfg(feature = "ssss")]
+pub mod ssss;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "krawczyk")]
+pub mod stream;
+pub mod strict;
+#[cfg(feature = "testvectors")]
+pub mod testvectors;
+#[cfg(feature = "shamir")]
+pub mod transcript;
+#[cfg(feature = "shamir")]
+pub mod vault;
+#[cfg(feature = "krawczyk")]
+pub mod watch;
+pub mod wire;
+pub mod words;
 
-mod share;
 use share::Share;
 
 #[doc(inline)]
-pub use crate::{
-    ids::RabinInformationDispersal,
-    secret::{KrawczykSecretSharing, ShamirSecretSharing},
-};
+pub use crate::error::SharingError;
+
+#[doc(inline)]
+pub use crate::share::{KrawczykBatchShare, KrawczykShare, RabinShare, ShamirShare};
+
+#[doc(inline)]
+pub use crate::migration::{migrate, AnyShare};
+
+#[cfg(feature = "rabin")]
+#[doc(inline)]
+pub use crate::ids::RabinInformationDispersal;
+
+#[cfg(feature = "shamir")]
+#[doc(inline)]
+pub use crate::{policy::PolicySecretSharing, secret::ShamirSecretSharing, set::ShareSet};
+
+#[cfg(feature = "krawczyk")]
+#[doc(inline)]
+pub use crate::secret::{KrawczykCombiner, KrawczykSecretSharing};
+
+#[cfg(all(feature = "shamir", feature = "rabin"))]
+#[doc(inline)]
+pub use crate::detect::{reconstruct, reconstruct_any};
+
+#[cfg(feature = "krawczyk")]
+#[doc(inline)]
+pub use crate::detect::{reconstruct_any_with, reconstruct_with};
+
+#[cfg(feature = "aes")]
+#[doc(inline)]
+pub use crate::secret::AesKrawczykSecretSharing;
+
+#[cfg(feature = "xchacha")]
+#[doc(inline)]
+pub use crate::secret::XChaChaKrawczykSecretSharing;
+
+/// The threshold parameters of a scheme: `k` out of `n` shares are needed to reconstruct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Parameters {
+    n: u8,
+    k: u8,
+}
+
+impl Parameters {
+    /// `None` unless `1 <= k <= n`.
+    pub fn new(n: u8, k: u8) -> Option<Self> {
+        if k < 1 || k > n {
+            None
+        } else {
+            Some(Self { n, k })
+        }
+    }
+
+    pub fn n(&self) -> u8 {
+        self.n
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Parameters {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let n = u.int_in_range(1..=255)?;
+        let k = u.int_in_range(1..=n)?;
+        Ok(Self { n, k })
+    }
+}
+
+/// The schemes implemented by this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    Shamir,
+    Rabin,
+    Krawczyk,
+}
 
+impl Scheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scheme::Shamir => "shamir",
+            Scheme::Rabin => "rabin",
+            Scheme::Krawczyk => "krawczyk",
+        }
+    }
+}
+
+impl std::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Scheme {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shamir" => Ok(Scheme::Shamir),
+            "rabin" => Ok(Scheme::Rabin),
+            "krawczyk" => Ok(Scheme::Krawczyk),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A threshold scheme, failing with a [`SharingError`] that tells why.
+///
+/// ```rust
+/// # #[cfg(feature = "shamir")] {
+/// use sharing::{ShamirSecretSharing, Sharing, SharingError};
+///
+/// let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+/// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+///
+/// match sharer.reconstruct(shares[..2].to_vec()) {
+///     Err(SharingError::InsufficientShares { needed, found }) => assert_eq!((needed, found), (3, 2)),
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// // a share given twice counts once
+/// let again = vec![shares[0].clone(), shares[0].clone(), shares[1].clone(), shares[2].clone()];
+/// assert_eq!(sharer.reconstruct(again).unwrap(), vec![1, 2, 3]);
+///
+/// let mut forged = shares[0].clone();
+/// forged.body[0] ^= 1;
+/// let shares = vec![shares[0].clone(), shares[1].clone(), forged];
+/// assert!(matches!(sharer.reconstruct(shares), Err(SharingError::DuplicateShareId(1))));
+/// # }
+/// ```
 pub trait Sharing {
     type Share: Share;
 
-    fn share(&self, data: Vec<u8>) -> Option<Vec<Self::Share>>;
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError>;
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError>;
+
+    /// The old, misspelled name of [`reconstruct`](Self::reconstruct).
+    #[deprecated(since = "0.2.0", note = "renamed to `reconstruct`")]
+    fn recontruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct(shares)
+    }
 
-    fn recontruct(&self, shares: Vec<Self::Share>) -> Option<Vec<u8>>;
+    /// Like [`reconstruct`](Self::reconstruct), but borrows the shares. The schemes of this
+    /// crate read them in place, by default they are cloned.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "rabin")] {
+    /// use sharing::{RabinInformationDispersal, Sharing};
+    ///
+    /// let sharer = RabinInformationDispersal::new(5, 3);
+    /// let shares = sharer.share(vec![7; 1 << 20]).unwrap();
+    /// assert_eq!(sharer.reconstruct_ref(&shares[2..]).unwrap(), vec![7; 1 << 20]);
+    /// # }
+    /// ```
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct(shares.to_vec())
+    }
+
+    /// Like [`share`](Self::share), but stores the shares in `out`, reusing its capacity,
+    /// for dealing many secrets one after another. The Shamir, Rabin and Krawczyk schemes
+    /// also reuse the bodies of the shares already in `out`.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "shamir")] {
+    /// use sharing::{ShamirSecretSharing, Sharing};
+    ///
+    /// let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+    /// let (mut shares, mut data) = (Vec::new(), Vec::new());
+    /// for key in [[1u8; 32], [2u8; 32]].iter() {
+    ///     sharer.share_into(key.to_vec(), &mut shares).unwrap();
+    ///     sharer.reconstruct_into(&shares[1..], &mut data).unwrap();
+    ///     assert_eq!(&data[..], &key[..]);
+    /// }
+    /// # }
+    /// ```
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<Self::Share>) -> Result<(), SharingError> {
+        let shares = self.share(data)?;
+        out.clear();
+        out.extend(shares);
+        Ok(())
+    }
+
+    /// Like [`reconstruct_ref`](Self::reconstruct_ref), but writes the data to `out`.
+    /// The Shamir, Rabin and Krawczyk schemes reuse its capacity, by default it is replaced.
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        *out = self.reconstruct_ref(shares)?;
+        Ok(())
+    }
+
+    /// Like [`reconstruct_into`](Self::reconstruct_into), but writes the data to locked memory.
+    /// The Shamir, Rabin and Krawczyk schemes reconstruct it there, by default it is moved
+    /// there and the intermediate buffer wiped. Available with the `secure-memory` feature.
+    #[cfg(feature = "secure-memory")]
+    fn reconstruct_secure_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut secure::SecretBuffer,
+    ) -> Result<(), SharingError> {
+        *out = secure::SecretBuffer::from_vec(self.reconstruct_ref(shares)?);
+        Ok(())
+    }
 
     // fn reconstruct_partial<S: ShareVec>(&self, shares: S, start: i64) -> Result<Vec<u8>>;
 
@@ -39,4 +384,271 @@ pub trait Sharing {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    /// Feeds random, truncated and mutually inconsistent input to the decoders and
+    /// reconstructions, which must reject it without panicking.
+    #[test]
+    #[cfg(feature = "krawczyk")]
+    fn no_panic_on_any_input() {
+        use crate::{
+            compression::Compression,
+            fragment::Fragment,
+            manifest::Manifest,
+            policy::Policy,
+            share::{KrawczykDataShare, KrawczykKeyShare, ShareBytes},
+            strict::{Limits, StrictBytes},
+            transcript::Transcript,
+            *,
+        };
+        use chacha20poly1305::ChaCha20Poly1305;
+        use rand::{rngs::ThreadRng, Rng};
+
+        fn bytes(rng: &mut ThreadRng, max: usize) -> Vec<u8> {
+            (0..rng.gen_range(0, max)).map(|_| rng.gen()).collect()
+        }
+
+        let mut rng = rand::thread_rng();
+        let limits = Limits::default();
+        for _ in 0..2000 {
+            let raw = bytes(&mut rng, 64);
+            let _ = ShamirShare::from_bytes(&raw);
+            let _ = RabinShare::from_bytes(&raw);
+            let _ = KrawczykShare::from_bytes(&raw);
+            let _ = KrawczykKeyShare::from_bytes(&raw);
+            let _ = KrawczykDataShare::from_bytes(&raw);
+            let _ = Fragment::from_bytes(&raw);
+            let _ = RabinShare::from_bytes_strict(&raw, &limits);
+            let _ = KrawczykShare::from_bytes_strict(&raw, &limits);
+            let _ = wire::WireShare::<RabinShare>::from_bytes_strict(&raw, &limits);
+            let text = String::from_utf8_lossy(&raw);
+            let _ = text.parse::<Manifest>();
+            let _ = text.parse::<Transcript>();
+            let _ = Policy::parse(&text);
+            let _ = text.parse::<KrawczykShare>();
+
+            let (n, k) = (rng.gen_range(0, 6), rng.gen_range(0, 6));
+            let count = rng.gen_range(0, 6);
+            let shamir: Vec<ShamirShare> = (0..count)
+                .map(|_| ShamirShare {
+                    id: rng.gen(),
+                    body: bytes(&mut rng, 8),
+                })
+                .collect();
+            let rabin: Vec<RabinShare> = (0..count)
+                .map(|_| RabinShare {
+                    id: rng.gen(),
+                    length: rng.gen_range(0, 64),
+                    body: bytes(&mut rng, 8),
+                })
+                .collect();
+            let krawczyk: Vec<KrawczykShare> = shamir
+                .iter()
+                .zip(&rabin)
+                .map(|(s, r)| KrawczykShare {
+                    id: s.id,
+                    length: r.length,
+                    key: s.body.clone(),
+                    compression: Compression::None,
+                    associated_data: Vec::new(),
+                    digest: [0; 32],
+                    body: r.body.clone(),
+                })
+                .collect();
+
+            let sharer = ShamirSecretSharing::new(n, k, rand::thread_rng());
+            let _ = sharer.share(raw.clone());
+            let _ = sharer.reconstruct(shamir.clone());
+            let sharer = RabinInformationDispersal::new(n, k);
+            let _ = sharer.share(raw.clone());
+            let _ = sharer.reconstruct(rabin);
+            let sharer =
+                KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(n, k, rand::thread_rng());
+            let _ = sharer.share(raw);
+            let _ = sharer.reconstruct(krawczyk);
+            let _ = ShareSet::new(k, shamir.clone()).verify();
+            let _ = mpc::aggregate(&[shamir.clone(), shamir]);
+
+            #[cfg(feature = "sskr")]
+            {
+                let len = rng.gen_range(0, 20);
+                let shares: Vec<sskr::SskrShare> = (0..count)
+                    .map(|_| sskr::SskrShare {
+                        identifier: 0,
+                        group_index: rng.gen_range(0, 3),
+                        group_threshold: rng.gen_range(0, 3),
+                        group_count: 3,
+                        member_index: rng.gen_range(0, 3),
+                        member_threshold: rng.gen_range(0, 3),
+                        value: (0..len).map(|_| rng.gen()).collect(),
+                    })
+                    .collect();
+                let _ = sskr::combine(&shares);
+            }
+        }
+
+        #[cfg(feature = "sskr")]
+        {
+            use crate::{share::Share, sskr::SskrShare};
+
+            let mut short = SskrShare::with_size(2);
+            short.member_threshold = 2;
+            assert!(sskr::combine(&[short.clone(), short]).is_err());
+            let mut unreachable = SskrShare::with_size(16);
+            unreachable.member_threshold = 0;
+            assert!(sskr::combine(&[unreachable]).is_err());
+            let mut no_groups = SskrShare::with_size(16);
+            no_groups.group_threshold = 0;
+            assert!(sskr::combine(&[no_groups]).is_err());
+        }
+
+        #[cfg(feature = "slip39")]
+        {
+            use crate::{share::Share, slip39::Slip39Share};
+
+            let mut short = Slip39Share::with_size(2);
+            short.member_threshold = 2;
+            assert!(slip39::combine(&[short.clone(), short], "").is_err());
+            let mut unreachable = Slip39Share::with_size(16);
+            unreachable.member_threshold = 0;
+            assert!(slip39::combine(&[unreachable], "").is_err());
+            assert!(slip39::combine(&[Slip39Share::with_size(17)], "").is_err());
+            let mut slow = Slip39Share::with_size(16);
+            slow.iteration_exponent = 40;
+            assert!(slip39::combine(&[slow], "").is_err());
+        }
+    }
+
+    /// Reused buffers, growing and shrinking, give the same results as fresh ones.
+    #[test]
+    #[cfg(all(feature = "shamir", feature = "rabin"))]
+    fn into_reuses_buffers() {
+        use crate::*;
+
+        fn check<T: Sharing>(sharer: T) -> Vec<u8> {
+            let (mut shares, mut data) = (Vec::new(), Vec::new());
+            for len in [100, 3, 0, 50].iter() {
+                let secret: Vec<u8> = (0..*len as u8).collect();
+                sharer.share_into(secret.clone(), &mut shares).unwrap();
+                assert_eq!(sharer.reconstruct(shares.clone()).unwrap(), secret);
+                sharer.reconstruct_into(&shares[1..], &mut data).unwrap();
+                assert_eq!(data, secret);
+            }
+            assert!(sharer.reconstruct_into(&shares[..1], &mut data).is_err());
+            data
+        }
+
+        assert_eq!(
+            check(ShamirSecretSharing::new(4, 3, rand::thread_rng())).len(),
+            50
+        );
+        assert_eq!(check(RabinInformationDispersal::new(4, 3)).len(), 50);
+        #[cfg(feature = "krawczyk")]
+        check(
+            KrawczykSecretSharing::<chacha20poly1305::ChaCha20Poly1305, _>::new(
+                4,
+                3,
+                rand::thread_rng(),
+            ),
+        );
+    }
+
+    /// Reconstructing into locked memory gives the same data as into a `Vec`.
+    #[test]
+    #[cfg(all(feature = "secure-memory", feature = "krawczyk"))]
+    fn secure_reconstructs() {
+        use crate::{secure::SecretBuffer, *};
+        use chacha20poly1305::ChaCha20Poly1305;
+
+        fn check<T: Sharing>(sharer: T) {
+            let mut data = SecretBuffer::new(0);
+            for len in [100, 0, 3].iter() {
+                let secret: Vec<u8> = (0..*len as u8).collect();
+                let shares = sharer.share(secret.clone()).unwrap();
+                sharer
+                    .reconstruct_secure_into(&shares[1..], &mut data)
+                    .unwrap();
+                assert_eq!(&data[..], &secret[..]);
+            }
+            assert!(sharer
+                .reconstruct_secure_into(&Vec::new(), &mut data)
+                .is_err());
+        }
+
+        check(ShamirSecretSharing::new(4, 3, rand::thread_rng()));
+        check(RabinInformationDispersal::new(4, 3));
+        check(KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(
+            4,
+            3,
+            rand::thread_rng(),
+        ));
+        check(metrics::Instrumented::new(
+            KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(4, 3, rand::thread_rng()),
+            metrics::Counters::default(),
+        ));
+        check(composite::Composite::new(
+            ShamirSecretSharing::new(3, 2, rand::thread_rng()),
+            RabinInformationDispersal::new(4, 3),
+        ));
+        #[cfg(feature = "lz4")]
+        check(
+            KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(4, 3, rand::thread_rng())
+                .with_compression(compression::Compression::Lz4),
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "serde", feature = "krawczyk"))]
+    fn serde_round_trips() {
+        use crate::{composite::CompositeShare, vault::VaultShare, wire::WireShare, *};
+        use chacha20poly1305::ChaCha20Poly1305;
+
+        let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+        let share = sharer.share(vec![1, 2, 3]).unwrap().remove(0);
+        let json = serde_json::to_string(&share).unwrap();
+        assert!(json.contains(r#""compression":"none""#));
+        assert_eq!(serde_json::from_str::<KrawczykShare>(&json).unwrap(), share);
+        // a digest must be exactly 32 bytes
+        let short = json.replace(&base64_digest(&share.digest), "AAAA");
+        assert!(serde_json::from_str::<KrawczykShare>(&short).is_err());
+
+        let wire = WireShare::new(
+            2,
+            RabinShare {
+                id: 1,
+                length: 2,
+                body: vec![9],
+            },
+        );
+        let json = serde_json::to_string(&wire).unwrap();
+        assert_eq!(
+            serde_json::from_str::<WireShare<RabinShare>>(&json).unwrap(),
+            wire
+        );
+
+        let composite = CompositeShare {
+            parts: vec![ShamirShare {
+                id: 1,
+                body: vec![7],
+            }],
+        };
+        let json = serde_json::to_string(&composite).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CompositeShare<ShamirShare>>(&json).unwrap(),
+            composite
+        );
+
+        let mut vault = VaultShare {
+            id: 1,
+            entries: Default::default(),
+        };
+        vault.entries.insert("db".to_string(), vec![0xff]);
+        let json = serde_json::to_string(&vault).unwrap();
+        assert_eq!(json, r#"{"id":1,"entries":{"db":"/w=="}}"#);
+        assert_eq!(serde_json::from_str::<VaultShare>(&json).unwrap(), vault);
+
+        fn base64_digest(digest: &[u8]) -> String {
+            use base64::{engine::general_purpose::STANDARD, Engine};
+            STANDARD.encode(digest)
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Share Manifests
+//!
+//! A [`Manifest`] describes a share set: the scheme, `n` and `k`, a set id,
+//! the SHA-256 of every encoded share and optionally a digest of the payload.
+//! Holders verify collected shares against it before reconstructing, and the manifest
+//! itself can be hashed with [`Manifest::digest`] to be signed or published.
+//!
+//! Manifests are written in the same line based text format as the test vectors:
+//!
+//! ```text
+//! version = 2
+//! scheme = shamir
+//! n = 3
+//! k = 2
+//! set = <16 bytes hex>
+//! payload = <hmac-sha256 hex>     # optional, keyed with the set id
+//! chunk = <length> <sha256 hex>   # for every chunk of a cdc sharing
+//! keyfile = <key template>         # optional, see `keyfile`
+//! share = <id> <sha256 hex>
+//! ```
+//!
+//! ```rust
+//! use sharing::{manifest::Manifest, Parameters, Scheme, ShamirSecretSharing, Sharing};
+//!
+//! let data = b"launch codes".to_vec();
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares = sharer.share(data.clone()).unwrap();
+//!
+//! let manifest = Manifest::new(Scheme::Shamir, Parameters::new(3, 2).unwrap(), &shares)
+//!     .with_payload(&data);
+//! let published = manifest.to_string();
+//!
+//! let manifest: Manifest = published.parse().unwrap();
+//! assert!(shares.iter().all(|s| manifest.verify_share(s)));
+//!
+//! let mut tampered = shares[0].clone();
+//! tampered.body[0] ^= 1;
+//! assert!(!manifest.verify_share(&tampered));
+//!
+//! let collected = manifest.collect(vec![tampered, shares[1].clone(), shares[2].clone()]);
+//! assert_eq!(collected.len(), 2);
+//! assert!(manifest.verify_payload(&sharer.reconstruct(collected).unwrap()));
+//! ```
+#[cfg(feature = "keyfile")]
+use crate::keyfile::KeyTemplate;
+use crate::{
+    cdc::Chunk,
+    drbg::hmac,
+    error::ParseTextError,
+    share::{parse_hex, Hex, ShareBytes, ShareId},
+    Parameters, Scheme,
+};
+use sha2::{Digest, Sha256};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+const VERSION: u32 = 2;
+const DOMAIN: &[u8] = b"sharing/manifest/v1";
+const PAYLOAD_DOMAIN: &[u8] = b"sharing/manifest/payload";
+
+/// Describes a share set and the digests to verify it with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    version: u32,
+    scheme: Scheme,
+    parameters: Parameters,
+    set_id: [u8; 16],
+    payload: Option<[u8; 32]>,
+    chunks: Vec<Chunk>,
+    #[cfg(feature = "keyfile")]
+    key_file: Option<KeyTemplate>,
+    shares: Vec<(u8, [u8; 32])>,
+}
+
+impl Manifest {
+    /// Describes freshly dealt shares, the set id is derived from their digests.
+    pub fn new<S: ShareBytes + ShareId>(
+        scheme: Scheme,
+        parameters: Parameters,
+        shares: &[S],
+    ) -> Self {
+        let shares: Vec<(u8, [u8; 32])> = shares
+            .iter()
+            .map(|s| (s.id(), Sha256::digest(s.to_bytes()).into()))
+            .collect();
+        let mut hasher = Sha256::new();
+        hasher.update(DOMAIN);
+        for (id, digest) in &shares {
+            hasher.update([*id]);
+            hasher.update(digest);
+        }
+        let mut set_id = [0u8; 16];
+        set_id.copy_from_slice(&hasher.finalize()[..16]);
+        Self {
+            version: VERSION,
+            scheme,
+            parameters,
+            set_id,
+            payload: None,
+            chunks: Vec::new(),
+            #[cfg(feature = "keyfile")]
+            key_file: None,
+            shares,
+        }
+    }
+
+    /// Records a digest of the shared data, HMAC-SHA256 keyed with the set id.
+    ///
+    /// The set id differs for every dealing, so the digest does not link the same data shared
+    /// twice and cannot be looked up in precomputed tables. It is still public: anyone with
+    /// the manifest can check a guess of the data against it, so only record the payload of
+    /// data with enough entropy to resist guessing, such as keys, not passwords or short
+    /// messages.
+    ///
+    /// ```rust
+    /// use sharing::{manifest::Manifest, Parameters, Scheme, ShamirSecretSharing, Sharing};
+    ///
+    /// let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+    /// let parameters = Parameters::new(3, 2).unwrap();
+    /// let key = [7u8; 32];
+    /// let deal = || {
+    ///     let shares = sharer.share(key.to_vec()).unwrap();
+    ///     Manifest::new(Scheme::Shamir, parameters, &shares).with_payload(&key)
+    /// };
+    /// let (first, second) = (deal(), deal());
+    /// assert_ne!(first.payload(), second.payload());
+    /// assert!(first.verify_payload(&key) && second.verify_payload(&key));
+    /// ```
+    pub fn with_payload(mut self, data: &[u8]) -> Self {
+        self.version = VERSION;
+        self.payload = Some(self.payload_digest(data));
+        self
+    }
+
+    /// Records the chunk index of a [`cdc`](crate::cdc) sharing.
+    pub fn with_chunks(mut self, chunks: &[Chunk]) -> Self {
+        self.chunks = chunks.to_vec();
+        self
+    }
+
+    /// Records the public part of a shared key file, see [`keyfile`](crate::keyfile).
+    #[cfg(feature = "keyfile")]
+    pub fn with_key_file(mut self, template: KeyTemplate) -> Self {
+        self.key_file = Some(template);
+        self
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn parameters(&self) -> Parameters {
+        self.parameters
+    }
+
+    pub fn set_id(&self) -> [u8; 16] {
+        self.set_id
+    }
+
+    /// The digest of the shared data, if recorded, see [`with_payload`](Self::with_payload).
+    pub fn payload(&self) -> Option<[u8; 32]> {
+        self.payload
+    }
+
+    /// The chunk index, empty unless the data was shared in chunks.
+    pub fn chunks(&self) -> &[Chunk] {
+        &self.chunks
+    }
+
+    /// The template of a shared key file, if recorded.
+    #[cfg(feature = "keyfile")]
+    pub fn key_file(&self) -> Option<&KeyTemplate> {
+        self.key_file.as_ref()
+    }
+
+    /// The ids and digests of the shares in the set.
+    pub fn shares(&self) -> &[(u8, [u8; 32])] {
+        &self.shares
+    }
+
+    /// Checks that the share is the one listed under its id.
+    pub fn verify_share<S: ShareBytes + ShareId>(&self, share: &S) -> bool {
+        let digest: [u8; 32] = Sha256::digest(share.to_bytes()).into();
+        self.shares
+            .iter()
+            .any(|(id, d)| *id == share.id() && *d == digest)
+    }
+
+    /// Keeps the collected shares that match the manifest, dropping tampered or foreign ones.
+    pub fn collect<S: ShareBytes + ShareId>(&self, mut shares: Vec<S>) -> Vec<S> {
+        shares.retain(|s| self.verify_share(s));
+        shares
+    }
+
+    /// Checks reconstructed data, `false` if the manifest has no payload digest.
+    ///
+    /// Manifests of version 1 recorded the unsalted SHA-256 of the data and are still checked.
+    ///
+    /// ```rust
+    /// use sharing::manifest::Manifest;
+    ///
+    /// // SHA-256 of "abc"
+    /// let manifest: Manifest = "version = 1\nscheme = shamir\nn = 3\nk = 2\nset = 00000000000000000000000000000000\npayload = ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(manifest.verify_payload(b"abc"));
+    /// assert!(manifest.to_string().starts_with("version = 1\n"));
+    /// ```
+    pub fn verify_payload(&self, data: &[u8]) -> bool {
+        self.payload == Some(self.payload_digest(data))
+    }
+
+    /// HMAC-SHA256 of `data` keyed with the set id, SHA-256 in version 1.
+    fn payload_digest(&self, data: &[u8]) -> [u8; 32] {
+        if self.version == 1 {
+            return Sha256::digest(data).into();
+        }
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(&self.set_id);
+        hmac(&key, &[PAYLOAD_DOMAIN, data])
+    }
+
+    /// SHA-256 of the text form, to sign or publish.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(DOMAIN);
+        hasher.update(self.to_string());
+        hasher.finalize().into()
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version = {}", self.version)?;
+        writeln!(f, "scheme = {}", self.scheme)?;
+        writeln!(f, "n = {}", self.parameters.n())?;
+        writeln!(f, "k = {}", self.parameters.k())?;
+        writeln!(f, "set = {}", Hex(&self.set_id))?;
+        if let Some(payload) = &self.payload {
+            writeln!(f, "payload = {}", Hex(payload))?;
+        }
+        for chunk in &self.chunks {
+            writeln!(f, "chunk = {} {}", chunk.length, Hex(&chunk.digest))?;
+        }
+        #[cfg(feature = "keyfile")]
+        if let Some(template) = &self.key_file {
+            writeln!(f, "keyfile = {}", template)?;
+        }
+        for (id, digest) in &self.shares {
+            writeln!(f, "share = {} {}", id, Hex(digest))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = ParseTextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut version = None;
+        let mut scheme = None;
+        let mut n = None;
+        let mut k = None;
+        let mut set_id = None;
+        let mut payload = None;
+        let mut chunks = Vec::new();
+        #[cfg(feature = "keyfile")]
+        let mut key_file = None;
+        let mut shares = Vec::new();
+
+        for (number, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |field| ParseTextError::new(number + 1, field);
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().ok_or_else(|| error("line"))?.trim();
+            match key {
+                "version" => version = Some(value.parse::<u32>().map_err(|_| error("version"))?),
+                "scheme" => scheme = Some(value.parse::<Scheme>().map_err(|_| error("scheme"))?),
+                "n" => n = Some(value.parse::<u8>().map_err(|_| error("n"))?),
+                "k" => k = Some(value.parse::<u8>().map_err(|_| error("k"))?),
+                "set" => set_id = Some(from_hex::<16>(value).ok_or_else(|| error("set"))?),
+                "payload" => payload = Some(from_hex::<32>(value).ok_or_else(|| error("payload"))?),
+                "chunk" => {
+                    let fields: Vec<&str> = value.split_whitespace().collect();
+                    match fields.as_slice() {
+                        [length, digest] => chunks.push(Chunk {
+                            length: length.parse().map_err(|_| error("chunk"))?,
+                            digest: from_hex::<32>(digest).ok_or_else(|| error("chunk"))?,
+                        }),
+                        _ => return Err(error("chunk")),
+                    }
+                }
+                #[cfg(feature = "keyfile")]
+                "keyfile" => key_file = Some(value.parse().map_err(|_| error("keyfile"))?),
+                "share" => {
+                    let fields: Vec<&str> = value.split_whitespace().collect();
+                    match fields.as_slice() {
+                        [id, digest] => shares.push((
+                            id.parse().map_err(|_| error("share"))?,
+                            from_hex::<32>(digest).ok_or_else(|| error("share"))?,
+                        )),
+                        _ => return Err(error("share")),
+                    }
+                }
+                _ => return Err(error("key")),
+            }
+        }
+
+        let missing = ParseTextError::missing;
+        let version = version.ok_or_else(|| missing("version"))?;
+        if !(1..=VERSION).contains(&version) {
+            return Err(missing("version"));
+        }
+        Ok(Self {
+            version,
+            scheme: scheme.ok_or_else(|| missing("scheme"))?,
+            parameters: Parameters::new(
+                n.ok_or_else(|| missing("n"))?,
+                k.ok_or_else(|| missing("k"))?,
+            )
+            .ok_or_else(|| missing("k"))?,
+            set_id: set_id.ok_or_else(|| missing("set"))?,
+            payload,
+            chunks,
+            #[cfg(feature = "keyfile")]
+            key_file,
+            shares,
+        })
+    }
+}
+
+fn from_hex<const N: usize>(s: &str) -> Option<[u8; N]> {
+    <[u8; N]>::try_from(parse_hex(s).ok()?).ok()
+}
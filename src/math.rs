@@ -0,0 +1,408 @@
+//! GF(256) Arithmetic
+//!
+//! The primitives the schemes in this crate are built on, operating on plain bytes.
+//! Addition and subtraction are both XOR; the free functions multiply using the
+//! default reduction polynomial `0x11d`. Use a [`Field`] to work with another polynomial.
+//!
+//! ```rust
+//! use sharing::math;
+//!
+//! // f(x) = 7 + 3x + x²
+//! let coefficients = [7, 3, 1];
+//! let points: Vec<(u8, u8)> = (1..=3).map(|x| (x, math::evaluate(&coefficients, x))).collect();
+//!
+//! assert_eq!(math::interpolate(&points, 0), Some(7));
+//! ```
+//!
+//! The tables are built at compile time and the arithmetic is `const`, so fields can live
+//! in read-only memory and fixed test vectors can be checked while building:
+//!
+//! ```rust
+//! use sharing::math::{self, Field};
+//!
+//! const AES: Field = match Field::new(Field::AES_POLYNOMIAL) {
+//!     Some(field) => field,
+//!     None => panic!(),
+//! };
+//! const SHARES: [(u8, u8); 2] = [
+//!     (1, math::encode_byte(0x42, &[0x17], 1)),
+//!     (2, math::encode_byte(0x42, &[0x17], 2)),
+//! ];
+//! const _: () = assert!(AES.mul(0x57, 0x83) == 0xc1);
+//! const _: () = assert!(matches!(math::interpolate_byte(&SHARES), Some(0x42)));
+//! ```
+use std::fmt;
+
+static DEFAULT: Field = Field::DEFAULT;
+
+/// GF(256) defined by a specific irreducible reduction polynomial.
+///
+/// Interoperating with other Shamir or Reed-Solomon implementations requires using the same field.
+///
+/// ```rust
+/// use sharing::math::Field;
+///
+/// let aes = Field::new(Field::AES_POLYNOMIAL).unwrap();
+/// assert_eq!(aes.mul(0x57, 0x83), 0xc1);
+///
+/// // x⁸ + 1 is not irreducible
+/// assert!(Field::new(0x101).is_none());
+/// ```
+#[derive(Clone)]
+pub struct Field {
+    polynomial: u16,
+    log: [u8; 256],
+    exp: [u8; 510],
+}
+
+impl Field {
+    /// `x⁸ + x⁴ + x³ + x² + 1`, used by this crate by default and by most Reed-Solomon codes.
+    pub const DEFAULT_POLYNOMIAL: u16 = 0x11d;
+    /// `x⁸ + x⁴ + x³ + x + 1`, the Rijndael field used by AES and many Shamir implementations.
+    pub const AES_POLYNOMIAL: u16 = 0x11b;
+    /// The field with the default polynomial, see [`default_ref`](Self::default_ref)
+    /// to borrow it without a copy.
+    pub const DEFAULT: Field = match Field::new(Field::DEFAULT_POLYNOMIAL) {
+        Some(field) => field,
+        None => panic!("0x11d is irreducible"),
+    };
+    /// The AES field.
+    pub const AES: Field = match Field::new(Field::AES_POLYNOMIAL) {
+        Some(field) => field,
+        None => panic!("0x11b is irreducible"),
+    };
+
+    /// Creates the field for the given degree 8 polynomial, `None` if it is not irreducible.
+    pub const fn new(polynomial: u16) -> Option<Self> {
+        if polynomial >> 8 != 1 {
+            return None;
+        }
+        // the polynomial is irreducible iff some element generates all 255 units
+        let mut generator = 2u16;
+        while generator < 256 {
+            let mut exp = [0u8; 510];
+            let mut log = [0u8; 256];
+            let mut seen = [false; 256];
+            let mut x = 1u16;
+            let mut i = 0;
+            while i < 255 {
+                if x == 0 || seen[x as usize] {
+                    break;
+                }
+                seen[x as usize] = true;
+                exp[i] = x as u8;
+                exp[i + 255] = x as u8;
+                log[x as usize] = i as u8;
+                x = Self::mul_slow(x, generator, polynomial);
+                i += 1;
+            }
+            if i == 255 && x == 1 {
+                return Some(Self {
+                    polynomial,
+                    log,
+                    exp,
+                });
+            }
+            generator += 1;
+        }
+        None
+    }
+
+    const fn mul_slow(mut a: u16, mut b: u16, polynomial: u16) -> u16 {
+        let mut res = 0;
+        while b != 0 {
+            if b & 1 == 1 {
+                res ^= a;
+            }
+            a <<= 1;
+            if a & 0x100 != 0 {
+                a ^= polynomial;
+            }
+            b >>= 1;
+        }
+        res
+    }
+
+    /// The field with the default polynomial `0x11d`.
+    pub const fn default_ref() -> &'static Field {
+        &DEFAULT
+    }
+
+    pub const fn polynomial(&self) -> u16 {
+        self.polynomial
+    }
+
+    pub const fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+    }
+
+    /// Divides `a` by `b`, `None` if `b` is zero.
+    pub const fn div(&self, a: u8, b: u8) -> Option<u8> {
+        if b == 0 {
+            None
+        } else if a == 0 {
+            Some(0)
+        } else {
+            Some(self.exp[self.log[a as usize] as usize + 255 - self.log[b as usize] as usize])
+        }
+    }
+
+    /// The multiplicative inverse, `None` for zero.
+    pub const fn inverse(&self, a: u8) -> Option<u8> {
+        self.div(1, a)
+    }
+
+    pub const fn pow(&self, a: u8, exp: usize) -> u8 {
+        if exp == 0 {
+            1
+        } else if a == 0 {
+            0
+        } else {
+            self.exp[(self.log[a as usize] as usize * (exp % 255)) % 255]
+        }
+    }
+
+    /// Evaluates the polynomial `coefficients[0] + coefficients[1]·x + …` at `x` using Horner's rule.
+    pub const fn evaluate(&self, coefficients: &[u8], x: u8) -> u8 {
+        let mut res = 0;
+        let mut i = coefficients.len();
+        while i > 0 {
+            i -= 1;
+            res = add(coefficients[i], self.mul(x, res));
+        }
+        res
+    }
+
+    /// The Lagrange basis polynomials for the points `xs`, evaluated at `at`.
+    ///
+    /// Returns `None` if the points are not distinct.
+    pub fn lagrange_coefficients(&self, xs: &[u8], at: u8) -> Option<Vec<u8>> {
+        xs.iter()
+            .enumerate()
+            .map(|(j, xj)| {
+                xs.iter()
+                    .enumerate()
+                    .filter(|(m, _)| *m != j)
+                    .try_fold(1, |acc, (_, xm)| {
+                        Some(self.mul(acc, self.div(sub(at, *xm), sub(*xj, *xm))?))
+                    })
+            })
+            .collect()
+    }
+
+    /// The leading coefficients of the Lagrange basis polynomials for the points `xs`,
+    /// i.e. the weights that recover the coefficient of `x^(len-1)` from the values at `xs`.
+    ///
+    /// Returns `None` if the points are not distinct.
+    pub fn leading_coefficients(&self, xs: &[u8]) -> Option<Vec<u8>> {
+        xs.iter()
+            .enumerate()
+            .map(|(j, xj)| {
+                let denominator = xs
+                    .iter()
+                    .enumerate()
+                    .filter(|(m, _)| *m != j)
+                    .fold(1, |acc, (_, xm)| self.mul(acc, sub(*xj, *xm)));
+                self.inverse(denominator)
+            })
+            .collect()
+    }
+
+    /// Evaluates the unique polynomial through `points` at `at`, without allocating.
+    ///
+    /// Returns `None` if the x coordinates are not distinct.
+    pub const fn interpolate(&self, points: &[(u8, u8)], at: u8) -> Option<u8> {
+        let mut acc = 0;
+        let mut j = 0;
+        while j < points.len() {
+            let (xj, yj) = points[j];
+            let mut basis = 1;
+            let mut m = 0;
+            while m < points.len() {
+                if m != j {
+                    let xm = points[m].0;
+                    basis = match self.div(sub(at, xm), sub(xj, xm)) {
+                        Some(factor) => self.mul(basis, factor),
+                        None => return None,
+                    };
+                }
+                m += 1;
+            }
+            acc = add(acc, self.mul(basis, yj));
+            j += 1;
+        }
+        Some(acc)
+    }
+
+    /// The share of `secret` at `x` for the polynomial `secret + coefficients[0]·x + …`.
+    ///
+    /// `coefficients` are the `k - 1` random coefficients, so shares of a byte can be computed
+    /// from a fixed size buffer without any allocation.
+    pub const fn encode_byte(&self, secret: u8, coefficients: &[u8], x: u8) -> u8 {
+        add(secret, self.mul(x, self.evaluate(coefficients, x)))
+    }
+
+    /// Recovers a secret byte from `k` `(x, share)` points without allocating,
+    /// `None` if the x coordinates are not distinct.
+    pub const fn interpolate_byte(&self, points: &[(u8, u8)]) -> Option<u8> {
+        self.interpolate(points, 0)
+    }
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        DEFAULT.clone()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Field {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let polynomial = 0x100 | u16::from(u.arbitrary::<u8>()?);
+        Ok(Field::new(polynomial).unwrap_or_default())
+    }
+}
+
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.polynomial == other.polynomial
+    }
+}
+
+impl Eq for Field {}
+
+impl fmt::Debug for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Field({:#x})", self.polynomial)
+    }
+}
+
+pub const fn add(a: u8, b: u8) -> u8 {
+    a ^ b
+}
+
+pub const fn sub(a: u8, b: u8) -> u8 {
+    a ^ b
+}
+
+pub const fn mul(a: u8, b: u8) -> u8 {
+    DEFAULT.mul(a, b)
+}
+
+/// Divides `a` by `b`, `None` if `b` is zero.
+pub const fn div(a: u8, b: u8) -> Option<u8> {
+    DEFAULT.div(a, b)
+}
+
+/// The multiplicative inverse, `None` for zero.
+pub const fn inverse(a: u8) -> Option<u8> {
+    DEFAULT.inverse(a)
+}
+
+pub const fn pow(a: u8, exp: usize) -> u8 {
+    DEFAULT.pow(a, exp)
+}
+
+/// Evaluates the polynomial `coefficients[0] + coefficients[1]·x + …` at `x` using Horner's rule.
+pub const fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    DEFAULT.evaluate(coefficients, x)
+}
+
+/// The Lagrange basis polynomials for the points `xs`, evaluated at `at`.
+///
+/// Returns `None` if the points are not distinct.
+pub fn lagrange_coefficients(xs: &[u8], at: u8) -> Option<Vec<u8>> {
+    DEFAULT.lagrange_coefficients(xs, at)
+}
+
+/// Evaluates the unique polynomial through `points` at `at`, without allocating.
+///
+/// Returns `None` if the x coordinates are not distinct.
+pub const fn interpolate(points: &[(u8, u8)], at: u8) -> Option<u8> {
+    DEFAULT.interpolate(points, at)
+}
+
+/// The share of `secret` at `x` for the polynomial `secret + coefficients[0]·x + …`,
+/// without allocating.
+///
+/// ```rust
+/// use sharing::math;
+///
+/// // a 32 byte key shared 2-of-3 with stack buffers only
+/// let key = [0x5au8; 32];
+/// let coefficients = [0x17u8; 32]; // use fresh random bytes for every key byte
+/// let mut shares = [[0u8; 32]; 3];
+/// for (i, byte) in key.iter().enumerate() {
+///     for (x, share) in (1..=3).zip(shares.iter_mut()) {
+///         share[i] = math::encode_byte(*byte, &coefficients[i..=i], x);
+///     }
+/// }
+///
+/// let mut recovered = [0u8; 32];
+/// for (i, byte) in recovered.iter_mut().enumerate() {
+///     *byte = math::interpolate_byte(&[(2, shares[1][i]), (3, shares[2][i])]).unwrap();
+/// }
+/// assert_eq!(recovered, key);
+/// ```
+pub const fn encode_byte(secret: u8, coefficients: &[u8], x: u8) -> u8 {
+    DEFAULT.encode_byte(secret, coefficients, x)
+}
+
+/// Recovers a secret byte from `k` `(x, share)` points without allocating,
+/// `None` if the x coordinates are not distinct.
+pub const fn interpolate_byte(points: &[(u8, u8)]) -> Option<u8> {
+    DEFAULT.interpolate_byte(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gf::GF;
+
+    #[test]
+    fn inverse_roundtrip() {
+        for a in 1..=255 {
+            assert_eq!(mul(a, inverse(a).unwrap()), 1);
+        }
+        assert_eq!(inverse(0), None);
+    }
+
+    #[test]
+    fn interpolate_duplicate_points() {
+        assert_eq!(interpolate(&[(1, 5), (1, 5)], 0), None);
+    }
+
+    #[test]
+    fn encode_byte_matches_evaluate() {
+        let coefficients = [0x42, 0x99, 0x07];
+        for x in 1..=255 {
+            assert_eq!(
+                encode_byte(0xab, &coefficients, x),
+                evaluate(&[0xab, 0x42, 0x99, 0x07], x)
+            );
+        }
+    }
+
+    #[test]
+    fn default_matches_gf() {
+        for a in 0..=255 {
+            for b in 0..=255 {
+                let expected: u8 = (GF(a) * GF(b)).into();
+                assert_eq!(mul(a, b), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn aes_field() {
+        let field = Field::new(Field::AES_POLYNOMIAL).unwrap();
+        for a in 1..=255 {
+            assert_eq!(field.mul(a, field.inverse(a).unwrap()), 1);
+        }
+        assert_eq!(field.pow(3, 255), 1);
+    }
+}
@@ -0,0 +1,294 @@
+//! Matrices over GF(256)
+//!
+//! ```rust
+//! use sharing::matrix::Matrix;
+//!
+//! let encoder = Matrix::vandermonde(&[1, 2, 3], 3);
+//! let decoder = encoder.inverse().unwrap();
+//!
+//! assert_eq!(encoder.mul(&decoder), Some(Matrix::identity(3)));
+//! ```
+use crate::math::{self, Field};
+
+/// A dense, row-major matrix of GF(256) elements.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<u8>,
+}
+
+impl Matrix {
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![0u8; rows * cols],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut m = Self::zero(size, size);
+        for i in 0..size {
+            m.put(i, i, 1);
+        }
+        m
+    }
+
+    /// Builds a matrix from its rows, `None` if the rows differ in length.
+    pub fn from_rows(rows: Vec<Vec<u8>>) -> Option<Self> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|r| r.len() != cols) {
+            return None;
+        }
+        Some(Self {
+            rows: rows.len(),
+            cols,
+            data: rows.concat(),
+        })
+    }
+
+    /// The Vandermonde matrix with rows `[1, x, x², …]` for every `x` in `xs`.
+    pub fn vandermonde(xs: &[u8], cols: usize) -> Self {
+        Self::vandermonde_in(Field::default_ref(), xs, cols)
+    }
+
+    /// [`Matrix::vandermonde`] over the given field.
+    pub fn vandermonde_in(field: &Field, xs: &[u8], cols: usize) -> Self {
+        let mut m = Self::zero(xs.len(), cols);
+        for (i, x) in xs.iter().enumerate() {
+            for j in 0..cols {
+                m.put(i, j, field.pow(*x, j));
+            }
+        }
+        m
+    }
+
+    /// The Cauchy matrix with entries `1 / (x_i + y_j)`.
+    ///
+    /// Returns `None` if any `x_i + y_j` is zero. Every square sub matrix
+    /// of a Cauchy matrix with distinct `xs` and `ys` is invertible.
+    pub fn cauchy(xs: &[u8], ys: &[u8]) -> Option<Self> {
+        Self::cauchy_in(Field::default_ref(), xs, ys)
+    }
+
+    /// [`Matrix::cauchy`] over the given field.
+    pub fn cauchy_in(field: &Field, xs: &[u8], ys: &[u8]) -> Option<Self> {
+        let mut m = Self::zero(xs.len(), ys.len());
+        for (i, x) in xs.iter().enumerate() {
+            for (j, y) in ys.iter().enumerate() {
+                m.put(i, j, field.inverse(math::add(*x, *y))?);
+            }
+        }
+        Some(m)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The element at `row` and `col`, `None` outside the matrix.
+    pub fn get(&self, row: usize, col: usize) -> Option<u8> {
+        self.index(row, col).map(|i| self.data[i])
+    }
+
+    /// Sets the element at `row` and `col`, `None` outside the matrix.
+    pub fn set(&mut self, row: usize, col: usize, value: u8) -> Option<()> {
+        let i = self.index(row, col)?;
+        self.data[i] = value;
+        Some(())
+    }
+
+    /// The elements of a row, `None` outside the matrix.
+    pub fn row(&self, row: usize) -> Option<&[u8]> {
+        if row >= self.rows {
+            return None;
+        }
+        Some(self.row_at(row))
+    }
+
+    fn index(&self, row: usize, col: usize) -> Option<usize> {
+        if row < self.rows && col < self.cols {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    // unchecked accessors for the loops below, which stay within the dimensions
+
+    fn at(&self, row: usize, col: usize) -> u8 {
+        self.data[row * self.cols + col]
+    }
+
+    fn put(&mut self, row: usize, col: usize, value: u8) {
+        self.data[row * self.cols + col] = value;
+    }
+
+    fn row_at(&self, row: usize) -> &[u8] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = Self::zero(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                m.put(j, i, self.at(i, j));
+            }
+        }
+        m
+    }
+
+    /// The matrix product, `None` if the dimensions do not match.
+    pub fn mul(&self, other: &Matrix) -> Option<Matrix> {
+        self.mul_in(Field::default_ref(), other)
+    }
+
+    /// [`Matrix::mul`] over the given field.
+    pub fn mul_in(&self, field: &Field, other: &Matrix) -> Option<Matrix> {
+        if self.cols != other.rows {
+            return None;
+        }
+        let mut m = Self::zero(self.rows, other.cols);
+        for i in 0..self.rows {
+            for j in 0..other.cols {
+                let value = (0..self.cols).fold(0, |acc, x| {
+                    math::add(acc, field.mul(self.at(i, x), other.at(x, j)))
+                });
+                m.put(i, j, value);
+            }
+        }
+        Some(m)
+    }
+
+    /// Multiplies the matrix with a column vector, `None` if the dimensions do not match.
+    pub fn mul_vec(&self, vector: &[u8]) -> Option<Vec<u8>> {
+        self.mul_vec_in(Field::default_ref(), vector)
+    }
+
+    /// [`Matrix::mul_vec`] over the given field.
+    pub fn mul_vec_in(&self, field: &Field, vector: &[u8]) -> Option<Vec<u8>> {
+        if self.cols != vector.len() {
+            return None;
+        }
+        Some(
+            (0..self.rows)
+                .map(|i| {
+                    self.row_at(i)
+                        .iter()
+                        .zip(vector)
+                        .fold(0, |acc, (a, b)| math::add(acc, field.mul(*a, *b)))
+                })
+                .collect(),
+        )
+    }
+
+    /// Inverts the matrix with Gauss-Jordan elimination and row pivoting.
+    ///
+    /// Returns `None` if the matrix is not square or singular.
+    pub fn inverse(&self) -> Option<Matrix> {
+        self.inverse_in(Field::default_ref())
+    }
+
+    /// [`Matrix::inverse`] over the given field.
+    pub fn inverse_in(&self, field: &Field) -> Option<Matrix> {
+        if self.rows != self.cols {
+            return None;
+        }
+        let size = self.rows;
+        let mut tmp = self.clone();
+        let mut res = Self::identity(size);
+
+        for i in 0..size {
+            let pivot = (i..size).find(|r| tmp.at(*r, i) != 0)?;
+            if pivot != i {
+                tmp.swap_rows(i, pivot);
+                res.swap_rows(i, pivot);
+            }
+
+            let inv = field.inverse(tmp.at(i, i))?;
+            tmp.scale_row(field, i, inv);
+            res.scale_row(field, i, inv);
+
+            for j in 0..size {
+                let coeff = tmp.at(j, i);
+                if j == i || coeff == 0 {
+                    continue;
+                }
+                tmp.mult_and_subtract(field, j, i, coeff);
+                res.mult_and_subtract(field, j, i, coeff);
+            }
+        }
+
+        Some(res)
+    }
+
+    fn swap_rows(&mut self, first: usize, second: usize) {
+        for col in 0..self.cols {
+            self.data
+                .swap(first * self.cols + col, second * self.cols + col);
+        }
+    }
+
+    fn scale_row(&mut self, field: &Field, row: usize, element: u8) {
+        for col in 0..self.cols {
+            self.put(row, col, field.mul(self.at(row, col), element));
+        }
+    }
+
+    fn mult_and_subtract(&mut self, field: &Field, row: usize, normalized: usize, coeff: u8) {
+        for col in 0..self.cols {
+            let value = math::sub(
+                self.at(row, col),
+                field.mul(self.at(normalized, col), coeff),
+            );
+            self.put(row, col, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_needs_pivoting() {
+        let m = Matrix::from_rows(vec![vec![0, 1], vec![1, 0]]).unwrap();
+        assert_eq!(m.inverse(), Some(m.clone()));
+    }
+
+    #[test]
+    fn singular() {
+        assert_eq!(Matrix::vandermonde(&[3, 3], 2).inverse(), None);
+        assert_eq!(Matrix::zero(2, 3).inverse(), None);
+    }
+
+    #[test]
+    fn cauchy_inverse() {
+        let m = Matrix::cauchy(&[1, 2, 3], &[4, 5, 6]).unwrap();
+        let inv = m.inverse().unwrap();
+        assert_eq!(inv.mul(&m), Some(Matrix::identity(3)));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut m = Matrix::zero(2, 3);
+        assert_eq!(m.set(1, 2, 7), Some(()));
+        assert_eq!(m.get(1, 2), Some(7));
+        assert_eq!(m.row(1), Some(&[0, 0, 7][..]));
+        assert_eq!((m.get(2, 0), m.get(0, 3), m.row(2)), (None, None, None));
+        assert_eq!(m.set(0, 3, 1), None);
+        assert_eq!(Matrix::zero(2, 0).row(1), Some(&[][..]));
+    }
+
+    #[test]
+    fn mul_vec() {
+        let m = Matrix::vandermonde(&[1, 2], 3);
+        assert_eq!(m.mul_vec(&[1, 1, 1]), Some(vec![1, 7]));
+        assert_eq!(m.mul_vec(&[1]), None);
+    }
+}
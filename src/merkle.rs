@@ -0,0 +1,168 @@
+//! Merkle Membership Proofs
+//!
+//! The dealer builds a Merkle tree over the dealt shares and publishes its root.
+//! Every holder gets a [`Proof`] for their share, with which they can show others that
+//! a share belongs to the published set by revealing only its leaf hash, not its body.
+//!
+//! Leaves are `SHA-256(0x00 || id || SHA-256(share bytes))` and inner nodes
+//! `SHA-256(0x01 || left || right)`; a node without a sibling is carried up unchanged.
+//!
+//! ```rust
+//! use sharing::{merkle, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let shares = sharer.share(vec![1, 2, 3]).unwrap();
+//! let (root, proofs) = merkle::prove(&shares);
+//!
+//! // the holder of share 4 proves membership without revealing its body
+//! assert!(proofs[3].verify(&root));
+//! // and anyone given the share can check that the proof is about it
+//! assert!(proofs[3].verify_share(&shares[3], &root));
+//! assert!(!proofs[3].verify_share(&shares[2], &root));
+//! ```
+use crate::share::{ShareBytes, ShareId};
+use sha2::{Digest, Sha256};
+
+/// The leaf hash of a share.
+pub fn leaf<S: ShareBytes + ShareId>(share: &S) -> [u8; 32] {
+    let body = Sha256::digest(share.to_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update([0, share.id()]);
+    hasher.update(body);
+    hasher.finalize().into()
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// A Merkle tree over the leaf hashes of a share set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new<S: ShareBytes + ShareId>(shares: &[S]) -> Self {
+        let mut levels = vec![shares.iter().map(leaf).collect::<Vec<_>>()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node(left, right),
+                    // a node without a sibling is carried up
+                    _ => pair[0],
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// The root to publish, all zeros for an empty set.
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or([0; 32])
+    }
+
+    /// The inclusion proof of the share at `index`.
+    pub fn proof(&self, index: usize) -> Option<Proof> {
+        let leaf = *self.levels[0].get(index)?;
+        let mut path = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                path.push(*sibling);
+            }
+            position /= 2;
+        }
+        Some(Proof {
+            index,
+            count: self.levels[0].len(),
+            leaf,
+            path,
+        })
+    }
+}
+
+/// Proves that the share with leaf hash `leaf` is at `index` of a set of `count` shares.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Proof {
+    pub index: usize,
+    pub count: usize,
+    pub leaf: [u8; 32],
+    /// The sibling hashes from the leaf up to the root.
+    pub path: Vec<[u8; 32]>,
+}
+
+impl Proof {
+    /// Checks that the leaf belongs to the tree with `root`.
+    pub fn verify(&self, root: &[u8; 32]) -> bool {
+        if self.index >= self.count {
+            return false;
+        }
+        let mut hash = self.leaf;
+        let mut siblings = self.path.iter();
+        let (mut position, mut width) = (self.index, self.count);
+        while width > 1 {
+            if position % 2 == 1 {
+                match siblings.next() {
+                    Some(left) => hash = node(left, &hash),
+                    None => return false,
+                }
+            } else if position + 1 < width {
+                match siblings.next() {
+                    Some(right) => hash = node(&hash, right),
+                    None => return false,
+                }
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == *root
+    }
+
+    /// Checks that the proof is about `share` and that it belongs to the tree with `root`.
+    pub fn verify_share<S: ShareBytes + ShareId>(&self, share: &S, root: &[u8; 32]) -> bool {
+        leaf(share) == self.leaf && self.verify(root)
+    }
+}
+
+/// Builds the tree over `shares` and returns its root and a proof for every share, in order.
+pub fn prove<S: ShareBytes + ShareId>(shares: &[S]) -> ([u8; 32], Vec<Proof>) {
+    let tree = MerkleTree::new(shares);
+    let proofs = (0..shares.len()).filter_map(|i| tree.proof(i)).collect();
+    (tree.root(), proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShamirShare;
+
+    #[test]
+    fn every_size_and_position() {
+        for count in 1..=9u8 {
+            let shares: Vec<ShamirShare> = (1..=count)
+                .map(|id| ShamirShare {
+                    id,
+                    body: vec![id; 4],
+                })
+                .collect();
+            let (root, proofs) = prove(&shares);
+            for (share, proof) in shares.iter().zip(&proofs) {
+                assert!(proof.verify_share(share, &root));
+                let mut moved = proof.clone();
+                moved.index = (moved.index + 1) % count as usize;
+                assert!(count == 1 || !moved.verify(&root));
+            }
+        }
+    }
+}
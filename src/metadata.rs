@@ -0,0 +1,151 @@
+//! Share Metadata
+//!
+//! Notes that help a custodian tell years later what they are holding: a label, when the share
+//! was dealt and a free-form comment. Metadata travels with a share in the
+//! [wire format](crate::wire) and its serde forms, but is not part of what is shared or
+//! reconstructed, nor authenticated, so anyone holding the share can change it.
+//!
+//! ```rust
+//! use sharing::{metadata::Metadata, share::ShareBytes, wire::WireShare, ShamirShare};
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! let metadata = Metadata::new()
+//!     .with_label("share 3 of 5, given to Alice")
+//!     .created_at(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+//!     .with_comment("in the blue envelope");
+//! assert_eq!(metadata.created.as_deref(), Some("2023-11-14T22:13:20Z"));
+//!
+//! let share = ShamirShare { id: 3, body: vec![1, 2, 3] };
+//! let bytes = WireShare::new(2, share).with_metadata(metadata.clone()).to_bytes();
+//! assert_eq!(WireShare::<ShamirShare>::from_bytes(&bytes).unwrap().metadata, metadata);
+//! ```
+use std::{
+    convert::TryFrom,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Descriptive fields of a share, all optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    /// A short description, e.g. who holds the share.
+    pub label: Option<String>,
+    /// When the share was dealt, an RFC 3339 timestamp.
+    pub created: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl Metadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Sets the creation time as an RFC 3339 timestamp in UTC, to the second.
+    pub fn created_at(mut self, time: SystemTime) -> Self {
+        self.created = Some(rfc3339(time));
+        self
+    }
+
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.label.is_none() && self.created.is_none() && self.comment.is_none()
+    }
+
+    /// Every field as its length (u32 big endian) followed by UTF-8, absent fields as
+    /// `0xffffffff`.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for field in [&self.label, &self.created, &self.comment].iter() {
+            match field {
+                Some(text) => {
+                    out.extend_from_slice(&(text.len() as u32).to_be_bytes());
+                    out.extend_from_slice(text.as_bytes());
+                }
+                None => out.extend_from_slice(&u32::MAX.to_be_bytes()),
+            }
+        }
+        out
+    }
+
+    /// `None` unless the bytes are exactly the three fields.
+    pub(crate) fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        let mut fields = Vec::with_capacity(3);
+        for _ in 0..3 {
+            let length = u32::from_be_bytes(<[u8; 4]>::try_from(bytes.get(..4)?).ok()?);
+            bytes = &bytes[4..];
+            if length == u32::MAX {
+                fields.push(None);
+                continue;
+            }
+            let length = usize::try_from(length).ok()?;
+            let text = std::str::from_utf8(bytes.get(..length)?).ok()?;
+            fields.push(Some(text.to_string()));
+            bytes = &bytes[length..];
+        }
+        let mut fields = fields.into_iter();
+        let metadata = Self {
+            label: fields.next()?,
+            created: fields.next()?,
+            comment: fields.next()?,
+        };
+        Some(metadata).filter(|_| bytes.is_empty())
+    }
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ`, times before 1970 as the epoch.
+fn rfc3339(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    // days to the civil calendar, after H. Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rest / 3_600,
+        rest / 60 % 60,
+        rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn timestamps() {
+        let at = |seconds| rfc3339(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(4_107_542_399), "2100-02-28T23:59:59Z");
+        assert_eq!(at(253_402_300_799), "9999-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let metadata = Metadata::new().with_label("").with_comment("\u{1f512}");
+        let bytes = metadata.to_bytes();
+        assert_eq!(Metadata::from_bytes(&bytes), Some(metadata));
+        assert_eq!(Metadata::from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(Metadata::from_bytes(&[]), None);
+    }
+}
@@ -0,0 +1,258 @@
+//! Metrics
+//!
+//! Services embedding the crate can observe its operations by wrapping a sharer in
+//! [`Instrumented`] with any [`Metrics`] sink: a closure, the atomic [`Counters`],
+//! or an adapter to their own observability stack.
+//!
+//! ```rust
+//! use sharing::{metrics::{Counters, Instrumented}, ShamirSecretSharing, Sharing};
+//! use std::sync::Arc;
+//!
+//! let counters = Arc::new(Counters::default());
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let sharer = Instrumented::new(sharer, counters.clone());
+//!
+//! let shares = sharer.share(vec![1, 2, 3]).unwrap();
+//! assert!(sharer.reconstruct(shares[..1].to_vec()).is_err());
+//! assert!(sharer.reconstruct(shares[1..].to_vec()).is_ok());
+//! // borrowed shares are passed through without copying
+//! assert!(sharer.reconstruct_ref(&shares[1..]).is_ok());
+//!
+//! let snapshot = counters.snapshot();
+//! assert_eq!((snapshot.bytes_in, snapshot.shares_emitted), (3, 3));
+//! assert_eq!((snapshot.reconstructions, snapshot.reconstruction_failures), (3, 1));
+//! assert_eq!(snapshot.bytes_out, 6);
+//! ```
+#[cfg(feature = "shamir")]
+use crate::{
+    manifest::Manifest,
+    share::{ShareBytes, ShareId},
+};
+use crate::{Sharing, SharingError};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// An operation worth recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// `bytes` of data were shared into `shares` shares, or sharing failed.
+    Shared {
+        bytes: usize,
+        shares: usize,
+        success: bool,
+    },
+    /// A reconstruction from `shares` shares gave `bytes` of data, or failed.
+    Reconstructed {
+        shares: usize,
+        bytes: usize,
+        success: bool,
+    },
+    /// The share with this id did not verify, e.g. against a manifest.
+    VerificationFailed { id: u8 },
+}
+
+/// Receives the events of an [`Instrumented`] sharer.
+pub trait Metrics: Send + Sync {
+    fn record(&self, event: Event);
+}
+
+impl<F: Fn(Event) + Send + Sync> Metrics for F {
+    fn record(&self, event: Event) {
+        self(event)
+    }
+}
+
+impl<M: Metrics + ?Sized> Metrics for Arc<M> {
+    fn record(&self, event: Event) {
+        (**self).record(event)
+    }
+}
+
+/// Totals of all recorded events.
+#[derive(Debug, Default)]
+pub struct Counters {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    shares_emitted: AtomicU64,
+    sharing_failures: AtomicU64,
+    reconstructions: AtomicU64,
+    reconstruction_failures: AtomicU64,
+    verification_failures: AtomicU64,
+}
+
+/// The values of [`Counters`] at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    /// Bytes of data shared.
+    pub bytes_in: u64,
+    /// Bytes of data reconstructed.
+    pub bytes_out: u64,
+    pub shares_emitted: u64,
+    pub sharing_failures: u64,
+    /// Reconstruction attempts, including failed ones.
+    pub reconstructions: u64,
+    pub reconstruction_failures: u64,
+    pub verification_failures: u64,
+}
+
+impl Counters {
+    pub fn snapshot(&self) -> Snapshot {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        Snapshot {
+            bytes_in: load(&self.bytes_in),
+            bytes_out: load(&self.bytes_out),
+            shares_emitted: load(&self.shares_emitted),
+            sharing_failures: load(&self.sharing_failures),
+            reconstructions: load(&self.reconstructions),
+            reconstruction_failures: load(&self.reconstruction_failures),
+            verification_failures: load(&self.verification_failures),
+        }
+    }
+}
+
+impl Metrics for Counters {
+    fn record(&self, event: Event) {
+        let add = |counter: &AtomicU64, value: usize| {
+            counter.fetch_add(value as u64, Ordering::Relaxed);
+        };
+        match event {
+            Event::Shared {
+                bytes,
+                shares,
+                success,
+            } => {
+                if success {
+                    add(&self.bytes_in, bytes);
+                    add(&self.shares_emitted, shares);
+                } else {
+                    add(&self.sharing_failures, 1);
+                }
+            }
+            Event::Reconstructed { bytes, success, .. } => {
+                add(&self.reconstructions, 1);
+                if success {
+                    add(&self.bytes_out, bytes);
+                } else {
+                    add(&self.reconstruction_failures, 1);
+                }
+            }
+            Event::VerificationFailed { .. } => add(&self.verification_failures, 1),
+        }
+    }
+}
+
+/// A sharer that reports every operation to `M`.
+pub struct Instrumented<T, M> {
+    inner: T,
+    metrics: M,
+}
+
+impl<T: Sharing, M: Metrics> Instrumented<T, M> {
+    pub fn new(inner: T, metrics: M) -> Self {
+        Self { inner, metrics }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn metrics(&self) -> &M {
+        &self.metrics
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Keeps the shares that match the manifest like [`Manifest::collect`],
+    /// recording a verification failure for every other share.
+    #[cfg(feature = "shamir")]
+    pub fn collect<S: ShareBytes + ShareId>(
+        &self,
+        manifest: &Manifest,
+        mut shares: Vec<S>,
+    ) -> Vec<S> {
+        shares.retain(|share| {
+            let valid = manifest.verify_share(share);
+            if !valid {
+                self.metrics
+                    .record(Event::VerificationFailed { id: share.id() });
+            }
+            valid
+        });
+        shares
+    }
+}
+
+impl<T: Sharing, M: Metrics> Sharing for Instrumented<T, M> {
+    type Share = T::Share;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError> {
+        let bytes = data.len();
+        let shares = self.inner.share(data);
+        self.metrics.record(Event::Shared {
+            bytes,
+            shares: shares.as_ref().map_or(0, Vec::len),
+            success: shares.is_ok(),
+        });
+        shares
+    }
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        let count = shares.len();
+        let data = self.inner.reconstruct(shares);
+        self.reconstructed(count, data.as_ref().map(Vec::len));
+        data
+    }
+
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        let data = self.inner.reconstruct_ref(shares);
+        self.reconstructed(shares.len(), data.as_ref().map(Vec::len));
+        data
+    }
+
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<Self::Share>) -> Result<(), SharingError> {
+        let bytes = data.len();
+        let result = self.inner.share_into(data, out);
+        self.metrics.record(Event::Shared {
+            bytes,
+            shares: if result.is_ok() { out.len() } else { 0 },
+            success: result.is_ok(),
+        });
+        result
+    }
+
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        let result = self.inner.reconstruct_into(shares, out);
+        self.reconstructed(shares.len(), result.as_ref().map(|_| out.len()));
+        result
+    }
+
+    #[cfg(feature = "secure-memory")]
+    fn reconstruct_secure_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut crate::secure::SecretBuffer,
+    ) -> Result<(), SharingError> {
+        let result = self.inner.reconstruct_secure_into(shares, out);
+        self.reconstructed(shares.len(), result.as_ref().map(|_| out.len()));
+        result
+    }
+}
+
+impl<T: Sharing, M: Metrics> Instrumented<T, M> {
+    /// Records a reconstruction from `shares` shares that gave `bytes` of data.
+    fn reconstructed(&self, shares: usize, bytes: Result<usize, &SharingError>) {
+        self.metrics.record(Event::Reconstructed {
+            shares,
+            bytes: bytes.unwrap_or(0),
+            success: bytes.is_ok(),
+        });
+    }
+}
@@ -0,0 +1,182 @@
+//! Format Migration
+//!
+//! [`migrate`] reads a share in any version of the [wire format](crate::wire) ever written,
+//! of any scheme, and [`AnyShare::to_bytes`] writes it back in the current version, so a
+//! share kept on paper for years can be brought up to date without knowing what it is.
+//!
+//! | version | written by                         | contents                                 |
+//! |---------|------------------------------------|------------------------------------------|
+//! | 1       | the first wire format              | header and payload                       |
+//! | 2       | with [`Metadata`]                  | header, payload and metadata             |
+//! | 3       | [`WIRE_VERSION`], current          | header, payload, metadata and CRC-32C    |
+//!
+//! Nothing is lost in the upgrade, a version 1 share gains empty metadata. The bare
+//! [`ShareBytes`] encoding of the share types has a single version so far and needs no
+//! migration.
+//!
+//! [`WIRE_VERSION`]: crate::wire::WIRE_VERSION
+//!
+//! ```rust
+//! use sharing::{migrate, share::ShareBytes, wire, AnyShare, Scheme};
+//!
+//! // a Shamir share written by the first wire format, threshold 2 and id 3
+//! let old = [0xa5, 1, b'S', 2, 3, 0, 0, 0, 0, 0, 0, 0, 2, 0xca, 0xfe];
+//! let share = migrate(&old).unwrap();
+//! assert_eq!((share.scheme(), share.k(), share.id()), (Scheme::Shamir, 2, 3));
+//!
+//! let current = share.to_bytes();
+//! assert_eq!(wire::header(&current).unwrap().version, wire::WIRE_VERSION);
+//! assert_eq!(migrate(&current).unwrap(), share);
+//! ```
+use crate::{
+    error::SharingError,
+    metadata::Metadata,
+    share::{KrawczykShare, RabinShare, ShamirShare, ShareBytes, ShareId},
+    wire::{self, WireShare},
+    Scheme,
+};
+
+/// A share in the wire format of whichever scheme it names.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnyShare {
+    Shamir(WireShare<ShamirShare>),
+    Rabin(WireShare<RabinShare>),
+    Krawczyk(WireShare<KrawczykShare>),
+}
+
+impl AnyShare {
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            AnyShare::Shamir(_) => Scheme::Shamir,
+            AnyShare::Rabin(_) => Scheme::Rabin,
+            AnyShare::Krawczyk(_) => Scheme::Krawczyk,
+        }
+    }
+
+    /// The threshold the share was dealt with.
+    pub fn k(&self) -> u8 {
+        match self {
+            AnyShare::Shamir(share) => share.k,
+            AnyShare::Rabin(share) => share.k,
+            AnyShare::Krawczyk(share) => share.k,
+        }
+    }
+
+    pub fn id(&self) -> u8 {
+        match self {
+            AnyShare::Shamir(share) => share.share.id(),
+            AnyShare::Rabin(share) => share.share.id(),
+            AnyShare::Krawczyk(share) => share.share.id(),
+        }
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        match self {
+            AnyShare::Shamir(share) => &share.metadata,
+            AnyShare::Rabin(share) => &share.metadata,
+            AnyShare::Krawczyk(share) => &share.metadata,
+        }
+    }
+}
+
+/// Writes the current wire format version, reads every version.
+impl ShareBytes for AnyShare {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            AnyShare::Shamir(share) => share.to_bytes(),
+            AnyShare::Rabin(share) => share.to_bytes(),
+            AnyShare::Krawczyk(share) => share.to_bytes(),
+        }
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        migrate(bytes).ok()
+    }
+}
+
+impl From<WireShare<ShamirShare>> for AnyShare {
+    fn from(share: WireShare<ShamirShare>) -> Self {
+        AnyShare::Shamir(share)
+    }
+}
+
+impl From<WireShare<RabinShare>> for AnyShare {
+    fn from(share: WireShare<RabinShare>) -> Self {
+        AnyShare::Rabin(share)
+    }
+}
+
+impl From<WireShare<KrawczykShare>> for AnyShare {
+    fn from(share: WireShare<KrawczykShare>) -> Self {
+        AnyShare::Krawczyk(share)
+    }
+}
+
+/// Reads a share in any version of the wire format, see the [module documentation](self).
+///
+/// Fails like [`wire::header`], with [`SharingError::UnsupportedVersion`] for a share
+/// written by a later version of this crate.
+pub fn migrate(bytes: &[u8]) -> Result<AnyShare, SharingError> {
+    Ok(match wire::header(bytes)?.scheme {
+        Scheme::Shamir => AnyShare::Shamir(WireShare::decode(bytes)?),
+        Scheme::Rabin => AnyShare::Rabin(WireShare::decode(bytes)?),
+        Scheme::Krawczyk => AnyShare::Krawczyk(WireShare::decode(bytes)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::{MAGIC, WIRE_VERSION};
+
+    // the same share as written by every version, which must keep reading
+    #[test]
+    fn every_version() {
+        let header = |version| vec![MAGIC, version, b'R', 3, 2, 0, 0, 0, 0, 0, 0, 0, 10];
+        let payload = [0, 0, 0, 0, 0, 0, 0, 5, 1, 2];
+        // no label, no creation time, the comment "safe"
+        let metadata = [
+            255, 255, 255, 255, 255, 255, 255, 255, 0, 0, 0, 4, b's', b'a', b'f', b'e',
+        ];
+        let v1 = [&header(1)[..], &payload].concat();
+        let v2 = [&header(2)[..], &payload, &metadata].concat();
+        let v3 = [&header(3)[..], &payload, &metadata, &[141, 68, 91, 246]].concat();
+
+        let share = WireShare::new(
+            3,
+            RabinShare {
+                id: 2,
+                length: 5,
+                body: vec![1, 2],
+            },
+        );
+        let with_metadata = share
+            .clone()
+            .with_metadata(Metadata::new().with_comment("safe"));
+        assert_eq!(migrate(&v1).unwrap(), AnyShare::Rabin(share));
+        assert_eq!(
+            migrate(&v2).unwrap(),
+            AnyShare::Rabin(with_metadata.clone())
+        );
+        assert_eq!(migrate(&v3).unwrap(), AnyShare::Rabin(with_metadata));
+        for old in [&v1, &v2].iter() {
+            let upgraded = migrate(old).unwrap().to_bytes();
+            assert_eq!(upgraded[1], WIRE_VERSION);
+            assert_eq!(AnyShare::from_bytes(&upgraded), migrate(old).ok());
+        }
+        assert_eq!(migrate(&v2).unwrap().to_bytes(), v3);
+
+        let mut future = v3;
+        future[1] = WIRE_VERSION + 1;
+        assert!(matches!(
+            migrate(&future),
+            Err(SharingError::UnsupportedVersion(_))
+        ));
+        let bare = ShamirShare {
+            id: 1,
+            body: vec![7],
+        }
+        .to_bytes();
+        assert!(migrate(&bare).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Mnemonic Shares
+//!
+//! Writes a [`ShamirShare`] as words of a BIP-39 word list, for shares of wallet seeds kept
+//! on paper. Each word holds 11 bits of
+//!
+//! ```text
+//! id || body length (u8) || body || checksum (first 4 bytes of SHA-256 of the preceding)
+//! ```
+//!
+//! zero padded to whole words, so a 32 byte seed takes 28 words. Bodies of up to 255 bytes
+//! fit. A misread word is reported with its position and the list's words it may have been.
+//!
+//! Available with the `mnemonic` feature, English only unless `mnemonic-all-languages`
+//! is enabled too.
+//!
+//! ```rust
+//! use sharing::{mnemonic::{self, Language, MnemonicError}, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares = sharer.share(vec![42; 32]).unwrap();
+//!
+//! let phrase = mnemonic::to_mnemonic(&shares[0], Language::English).unwrap();
+//! assert_eq!(phrase.split(' ').count(), 28);
+//! assert_eq!(mnemonic::from_mnemonic(&phrase, Language::English).unwrap(), shares[0]);
+//!
+//! let mut words: Vec<&str> = phrase.split(' ').collect();
+//! words[4] = "abandonn";
+//! match mnemonic::from_mnemonic(&words.join(" "), Language::English) {
+//!     Err(MnemonicError::UnknownWord { index, suggestions, .. }) => {
+//!         assert_eq!(index, 4);
+//!         assert!(suggestions.contains(&"abandon"));
+//!     }
+//!     other => panic!("unexpected {:?}", other),
+//! }
+//!
+//! // a valid but wrong word
+//! let mut words: Vec<&str> = phrase.split(' ').collect();
+//! words[5] = if words[5] == "zoo" { "zone" } else { "zoo" };
+//! let error = mnemonic::from_mnemonic(&words.join(" "), Language::English);
+//! assert_eq!(error, Err(MnemonicError::Checksum));
+//! ```
+use crate::share::ShamirShare;
+#[doc(no_inline)]
+pub use bip39::Language;
+use sha2::{Digest, Sha256};
+use std::{convert::TryFrom, error::Error, fmt};
+
+/// id, body length and checksum
+const OVERHEAD: usize = 6;
+
+/// Why a phrase did not decode with [`from_mnemonic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// The word at this 0-based position is not in the word list.
+    UnknownWord {
+        index: usize,
+        word: String,
+        /// Words of the list with the same first letters.
+        suggestions: Vec<&'static str>,
+    },
+    /// The phrase has `found` words where its length field calls for `expected`,
+    /// e.g. a word was skipped.
+    WordCount { expected: usize, found: usize },
+    /// All words are valid, but one of them is not the right one.
+    Checksum,
+}
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MnemonicError::UnknownWord {
+                index,
+                word,
+                suggestions,
+            } => {
+                write!(f, "unknown word `{}` at position {}", word, index + 1)?;
+                if !suggestions.is_empty() {
+                    write!(f, ", did you mean {}", suggestions.join(" or "))?;
+                }
+                Ok(())
+            }
+            MnemonicError::WordCount { expected, found } => {
+                write!(f, "expected {} words, found {}", expected, found)
+            }
+            MnemonicError::Checksum => f.write_str("mnemonic checksum mismatch"),
+        }
+    }
+}
+
+impl Error for MnemonicError {}
+
+fn checksum(bytes: &[u8]) -> [u8; 4] {
+    let mut out = [0u8; 4];
+    out.copy_from_slice(&Sha256::digest(bytes)[..4]);
+    out
+}
+
+fn word_count(body: usize) -> usize {
+    ((body + OVERHEAD) * 8).div_ceil(11)
+}
+
+/// The words of the share, `None` if its body is longer than 255 bytes.
+pub fn to_words(share: &ShamirShare, language: Language) -> Option<Vec<&'static str>> {
+    let length = u8::try_from(share.body.len()).ok()?;
+    let mut bytes = Vec::with_capacity(share.body.len() + OVERHEAD);
+    bytes.extend_from_slice(&[share.id, length]);
+    bytes.extend_from_slice(&share.body);
+    let checksum = checksum(&bytes);
+    bytes.extend_from_slice(&checksum);
+
+    let list = language.word_list();
+    let mut words = Vec::with_capacity(word_count(share.body.len()));
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for byte in &bytes {
+        acc = (acc << 8) | u32::from(*byte);
+        bits += 8;
+        if bits >= 11 {
+            bits -= 11;
+            words.push(list[((acc >> bits) & 0x7ff) as usize]);
+        }
+    }
+    if bits > 0 {
+        words.push(list[((acc << (11 - bits)) & 0x7ff) as usize]);
+    }
+    Some(words)
+}
+
+/// The words of the share separated by spaces, see [`to_words`].
+pub fn to_mnemonic(share: &ShamirShare, language: Language) -> Option<String> {
+    Some(to_words(share, language)?.join(" "))
+}
+
+/// Reads a share back from its words, in any case and separated by any whitespace.
+pub fn from_mnemonic(phrase: &str, language: Language) -> Result<ShamirShare, MnemonicError> {
+    let words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    let mut bytes = Vec::with_capacity(words.len() * 11 / 8);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for (index, word) in words.iter().enumerate() {
+        let value = language
+            .find_word(word)
+            .ok_or_else(|| MnemonicError::UnknownWord {
+                index,
+                word: word.clone(),
+                suggestions: suggestions(word, language),
+            })?;
+        acc = (acc << 11) | u32::from(value);
+        bits += 11;
+        while bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+
+    let length = bytes.get(1).map_or(0, |length| usize::from(*length));
+    let expected = word_count(length);
+    if words.len() != expected {
+        return Err(MnemonicError::WordCount {
+            expected,
+            found: words.len(),
+        });
+    }
+    // whole padding bytes of the last word, and the remaining padding bits
+    let end = length + OVERHEAD;
+    if bytes[end..].iter().any(|b| *b != 0) || acc != 0 {
+        return Err(MnemonicError::Checksum);
+    }
+    let (payload, sum) = bytes[..end].split_at(end - 4);
+    if checksum(payload) != sum {
+        return Err(MnemonicError::Checksum);
+    }
+    Ok(ShamirShare {
+        id: payload[0],
+        body: payload[2..].to_vec(),
+    })
+}
+
+/// The words sharing the longest prefix with `word`, at most its first four letters.
+fn suggestions(word: &str, language: Language) -> Vec<&'static str> {
+    (2..=4)
+        .rev()
+        .filter_map(|n| word.char_indices().nth(n).map(|(i, _)| &word[..i]))
+        .map(|prefix| language.words_by_prefix(prefix))
+        .find(|words| !words.is_empty())
+        .map_or_else(Vec::new, <[_]>::to_vec)
+}
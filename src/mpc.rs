@@ -0,0 +1,275 @@
+//! Secure Computation on Shamir Shares
+//!
+//! Multiplication of two shared secrets using local multiplication followed by
+//! degree reduction through resharing (Gennaro, Rabin and Rabin).
+//! All arithmetic is byte-wise in GF(256).
+//!
+//! Every party holding shares of `a` and `b`
+//!
+//! 1. computes its [`local_product`], a share of `a·b` on a polynomial of degree `2(k - 1)`,
+//! 2. [`reshare`]s that product share with a degree `k - 1` polynomial and sends one sub share to every party,
+//! 3. [`combine_reshares`] the sub shares it received from at least `2k - 1` parties.
+//!
+//! The result is a regular `k` out of `n` sharing of `a·b`. This requires `n >= 2k - 1`.
+//!
+//! Sums need no interaction, see [`local_sum`], which also lets independent dealers
+//! [`aggregate`] their sharings into one of a secret none of them knows.
+//!
+//! ```rust
+//! use sharing::{mpc, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(5, 2, rand::thread_rng());
+//!
+//! let a = sharer.share(vec![2, 10]).unwrap();
+//! let b = sharer.share(vec![3, 1]).unwrap();
+//!
+//! let product = mpc::multiply(&sharer, &a, &b).unwrap();
+//! // Any 2 shares of the product reconstruct a·b
+//! let rec = sharer.reconstruct(product[3..5].to_vec()).unwrap();
+//!
+//! assert_eq!(vec![6, 10], rec);
+//! ```
+use crate::{
+    error::SharingError,
+    math,
+    placement::Placement,
+    share::{valid_ids, ShamirShare},
+    ShamirSecretSharing,
+};
+use rand::Rng;
+
+/// A sub share of one party's product share, addressed to another party.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Reshare {
+    /// The id of the party that created this sub share.
+    pub from: u8,
+    /// The sub share, its id is the id of the receiving party.
+    pub share: ShamirShare,
+}
+
+/// Multiplies two shares held by the same party.
+///
+/// The result is a share of the product on a polynomial of degree `2(k - 1)`.
+pub fn local_product<R: Rng>(
+    sharer: &ShamirSecretSharing<R>,
+    a: &ShamirShare,
+    b: &ShamirShare,
+) -> Result<ShamirShare, SharingError> {
+    if a.id != b.id || a.body.len() != b.body.len() {
+        return Err(SharingError::MismatchedParameters);
+    }
+    Ok(ShamirShare {
+        id: a.id,
+        body: a
+            .body
+            .iter()
+            .zip(&b.body)
+            .map(|(x, y)| sharer.field().mul(*x, *y))
+            .collect(),
+    })
+}
+
+/// Reshares a product share with a degree `k - 1` polynomial, producing one sub share for each
+/// of the `parties`, addressed by their ids, which must be distinct and nonzero.
+///
+/// Fails with [`SharingError::InvalidParameters`] unless the sharer uses the standard
+/// [`Placement`], since the protocol interpolates at the party ids.
+pub fn reshare<R: Rng>(
+    sharer: &ShamirSecretSharing<R>,
+    product: &ShamirShare,
+    parties: &[u8],
+) -> Result<Vec<Reshare>, SharingError> {
+    standard(sharer)?;
+    if !valid_ids(parties) {
+        return Err(SharingError::MismatchedParameters);
+    }
+    Ok(sharer
+        .share_at(product.body.clone(), parties)?
+        .into_iter()
+        .map(|share| Reshare {
+            from: product.id,
+            share,
+        })
+        .collect())
+}
+
+/// Combines the sub shares received by one party into its share of the product.
+///
+/// At least `2k - 1` sub shares from distinct parties are required.
+pub fn combine_reshares<R: Rng>(
+    sharer: &ShamirSecretSharing<R>,
+    received: &[Reshare],
+) -> Result<ShamirShare, SharingError> {
+    standard(sharer)?;
+    let k = sharer.k();
+    if k < 1 {
+        return Err(SharingError::InvalidParameters);
+    }
+    let needed = 2 * k as usize - 1;
+    let received = received
+        .get(..needed)
+        .ok_or(SharingError::InsufficientShares {
+            needed,
+            found: received.len(),
+        })?;
+    let id = received[0].share.id;
+    let length = received[0].share.body.len();
+    if received
+        .iter()
+        .any(|r| r.share.id != id || r.share.body.len() != length)
+    {
+        return Err(SharingError::MismatchedParameters);
+    }
+    let xs: Vec<u8> = received.iter().map(|r| r.from).collect();
+    let coefficients = sharer
+        .field()
+        .lagrange_coefficients(&xs, 0)
+        .ok_or(SharingError::MismatchedParameters)?;
+
+    Ok(ShamirShare {
+        id,
+        body: (0..length)
+            .map(|i| {
+                received.iter().zip(&coefficients).fold(0, |acc, (r, c)| {
+                    math::add(acc, sharer.field().mul(r.share.body[i], *c))
+                })
+            })
+            .collect(),
+    })
+}
+
+/// Runs the whole multiplication protocol locally.
+///
+/// `a` and `b` must contain the shares of the same `2k - 1` or more parties, in the same order.
+/// Returns one share of the product for every participating party.
+pub fn multiply<R: Rng>(
+    sharer: &ShamirSecretSharing<R>,
+    a: &[ShamirShare],
+    b: &[ShamirShare],
+) -> Result<Vec<ShamirShare>, SharingError> {
+    let k = sharer.k();
+    if k < 1 || (sharer.n() as usize) < 2 * k as usize - 1 {
+        return Err(SharingError::InvalidParameters);
+    }
+    if a.len() != b.len() {
+        return Err(SharingError::MismatchedParameters);
+    }
+    let parties: Vec<u8> = a.iter().map(|share| share.id).collect();
+    let reshares = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| reshare(sharer, &local_product(sharer, a, b)?, &parties))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    a.iter()
+        .map(|party| {
+            let received: Vec<Reshare> = reshares
+                .iter()
+                .map(|r| r.iter().find(|r| r.share.id == party.id).cloned())
+                .collect::<Option<_>>()
+                .ok_or(SharingError::MismatchedParameters)?;
+            combine_reshares(sharer, &received)
+        })
+        .collect()
+}
+
+/// Adds two shares held by the same party, giving its share of the sum of both secrets.
+///
+/// Addition needs no interaction and works in every field, since it is XOR.
+pub fn local_sum(a: &ShamirShare, b: &ShamirShare) -> Result<ShamirShare, SharingError> {
+    if a.id != b.id || a.body.len() != b.body.len() {
+        return Err(SharingError::MismatchedParameters);
+    }
+    Ok(ShamirShare {
+        id: a.id,
+        body: a
+            .body
+            .iter()
+            .zip(&b.body)
+            .map(|(x, y)| math::add(*x, *y))
+            .collect(),
+    })
+}
+
+/// Combines the share sets of independent dealers into one set of the sum of their secrets.
+///
+/// Every dealer shares a random contribution with the same `k` and ids, and every party adds
+/// up the shares it received. The result reconstructs the sum of all contributions,
+/// which no single dealer knows as long as one of them is honest.
+/// Returns the aggregated shares in the order of the first set, and fails with
+/// [`SharingError::MismatchedParameters`] if the sets do not have the same ids or lengths.
+///
+/// ```rust
+/// use sharing::{mpc, ShamirSecretSharing, Sharing};
+///
+/// let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+/// let first = sharer.share(vec![0x0f, 1]).unwrap();
+/// let mut second = sharer.share(vec![0xf0, 2]).unwrap();
+/// second.reverse();
+///
+/// let joint = mpc::aggregate(&[first, second]).unwrap();
+/// assert_eq!(sharer.reconstruct(joint[1..].to_vec()).unwrap(), vec![0xff, 3]);
+/// ```
+pub fn aggregate(sets: &[Vec<ShamirShare>]) -> Result<Vec<ShamirShare>, SharingError> {
+    let (first, rest) = sets.split_first().ok_or(SharingError::EmptyShareSet)?;
+    if rest.iter().any(|set| set.len() != first.len()) {
+        return Err(SharingError::MismatchedParameters);
+    }
+    first
+        .iter()
+        .map(|share| {
+            rest.iter().try_fold(share.clone(), |sum, set| {
+                let other = set
+                    .iter()
+                    .find(|s| s.id == share.id)
+                    .ok_or(SharingError::MismatchedParameters)?;
+                local_sum(&sum, other)
+            })
+        })
+        .collect()
+}
+
+/// The protocol interpolates at the raw ids with the secret at coefficient 0.
+fn standard<R: Rng>(sharer: &ShamirSecretSharing<R>) -> Result<(), SharingError> {
+    if sharer.placement() == Placement::STANDARD {
+        Ok(())
+    } else {
+        Err(SharingError::InvalidParameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        placement::{Coefficient, Points},
+        Sharing,
+    };
+
+    #[test]
+    fn random_ids() {
+        let sharer = ShamirSecretSharing::new(5, 2, rand::thread_rng()).with_random_ids();
+        let a = sharer.share(vec![2, 10]).unwrap();
+        // the same parties hold shares of both secrets
+        let ids: Vec<u8> = a.iter().map(|share| share.id).collect();
+        let b = sharer.share_with_ids(vec![3, 1], &ids).unwrap();
+
+        let product = multiply(&sharer, &a, &b).unwrap();
+        assert_eq!(
+            product.iter().map(|share| share.id).collect::<Vec<_>>(),
+            ids
+        );
+        assert_eq!(
+            sharer.reconstruct(product[1..3].to_vec()).unwrap(),
+            vec![6, 10]
+        );
+
+        let placement = Placement::new(Coefficient::Leading, Points::ZeroBased).unwrap();
+        let sharer = ShamirSecretSharing::new(5, 2, rand::thread_rng()).with_placement(placement);
+        assert!(matches!(
+            multiply(&sharer, &a, &b),
+            Err(SharingError::InvalidParameters)
+        ));
+    }
+}
@@ -0,0 +1,268 @@
+//! Paper Backups
+//!
+//! A printable text block per share, in the spirit of `paperkey`: the [`ShareBytes`] encoding
+//! as numbered lines of hex, each ending in a CRC-24 of the line, and a last line with the
+//! CRC-24 of the whole share. When a share is typed back in, a damaged line is pointed out by
+//! its number instead of failing the whole share.
+//!
+//! ```text
+//! -----BEGIN SHARE BACKUP-----
+//!   1: 01 53 02 6d 79 20 73 65 65 64 20 70 68 72 61 73 be132e
+//!   2: 65 21 5a16fc
+//!   3: 195951
+//! -----END SHARE BACKUP-----
+//! ```
+//!
+//! Reading is lenient: text around the armor, comment lines starting with `#`, blank lines,
+//! whitespace within lines and the case of the hex digits are ignored. A line typed again
+//! further down, e.g. to correct a typo, takes the place of an earlier copy that fails its
+//! checksum, as long as it comes before the end of the armor.
+//!
+//! ```rust
+//! use sharing::{paper::{Paper, PaperError}, ShamirShare};
+//!
+//! let share = ShamirShare { id: 1, body: vec![0x5a; 40] };
+//! let text = share.to_paper();
+//!
+//! // a typo on the second line ...
+//! let mut lines: Vec<String> = text.lines().map(String::from).collect();
+//! lines[2] = lines[2].replacen("5a", "5b", 1);
+//! match ShamirShare::from_paper(&lines.join("\n")) {
+//!     Err(PaperError::Damaged(lines)) => assert_eq!(lines, vec![2]),
+//!     other => panic!("unexpected {:?}", other),
+//! }
+//!
+//! // ... fixed by typing that line again below
+//! lines.insert(lines.len() - 1, text.lines().nth(2).unwrap().to_string());
+//! let corrected = lines.join("\n");
+//! assert_eq!(ShamirShare::from_paper(&corrected).unwrap(), share);
+//! ```
+use crate::{
+    error::SharingError,
+    share::{decode_error, parse_hex, ShareBytes},
+};
+use std::{collections::BTreeMap, error::Error, fmt};
+
+/// The first line of a paper backup.
+pub const BEGIN: &str = "-----BEGIN SHARE BACKUP-----";
+/// The last line of a paper backup.
+pub const END: &str = "-----END SHARE BACKUP-----";
+/// Bytes of the share on each line.
+pub const BYTES_PER_LINE: usize = 16;
+
+const CRC_LEN: usize = 3;
+
+/// The printable paper backup of any share with a [`ShareBytes`] encoding.
+pub trait Paper: ShareBytes {
+    fn to_paper(&self) -> String {
+        let bytes = self.to_bytes();
+        let mut out = format!("{}\n", BEGIN);
+        let mut number = 1;
+        for chunk in bytes.chunks(BYTES_PER_LINE) {
+            out.push_str(&format!("{:3}:", number));
+            for byte in chunk {
+                out.push_str(&format!(" {:02x}", byte));
+            }
+            out.push_str(&format!(" {:06x}\n", line_crc(number, chunk)));
+            number += 1;
+        }
+        out.push_str(&format!("{:3}: {:06x}\n{}\n", number, crc24(&bytes), END));
+        out
+    }
+
+    /// Reads a paper backup, see the [module documentation](self) for what is accepted.
+    fn from_paper(text: &str) -> Result<Self, PaperError> {
+        let bytes = read(text)?;
+        Self::from_bytes(&bytes).ok_or_else(|| PaperError::Decode(decode_error(&bytes)))
+    }
+}
+
+impl<T: ShareBytes> Paper for T {}
+
+/// Why a paper backup could not be read.
+#[derive(Debug)]
+pub enum PaperError {
+    /// A line that is neither a numbered line, a comment nor part of the armor.
+    Malformed(String),
+    /// These lines are missing or fail their checksum and need to be typed again.
+    Damaged(Vec<usize>),
+    /// Every line checks, but the share as a whole does not.
+    Checksum,
+    /// Intact, but not a share of this type.
+    Decode(SharingError),
+}
+
+impl fmt::Display for PaperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaperError::Malformed(line) => write!(f, "not a line of a paper backup: {:?}", line),
+            PaperError::Damaged(lines) => {
+                let lines: Vec<String> = lines.iter().map(ToString::to_string).collect();
+                write!(f, "damaged or missing lines {}", lines.join(", "))
+            }
+            PaperError::Checksum => f.write_str("paper backup does not match its checksum"),
+            PaperError::Decode(error) => write!(f, "invalid share: {}", error),
+        }
+    }
+}
+
+impl Error for PaperError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PaperError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// The bytes of the share, from the last intact copy of every line.
+fn read(text: &str) -> Result<Vec<u8>, PaperError> {
+    let mut lines = text.lines().map(str::trim);
+    let mut body: Vec<&str> = if text.contains(BEGIN) {
+        lines.by_ref().find(|line| *line == BEGIN);
+        lines.take_while(|line| *line != END).collect()
+    } else {
+        lines.collect()
+    };
+    body.retain(|line| !line.is_empty() && !line.starts_with('#'));
+
+    // every copy of every line, `None` where the digits do not even parse
+    let mut copies: BTreeMap<usize, Vec<Option<Vec<u8>>>> = BTreeMap::new();
+    for line in body {
+        let (number, digits) = line
+            .split_once(':')
+            .and_then(|(number, digits)| Some((number.trim().parse::<usize>().ok()?, digits)))
+            .ok_or_else(|| PaperError::Malformed(line.to_string()))?;
+        let digits: String = digits.split_whitespace().collect();
+        copies
+            .entry(number)
+            .or_default()
+            .push(parse_hex(&digits).ok());
+    }
+
+    // the checksum line is the last one holding nothing but a CRC
+    let last = copies
+        .iter()
+        .rev()
+        .find_map(|(number, versions)| {
+            versions
+                .iter()
+                .rev()
+                .flatten()
+                .find(|bytes| bytes.len() == CRC_LEN)
+                .map(|crc| (*number, crc))
+        })
+        .filter(|(number, _)| *number > 0);
+    let (last, crc) = match last {
+        Some(last) => last,
+        None => {
+            let next = copies.keys().next_back().map_or(1, |number| number + 1);
+            return Err(PaperError::Damaged(vec![next]));
+        }
+    };
+
+    let mut bytes = Vec::new();
+    let mut damaged = Vec::new();
+    for number in 1..last {
+        let intact = copies.get(&number).and_then(|versions| {
+            versions.iter().rev().flatten().find_map(|line| {
+                let (data, crc) = line.split_at(line.len().checked_sub(CRC_LEN)?);
+                Some(data)
+                    .filter(|data| !data.is_empty() && crc == crc_bytes(line_crc(number, data)))
+            })
+        });
+        match intact {
+            Some(data) => bytes.extend_from_slice(data),
+            None => damaged.push(number),
+        }
+    }
+    if !damaged.is_empty() {
+        return Err(PaperError::Damaged(damaged));
+    }
+    if crc[..] != crc_bytes(crc24(&bytes)) {
+        return Err(PaperError::Checksum);
+    }
+    Ok(bytes)
+}
+
+/// The CRC-24 of a line covers its number, so lines cannot be swapped.
+fn line_crc(number: usize, data: &[u8]) -> u32 {
+    let mut line = (number as u32).to_be_bytes().to_vec();
+    line.extend_from_slice(data);
+    crc24(&line)
+}
+
+fn crc_bytes(crc: u32) -> [u8; CRC_LEN] {
+    let [_, a, b, c] = crc.to_be_bytes();
+    [a, b, c]
+}
+
+/// CRC-24 of OpenPGP armor (RFC 4880), as used by `paperkey`.
+fn crc24(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x00b7_04ce, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte) << 16, |crc, _| {
+            let crc = crc << 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^ 0x0186_4cfb
+            } else {
+                crc
+            }
+        })
+    }) & 0x00ff_ffff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShamirShare;
+
+    #[test]
+    fn crc24_reference_value() {
+        // the check value of CRC-24/OPENPGP
+        assert_eq!(crc24(b"123456789"), 0x0021_cf02);
+    }
+
+    #[test]
+    fn lenient_reading() {
+        let share = ShamirShare {
+            id: 2,
+            body: b"my seed phrase!".to_vec(),
+        };
+        let text = share.to_paper();
+        assert_eq!(text.lines().nth(2), Some("  2: 65 21 5a16fc"));
+
+        // notes around the backup, comments, odd spacing and case
+        let retyped = format!(
+            "Share 2 of 3, keep safe\n\n{}\n# checked 2024\n{}\n\n{}\n{}\n{}\nsigned: A.",
+            BEGIN,
+            text.lines().nth(1).unwrap().to_uppercase().replace(' ', ""),
+            text.lines().nth(2).unwrap().replace(' ', "   "),
+            text.lines().nth(3).unwrap(),
+            END
+        );
+        assert_eq!(ShamirShare::from_paper(&retyped).unwrap(), share);
+
+        // a missing line, swapped lines and a missing checksum line
+        let lines: Vec<&str> = text.lines().collect();
+        let missing = [lines[0], lines[1], lines[3], lines[4]].join("\n");
+        assert!(
+            matches!(ShamirShare::from_paper(&missing), Err(PaperError::Damaged(l)) if l == [2])
+        );
+        let swapped = [
+            lines[2].replacen('2', "1", 1),
+            lines[1].replacen('1', "2", 1),
+            lines[3].to_string(),
+        ]
+        .join("\n");
+        assert!(
+            matches!(ShamirShare::from_paper(&swapped), Err(PaperError::Damaged(l)) if l == [1, 2])
+        );
+        assert!(
+            matches!(ShamirShare::from_paper("  1: 00"), Err(PaperError::Damaged(l)) if l == [2])
+        );
+        assert!(matches!(
+            ShamirShare::from_paper("share"),
+            Err(PaperError::Malformed(_))
+        ));
+    }
+}
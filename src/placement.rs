@@ -0,0 +1,154 @@
+//! Secret Placement
+//!
+//! Shamir's scheme as published puts the secret in the constant term of every polynomial
+//! and evaluates share `i` at `x = i`, which is what this crate does by default.
+//! Some implementations differ, and shares only combine across implementations that agree:
+//!
+//! - [`Coefficient::Leading`] puts the secret in the coefficient of `x^(k-1)` instead,
+//! - [`Points::ZeroBased`] evaluates share `i` at `x = i - 1`, which is only possible
+//!   if the secret is not the constant term, as share 1 would be the secret itself.
+//!
+//! ```rust
+//! use sharing::{placement::{Coefficient, Placement, Points}, ShamirSecretSharing, Sharing};
+//!
+//! let placement = Placement::new(Coefficient::Leading, Points::ZeroBased).unwrap();
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng()).with_placement(placement);
+//!
+//! let shares = sharer.share(vec![1, 2, 3]).unwrap();
+//! assert_eq!(sharer.reconstruct(shares[2..].to_vec()).unwrap(), vec![1, 2, 3]);
+//!
+//! // the constant term and the first point would coincide
+//! assert!(Placement::new(Coefficient::Constant, Points::ZeroBased).is_none());
+//! ```
+use crate::math::Field;
+
+/// The coefficient holding the secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Coefficient {
+    /// The constant term, the value at `x = 0`.
+    Constant,
+    /// The coefficient of `x^(k-1)`.
+    Leading,
+}
+
+/// Where the share with id `i` is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Points {
+    /// At `x = i`.
+    Id,
+    /// At `x = i - 1`.
+    ZeroBased,
+}
+
+/// Where the secret sits in the polynomials and where shares are evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Placement {
+    coefficient: Coefficient,
+    points: Points,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+impl Placement {
+    /// The secret in the constant term and shares at their id, as in Shamir's paper.
+    pub const STANDARD: Placement = Placement {
+        coefficient: Coefficient::Constant,
+        points: Points::Id,
+    };
+
+    /// `None` for a constant term with zero based points, which would give the secret away.
+    pub fn new(coefficient: Coefficient, points: Points) -> Option<Self> {
+        if coefficient == Coefficient::Constant && points == Points::ZeroBased {
+            return None;
+        }
+        Some(Self {
+            coefficient,
+            points,
+        })
+    }
+
+    pub fn coefficient(&self) -> Coefficient {
+        self.coefficient
+    }
+
+    pub fn points(&self) -> Points {
+        self.points
+    }
+
+    /// The point the share with this id is evaluated at.
+    pub fn x(&self, id: u8) -> u8 {
+        match self.points {
+            Points::Id => id,
+            Points::ZeroBased => id.wrapping_sub(1),
+        }
+    }
+
+    /// The index of the secret among the `k` coefficients.
+    pub(crate) fn index(&self, k: u8) -> usize {
+        match self.coefficient {
+            Coefficient::Constant => 0,
+            Coefficient::Leading => (k as usize).saturating_sub(1),
+        }
+    }
+
+    /// The weights that recover the secret from the shares with these ids.
+    pub(crate) fn weights(&self, field: &Field, ids: &[u8]) -> Option<Vec<u8>> {
+        let xs: Vec<u8> = ids.iter().map(|id| self.x(*id)).collect();
+        match self.coefficient {
+            Coefficient::Constant => field.lagrange_coefficients(&xs, 0),
+            Coefficient::Leading => field.leading_coefficients(&xs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set::ShareSet, ShamirSecretSharing, Sharing, SharingError};
+
+    #[test]
+    fn every_placement_round_trips() {
+        let placements = [
+            Placement::STANDARD,
+            Placement::new(Coefficient::Leading, Points::Id).unwrap(),
+            Placement::new(Coefficient::Leading, Points::ZeroBased).unwrap(),
+        ];
+        for placement in placements {
+            for k in 1..=4 {
+                let sharer =
+                    ShamirSecretSharing::new(4, k, rand::thread_rng()).with_placement(placement);
+                let mut shares = sharer.share(vec![0, 1, 0x80, 0xff]).unwrap();
+                let set = ShareSet::new(k, shares.clone()).with_placement(placement);
+                assert!(set.verify().is_ok(), "{:?} k = {}", placement, k);
+                let rec = sharer.reconstruct(shares[4 - k as usize..].to_vec());
+                assert_eq!(
+                    rec.unwrap(),
+                    vec![0, 1, 0x80, 0xff],
+                    "{:?} k = {}",
+                    placement,
+                    k
+                );
+                if k < 4 {
+                    shares[3].body[0] ^= 1;
+                    let set = ShareSet::new(k, shares).with_placement(placement);
+                    assert!(matches!(set.verify(), Err(SharingError::CorruptShare(4))));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn leading_coefficient_of_a_line_is_its_slope() {
+        let field = Field::default();
+        let placement = Placement::new(Coefficient::Leading, Points::Id).unwrap();
+        // y = 7 + 5x
+        let ys: Vec<u8> = [1, 2].iter().map(|x| field.evaluate(&[7, 5], *x)).collect();
+        let weights = placement.weights(&field, &[1, 2]).unwrap();
+        let slope = field.mul(weights[0], ys[0]) ^ field.mul(weights[1], ys[1]);
+        assert_eq!(slope, 5);
+    }
+}
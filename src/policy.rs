@@ -0,0 +1,577 @@
+//! Access Structure Policies
+//!
+//! A [`Policy`] describes which groups of parties may reconstruct a secret.
+//! Policies are written in a small language of nested thresholds:
+//!
+//! ```text
+//! policy := term ("or" term)*
+//! term   := factor ("and" factor)*
+//! factor := NUMBER "of" "[" policy ("," policy)* "]" | NAME | "(" policy ")"
+//! ```
+//!
+//! `a and b` is short for `2 of [a, b]` and `a or b` for `1 of [a, b]`. Policies nest at
+//! most 64 levels deep.
+//!
+//! ```rust
+//! use sharing::{policy::Policy, PolicySecretSharing, Sharing};
+//!
+//! let policy = Policy::parse("2 of [ceo, cfo] and 2 of [eng1, eng2, eng3]").unwrap();
+//! let sharer = PolicySecretSharing::new(policy, rand::thread_rng());
+//!
+//! let data = [1, 2, 3, 4, 5].to_vec();
+//! let shares = sharer.share(data.clone()).unwrap();
+//!
+//! // one share per party: ceo, cfo, eng1, eng2, eng3
+//! let quorum: Vec<_> = shares
+//!     .into_iter()
+//!     .filter(|s| ["ceo", "cfo", "eng1", "eng3"].contains(&s.party.as_str()))
+//!     .collect();
+//! assert_eq!(data, sharer.reconstruct(quorum).unwrap());
+//! ```
+use crate::{
+    error::SharingError,
+    roles::Combiner,
+    share::{ShamirShare, Share},
+    ShamirSecretSharing, Sharing,
+};
+use rand::Rng;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    fmt,
+    str::FromStr,
+};
+
+/// A monotone access structure built from nested threshold gates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Policy {
+    /// A single named party.
+    Party(String),
+    /// Satisfied if at least `k` of the sub policies are satisfied.
+    Threshold { k: u8, of: Vec<Policy> },
+}
+
+impl Policy {
+    pub fn party<S: Into<String>>(name: S) -> Self {
+        Policy::Party(name.into())
+    }
+
+    pub fn threshold(k: u8, of: Vec<Policy>) -> Self {
+        Policy::Threshold { k, of }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParsePolicyError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            depth: 0,
+        };
+        let policy = parser.policy()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(policy),
+            Some((offset, _)) => Err(ParsePolicyError::new(*offset, "unexpected trailing input")),
+        }
+    }
+
+    /// Checks that every threshold gate is satisfiable and addresses at most 255 sub policies.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Policy::Party(name) => !name.is_empty(),
+            Policy::Threshold { k, of } => {
+                *k >= 1
+                    && (*k as usize) <= of.len()
+                    && of.len() <= 255
+                    && of.iter().all(Policy::is_valid)
+            }
+        }
+    }
+
+    /// All distinct parties mentioned in the policy, sorted by name.
+    pub fn parties(&self) -> Vec<&str> {
+        let mut parties = BTreeSet::new();
+        self.collect_parties(&mut parties);
+        parties.into_iter().collect()
+    }
+
+    fn collect_parties<'a>(&'a self, parties: &mut BTreeSet<&'a str>) {
+        match self {
+            Policy::Party(name) => {
+                parties.insert(name);
+            }
+            Policy::Threshold { of, .. } => of.iter().for_each(|p| p.collect_parties(parties)),
+        }
+    }
+
+    /// Checks whether the given parties together satisfy the policy.
+    ///
+    /// ```rust
+    /// use sharing::policy::Policy;
+    ///
+    /// let policy: Policy = "alice and (bob or carol)".parse().unwrap();
+    /// assert!(policy.is_satisfied_by(&["alice", "carol"]));
+    /// assert!(!policy.is_satisfied_by(&["bob", "carol"]));
+    /// ```
+    pub fn is_satisfied_by(&self, parties: &[&str]) -> bool {
+        match self {
+            Policy::Party(name) => parties.contains(&name.as_str()),
+            Policy::Threshold { k, of } => {
+                of.iter().filter(|p| p.is_satisfied_by(parties)).count() >= *k as usize
+            }
+        }
+    }
+}
+
+impl FromStr for Policy {
+    type Err = ParsePolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Policy::parse(s)
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::Party(name) => write!(f, "{}", name),
+            Policy::Threshold { k, of } => {
+                write!(f, "{} of [", k)?;
+                for (i, p) in of.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", p)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Error returned when a policy string cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePolicyError {
+    offset: usize,
+    message: &'static str,
+}
+
+impl ParsePolicyError {
+    fn new(offset: usize, message: &'static str) -> Self {
+        Self { offset, message }
+    }
+
+    /// Byte offset into the input where the error was detected.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for ParsePolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl Error for ParsePolicyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(u8),
+    Name(String),
+    Of,
+    And,
+    Or,
+    Comma,
+    Open,
+    Close,
+    OpenParen,
+    CloseParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParsePolicyError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            ',' => Token::Comma,
+            '[' => Token::Open,
+            ']' => Token::Close,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            c if c.is_ascii_digit() => {
+                let mut end = offset;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                let number = input[offset..end]
+                    .parse()
+                    .map_err(|_| ParsePolicyError::new(offset, "threshold must be at most 255"))?;
+                tokens.push((offset, Token::Number(number)));
+                continue;
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut end = offset;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '@') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let token = match &input[offset..end] {
+                    "of" => Token::Of,
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    name => Token::Name(name.to_string()),
+                };
+                tokens.push((offset, token));
+                continue;
+            }
+            _ => return Err(ParsePolicyError::new(offset, "unexpected character")),
+        };
+        chars.next();
+        tokens.push((offset, token));
+    }
+    Ok(tokens)
+}
+
+/// Nesting of parentheses and threshold gates accepted by [`Policy::parse`], so untrusted
+/// input cannot overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(0, |(o, _)| *o)
+    }
+
+    fn expect(&mut self, token: Token, message: &'static str) -> Result<(), ParsePolicyError> {
+        if self.peek() == Some(&token) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(ParsePolicyError::new(self.offset(), message))
+        }
+    }
+
+    fn policy(&mut self) -> Result<Policy, ParsePolicyError> {
+        let offset = self.offset();
+        if self.depth == MAX_DEPTH {
+            return Err(ParsePolicyError::new(offset, "policy nested too deeply"));
+        }
+        self.depth += 1;
+        let policy = self.alternatives(offset);
+        self.depth -= 1;
+        policy
+    }
+
+    fn alternatives(&mut self, offset: usize) -> Result<Policy, ParsePolicyError> {
+        let mut of = vec![self.term()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            of.push(self.term()?);
+        }
+        gate(offset, 1, of)
+    }
+
+    fn term(&mut self) -> Result<Policy, ParsePolicyError> {
+        let offset = self.offset();
+        let mut of = vec![self.factor()?];
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            of.push(self.factor()?);
+        }
+        let k = of.len().min(255) as u8;
+        gate(offset, k, of)
+    }
+
+    fn factor(&mut self) -> Result<Policy, ParsePolicyError> {
+        let offset = self.offset();
+        match self.peek().cloned() {
+            Some(Token::Name(name)) => {
+                self.pos += 1;
+                Ok(Policy::Party(name))
+            }
+            Some(Token::OpenParen) => {
+                self.pos += 1;
+                let policy = self.policy()?;
+                self.expect(Token::CloseParen, "expected `)`")?;
+                Ok(policy)
+            }
+            Some(Token::Number(k)) => {
+                self.pos += 1;
+                self.expect(Token::Of, "expected `of`")?;
+                self.expect(Token::Open, "expected `[`")?;
+                let mut of = vec![self.policy()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    of.push(self.policy()?);
+                }
+                self.expect(Token::Close, "expected `]`")?;
+                if k < 1 || k as usize > of.len() {
+                    return Err(ParsePolicyError::new(offset, "threshold out of range"));
+                }
+                gate(offset, k, of)
+            }
+            _ => Err(ParsePolicyError::new(
+                offset,
+                "expected a party, threshold or `(`",
+            )),
+        }
+    }
+}
+
+fn gate(offset: usize, k: u8, mut of: Vec<Policy>) -> Result<Policy, ParsePolicyError> {
+    if of.len() > 255 {
+        return Err(ParsePolicyError::new(offset, "too many sub policies"));
+    }
+    if of.len() == 1 {
+        Ok(of.remove(0))
+    } else {
+        Ok(Policy::Threshold { k, of })
+    }
+}
+
+/// The pieces of a policy dealing held by a single party.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PolicyShare {
+    pub party: String,
+    pub pieces: Vec<PolicyPiece>,
+}
+
+/// A share for one occurrence of a party in the policy tree.
+///
+/// `path` lists the child index taken at every threshold gate from the root down to the party.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PolicyPiece {
+    pub path: Vec<u8>,
+    pub body: Vec<u8>,
+}
+
+impl Share for PolicyShare {
+    fn size(&self) -> usize {
+        self.pieces.first().map_or(0, |p| p.body.len())
+    }
+    fn with_size(size: usize) -> Self {
+        Self {
+            party: String::new(),
+            pieces: vec![PolicyPiece {
+                path: Vec::new(),
+                body: vec![0u8; size],
+            }],
+        }
+    }
+}
+
+/// # Policy Secret Sharing
+///
+/// Shares a secret according to a [`Policy`] by recursively applying
+/// Shamir Secret Sharing at every threshold gate.
+///
+/// ```rust
+/// use sharing::{policy::Policy, PolicySecretSharing, Sharing};
+///
+/// let data = [1, 2, 3, 4, 5].to_vec();
+///
+/// let sharer = PolicySecretSharing::new("alice or 2 of [bob, carol, dave]".parse().unwrap(), rand::thread_rng());
+///
+/// let shares = sharer.share(data.clone()).unwrap();
+/// // alice alone is enough
+/// let rec = sharer.reconstruct(shares[0..1].to_vec()).unwrap();
+///
+/// assert_eq!(data, rec);
+/// ```
+pub struct PolicySecretSharing<R: Rng> {
+    policy: Policy,
+    rng: RefCell<R>,
+}
+
+impl<R: Rng> PolicySecretSharing<R> {
+    /// The policy is checked when sharing, see [`try_new`](Self::try_new)
+    /// to check it up front.
+    pub fn new(policy: Policy, rng: R) -> Self {
+        Self {
+            policy,
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Like [`new`](Self::new), but fails with [`SharingError::InvalidParameters`]
+    /// unless the policy [`is_valid`](Policy::is_valid).
+    ///
+    /// ```rust
+    /// use sharing::{policy::Policy, PolicySecretSharing};
+    ///
+    /// let policy: Policy = "2 of [alice, bob]".parse().unwrap();
+    /// assert!(PolicySecretSharing::try_new(policy, rand::thread_rng()).is_ok());
+    ///
+    /// let unsatisfiable = Policy::threshold(3, vec![Policy::party("alice")]);
+    /// assert!(PolicySecretSharing::try_new(unsatisfiable, rand::thread_rng()).is_err());
+    /// ```
+    pub fn try_new(policy: Policy, rng: R) -> Result<Self, SharingError> {
+        if !policy.is_valid() {
+            return Err(SharingError::InvalidParameters);
+        }
+        Ok(Self::new(policy, rng))
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    fn deal(
+        &self,
+        node: &Policy,
+        path: &mut Vec<u8>,
+        secret: Vec<u8>,
+        out: &mut BTreeMap<String, Vec<PolicyPiece>>,
+    ) -> Result<(), SharingError> {
+        match node {
+            Policy::Party(name) => {
+                out.entry(name.clone()).or_default().push(PolicyPiece {
+                    path: path.clone(),
+                    body: secret,
+                });
+            }
+            Policy::Threshold { k, of } => {
+                let shares = {
+                    let mut rng = self.rng.borrow_mut();
+                    ShamirSecretSharing::new(of.len() as u8, *k, &mut *rng).share(secret)?
+                };
+                for (i, (child, share)) in of.iter().zip(shares).enumerate() {
+                    path.push(i as u8);
+                    self.deal(child, path, share.body, out)?;
+                    path.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn recover(
+        &self,
+        node: &Policy,
+        path: &mut Vec<u8>,
+        pieces: &HashMap<&[u8], &[u8]>,
+    ) -> Result<Vec<u8>, SharingError> {
+        match node {
+            Policy::Party(_) => pieces.get(path.as_slice()).map(|body| body.to_vec()).ok_or(
+                SharingError::InsufficientShares {
+                    needed: 1,
+                    found: 0,
+                },
+            ),
+            Policy::Threshold { k, of } => {
+                let mut shares = Vec::with_capacity(*k as usize);
+                for (i, child) in of.iter().enumerate() {
+                    if shares.len() == *k as usize {
+                        break;
+                    }
+                    path.push(i as u8);
+                    // a missing child is made up for by the others, anything else is reported
+                    match self.recover(child, path, pieces) {
+                        Ok(body) => shares.push(ShamirShare {
+                            id: i as u8 + 1,
+                            body,
+                        }),
+                        Err(SharingError::InsufficientShares { .. }) => {}
+                        Err(error) => return Err(error),
+                    }
+                    path.pop();
+                }
+                Combiner::new(*k).reconstruct(shares)
+            }
+        }
+    }
+}
+
+impl<R: Rng> Sharing for PolicySecretSharing<R> {
+    type Share = PolicyShare;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError> {
+        if !self.policy.is_valid() {
+            return Err(SharingError::InvalidParameters);
+        }
+        let mut out = BTreeMap::new();
+        self.deal(&self.policy, &mut Vec::new(), data, &mut out)?;
+        Ok(out
+            .into_iter()
+            .map(|(party, pieces)| PolicyShare { party, pieces })
+            .collect())
+    }
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        let pieces = shares
+            .iter()
+            .flat_map(|s| s.pieces.iter())
+            .map(|p| (p.path.as_slice(), p.body.as_slice()))
+            .collect();
+        self.recover(&self.policy, &mut Vec::new(), &pieces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(
+            Policy::parse(&nested(MAX_DEPTH - 1)).unwrap(),
+            Policy::party("a")
+        );
+        assert!(Policy::parse(&nested(MAX_DEPTH)).is_err());
+        let error = Policy::parse(&"1 of [".repeat(100_000)).unwrap_err();
+        assert_eq!(error.to_string(), "policy nested too deeply at offset 384");
+    }
+
+    #[test]
+    fn child_errors() {
+        let policy = Policy::parse("ceo or 2 of [eng1, eng2]").unwrap();
+        let sharer = PolicySecretSharing::new(policy, rand::thread_rng());
+        let shares = sharer.share(vec![1, 2, 3]).unwrap();
+        let engineers: Vec<PolicyShare> = shares
+            .into_iter()
+            .filter(|s| s.party.starts_with("eng"))
+            .collect();
+        assert_eq!(
+            sharer.reconstruct(engineers.clone()).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(matches!(
+            sharer.reconstruct(engineers[..1].to_vec()),
+            Err(SharingError::InsufficientShares { .. })
+        ));
+
+        let mut truncated = engineers;
+        truncated[1].pieces[0].body.pop();
+        assert!(matches!(
+            sharer.reconstruct(truncated),
+            Err(SharingError::BodyLengthMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,68 @@
+//! Postcard
+//!
+//! The `serde` representation of the share types in the compact [postcard] format, for
+//! embedded devices. Postcard does not depend on the word size of the platform, integers are
+//! varints, so a `no_std` + `alloc` target reads these bytes with `postcard` and structs with
+//! the same fields in the same order as the share types.
+//!
+//! Available with the `postcard` feature, which enables `serde`.
+//!
+//! [postcard]: https://postcard.jamesmunns.com/wire-format
+//!
+//! ```rust
+//! use sharing::{postcard::Postcard, ShamirShare, RabinShare};
+//!
+//! let share = ShamirShare { id: 2, body: vec![0xca, 0xfe] };
+//! let bytes = share.to_postcard().unwrap();
+//! assert_eq!(bytes, [2, 2, 0xca, 0xfe]);
+//! assert_eq!(ShamirShare::from_postcard(&bytes).unwrap(), share);
+//!
+//! let share = RabinShare { id: 1, length: 300, body: vec![7] };
+//! assert_eq!(share.to_postcard().unwrap(), [1, 0xac, 0x02, 1, 7]);
+//! ```
+use crate::{
+    error::SharingError,
+    share::{
+        KrawczykBatchShare, KrawczykDataShare, KrawczykKeyShare, KrawczykShare, RabinShare,
+        ShamirShare,
+    },
+    strict::{Limits, StrictCheck},
+    wire::{WireScheme, WireShare},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Share types with a postcard encoding.
+pub trait Postcard: Serialize + DeserializeOwned {
+    /// Fails with [`SharingError::Malformed`] only if the share does not fit in memory.
+    fn to_postcard(&self) -> Result<Vec<u8>, SharingError> {
+        ::postcard::to_allocvec(self).map_err(|_| SharingError::Malformed("postcard"))
+    }
+
+    /// Fails with [`SharingError::Malformed`] if the bytes are not exactly one share.
+    fn from_postcard(bytes: &[u8]) -> Result<Self, SharingError> {
+        match ::postcard::take_from_bytes(bytes) {
+            Ok((share, [])) => Ok(share),
+            _ => Err(SharingError::Malformed("postcard share")),
+        }
+    }
+
+    /// Decodes like [`from_postcard`](Postcard::from_postcard), then checks the share against
+    /// `limits`.
+    fn from_postcard_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError>
+    where
+        Self: StrictCheck,
+    {
+        limits.size(bytes)?;
+        let share = Self::from_postcard(bytes)?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl Postcard for ShamirShare {}
+impl Postcard for RabinShare {}
+impl Postcard for KrawczykShare {}
+impl Postcard for KrawczykKeyShare {}
+impl Postcard for KrawczykDataShare {}
+impl Postcard for KrawczykBatchShare {}
+impl<S: WireScheme + Serialize + DeserializeOwned> Postcard for WireShare<S> {}
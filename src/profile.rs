@@ -0,0 +1,124 @@
+//! Parameter Profiles
+//!
+//! A [`Profile`] bundles the parameters, scheme, integrity and encoding options
+//! recommended for a common situation, so users who are not familiar with the trade-offs
+//! get a sound configuration without choosing every option themselves.
+//!
+//! | Profile | `k` of `n` | Scheme | Ids | Encoding |
+//! |---|---|---|---|---|
+//! | [`PaperBackup3of5`](Profile::PaperBackup3of5) | 3 of 5 | Shamir | `1..=5`, printed on the sheets | QR sized fragments |
+//! | [`Datacenter10of14`](Profile::Datacenter10of14) | 10 of 14 | Krawczyk | random | whole shares |
+//! | [`TwoPersonRule2of2`](Profile::TwoPersonRule2of2) | 2 of 2 | Shamir | `1..=2` | whole shares |
+//!
+//! Every profile records a [`Manifest`] of the shares for integrity checks.
+//!
+//! ```rust
+//! use sharing::{fragment, profile::Profile, share::ShareBytes, Sharing};
+//!
+//! let profile = Profile::PaperBackup3of5;
+//! let sharer = profile.shamir(rand::thread_rng());
+//! let shares = sharer.share(b"wallet seed".to_vec()).unwrap();
+//! let manifest = profile.manifest(&shares);
+//!
+//! let size = profile.fragment_size().unwrap();
+//! assert!(fragment::fragment(&shares[0].to_bytes(), size).is_some());
+//!
+//! let collected = manifest.collect(shares[2..].to_vec());
+//! assert_eq!(sharer.reconstruct(collected).unwrap(), b"wallet seed");
+//! ```
+use crate::{
+    manifest::Manifest,
+    secret::ShamirSecretSharing,
+    share::{ShareBytes, ShareId},
+    Parameters, Scheme,
+};
+use rand::Rng;
+#[cfg(feature = "krawczyk")]
+use {
+    crate::{compression::Compression, secret::KrawczykSecretSharing},
+    aead::{AeadInPlace, KeyInit},
+};
+
+/// A recommended configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// A small secret printed on five sheets kept in different places, any three recover it.
+    PaperBackup3of5,
+    /// Bulk data spread over fourteen storage nodes, surviving the loss of any four.
+    Datacenter10of14,
+    /// Two people must both take part, neither can recover the secret alone.
+    TwoPersonRule2of2,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 3] = [
+        Profile::PaperBackup3of5,
+        Profile::Datacenter10of14,
+        Profile::TwoPersonRule2of2,
+    ];
+
+    pub fn parameters(&self) -> Parameters {
+        match self {
+            Profile::PaperBackup3of5 => Parameters { n: 5, k: 3 },
+            Profile::Datacenter10of14 => Parameters { n: 14, k: 10 },
+            Profile::TwoPersonRule2of2 => Parameters { n: 2, k: 2 },
+        }
+    }
+
+    /// The recommended scheme: Shamir for small secrets, Krawczyk for bulk data.
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            Profile::Datacenter10of14 => Scheme::Krawczyk,
+            Profile::PaperBackup3of5 | Profile::TwoPersonRule2of2 => Scheme::Shamir,
+        }
+    }
+
+    /// Whether shares get random ids, which hide how many nodes there are.
+    pub fn random_ids(&self) -> bool {
+        matches!(self, Profile::Datacenter10of14)
+    }
+
+    /// The largest encoded fragment, if shares should be split for their medium,
+    /// see [`fragment`](crate::fragment::fragment).
+    ///
+    /// Paper backups use fragments that fit a QR code at medium error correction.
+    pub fn fragment_size(&self) -> Option<usize> {
+        match self {
+            Profile::PaperBackup3of5 => Some(1024),
+            Profile::Datacenter10of14 | Profile::TwoPersonRule2of2 => None,
+        }
+    }
+
+    /// A Shamir sharer with the profile's parameters and ids.
+    pub fn shamir<R: Rng>(&self, rng: R) -> ShamirSecretSharing<R> {
+        let parameters = self.parameters();
+        let sharer = ShamirSecretSharing::new(parameters.n(), parameters.k(), rng);
+        if self.random_ids() {
+            sharer.with_random_ids()
+        } else {
+            sharer
+        }
+    }
+
+    /// A Krawczyk sharer with the profile's parameters and ids, without compression
+    /// so the share sizes do not reveal how compressible the data is.
+    #[cfg(feature = "krawczyk")]
+    pub fn krawczyk<C: AeadInPlace + KeyInit, R: Rng>(
+        &self,
+        rng: R,
+    ) -> KrawczykSecretSharing<C, R> {
+        let parameters = self.parameters();
+        let sharer = KrawczykSecretSharing::new(parameters.n(), parameters.k(), rng)
+            .with_compression(Compression::None);
+        if self.random_ids() {
+            sharer.with_random_ids()
+        } else {
+            sharer
+        }
+    }
+
+    /// The manifest to publish with the shares.
+    pub fn manifest<S: ShareBytes + ShareId>(&self, shares: &[S]) -> Manifest {
+        Manifest::new(self.scheme(), self.parameters(), shares)
+    }
+}
@@ -0,0 +1,163 @@
+//! QR Codes
+//!
+//! Renders shares as QR codes and reads them back from scanned images, for paper backups.
+//! Available with the `qr` feature. A QR code holds at most 2953 bytes, so larger shares are
+//! split into several numbered frames with [`QrShare::to_qr_frames`], using [`fragment`],
+//! and put back together by a [`QrDecoder`] in whatever order they are scanned.
+//!
+//! ```rust
+//! use sharing::{qr::QrShare, ShamirSecretSharing, ShamirShare, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares = sharer.share(b"correct horse battery staple".to_vec()).unwrap();
+//!
+//! let image = shares[0].to_qr_image().unwrap();
+//! let scanned = ShamirShare::from_qr_image(&image).unwrap();
+//! assert_eq!(scanned.body, shares[0].body);
+//!
+//! let png = shares[1].to_qr_png().unwrap();
+//! assert!(png.starts_with(b"\x89PNG"));
+//! println!("{}", shares[2].to_qr_string().unwrap());
+//! ```
+//!
+//! ```rust
+//! use sharing::{qr::{QrDecoder, QrShare}, ShamirSecretSharing, ShamirShare, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares = sharer.share(vec![7; 600]).unwrap();
+//!
+//! let frames = shares[0].to_qr_frames(256).unwrap();
+//! assert_eq!(frames.len(), 3);
+//!
+//! let mut decoder = QrDecoder::<ShamirShare>::new();
+//! assert_eq!(decoder.add_image(&frames[2]).unwrap(), None);
+//! assert_eq!(decoder.add_image(&frames[0]).unwrap(), None);
+//! assert_eq!(decoder.add_image(&frames[0]).unwrap(), None);
+//! assert_eq!(decoder.missing(), vec![1]);
+//! assert_eq!(decoder.add_image(&frames[1]).unwrap(), Some(shares[0].clone()));
+//! ```
+//!
+//! [`fragment`]: crate::fragment
+use crate::{
+    fragment::{self, Fragment, FragmentError, Reassembler},
+    share::ShareBytes,
+};
+use image::{GrayImage, ImageFormat, Luma};
+use qrcode::{render::unicode::Dense1x2, EcLevel, QrCode};
+use std::{io::Cursor, marker::PhantomData};
+
+/// Shares that can be printed as and scanned from QR codes.
+pub trait QrShare: ShareBytes {
+    /// Renders the share with medium error correction, `None` if it is too large for a QR code.
+    fn to_qr_image(&self) -> Option<GrayImage> {
+        render(&self.to_bytes())
+    }
+
+    /// The image of [`to_qr_image`](QrShare::to_qr_image) as a PNG file.
+    fn to_qr_png(&self) -> Option<Vec<u8>> {
+        let mut png = Vec::new();
+        self.to_qr_image()?
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .ok()?;
+        Some(png)
+    }
+
+    /// Renders the share with half block characters, to print to a terminal.
+    fn to_qr_string(&self) -> Option<String> {
+        let code = QrCode::with_error_correction_level(self.to_bytes(), EcLevel::M).ok()?;
+        Some(code.render::<Dense1x2>().build())
+    }
+
+    /// Splits the share into frames of at most `max_size` bytes each, to be read with a
+    /// [`QrDecoder`]. `None` under the same conditions as [`fragment::fragment`], or if
+    /// `max_size` is too large for a QR code.
+    fn to_qr_frames(&self, max_size: usize) -> Option<Vec<GrayImage>> {
+        fragment::fragment(&self.to_bytes(), max_size)?
+            .iter()
+            .map(|fragment| render(&fragment.to_bytes()))
+            .collect()
+    }
+
+    /// Decodes the first QR code found in the image that holds a share of this type.
+    fn from_qr_image(image: &GrayImage) -> Option<Self> {
+        scan(image)
+            .into_iter()
+            .find_map(|bytes| Self::from_bytes(&bytes))
+    }
+}
+
+impl<S: ShareBytes> QrShare for S {}
+
+/// Reassembles a share from the frames of [`QrShare::to_qr_frames`], scanned in any order.
+#[derive(Debug, Clone)]
+pub struct QrDecoder<S> {
+    reassembler: Reassembler,
+    share: PhantomData<S>,
+}
+
+impl<S: ShareBytes> Default for QrDecoder<S> {
+    fn default() -> Self {
+        Self {
+            reassembler: Reassembler::new(),
+            share: PhantomData,
+        }
+    }
+}
+
+impl<S: ShareBytes> QrDecoder<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the content of a scanned frame, e.g. from a camera app, and returns the share
+    /// once the last frame is in. Frames scanned twice are ignored. A payload that is not a
+    /// frame is [`FragmentError::Foreign`], and so is a complete set of frames that does not
+    /// hold a share of this type.
+    pub fn add_payload(&mut self, payload: &[u8]) -> Result<Option<S>, FragmentError> {
+        let fragment = Fragment::from_bytes(payload).ok_or(FragmentError::Foreign)?;
+        match self.reassembler.add(fragment) {
+            Err(FragmentError::Duplicate(_)) => return Ok(None),
+            result => result?,
+        }
+        if !self.reassembler.is_complete() {
+            return Ok(None);
+        }
+        let bytes = std::mem::take(&mut self.reassembler).finish()?;
+        S::from_bytes(&bytes)
+            .map(Some)
+            .ok_or(FragmentError::Foreign)
+    }
+
+    /// Adds every frame found in the image, `None` until the share is complete.
+    pub fn add_image(&mut self, image: &GrayImage) -> Result<Option<S>, FragmentError> {
+        let mut share = None;
+        for payload in scan(image) {
+            share = share.or(self.add_payload(&payload)?);
+        }
+        Ok(share)
+    }
+
+    /// The indices of the frames still missing, empty before the first frame.
+    pub fn missing(&self) -> Vec<u16> {
+        self.reassembler.missing()
+    }
+}
+
+fn render(bytes: &[u8]) -> Option<GrayImage> {
+    let code = QrCode::with_error_correction_level(bytes, EcLevel::M).ok()?;
+    Some(code.render::<Luma<u8>>().build())
+}
+
+/// The contents of every QR code that decodes in the image.
+fn scan(image: &GrayImage) -> Vec<Vec<u8>> {
+    let mut prepared = rqrr::PreparedImage::prepare(image.clone());
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| {
+            let mut bytes = Vec::new();
+            grid.decode_to(&mut bytes).ok()?;
+            Some(bytes)
+        })
+        .collect()
+}
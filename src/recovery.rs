@@ -0,0 +1,209 @@
+//! Recovery
+//!
+//! A [`Recovery`] guides whoever collects the shares of a secret: every share is checked
+//! the moment it arrives, so a holder who sent a wrong or foreign share can be asked again
+//! while still at hand, and the collector always knows how many shares are still needed.
+//!
+//! Each share is checked for
+//!
+//! - its format, with the [`strict`](crate::strict) decoder,
+//! - its set, given the [`Manifest`] of the set: it must be listed there with its digest,
+//! - the dealer's [`Commitment`] under its id, if commitments were published, opened with
+//!   the salt its holder hands in,
+//! - its size, which must match the shares collected before.
+//!
+//! ```rust
+//! use sharing::{
+//!     manifest::Manifest, recovery::{Progress, Recovery}, share::ShareBytes,
+//!     Parameters, Scheme, ShamirSecretSharing, SharingError,
+//! };
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let (shares, openings, commitments) =
+//!     sharer.share_with_commitments(b"vault".to_vec()).unwrap();
+//! let manifest = Manifest::new(Scheme::Shamir, Parameters::new(5, 3).unwrap(), &shares)
+//!     .with_payload(b"vault");
+//!
+//! let mut recovery = Recovery::from_manifest(&manifest)
+//!     .unwrap()
+//!     .with_commitments(commitments, openings);
+//! assert_eq!(recovery.add(&shares[0].to_bytes()).unwrap(), Progress::Missing(2));
+//!
+//! let mut foreign = shares[1].clone();
+//! foreign.body[0] ^= 1;
+//! assert!(matches!(recovery.add_share(foreign), Err(SharingError::CorruptShare(2))));
+//! assert_eq!(recovery.outstanding(), vec![2, 3, 4, 5]);
+//!
+//! recovery.add_share(shares[3].clone()).unwrap();
+//! assert_eq!(recovery.add_share(shares[4].clone()).unwrap(), Progress::Complete);
+//! assert_eq!(recovery.collected(), vec![1, 4, 5]);
+//! assert_eq!(recovery.finalize().unwrap(), b"vault");
+//! ```
+use crate::{
+    commitment::{verify_opened, Commitment, Opening},
+    error::SharingError,
+    manifest::Manifest,
+    math::Field,
+    roles::Combiner,
+    set::ShareSet,
+    share::ShamirShare,
+    strict::{Limits, StrictBytes},
+    Scheme,
+};
+use std::collections::BTreeMap;
+
+#[doc(inline)]
+pub use crate::incremental::Progress;
+
+/// Collects and checks the Shamir shares of one secret until they reconstruct it.
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    k: u8,
+    field: Field,
+    limits: Limits,
+    manifest: Option<Manifest>,
+    commitments: Vec<Commitment>,
+    openings: Vec<Opening>,
+    shares: BTreeMap<u8, ShamirShare>,
+}
+
+impl Recovery {
+    /// Collects shares with threshold `k`, checking only their format and size.
+    pub fn new(k: u8) -> Self {
+        Self {
+            k,
+            field: Field::default(),
+            limits: Limits::default(),
+            manifest: None,
+            commitments: Vec::new(),
+            openings: Vec::new(),
+            shares: BTreeMap::new(),
+        }
+    }
+
+    /// Collects the shares listed in a Shamir manifest, with its threshold.
+    pub fn from_manifest(manifest: &Manifest) -> Result<Self, SharingError> {
+        if manifest.scheme() != Scheme::Shamir {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let mut recovery = Self::new(manifest.parameters().k());
+        recovery.limits = recovery.limits.with_parameters(manifest.parameters());
+        recovery.manifest = Some(manifest.clone());
+        Ok(recovery)
+    }
+
+    /// The field the shares were dealt in.
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// Caps for decoding shares with [`add`](Self::add).
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Only accepts shares matching the dealer's commitments, opened with the holders'
+    /// openings of the same id.
+    pub fn with_commitments(
+        mut self,
+        commitments: Vec<Commitment>,
+        openings: Vec<Opening>,
+    ) -> Self {
+        self.commitments = commitments;
+        self.openings = openings;
+        self
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// Decodes and adds an encoded share.
+    pub fn add(&mut self, bytes: &[u8]) -> Result<Progress, SharingError> {
+        let share = ShamirShare::from_bytes_strict(bytes, &self.limits)?;
+        self.add_share(share)
+    }
+
+    /// Adds a share, or fails without changing the collected shares.
+    ///
+    /// Adding a share again is harmless, a different share under a collected id is corrupt.
+    pub fn add_share(&mut self, share: ShamirShare) -> Result<Progress, SharingError> {
+        if share.id == 0 {
+            return Err(SharingError::Malformed("share id"));
+        }
+        let listed = self
+            .manifest
+            .as_ref()
+            .is_none_or(|manifest| manifest.verify_share(&share));
+        let committed =
+            self.commitments.is_empty() || verify_opened(&share, &self.openings, &self.commitments);
+        if !listed || !committed {
+            return Err(SharingError::CorruptShare(share.id));
+        }
+        if let Some(collected) = self.shares.get(&share.id) {
+            if *collected != share {
+                return Err(SharingError::CorruptShare(share.id));
+            }
+        } else if let Some(other) = self.shares.values().next() {
+            if other.body.len() != share.body.len() {
+                return Err(SharingError::BodyLengthMismatch {
+                    id: share.id,
+                    expected: other.body.len(),
+                    found: share.body.len(),
+                });
+            }
+        }
+        self.shares.insert(share.id, share);
+        Ok(self.progress())
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress::of(self.k as usize, self.shares.len())
+    }
+
+    /// How many more shares are needed.
+    pub fn missing(&self) -> usize {
+        (self.k as usize).saturating_sub(self.shares.len())
+    }
+
+    /// The ids of the collected shares, in order.
+    pub fn collected(&self) -> Vec<u8> {
+        self.shares.keys().copied().collect()
+    }
+
+    /// The ids that are known from the manifest or commitments but not yet collected,
+    /// empty if neither was given.
+    pub fn outstanding(&self) -> Vec<u8> {
+        let mut ids: Vec<u8> = match &self.manifest {
+            Some(manifest) => manifest.shares().iter().map(|(id, _)| *id).collect(),
+            None => self.commitments.iter().map(|c| c.id).collect(),
+        };
+        ids.sort_unstable();
+        ids.dedup();
+        ids.retain(|id| !self.shares.contains_key(id));
+        ids
+    }
+
+    /// Reconstructs the secret once the threshold is met.
+    ///
+    /// Shares beyond the threshold are checked for consistency first and, if the manifest
+    /// records the payload digest, so is the secret.
+    pub fn finalize(self) -> Result<Vec<u8>, SharingError> {
+        let set = ShareSet::new(self.k, self.shares.into_values().collect())
+            .with_field(self.field.clone());
+        set.verify()?;
+        let data = Combiner::new(self.k)
+            .with_field(self.field)
+            .reconstruct(set.into_shares())?;
+        let payload = self
+            .manifest
+            .as_ref()
+            .is_none_or(|manifest| manifest.payload().is_none() || manifest.verify_payload(&data));
+        if !payload {
+            return Err(SharingError::ReconstructionFailed);
+        }
+        Ok(data)
+    }
+}
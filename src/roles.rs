@@ -0,0 +1,315 @@
+//! Dealer and Combiner Roles
+//!
+//! Splits Shamir sharing into its two roles, so a program can state in its types which
+//! side of the trust boundary it is on:
+//!
+//! - a [`Dealer`] owns the random number generator and produces shares and commitments,
+//! - a [`Combiner`] holds only the threshold and field, verifies shares and reconstructs.
+//!
+//! A reconstruction-only binary depends on [`Combiner`] alone and never carries RNG state.
+//! Generic code can take either side through the [`Dealer`](crate::split::Dealer) and
+//! [`Reconstructor`](crate::split::Reconstructor) traits.
+//!
+//! ```rust
+//! use sharing::roles::{Combiner, Dealer};
+//!
+//! let dealer = Dealer::new(5, 3, rand::thread_rng());
+//! let (shares, openings, commitments) = dealer.deal(vec![1, 2, 3]).unwrap();
+//!
+//! // shipped to the holders, who only ever combine
+//! let combiner: Combiner = dealer.combiner();
+//! assert!(combiner.verify(&shares, &openings, &commitments).is_ok());
+//! assert_eq!(combiner.reconstruct(shares[2..].to_vec()).unwrap(), vec![1, 2, 3]);
+//! ```
+#[cfg(feature = "secure-memory")]
+use crate::secure::SecretBuffer;
+use crate::{
+    backend::Backend,
+    commitment::{Commitment, CommittedShares, Opening},
+    error::SharingError,
+    executor::{Executor, Sequential, Task},
+    math::Field,
+    placement::Placement,
+    secret::ShamirSecretSharing,
+    set::ShareSet,
+    share::{dedup_shares, validate_shares, ShamirShare},
+    Sharing,
+};
+use rand::Rng;
+
+/// The bytes interpolated by one task of [`Combiner::reconstruct_on`].
+const BLOCK: usize = 64 * 1024;
+
+/// Produces Shamir shares and the commitments to publish for them.
+pub struct Dealer<R: Rng> {
+    sharer: ShamirSecretSharing<R>,
+}
+
+impl<R: Rng> Dealer<R> {
+    pub fn new(n: u8, k: u8, rng: R) -> Self {
+        Self {
+            sharer: ShamirSecretSharing::new(n, k, rng),
+        }
+    }
+
+    /// See [`ShamirSecretSharing::with_random_ids`].
+    pub fn with_random_ids(mut self) -> Self {
+        self.sharer = self.sharer.with_random_ids();
+        self
+    }
+
+    /// See [`ShamirSecretSharing::with_field`].
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.sharer = self.sharer.with_field(field);
+        self
+    }
+
+    pub fn n(&self) -> u8 {
+        self.sharer.n()
+    }
+
+    pub fn k(&self) -> u8 {
+        self.sharer.k()
+    }
+
+    /// The combiner for the shares of this dealer.
+    pub fn combiner(&self) -> Combiner {
+        self.sharer.combiner()
+    }
+
+    pub fn share(&self, data: Vec<u8>) -> Result<Vec<ShamirShare>, SharingError> {
+        self.sharer.try_share(data)
+    }
+
+    /// Like [`share`](Self::share), but overwrites the shares in `out`, reusing their bodies.
+    pub fn share_into(
+        &self,
+        data: Vec<u8>,
+        out: &mut Vec<ShamirShare>,
+    ) -> Result<(), SharingError> {
+        Sharing::share_into(&self.sharer, data, out)
+    }
+
+    /// Shares the data and returns a [`Commitment`] and [`Opening`] for every share.
+    pub fn deal(&self, data: Vec<u8>) -> Result<CommittedShares, SharingError> {
+        let shares = self.share(data)?;
+        let (openings, commitments) = self.sharer.commit(&shares)?;
+        Ok((shares, openings, commitments))
+    }
+}
+
+impl<R: Rng> From<ShamirSecretSharing<R>> for Dealer<R> {
+    fn from(sharer: ShamirSecretSharing<R>) -> Self {
+        Self { sharer }
+    }
+}
+
+/// Verifies and reconstructs Shamir shares, without any random state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Combiner {
+    k: u8,
+    field: Field,
+    placement: Placement,
+    backend: Backend,
+}
+
+impl Combiner {
+    pub fn new(k: u8) -> Self {
+        Self {
+            k,
+            field: Field::default(),
+            placement: Placement::STANDARD,
+            backend: Backend::detect(),
+        }
+    }
+
+    /// The field the shares were dealt in.
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// Where the secret sits in the polynomials, see [`placement`](crate::placement).
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// The multiplication used for the data, [`Backend::detect`] by default.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Checks the shares against the dealer's commitments and each other,
+    /// see [`ShareSet::verify_commitments`].
+    pub fn verify(
+        &self,
+        shares: &[ShamirShare],
+        openings: &[Opening],
+        commitments: &[Commitment],
+    ) -> Result<(), SharingError> {
+        ShareSet::new(self.k, shares.to_vec())
+            .with_field(self.field.clone())
+            .with_placement(self.placement)
+            .verify_commitments(openings, commitments)
+    }
+
+    /// Reconstructs the data from the first `k` shares.
+    pub fn reconstruct(&self, shares: Vec<ShamirShare>) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct_ref(&shares)
+    }
+
+    /// The old, misspelled name of [`reconstruct`](Self::reconstruct).
+    #[deprecated(since = "0.2.0", note = "renamed to `reconstruct`")]
+    pub fn recontruct(&self, shares: Vec<ShamirShare>) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct(shares)
+    }
+
+    /// Like [`reconstruct`](Self::reconstruct), but reads the shares in place.
+    pub fn reconstruct_ref(&self, shares: &[ShamirShare]) -> Result<Vec<u8>, SharingError> {
+        let mut data = Vec::new();
+        self.combine_on(&Sequential, shares, &mut data)?;
+        Ok(data)
+    }
+
+    /// Like [`reconstruct_ref`](Self::reconstruct_ref), but writes the data to `out`,
+    /// reusing its capacity. `out` is left untouched if reconstruction fails.
+    pub fn reconstruct_into(
+        &self,
+        shares: &[ShamirShare],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        self.combine_on(&Sequential, shares, out)
+    }
+
+    /// Like [`reconstruct`](Self::reconstruct), but interpolates blocks of the data
+    /// as separate tasks on `executor`.
+    ///
+    /// ```rust
+    /// use sharing::{executor::Threads, roles::Dealer};
+    ///
+    /// let dealer = Dealer::new(3, 2, rand::thread_rng());
+    /// let data = vec![7u8; 200_000];
+    /// let shares = dealer.share(data.clone()).unwrap();
+    /// assert_eq!(dealer.combiner().reconstruct_on(&Threads, shares).unwrap(), data);
+    /// ```
+    pub fn reconstruct_on<E: Executor + ?Sized>(
+        &self,
+        executor: &E,
+        shares: Vec<ShamirShare>,
+    ) -> Result<Vec<u8>, SharingError> {
+        let mut data = Vec::new();
+        self.combine_on(executor, &shares, &mut data)?;
+        Ok(data)
+    }
+
+    /// Like [`reconstruct_into`](Self::reconstruct_into), but writes the data to locked memory.
+    /// Available with the `secure-memory` feature.
+    #[cfg(feature = "secure-memory")]
+    pub fn reconstruct_secure_into(
+        &self,
+        shares: &[ShamirShare],
+        out: &mut SecretBuffer,
+    ) -> Result<(), SharingError> {
+        self.combine_with(&Sequential, shares, |size| {
+            *out = SecretBuffer::new(size);
+            out
+        })
+    }
+
+    fn combine_on<E: Executor + ?Sized>(
+        &self,
+        executor: &E,
+        shares: &[ShamirShare],
+        data: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        self.combine_with(executor, shares, |size| {
+            data.clear();
+            data.resize(size, 0);
+            data
+        })
+    }
+
+    /// Interpolates the shares into the zeroed bytes `out` returns for the size of the data.
+    fn combine_with<'a, E: Executor + ?Sized>(
+        &self,
+        executor: &E,
+        shares: &[ShamirShare],
+        out: impl FnOnce(usize) -> &'a mut [u8],
+    ) -> Result<(), SharingError> {
+        if self.k < 1 {
+            return Err(SharingError::InvalidParameters);
+        }
+        let unique = dedup_shares(shares.iter().collect())?;
+        if unique.len() < self.k as usize {
+            return Err(SharingError::reconstruction(self.k, unique.len()));
+        }
+        let size = validate_shares(shares)?;
+        let shares = &unique[..self.k as usize];
+        let ids: Vec<u8> = shares.iter().map(|s| s.id).collect();
+        let coefficients = self
+            .placement
+            .weights(&self.field, &ids)
+            .ok_or(SharingError::ReconstructionFailed)?;
+        let (coefficients, field, backend) = (&coefficients, &self.field, self.backend);
+        let tasks = out(size)
+            .chunks_mut(BLOCK)
+            .enumerate()
+            .map(|(block, out)| {
+                Box::new(move || {
+                    let start = block * BLOCK;
+                    for (s, c) in shares.iter().zip(coefficients) {
+                        backend.mul_add(field, *c, &s.body[start..], out);
+                    }
+                }) as Task<'_>
+            })
+            .collect();
+        executor.scope(tasks);
+        Ok(())
+    }
+}
+
+impl<R: Rng> crate::split::Dealer for Dealer<R> {
+    type Share = ShamirShare;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<ShamirShare>, SharingError> {
+        Dealer::share(self, data)
+    }
+
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<ShamirShare>) -> Result<(), SharingError> {
+        Dealer::share_into(self, data, out)
+    }
+}
+
+impl crate::split::Reconstructor for Combiner {
+    type Share = ShamirShare;
+
+    fn reconstruct(&self, shares: Vec<ShamirShare>) -> Result<Vec<u8>, SharingError> {
+        Combiner::reconstruct(self, shares)
+    }
+
+    fn reconstruct_ref(&self, shares: &[ShamirShare]) -> Result<Vec<u8>, SharingError> {
+        Combiner::reconstruct_ref(self, shares)
+    }
+
+    fn reconstruct_into(
+        &self,
+        shares: &[ShamirShare],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        Combiner::reconstruct_into(self, shares, out)
+    }
+}
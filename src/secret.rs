@@ -1,13 +1,32 @@
 //! Secret Sharing
+#[cfg(feature = "secure-memory")]
+use crate::secure::SecretBuffer;
 use crate::{
+    backend::{Backend, STRIPE},
+    commitment::{commit, Commitment, CommittedShares, Opening},
+    dealing::Dealing,
+    drbg::HmacDrbg,
+    error::SharingError,
+    math::Field,
+    placement::Placement,
+    roles::Combiner,
+    share::{check_ids, ShamirShare, Share},
+    Parameters, Sharing,
+};
+#[cfg(feature = "krawczyk")]
+use crate::{
+    compression::Compression,
     ids::RabinInformationDispersal,
-    share::{KrawczykShare, RabinShare, ShamirShare, ShareVec},
-    Sharing,
+    share::{dedup_shares, BatchFile, KrawczykBatchShare, KrawczykShare, RabinShare},
 };
-use gf::{Field, GF};
+#[cfg(feature = "krawczyk")]
+use aead::{generic_array::typenum::Unsigned, AeadInPlace, KeyInit, Nonce, Tag};
 use rand::Rng;
+#[cfg(feature = "krawczyk")]
+use sha2::{Digest, Sha256};
 use std::cell::RefCell;
-use stream_cipher::{NewStreamCipher, StreamCipher};
+#[cfg(feature = "krawczyk")]
+use std::marker::PhantomData;
 
 /// # Shamir Secret Sharing
 ///
@@ -20,172 +39,1325 @@ use stream_cipher::{NewStreamCipher, StreamCipher};
 ///
 /// let shares = sharer.share(data.clone()).unwrap();
 /// // You only need 3 out of the 5 shares to reconstruct
-/// let rec = sharer.recontruct(shares[1..=3].to_vec()).unwrap();
+/// let rec = sharer.reconstruct(shares[1..=3].to_vec()).unwrap();
 ///
 /// assert_eq!(data, rec);
 /// ```
 pub struct ShamirSecretSharing<R: Rng> {
     n: u8,
     k: u8,
+    field: Field,
+    placement: Placement,
+    backend: Backend,
+    random_ids: bool,
     rng: RefCell<R>,
 }
 
 impl<R: Rng> ShamirSecretSharing<R> {
+    /// The parameters are checked when sharing, see [`try_new`](Self::try_new)
+    /// to check them up front.
     pub fn new(n: u8, k: u8, rng: R) -> Self {
         Self {
             n,
             k,
+            field: Field::default(),
+            placement: Placement::STANDARD,
+            backend: Backend::detect(),
+            random_ids: false,
             rng: RefCell::new(rng),
         }
     }
-}
 
-impl<R: Rng> Sharing for ShamirSecretSharing<R> {
-    type Share = ShamirShare;
-    fn share(&self, data: Vec<u8>) -> Option<Vec<Self::Share>> {
-        if self.k < 1 || self.k > self.n {
-            return None;
+    /// Like [`new`](Self::new), but fails with [`SharingError::InvalidParameters`]
+    /// unless `1 <= k <= n`.
+    ///
+    /// ```rust
+    /// use sharing::{ShamirSecretSharing, SharingError};
+    ///
+    /// assert!(ShamirSecretSharing::try_new(5, 3, rand::thread_rng()).is_ok());
+    /// assert!(matches!(
+    ///     ShamirSecretSharing::try_new(2, 3, rand::thread_rng()),
+    ///     Err(SharingError::InvalidParameters)
+    /// ));
+    /// assert!(ShamirSecretSharing::try_new(3, 0, rand::thread_rng()).is_err());
+    /// ```
+    pub fn try_new(n: u8, k: u8, rng: R) -> Result<Self, SharingError> {
+        Parameters::new(n, k).ok_or(SharingError::InvalidParameters)?;
+        Ok(Self::new(n, k, rng))
+    }
+
+    /// Give every share a random distinct nonzero id instead of `1..=n`,
+    /// so ids reveal neither the number of shares nor the position of a holder.
+    ///
+    /// ```rust
+    /// use sharing::{ShamirSecretSharing, Sharing};
+    ///
+    /// let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng()).with_random_ids();
+    ///
+    /// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+    /// assert_eq!(sharer.reconstruct(shares[..3].to_vec()).unwrap(), vec![1, 2, 3]);
+    /// ```
+    pub fn with_random_ids(mut self) -> Self {
+        self.random_ids = true;
+        self
+    }
+
+    /// Use a different GF(256) reduction polynomial, e.g. to interoperate with other implementations.
+    ///
+    /// ```rust
+    /// use sharing::{math::Field, ShamirSecretSharing, Sharing};
+    ///
+    /// let field = Field::new(Field::AES_POLYNOMIAL).unwrap();
+    /// let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng()).with_field(field);
+    ///
+    /// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+    /// assert_eq!(sharer.reconstruct(shares[2..].to_vec()).unwrap(), vec![1, 2, 3]);
+    /// ```
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.field = field;
+        self
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    /// Put the secret in another coefficient or evaluate shares at other points,
+    /// see [`placement`](crate::placement).
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    pub fn placement(&self) -> Placement {
+        self.placement
+    }
+
+    /// The multiplication used for the data, [`Backend::detect`] by default.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn n(&self) -> u8 {
+        self.n
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// The combiner for the shares of this sharer, without the random state.
+    pub fn combiner(&self) -> Combiner {
+        Combiner::new(self.k)
+            .with_field(self.field.clone())
+            .with_placement(self.placement)
+            .with_backend(self.backend)
+    }
+
+    /// The ids for the next dealing.
+    pub(crate) fn ids(&self) -> Result<Vec<u8>, SharingError> {
+        self.ids_from(&mut *self.rng.borrow_mut())
+    }
+
+    fn ids_from<G: Rng>(&self, rng: &mut G) -> Result<Vec<u8>, SharingError> {
+        if !self.random_ids {
+            return Ok((1..=self.n).collect());
+        }
+        // partial Fisher-Yates over all nonzero ids
+        let mut ids: Vec<u8> = (1..=255).collect();
+        for i in 0..self.n as usize {
+            let j = i + uniform(rng, 255 - i)?;
+            ids.swap(i, j);
         }
+        ids.truncate(self.n as usize);
+        Ok(ids)
+    }
 
-        let mut rand = vec![0u8; self.k as usize];
-        let mut out: Vec<ShamirShare> = ShareVec::with_size(self.n as usize, data.len());
+    pub(crate) fn share_at<D: AsMut<[u8]>>(
+        &self,
+        data: D,
+        ids: &[u8],
+    ) -> Result<Vec<ShamirShare>, SharingError> {
+        if self.k as usize > ids.len() {
+            return Err(SharingError::MismatchedParameters);
+        }
+        self.share_at_with(data, ids, &mut *self.rng.borrow_mut())
+    }
 
-        for i in 0..data.len() {
-            rand[0] = data[i];
-            self.rng.borrow_mut().fill(&mut rand[1..]);
+    /// Evaluates a fresh polynomial per byte at `ids`, which may be fewer than `k`.
+    fn share_at_with<D: AsMut<[u8]>, G: Rng>(
+        &self,
+        data: D,
+        ids: &[u8],
+        rng: &mut G,
+    ) -> Result<Vec<ShamirShare>, SharingError> {
+        let mut out = Vec::with_capacity(ids.len());
+        self.share_at_into(data, ids, rng, &mut out)?;
+        Ok(out)
+    }
 
-            for x in 0..self.n {
-                if i == 0 {
-                    out[x as usize].id = x + 1
-                }
+    /// Like `share_at_with`, but overwrites the shares in `out`, reusing their bodies.
+    fn share_at_into<D: AsMut<[u8]>, G: Rng>(
+        &self,
+        mut data: D,
+        ids: &[u8],
+        rng: &mut G,
+        out: &mut Vec<ShamirShare>,
+    ) -> Result<(), SharingError> {
+        if self.k < 1 {
+            return Err(SharingError::MismatchedParameters);
+        }
 
-                out[x as usize].body[i] = rand
-                    .iter()
-                    .enumerate()
-                    .map(|(j, r)| (GF(x + 1).pow(j) * GF(*r)))
-                    .sum::<GF<u8>>()
-                    .into()
+        let data = data.as_mut();
+        let k = self.k as usize;
+        let mut rand = secret_bytes(k);
+        let secret = self.placement.index(self.k);
+        out.truncate(ids.len());
+        out.resize_with(ids.len(), || ShamirShare::with_size(0));
+        for (share, id) in out.iter_mut().zip(ids) {
+            share.id = *id;
+            share.body.clear();
+            share.body.resize(data.len(), 0);
+        }
+
+        // the coefficients of a stripe of polynomials, one column per power
+        let mut columns = secret_bytes(k * STRIPE.min(data.len()));
+        let mut start = 0;
+        while start < data.len() {
+            let len = STRIPE.min(data.len() - start);
+            for i in 0..len {
+                // drawn polynomial by polynomial, so seeded generators give the same shares
+                rand[0] = data[start + i];
+                if let Err(error) = try_fill(rng, &mut rand[1..]) {
+                    wipe(&mut rand);
+                    wipe(&mut columns);
+                    wipe(data);
+                    return Err(error);
+                }
+                rand.rotate_right(secret);
+                for (j, coefficient) in rand.iter().enumerate() {
+                    columns[j * len + i] = *coefficient;
+                }
+            }
+            for share in out.iter_mut() {
+                self.backend.evaluate_add(
+                    &self.field,
+                    &columns[..k * len],
+                    self.placement.x(share.id),
+                    &mut share.body[start..start + len],
+                );
             }
+            start += len;
         }
+        wipe(&mut rand);
+        wipe(&mut columns);
+        wipe(data);
 
-        Some(out)
-    }
-
-    fn recontruct(&self, shares: Vec<Self::Share>) -> Option<Vec<u8>> {
-        if shares.len() < self.k as usize {
-            return None;
-        }
-        Some(
-            (0..shares.size())
-                .map(|i| {
-                    (0..self.k as usize)
-                        .map(|j| {
-                            GF(shares[j].body[i])
-                                * (0..self.k as usize)
-                                    .filter(|m| *m != j)
-                                    .map(|m| {
-                                        GF(shares[m].id) / (GF(shares[m].id) - GF(shares[j].id))
-                                    })
-                                    .product::<GF<u8>>()
-                        })
-                        .sum::<GF<u8>>()
-                        .into()
-                })
-                .collect(),
-        )
+        Ok(())
+    }
+
+    /// Like [`share`](Sharing::share), but reports why sharing failed,
+    /// including failures of the random number generator.
+    ///
+    /// ```rust
+    /// use rand::{rngs::mock::StepRng, Error, RngCore};
+    /// use sharing::{ShamirSecretSharing, SharingError};
+    ///
+    /// struct Unavailable;
+    ///
+    /// impl RngCore for Unavailable {
+    ///     fn next_u32(&mut self) -> u32 { unimplemented!() }
+    ///     fn next_u64(&mut self) -> u64 { unimplemented!() }
+    ///     fn fill_bytes(&mut self, _: &mut [u8]) { unimplemented!() }
+    ///     fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), Error> {
+    ///         Err(Error::new("entropy source not ready"))
+    ///     }
+    /// }
+    ///
+    /// let sharer = ShamirSecretSharing::new(3, 2, Unavailable);
+    /// assert!(matches!(sharer.try_share(vec![1, 2, 3]), Err(SharingError::Rng(_))));
+    ///
+    /// let sharer = ShamirSecretSharing::new(2, 3, StepRng::new(0, 1));
+    /// assert!(matches!(sharer.try_share(vec![1]), Err(SharingError::InvalidParameters)));
+    /// ```
+    pub fn try_share(&self, data: Vec<u8>) -> Result<Vec<ShamirShare>, SharingError> {
+        if self.k < 1 || self.k > self.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        self.share_at(data, &self.ids()?)
+    }
+
+    /// Shares the data at the given ids instead of `1..=n`, e.g. to match the ids of another system.
+    ///
+    /// One share is returned per id. Fails with [`SharingError::InvalidParameters`] if an id is
+    /// zero, [`SharingError::DuplicateShareId`] if one is repeated and
+    /// [`SharingError::MismatchedParameters`] if there are fewer than `k`.
+    ///
+    /// ```rust
+    /// use sharing::{ShamirSecretSharing, Sharing, SharingError};
+    ///
+    /// let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+    /// let shares = sharer.share_with_ids(vec![1, 2, 3], &[17, 42, 200]).unwrap();
+    /// assert_eq!(shares[1].id, 42);
+    /// assert_eq!(sharer.reconstruct(shares[1..].to_vec()).unwrap(), vec![1, 2, 3]);
+    ///
+    /// assert!(matches!(
+    ///     sharer.share_with_ids(vec![1], &[0, 1, 2]),
+    ///     Err(SharingError::InvalidParameters)
+    /// ));
+    /// assert!(matches!(
+    ///     sharer.share_with_ids(vec![1], &[5, 5, 6]),
+    ///     Err(SharingError::DuplicateShareId(5))
+    /// ));
+    /// ```
+    pub fn share_with_ids(
+        &self,
+        data: Vec<u8>,
+        ids: &[u8],
+    ) -> Result<Vec<ShamirShare>, SharingError> {
+        check_ids(ids)?;
+        self.share_at(data, ids)
+    }
+
+    /// Shares the data with coefficients, and random ids if enabled, drawn from an [`HmacDrbg`]
+    /// seeded with `key`, the data and `context`, so the same inputs always give the same shares.
+    ///
+    /// See [`drbg`](crate::drbg) for the requirements on `key` and `context`.
+    pub fn share_deterministic(
+        &self,
+        data: Vec<u8>,
+        key: &[u8],
+        context: &[u8],
+    ) -> Result<Vec<ShamirShare>, SharingError> {
+        if self.k < 1 || self.k > self.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        let mut drbg = HmacDrbg::new(key, &data, context);
+        let ids = self.ids_from(&mut drbg)?;
+        self.share_at_with(data, &ids, &mut drbg)
+    }
+
+    /// Deals only the shares of the participants in `ids`, from the same polynomials as
+    /// [`share_deterministic`](Self::share_deterministic) with the same `key` and `context`,
+    /// so shares of absent participants are never generated and need not be destroyed.
+    /// They can be dealt later by calling this again with their ids.
+    ///
+    /// Fails like [`share_with_ids`](Self::share_with_ids) for a zero or repeated id,
+    /// but unlike the other methods fewer than `k` ids are allowed.
+    ///
+    /// ```rust
+    /// use sharing::{ShamirSecretSharing, Sharing};
+    ///
+    /// let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+    /// let key = [9u8; 32];
+    ///
+    /// let present = sharer.share_for_subset(vec![1, 2, 3], &key, b"vault", &[2, 5]).unwrap();
+    /// let all = sharer.share_deterministic(vec![1, 2, 3], &key, b"vault").unwrap();
+    /// assert_eq!(present, vec![all[1].clone(), all[4].clone()]);
+    ///
+    /// // the third holder arrives later
+    /// let late = sharer.share_for_subset(vec![1, 2, 3], &key, b"vault", &[3]).unwrap();
+    /// let shares = vec![present[0].clone(), late[0].clone(), present[1].clone()];
+    /// assert_eq!(sharer.reconstruct(shares).unwrap(), vec![1, 2, 3]);
+    /// ```
+    pub fn share_for_subset(
+        &self,
+        data: Vec<u8>,
+        key: &[u8],
+        context: &[u8],
+        ids: &[u8],
+    ) -> Result<Vec<ShamirShare>, SharingError> {
+        if self.k > self.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        check_ids(ids)?;
+        let mut drbg = HmacDrbg::new(key, &data, context);
+        // draw the dealing's ids to stay in step with `share_deterministic`
+        self.ids_from(&mut drbg)?;
+        self.share_at_with(data, ids, &mut drbg)
+    }
+
+    /// Shares the data and returns a [`Commitment`] for every share, for the dealer to publish,
+    /// and its [`Opening`], to hand to the holder with the share.
+    pub fn share_with_commitments(&self, data: Vec<u8>) -> Result<CommittedShares, SharingError> {
+        let shares = self.try_share(data)?;
+        let (openings, commitments) = self.commit(&shares)?;
+        Ok((shares, openings, commitments))
+    }
+
+    /// Commits to every share with a fresh salt.
+    pub(crate) fn commit(
+        &self,
+        shares: &[ShamirShare],
+    ) -> Result<(Vec<Opening>, Vec<Commitment>), SharingError> {
+        let mut rng = self.rng.borrow_mut();
+        let mut openings = Vec::with_capacity(shares.len());
+        let mut commitments = Vec::with_capacity(shares.len());
+        for share in shares {
+            let (commitment, opening) = commit(share, &mut *rng)?;
+            openings.push(opening);
+            commitments.push(commitment);
+        }
+        Ok((openings, commitments))
+    }
+
+    /// Draws the polynomials for `data` and keeps them, so shares can be minted at any id later,
+    /// see [`dealing`](crate::dealing).
+    pub fn dealing(&self, mut data: Vec<u8>) -> Result<Dealing, SharingError> {
+        if self.k < 1 || self.placement != Placement::STANDARD {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let k = self.k as usize;
+        let mut coefficients = secret_bytes(data.len() * k);
+        let filled = try_fill(&mut *self.rng.borrow_mut(), &mut coefficients);
+        for (polynomial, byte) in coefficients.chunks_mut(k).zip(&data) {
+            polynomial[0] = *byte;
+        }
+        wipe(&mut data);
+        if let Err(error) = filled {
+            wipe(&mut coefficients);
+            return Err(error);
+        }
+        Ok(Dealing::new(self.field.clone(), self.k, coefficients))
+    }
+
+    /// Creates a decoy for every share: same id and length, but a random body,
+    /// so it looks like a real share to anyone without the dealer's commitments.
+    ///
+    /// Decoys never match a [`Commitment`], see [`separate`](crate::commitment::separate).
+    ///
+    /// ```rust
+    /// use sharing::{commitment, ShamirSecretSharing};
+    ///
+    /// let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+    /// let (shares, openings, commitments) = sharer.share_with_commitments(vec![1, 2, 3]).unwrap();
+    /// let decoys = sharer.decoys(&shares).unwrap();
+    /// assert_eq!((decoys[0].id, decoys[0].body.len()), (shares[0].id, 3));
+    ///
+    /// let stash = vec![decoys[0].clone(), shares[0].clone(), decoys[1].clone()];
+    /// let (genuine, decoys) = commitment::separate(stash, &openings, &commitments);
+    /// assert_eq!(genuine, vec![shares[0].clone()]);
+    /// assert_eq!(decoys.len(), 2);
+    /// ```
+    pub fn decoys(&self, shares: &[ShamirShare]) -> Result<Vec<ShamirShare>, SharingError> {
+        let mut rng = self.rng.borrow_mut();
+        shares
+            .iter()
+            .map(|share| {
+                let mut body = vec![0u8; share.body.len()];
+                try_fill(&mut *rng, &mut body)?;
+                Ok(ShamirShare { id: share.id, body })
+            })
+            .collect()
     }
 }
 
-use std::marker::PhantomData;
+impl<R: Rng> Sharing for ShamirSecretSharing<R> {
+    type Share = ShamirShare;
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError> {
+        self.try_share(data)
+    }
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        self.combiner().reconstruct(shares)
+    }
+
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        self.combiner().reconstruct_ref(shares)
+    }
+
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<Self::Share>) -> Result<(), SharingError> {
+        if self.k < 1 || self.k > self.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        let ids = self.ids()?;
+        self.share_at_into(data, &ids, &mut *self.rng.borrow_mut(), out)
+    }
+
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        self.combiner().reconstruct_into(shares, out)
+    }
+
+    #[cfg(feature = "secure-memory")]
+    fn reconstruct_secure_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut SecretBuffer,
+    ) -> Result<(), SharingError> {
+        self.combiner().reconstruct_secure_into(shares, out)
+    }
+}
 
 /// # Krawczyk Secret Sharing
 ///
+/// The data is encrypted with a fresh random key under any AEAD cipher,
+/// dispersed with Rabin's IDA, and the key is shared with Shamir's scheme.
+///
 /// ```rust
+/// use chacha20poly1305::ChaCha20Poly1305;
 /// use sharing::{KrawczykSecretSharing, Sharing};
 ///
 /// let data = [1, 2, 3, 4, 5].to_vec();
 ///
-/// let sharer = KrawczykSecretSharing::<chacha20::ChaCha20, _>::new(5, 3, rand::thread_rng());
+/// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(5, 3, rand::thread_rng());
 ///
 /// let shares = sharer.share(data.clone()).unwrap();
 /// // You only need 3 out of the 5 shares to reconstruct
-/// let rec = sharer.recontruct(shares[1..=3].to_vec()).unwrap();
+/// let rec = sharer.reconstruct(shares[1..=3].to_vec()).unwrap();
 ///
 /// assert_eq!(data, rec);
 /// ```
-pub struct KrawczykSecretSharing<C: StreamCipher + NewStreamCipher, R: Rng> {
-    rng: RefCell<R>,
-    shamir: ShamirSecretSharing<R>,
-    rabin: RabinInformationDispersal,
+#[cfg(feature = "krawczyk")]
+pub struct KrawczykSecretSharing<C: AeadInPlace + KeyInit, R: Rng> {
+    pub(crate) shamir: ShamirSecretSharing<R>,
+    pub(crate) rabin: RabinInformationDispersal,
+    compression: Compression,
     phantom: PhantomData<C>,
 }
 
-impl<R: Rng + Clone, C: StreamCipher + NewStreamCipher> KrawczykSecretSharing<C, R> {
+#[cfg(feature = "krawczyk")]
+impl<R: Rng, C: AeadInPlace + KeyInit> KrawczykSecretSharing<C, R> {
+    /// The parameters are checked when sharing, see [`try_new`](Self::try_new)
+    /// to check them up front.
     pub fn new(n: u8, k: u8, rng: R) -> Self {
         Self {
-            rng: RefCell::new(rng.clone()),
             shamir: ShamirSecretSharing::new(n, k, rng),
             rabin: RabinInformationDispersal::new(n, k),
+            compression: Compression::None,
             phantom: PhantomData,
         }
     }
-}
 
-impl<R: Rng, C: StreamCipher + NewStreamCipher> Sharing for KrawczykSecretSharing<C, R> {
-    type Share = KrawczykShare;
-    fn share(&self, data: Vec<u8>) -> Option<Vec<Self::Share>> {
-        let length = data.len();
-        let key_nonce = {
-            let mut rand = [0u8; 44];
-            self.rng.borrow_mut().fill(&mut rand[..]);
-            rand
-        };
-        let mut cipher = C::new_var(&key_nonce[0..32], &key_nonce[32..44]).expect("Use ChaCha20 Stream Cipher");
-        let mut data = data;
-        cipher.encrypt(&mut data);
-        let shares = self.rabin.share(data)?;
-
-        let key_nonce_shares = self.shamir.share(key_nonce.to_vec())?;
-
-        Some(
-            shares
-                .into_iter()
-                .zip(key_nonce_shares)
-                .map(|(r, s)| KrawczykShare {
-                    id: r.id,
-                    length,
-                    key: {
-                        let mut a = [0u8; 44];
-                        a.copy_from_slice(&s.body[0..44]);
-                        a
-                    },
+    /// Like [`new`](Self::new), but fails with [`SharingError::InvalidParameters`]
+    /// unless `1 <= k <= n`.
+    pub fn try_new(n: u8, k: u8, rng: R) -> Result<Self, SharingError> {
+        Parameters::new(n, k).ok_or(SharingError::InvalidParameters)?;
+        Ok(Self::new(n, k, rng))
+    }
+
+    /// Compress the data before encrypting it, sharing fails if the codec is not compiled in.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Give every share a random distinct nonzero id instead of `1..=n`.
+    pub fn with_random_ids(mut self) -> Self {
+        self.shamir = self.shamir.with_random_ids();
+        self
+    }
+
+    /// Use a different GF(256) reduction polynomial for the key and data shares.
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.rabin = self.rabin.with_field(field.clone());
+        self.shamir = self.shamir.with_field(field);
+        self
+    }
+
+    /// The multiplication used for the key and data shares, [`Backend::detect`] by default.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.rabin = self.rabin.with_backend(backend);
+        self.shamir = self.shamir.with_backend(backend);
+        self
+    }
+
+    /// Encrypts and disperses many files under a single shared key,
+    /// so one key ceremony protects a whole backup.
+    ///
+    /// Every holder gets one share with its key share and a data share for every file.
+    ///
+    /// ```rust
+    /// use chacha20poly1305::ChaCha20Poly1305;
+    /// use sharing::{KrawczykSecretSharing, SharingError};
+    ///
+    /// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(5, 3, rand::thread_rng());
+    /// let files = vec![b"first".to_vec(), b"second".to_vec()];
+    ///
+    /// let shares = sharer.share_batch(files.clone()).unwrap();
+    /// assert_eq!(sharer.reconstruct_batch(shares[..3].to_vec()).unwrap(), files);
+    /// assert_eq!(sharer.reconstruct_file(shares[2..].to_vec(), 1).unwrap(), b"second");
+    /// assert!(matches!(
+    ///     sharer.reconstruct_file(shares[2..].to_vec(), 2),
+    ///     Err(SharingError::InvalidParameters)
+    /// ));
+    /// ```
+    pub fn share_batch(
+        &self,
+        files: Vec<Vec<u8>>,
+    ) -> Result<Vec<KrawczykBatchShare>, SharingError> {
+        if self.shamir.k < 1 || self.shamir.k > self.shamir.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        let key = self.key()?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::MismatchedParameters)?;
+        let ids = self.shamir.ids()?;
+        let mut shares: Vec<KrawczykBatchShare> = ids
+            .iter()
+            .map(|id| KrawczykBatchShare {
+                id: *id,
+                key: Vec::new(),
+                compression: self.compression,
+                files: Vec::with_capacity(files.len()),
+            })
+            .collect();
+        for (index, file) in files.into_iter().enumerate() {
+            let data = Self::nonce(index as u64)
+                .and_then(|nonce| self.seal(&cipher, &nonce, &[], file))
+                .ok_or(SharingError::MismatchedParameters)?;
+            let digest = ciphertext_digest(&data);
+            let dispersed = self.rabin.share_at(data, &ids)?;
+            for (share, r) in shares.iter_mut().zip(dispersed) {
+                share.files.push(BatchFile {
+                    length: r.length,
+                    digest,
                     body: r.body,
+                });
+            }
+        }
+        for (share, s) in shares.iter_mut().zip(self.shamir.share_at(key, &ids)?) {
+            share.key = s.body;
+        }
+        Ok(shares)
+    }
+
+    /// Reconstructs every file of a batch.
+    ///
+    /// Fails with [`SharingError::MismatchedParameters`] if the shares hold different files.
+    pub fn reconstruct_batch(
+        &self,
+        shares: Vec<KrawczykBatchShare>,
+    ) -> Result<Vec<Vec<u8>>, SharingError> {
+        let cipher = self.batch_cipher(&shares)?;
+        let count = shares[0].files.len();
+        if shares.iter().any(|s| s.files.len() != count) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        (0..count)
+            .map(|index| self.open_file(&cipher, &shares, index))
+            .collect()
+    }
+
+    /// Reconstructs only the file at `index` of a batch.
+    ///
+    /// Fails with [`SharingError::InvalidParameters`] if a share holds no file at `index`.
+    pub fn reconstruct_file(
+        &self,
+        shares: Vec<KrawczykBatchShare>,
+        index: usize,
+    ) -> Result<Vec<u8>, SharingError> {
+        if shares.iter().any(|s| s.files.len() <= index) {
+            return Err(SharingError::InvalidParameters);
+        }
+        let cipher = self.batch_cipher(&shares)?;
+        self.open_file(&cipher, &shares, index)
+    }
+
+    /// A fresh random key.
+    pub(crate) fn key(&self) -> Result<SecretBytes, SharingError> {
+        let mut key = secret_bytes(C::KeySize::USIZE);
+        // share the Shamir RNG so the key never repeats the polynomial coefficients
+        try_fill(&mut *self.shamir.rng.borrow_mut(), &mut key)?;
+        Ok(key)
+    }
+
+    /// The big endian `index`, `None` if the cipher's nonce is too short to hold it.
+    fn nonce(index: u64) -> Option<Nonce<C>> {
+        let mut nonce = Nonce::<C>::default();
+        let start = nonce.len().checked_sub(8)?;
+        nonce[start..].copy_from_slice(&index.to_be_bytes());
+        Some(nonce)
+    }
+
+    /// Compresses and encrypts `data`, with the tag appended.
+    fn seal(
+        &self,
+        cipher: &C,
+        nonce: &Nonce<C>,
+        associated_data: &[u8],
+        data: Vec<u8>,
+    ) -> Option<Vec<u8>> {
+        let mut data = self.compression.compress(data)?;
+        let associated_data = self.compression.associated_data(associated_data);
+        let tag = cipher
+            .encrypt_in_place_detached(nonce, &associated_data, &mut data)
+            .ok()?;
+        data.extend_from_slice(&tag);
+        Some(data)
+    }
+
+    /// The batch key, from at least one share with a single compression.
+    fn batch_cipher(&self, shares: &[KrawczykBatchShare]) -> Result<C, SharingError> {
+        let compression = shares
+            .first()
+            .ok_or_else(|| SharingError::reconstruction(self.shamir.k, 0))?
+            .compression;
+        if shares.iter().any(|s| s.compression != compression) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let key_shares: Vec<ShamirShare> = shares
+            .iter()
+            .map(|s| ShamirShare {
+                id: s.id,
+                body: s.key.clone(),
+            })
+            .collect();
+        let mut key = reconstruct_key(&self.shamir.combiner(), &key_shares)?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::ReconstructionFailed);
+        wipe(&mut key);
+        cipher
+    }
+
+    fn open_file(
+        &self,
+        cipher: &C,
+        shares: &[KrawczykBatchShare],
+        index: usize,
+    ) -> Result<Vec<u8>, SharingError> {
+        let digest = shares[0].files[index].digest;
+        if shares.iter().any(|s| s.files[index].digest != digest) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let data = self.rabin.reconstruct(
+            shares
+                .iter()
+                .map(|s| RabinShare {
+                    id: s.id,
+                    length: s.files[index].length,
+                    body: s.files[index].body.clone(),
                 })
                 .collect(),
-        )
+        )?;
+        if ciphertext_digest(&data) != digest {
+            return Err(SharingError::ReconstructionFailed);
+        }
+        let nonce = Self::nonce(index as u64).ok_or(SharingError::MismatchedParameters)?;
+        open(cipher, &nonce, shares[0].compression, &[], data)
+            .ok_or(SharingError::ReconstructionFailed)
+    }
+}
+
+#[cfg(feature = "krawczyk")]
+impl<R: Rng, C: AeadInPlace + KeyInit> Sharing for KrawczykSecretSharing<C, R> {
+    type Share = KrawczykShare;
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError> {
+        self.try_share(data)
+    }
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        self.combiner().reconstruct(shares)
+    }
+
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        self.combiner().reconstruct_ref(shares)
+    }
+
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<Self::Share>) -> Result<(), SharingError> {
+        self.try_share_into(data, out)
+    }
+
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        self.combiner().reconstruct_into(shares, out)
+    }
+
+    #[cfg(feature = "secure-memory")]
+    fn reconstruct_secure_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut SecretBuffer,
+    ) -> Result<(), SharingError> {
+        self.combiner().reconstruct_secure_into(shares, out)
+    }
+}
+
+/// Reconstructs Krawczyk shares without any random state,
+/// for programs that never deal.
+#[cfg(feature = "krawczyk")]
+pub struct KrawczykCombiner<C> {
+    shamir: Combiner,
+    rabin: RabinInformationDispersal,
+    phantom: PhantomData<C>,
+}
+
+#[cfg(feature = "krawczyk")]
+impl<C: AeadInPlace + KeyInit> KrawczykCombiner<C> {
+    pub fn new(k: u8) -> Self {
+        Self {
+            shamir: Combiner::new(k),
+            rabin: RabinInformationDispersal::new(k, k),
+            phantom: PhantomData,
+        }
+    }
+
+    /// The GF(256) reduction polynomial the shares were dealt with.
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.rabin = self.rabin.with_field(field.clone());
+        self.shamir = self.shamir.with_field(field);
+        self
+    }
+
+    /// The multiplication used for the key and data, [`Backend::detect`] by default.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.rabin = self.rabin.with_backend(backend);
+        self.shamir = self.shamir.with_backend(backend);
+        self
+    }
+
+    pub fn k(&self) -> u8 {
+        self.shamir.k()
+    }
+
+    pub fn reconstruct(&self, shares: Vec<KrawczykShare>) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct_ref(&shares)
+    }
+
+    /// The old, misspelled name of [`reconstruct`](Self::reconstruct).
+    #[deprecated(since = "0.2.0", note = "renamed to `reconstruct`")]
+    pub fn recontruct(&self, shares: Vec<KrawczykShare>) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct(shares)
     }
 
-    fn recontruct(&self, shares: Vec<Self::Share>) -> Option<Vec<u8>> {
-        let (shamir_shares, rabin_shares): (Vec<_>, Vec<_>) = shares
-            .into_iter()
-            .map(|s| {
-                (
-                    ShamirShare {
-                        id: s.id,
-                        body: s.key.to_vec(),
-                    },
-                    RabinShare {
-                        id: s.id,
-                        length: s.length,
-                        body: s.body,
-                    },
-                )
+    /// Reconstructs from borrowed shares, only the small key shares are copied.
+    pub fn reconstruct_ref(&self, shares: &[KrawczykShare]) -> Result<Vec<u8>, SharingError> {
+        let mut data = Vec::new();
+        self.reconstruct_into(shares, &mut data)?;
+        Ok(data)
+    }
+
+    /// Like [`reconstruct_ref`](Self::reconstruct_ref), but decodes and decrypts the data in
+    /// `out`, reusing its capacity unless the data was compressed.
+    pub fn reconstruct_into(
+        &self,
+        shares: &[KrawczykShare],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        let mut combined = self.combine(shares, std::mem::take(out))?;
+        let cipher = C::new_from_slice(&combined.shared).ok();
+        wipe(&mut combined.shared);
+        *out = cipher
+            .and_then(|cipher| combined.open(&cipher))
+            .ok_or(SharingError::ReconstructionFailed)?;
+        Ok(())
+    }
+
+    /// Like [`reconstruct_ref`](Self::reconstruct_ref), but decrypts the data in locked memory.
+    /// Available with the `secure-memory` feature.
+    #[cfg(feature = "secure-memory")]
+    pub fn reconstruct_secure_into(
+        &self,
+        shares: &[KrawczykShare],
+        out: &mut SecretBuffer,
+    ) -> Result<(), SharingError> {
+        let combined = self.combine(shares, Vec::new())?;
+        let data = C::new_from_slice(&combined.shared)
+            .ok()
+            .and_then(|cipher| combined.open_secure(&cipher))
+            .ok_or(SharingError::ReconstructionFailed)?;
+        *out = data;
+        Ok(())
+    }
+
+    /// Recovers the Shamir shared value and the verified ciphertext, decoded into `data`.
+    fn combine(
+        &self,
+        shares: &[KrawczykShare],
+        mut data: Vec<u8>,
+    ) -> Result<Combined, SharingError> {
+        let shares = dedup_shares(shares.iter().collect())?;
+        let first = shares
+            .first()
+            .ok_or_else(|| SharingError::reconstruction(self.k(), 0))?;
+        let compression = first.compression;
+        let digest = first.digest;
+        let associated_data = first.associated_data.clone();
+        if shares.iter().any(|s| {
+            s.compression != compression
+                || s.digest != digest
+                || s.associated_data != associated_data
+        }) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let key_shares: Vec<ShamirShare> = shares
+            .iter()
+            .map(|s| ShamirShare {
+                id: s.id,
+                body: s.key.clone(),
             })
-            .unzip();
-        let key_nonce = self.shamir.recontruct(shamir_shares)?;
-        let mut data = self.rabin.recontruct(rabin_shares)?;
-        let mut cypher = C::new_var(&key_nonce[0..32], &key_nonce[32..44]).expect("Use ChaCha20 Stream Cipher");
-        cypher.decrypt(&mut data);
-        Some(data)
+            .collect();
+        let mut shared = reconstruct_key(&self.shamir, &key_shares)?;
+        if let Err(e) = self.rabin.decode_into(&shares, &mut data) {
+            wipe(&mut shared);
+            return Err(e);
+        }
+        // catch corrupted data shares before decrypting
+        if ciphertext_digest(&data) != digest {
+            wipe(&mut shared);
+            return Err(SharingError::ReconstructionFailed);
+        }
+        Ok(Combined {
+            shared,
+            data,
+            compression,
+            associated_data,
+        })
+    }
+}
+
+#[cfg(feature = "krawczyk")]
+impl<C: AeadInPlace + KeyInit> crate::split::Reconstructor for KrawczykCombiner<C> {
+    type Share = KrawczykShare;
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        KrawczykCombiner::reconstruct(self, shares)
+    }
+
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        KrawczykCombiner::reconstruct_ref(self, shares)
+    }
+
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        KrawczykCombiner::reconstruct_into(self, shares, out)
+    }
+}
+
+#[cfg(feature = "krawczyk")]
+impl<R: Rng, C: AeadInPlace + KeyInit> KrawczykSecretSharing<C, R> {
+    /// Binds `associated_data`, e.g. a file name, version or purpose, to the shares.
+    /// It is stored in every share and authenticated with the ciphertext,
+    /// so shares cannot be relabeled or moved to another context unnoticed.
+    ///
+    /// ```rust
+    /// use chacha20poly1305::ChaCha20Poly1305;
+    /// use sharing::{KrawczykSecretSharing, Sharing};
+    ///
+    /// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+    ///
+    /// let mut shares = sharer
+    ///     .share_with_associated_data(vec![1, 2, 3], b"backup.tar v2".to_vec())
+    ///     .unwrap();
+    /// assert_eq!(shares[0].associated_data, b"backup.tar v2");
+    /// assert_eq!(sharer.reconstruct(shares[..2].to_vec()).unwrap(), vec![1, 2, 3]);
+    ///
+    /// for share in shares.iter_mut() {
+    ///     share.associated_data = b"other.tar v2".to_vec();
+    /// }
+    /// assert!(sharer.reconstruct(shares[..2].to_vec()).is_err());
+    /// ```
+    pub fn share_with_associated_data(
+        &self,
+        data: Vec<u8>,
+        associated_data: Vec<u8>,
+    ) -> Result<Vec<KrawczykShare>, SharingError> {
+        if self.shamir.k < 1 || self.shamir.k > self.shamir.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        let key = self.key()?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::MismatchedParameters)?;
+        let ids = self.shamir.ids()?;
+        self.deal(&cipher, key, associated_data, data, &ids)
+    }
+
+    /// Shares the data at the given ids instead of `1..=n`,
+    /// failing like [`ShamirSecretSharing::share_with_ids`] for ids that cannot be used.
+    ///
+    /// ```rust
+    /// use chacha20poly1305::ChaCha20Poly1305;
+    /// use sharing::{KrawczykSecretSharing, Sharing};
+    ///
+    /// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+    /// let shares = sharer.share_with_ids(vec![1, 2, 3], &[9, 3, 250, 7]).unwrap();
+    /// assert_eq!(shares.len(), 4);
+    /// assert_eq!(sharer.reconstruct(shares[2..].to_vec()).unwrap(), vec![1, 2, 3]);
+    /// ```
+    pub fn share_with_ids(
+        &self,
+        data: Vec<u8>,
+        ids: &[u8],
+    ) -> Result<Vec<KrawczykShare>, SharingError> {
+        if self.shamir.k < 1 {
+            return Err(SharingError::InvalidParameters);
+        }
+        check_ids(ids)?;
+        let key = self.key()?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::MismatchedParameters)?;
+        self.deal(&cipher, key, Vec::new(), data, ids)
+    }
+
+    /// Like [`share`](Sharing::share), but reports why sharing failed,
+    /// including failures of the random number generator.
+    pub fn try_share(&self, data: Vec<u8>) -> Result<Vec<KrawczykShare>, SharingError> {
+        let mut out = Vec::new();
+        self.try_share_into(data, &mut out)?;
+        Ok(out)
+    }
+
+    /// Like [`try_share`](Self::try_share), but overwrites the shares in `out`,
+    /// reusing their key and data bodies.
+    fn try_share_into(
+        &self,
+        data: Vec<u8>,
+        out: &mut Vec<KrawczykShare>,
+    ) -> Result<(), SharingError> {
+        if self.shamir.k < 1 || self.shamir.k > self.shamir.n {
+            return Err(SharingError::InvalidParameters);
+        }
+        let key = self.key()?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::MismatchedParameters)?;
+        let ids = self.shamir.ids()?;
+        self.deal_into(&cipher, key, Vec::new(), data, &ids, out)
+    }
+
+    /// Encrypts `data` under `cipher`, disperses it and Shamir shares `shared`.
+    fn deal<D: AsMut<[u8]>>(
+        &self,
+        cipher: &C,
+        shared: D,
+        associated_data: Vec<u8>,
+        data: Vec<u8>,
+        ids: &[u8],
+    ) -> Result<Vec<KrawczykShare>, SharingError> {
+        let mut out = Vec::with_capacity(ids.len());
+        self.deal_into(cipher, shared, associated_data, data, ids, &mut out)?;
+        Ok(out)
     }
+
+    /// Like `deal`, but overwrites the shares in `out`, reusing their key and data bodies.
+    fn deal_into<D: AsMut<[u8]>>(
+        &self,
+        cipher: &C,
+        shared: D,
+        associated_data: Vec<u8>,
+        data: Vec<u8>,
+        ids: &[u8],
+        out: &mut Vec<KrawczykShare>,
+    ) -> Result<(), SharingError> {
+        // every key encrypts a single message, so a fixed nonce never repeats
+        let data = self
+            .seal(cipher, &Nonce::<C>::default(), &associated_data, data)
+            .ok_or(SharingError::MismatchedParameters)?;
+
+        let length = data.len();
+        let digest = ciphertext_digest(&data);
+        out.truncate(ids.len());
+        out.resize_with(ids.len(), || KrawczykShare::with_size(0));
+        let mut dispersed: Vec<RabinShare> = out
+            .iter_mut()
+            .map(|s| RabinShare {
+                id: s.id,
+                length: 0,
+                body: std::mem::take(&mut s.body),
+            })
+            .collect();
+        self.rabin.share_at_into(&data, ids, &mut dispersed)?;
+        let mut key_shares: Vec<ShamirShare> = out
+            .iter_mut()
+            .map(|s| ShamirShare {
+                id: s.id,
+                body: std::mem::take(&mut s.key),
+            })
+            .collect();
+        self.shamir.share_at_into(
+            shared,
+            ids,
+            &mut *self.shamir.rng.borrow_mut(),
+            &mut key_shares,
+        )?;
+
+        for ((share, r), s) in out.iter_mut().zip(dispersed).zip(key_shares) {
+            *share = KrawczykShare {
+                id: r.id,
+                length,
+                key: s.body,
+                compression: self.compression,
+                associated_data: associated_data.clone(),
+                digest,
+                body: r.body,
+            };
+        }
+        Ok(())
+    }
+
+    /// The combiner for the shares of this sharer, without the random state.
+    pub fn combiner(&self) -> KrawczykCombiner<C> {
+        KrawczykCombiner {
+            shamir: self.shamir.combiner(),
+            rabin: self.rabin.clone(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Derives the key from `passphrase` with Argon2id and only shares a random salt,
+    /// so reconstructing needs both `k` shares and the passphrase.
+    ///
+    /// Available with the `passphrase` feature. The key shares of the result hold the salt.
+    /// The data is sealed with a fixed nonce, which is safe only because every fresh salt
+    /// gives a fresh key, see [`share_with_passphrase_and_salt`](Self::share_with_passphrase_and_salt).
+    ///
+    /// ```rust
+    /// use chacha20poly1305::ChaCha20Poly1305;
+    /// use sharing::KrawczykSecretSharing;
+    ///
+    /// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(5, 3, rand::thread_rng());
+    ///
+    /// let shares = sharer.share_with_passphrase(vec![1, 2, 3], b"correct horse").unwrap();
+    /// let rec = sharer.reconstruct_with_passphrase(shares[..3].to_vec(), b"correct horse");
+    /// assert_eq!(rec.unwrap(), vec![1, 2, 3]);
+    /// assert!(sharer.reconstruct_with_passphrase(shares[..3].to_vec(), b"wrong").is_err());
+    /// ```
+    #[cfg(feature = "passphrase")]
+    pub fn share_with_passphrase(
+        &self,
+        data: Vec<u8>,
+        passphrase: &[u8],
+    ) -> Result<Vec<KrawczykShare>, SharingError> {
+        let mut salt = vec![0u8; PASSPHRASE_SALT_LEN];
+        try_fill(&mut *self.shamir.rng.borrow_mut(), &mut salt)?;
+        self.share_with_passphrase_and_salt(data, passphrase, &salt)
+    }
+
+    /// Like [`share_with_passphrase`](Self::share_with_passphrase), but with the caller's salt
+    /// instead of a random one, so sharing the same data again gives the same key and data
+    /// shares, only the Shamir shares of the salt are drawn anew.
+    ///
+    /// The data is sealed with a fixed nonce, so a salt must never be used for different data
+    /// under the same passphrase: both would be encrypted with the same key and nonce.
+    /// Fails with [`SharingError::InvalidParameters`] for a salt shorter than 8 bytes.
+    ///
+    /// ```rust
+    /// use chacha20poly1305::ChaCha20Poly1305;
+    /// use sharing::KrawczykSecretSharing;
+    ///
+    /// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(5, 3, rand::thread_rng());
+    /// let salt = b"backup/2026 host-a";
+    ///
+    /// let first = sharer.share_with_passphrase_and_salt(vec![1, 2, 3], b"pass", salt).unwrap();
+    /// let again = sharer.share_with_passphrase_and_salt(vec![1, 2, 3], b"pass", salt).unwrap();
+    /// assert_eq!(first[0].body, again[0].body);
+    ///
+    /// let rec = sharer.reconstruct_with_passphrase(again[2..].to_vec(), b"pass");
+    /// assert_eq!(rec.unwrap(), vec![1, 2, 3]);
+    /// assert!(sharer.share_with_passphrase_and_salt(vec![1], b"pass", b"short").is_err());
+    /// ```
+    #[cfg(feature = "passphrase")]
+    pub fn share_with_passphrase_and_salt(
+        &self,
+        data: Vec<u8>,
+        passphrase: &[u8],
+        salt: &[u8],
+    ) -> Result<Vec<KrawczykShare>, SharingError> {
+        if self.shamir.k < 1 || self.shamir.k > self.shamir.n || salt.len() < argon2::MIN_SALT_LEN {
+            return Err(SharingError::InvalidParameters);
+        }
+        let cipher = Self::passphrase_cipher(passphrase, salt)?;
+        let ids = self.shamir.ids()?;
+        self.deal(&cipher, salt.to_vec(), Vec::new(), data, &ids)
+    }
+
+    /// Reverses [`share_with_passphrase`](Self::share_with_passphrase),
+    /// [`SharingError::ReconstructionFailed`] if the passphrase is wrong.
+    #[cfg(feature = "passphrase")]
+    pub fn reconstruct_with_passphrase(
+        &self,
+        shares: Vec<KrawczykShare>,
+        passphrase: &[u8],
+    ) -> Result<Vec<u8>, SharingError> {
+        let combined = self.combiner().combine(&shares, Vec::new())?;
+        let cipher = Self::passphrase_cipher(passphrase, &combined.shared)?;
+        combined
+            .open(&cipher)
+            .ok_or(SharingError::ReconstructionFailed)
+    }
+
+    /// The cipher keyed with Argon2id of `passphrase` and `salt`, a salt of the wrong size
+    /// does not fit these parameters.
+    #[cfg(feature = "passphrase")]
+    fn passphrase_cipher(passphrase: &[u8], salt: &[u8]) -> Result<C, SharingError> {
+        let mut key = secret_bytes(C::KeySize::USIZE);
+        if argon2::Argon2::default()
+            .hash_password_into(passphrase, salt, &mut key)
+            .is_err()
+        {
+            wipe(&mut key);
+            return Err(SharingError::MismatchedParameters);
+        }
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::MismatchedParameters);
+        wipe(&mut key);
+        cipher
+    }
+}
+
+/// Krawczyk with AES-256-GCM, available with the `aes` feature.
+///
+/// AES uses the AES-NI and ARMv8 crypto instructions when the CPU supports them,
+/// which is usually faster than ChaCha20 on servers.
+///
+/// ```rust
+/// use sharing::{AesKrawczykSecretSharing, Sharing};
+///
+/// let sharer = AesKrawczykSecretSharing::new(5, 3, rand::thread_rng());
+///
+/// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+/// // every key share holds a 32 byte AES-256 key share
+/// assert_eq!(shares[0].key.len(), 32);
+/// assert_eq!(sharer.reconstruct(shares[2..].to_vec()).unwrap(), vec![1, 2, 3]);
+/// ```
+#[cfg(feature = "aes")]
+pub type AesKrawczykSecretSharing<R> = KrawczykSecretSharing<aes_gcm::Aes256Gcm, R>;
+
+/// Krawczyk with XChaCha20-Poly1305, available with the `xchacha` feature.
+///
+/// Key and nonce sizes come from the cipher, so the 24 byte extended nonce needs no special handling.
+///
+/// ```rust
+/// use sharing::{Sharing, XChaChaKrawczykSecretSharing};
+///
+/// let sharer = XChaChaKrawczykSecretSharing::new(4, 2, rand::thread_rng());
+///
+/// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+/// assert_eq!(sharer.reconstruct(shares[..2].to_vec()).unwrap(), vec![1, 2, 3]);
+/// ```
+#[cfg(feature = "xchacha")]
+pub type XChaChaKrawczykSecretSharing<R> =
+    KrawczykSecretSharing<chacha20poly1305::XChaCha20Poly1305, R>;
+
+/// What [`KrawczykCombiner::combine`] recovers from the shares.
+#[cfg(feature = "krawczyk")]
+struct Combined {
+    shared: SecretBytes,
+    data: Vec<u8>,
+    compression: Compression,
+    associated_data: Vec<u8>,
+}
+
+#[cfg(feature = "krawczyk")]
+impl Combined {
+    fn open<C: AeadInPlace + KeyInit>(self, cipher: &C) -> Option<Vec<u8>> {
+        open(
+            cipher,
+            &Nonce::<C>::default(),
+            self.compression,
+            &self.associated_data,
+            self.data,
+        )
+    }
+
+    /// Like `open`, but decrypts in locked memory.
+    #[cfg(feature = "secure-memory")]
+    fn open_secure<C: AeadInPlace + KeyInit>(self, cipher: &C) -> Option<SecretBuffer> {
+        let tag_start = self.data.len().checked_sub(C::TagSize::USIZE)?;
+        let tag = Tag::<C>::clone_from_slice(&self.data[tag_start..]);
+        let mut data = SecretBuffer::new(tag_start);
+        data.copy_from_slice(&self.data[..tag_start]);
+        let associated_data = self.compression.associated_data(&self.associated_data);
+        cipher
+            .decrypt_in_place_detached(&Nonce::<C>::default(), &associated_data, &mut data, &tag)
+            .ok()?;
+        match self.compression {
+            Compression::None => Some(data),
+            compression => compression
+                .decompress(data.to_vec())
+                .map(SecretBuffer::from_vec),
+        }
+    }
+}
+
+/// Reverses [`KrawczykSecretSharing::seal`], `None` if the data was tampered with.
+#[cfg(feature = "krawczyk")]
+fn open<C: AeadInPlace>(
+    cipher: &C,
+    nonce: &Nonce<C>,
+    compression: Compression,
+    associated_data: &[u8],
+    mut data: Vec<u8>,
+) -> Option<Vec<u8>> {
+    let tag_start = data.len().checked_sub(C::TagSize::USIZE)?;
+    let tag = Tag::<C>::clone_from_slice(&data[tag_start..]);
+    data.truncate(tag_start);
+    let associated_data = compression.associated_data(associated_data);
+    cipher
+        .decrypt_in_place_detached(nonce, &associated_data, &mut data, &tag)
+        .ok()?;
+    compression.decompress(data)
+}
+
+#[cfg(feature = "passphrase")]
+const PASSPHRASE_SALT_LEN: usize = 16;
+
+#[cfg(feature = "krawczyk")]
+fn ciphertext_digest(ciphertext: &[u8]) -> [u8; 32] {
+    Sha256::digest(ciphertext).into()
+}
+
+/// Fills `dest` with random bytes, reporting a failing generator instead of panicking.
+pub(crate) fn try_fill<G: Rng + ?Sized>(rng: &mut G, dest: &mut [u8]) -> Result<(), SharingError> {
+    rng.try_fill(dest)
+        .map_err(|error| SharingError::Rng(Box::new(error)))
+}
+
+/// A uniform index below `bound`, at most 256, by rejection sampling single bytes.
+fn uniform<G: Rng + ?Sized>(rng: &mut G, bound: usize) -> Result<usize, SharingError> {
+    let zone = 256 - 256 % bound;
+    loop {
+        let mut byte = [0u8];
+        try_fill(rng, &mut byte)?;
+        if (byte[0] as usize) < zone {
+            return Ok(byte[0] as usize % bound);
+        }
+    }
+}
+
+/// Overwrites secret material with the `secure-memory` feature, a no-op otherwise.
+#[cfg(feature = "secure-memory")]
+pub(crate) fn wipe(bytes: &mut [u8]) {
+    zeroize::Zeroize::zeroize(bytes);
+}
+
+#[cfg(not(feature = "secure-memory"))]
+pub(crate) fn wipe(_bytes: &mut [u8]) {}
+
+/// Keys and coefficients, in locked memory with the `secure-memory` feature.
+#[cfg(feature = "secure-memory")]
+pub(crate) type SecretBytes = SecretBuffer;
+#[cfg(not(feature = "secure-memory"))]
+pub(crate) type SecretBytes = Vec<u8>;
+
+/// `len` zeroed [`SecretBytes`].
+#[cfg(feature = "secure-memory")]
+pub(crate) fn secret_bytes(len: usize) -> SecretBytes {
+    SecretBuffer::new(len)
+}
+
+#[cfg(not(feature = "secure-memory"))]
+pub(crate) fn secret_bytes(len: usize) -> SecretBytes {
+    vec![0; len]
+}
+
+/// Reconstructs Shamir shared key material into [`SecretBytes`].
+#[cfg(feature = "krawczyk")]
+fn reconstruct_key(
+    combiner: &Combiner,
+    shares: &[ShamirShare],
+) -> Result<SecretBytes, SharingError> {
+    let mut key = secret_bytes(0);
+    #[cfg(feature = "secure-memory")]
+    combiner.reconstruct_secure_into(shares, &mut key)?;
+    #[cfg(not(feature = "secure-memory"))]
+    combiner.reconstruct_into(shares, &mut key)?;
+    Ok(key)
 }
@@ -0,0 +1,197 @@
+//! Locked Memory for Secrets
+//!
+//! Available with the `secure-memory` feature.
+//! [`SecretBuffer`] keeps its bytes in whole memory pages that are locked against
+//! swapping (`mlock`) where the platform supports it, and wipes them when dropped.
+//!
+//! With the feature, the Shamir and Rabin schemes reconstruct straight into a
+//! [`SecretBuffer`], and Krawczyk keys, Shamir polynomial coefficients and dealings are held
+//! in them. Krawczyk data is decrypted in one as well, but compressed data is decompressed in
+//! ordinary memory before it is moved there.
+//!
+//! ```rust
+//! use sharing::{secure::SecureReconstruct, ShamirSecretSharing, Sharing};
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let shares = sharer.share(b"master key".to_vec()).unwrap();
+//!
+//! let secret = sharer.reconstruct_secure(shares).unwrap();
+//! assert_eq!(&secret[..], b"master key");
+//!
+//! // reusing the buffer of an earlier reconstruction, which is wiped
+//! # #[cfg(feature = "krawczyk")] {
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! use sharing::{secure::SecretBuffer, KrawczykSecretSharing};
+//!
+//! let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(5, 3, rand::thread_rng());
+//! let mut secret = SecretBuffer::new(0);
+//! for data in [&b"first"[..], &b"second"[..]].iter() {
+//!     let shares = sharer.share(data.to_vec()).unwrap();
+//!     sharer.reconstruct_secure_into(&shares[2..], &mut secret).unwrap();
+//!     assert_eq!(&secret[..], *data);
+//! }
+//! # }
+//! ```
+use crate::{Sharing, SharingError};
+use std::{
+    alloc::{self, Layout},
+    fmt,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+    slice,
+};
+use zeroize::Zeroize;
+
+/// A fixed size byte buffer in locked, page aligned memory that is zeroed on drop.
+pub struct SecretBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+    locked: bool,
+}
+
+// the buffer owns its allocation exclusively
+unsafe impl Send for SecretBuffer {}
+unsafe impl Sync for SecretBuffer {}
+
+impl SecretBuffer {
+    /// Allocates a zeroed buffer of `len` bytes.
+    pub fn new(len: usize) -> Self {
+        let page = page_size();
+        let size = len.div_ceil(page).max(1).saturating_mul(page);
+        #[allow(clippy::expect_used)] // like `Vec`, only sizes beyond `isize::MAX` fail
+        let layout = Layout::from_size_align(size, page).expect("page aligned layout");
+        // SAFETY: the layout has a nonzero size
+        let ptr = NonNull::new(unsafe { alloc::alloc_zeroed(layout) })
+            .unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        let locked = lock(ptr.as_ptr(), size);
+        Self {
+            ptr,
+            len,
+            layout,
+            locked,
+        }
+    }
+
+    /// Moves the bytes into locked memory and wipes the original allocation.
+    pub fn from_vec(mut data: Vec<u8>) -> Self {
+        let mut buffer = Self::new(data.len());
+        buffer.copy_from_slice(&data);
+        data.zeroize();
+        buffer
+    }
+
+    /// Whether the pages could be locked, locking is best effort
+    /// and fails e.g. when `RLIMIT_MEMLOCK` is exhausted.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl Deref for SecretBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: ptr is valid for len initialized bytes for the lifetime of self
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for SecretBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: ptr is valid for len initialized bytes and uniquely borrowed
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Clone for SecretBuffer {
+    /// Copies the bytes into a new locked buffer.
+    fn clone(&self) -> Self {
+        let mut buffer = Self::new(self.len);
+        buffer.copy_from_slice(self);
+        buffer
+    }
+}
+
+impl AsRef<[u8]> for SecretBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for SecretBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        // SAFETY: the whole allocation is owned by self
+        let all = unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.layout.size()) };
+        all.zeroize();
+        if self.locked {
+            unlock(self.ptr.as_ptr(), self.layout.size());
+        }
+        // SAFETY: allocated in `new` with the same layout
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+impl fmt::Debug for SecretBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretBuffer")
+            .field("len", &self.len)
+            .field("locked", &self.locked)
+            .finish()
+    }
+}
+
+/// Reconstruction straight into a [`SecretBuffer`].
+pub trait SecureReconstruct: Sharing {
+    /// Reconstructs the secret into locked memory,
+    /// see [`Sharing::reconstruct_secure_into`].
+    fn reconstruct_secure(&self, shares: Vec<Self::Share>) -> Result<SecretBuffer, SharingError> {
+        let mut out = SecretBuffer::new(0);
+        self.reconstruct_secure_into(&shares, &mut out)?;
+        Ok(out)
+    }
+}
+
+impl<S: Sharing> SecureReconstruct for S {}
+
+#[cfg(unix)]
+fn page_size() -> usize {
+    // SAFETY: sysconf has no preconditions
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096,
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> usize {
+    4096
+}
+
+#[cfg(unix)]
+fn lock(ptr: *mut u8, len: usize) -> bool {
+    // SAFETY: the range is a live allocation owned by the caller
+    unsafe { libc::mlock(ptr as *const libc::c_void, len) == 0 }
+}
+
+#[cfg(not(unix))]
+fn lock(_ptr: *mut u8, _len: usize) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn unlock(ptr: *mut u8, len: usize) {
+    // SAFETY: the range was locked by `lock`
+    unsafe {
+        libc::munlock(ptr as *const libc::c_void, len);
+    }
+}
+
+#[cfg(not(unix))]
+fn unlock(_ptr: *mut u8, _len: usize) {}
@@ -0,0 +1,187 @@
+//! Share Sets
+//!
+//! A [`ShareSet`] holds the Shamir shares of one secret together with the threshold,
+//! so custodians can audit them with [`ShareSet::verify`] without reconstructing the secret.
+//!
+//! ```rust
+//! use sharing::{set::ShareSet, ShamirSecretSharing, Sharing, SharingError};
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let (shares, openings, commitments) = sharer.share_with_commitments(vec![1, 2, 3]).unwrap();
+//!
+//! let set = ShareSet::new(3, shares.clone());
+//! assert!(set.verify().is_ok());
+//! assert!(set.verify_commitments(&openings, &commitments).is_ok());
+//!
+//! let mut tampered = shares;
+//! tampered[4].body[1] ^= 1;
+//! let set = ShareSet::new(3, tampered);
+//! assert!(matches!(set.verify(), Err(SharingError::CorruptShare(5))));
+//! ```
+use crate::{
+    commitment::{verify_opened, Commitment, Opening},
+    error::SharingError,
+    math::{self, Field},
+    placement::Placement,
+    share::{valid_ids, ShamirShare},
+};
+use std::{convert::TryFrom, iter::FromIterator};
+
+/// The shares of one secret and the threshold they were dealt with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareSet {
+    k: u8,
+    field: Field,
+    placement: Placement,
+    shares: Vec<ShamirShare>,
+}
+
+impl ShareSet {
+    pub fn new(k: u8, shares: Vec<ShamirShare>) -> Self {
+        Self {
+            k,
+            field: Field::default(),
+            placement: Placement::STANDARD,
+            shares,
+        }
+    }
+
+    /// The field the shares were dealt in, see
+    /// [`ShamirSecretSharing::with_field`](crate::ShamirSecretSharing::with_field).
+    pub fn with_field(mut self, field: Field) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// Where the secret sits in the polynomials, see
+    /// [`ShamirSecretSharing::with_placement`](crate::ShamirSecretSharing::with_placement).
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// The threshold the shares were dealt with, needed after collecting a set from an iterator.
+    pub fn with_k(mut self, k: u8) -> Self {
+        self.k = k;
+        self
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn placement(&self) -> Placement {
+        self.placement
+    }
+
+    pub fn shares(&self) -> &[ShamirShare] {
+        &self.shares
+    }
+
+    pub fn into_shares(self) -> Vec<ShamirShare> {
+        self.shares
+    }
+
+    /// Checks that the set is sufficient and mutually consistent, i.e. that every
+    /// `k`-subset reconstructs the same secret, without computing the secret.
+    ///
+    /// The first `k` shares define the polynomials and every further share is checked
+    /// against their value at its point, so exactly `k` shares are always consistent.
+    /// Fails with [`SharingError::CorruptShare`] for the first share that disagrees;
+    /// if several disagree, one of the first `k` may be the corrupt one.
+    pub fn verify(&self) -> Result<(), SharingError> {
+        let k = self.k as usize;
+        if k < 1 {
+            return Err(SharingError::MismatchedParameters);
+        }
+        if self.shares.len() < k {
+            return Err(SharingError::reconstruction(self.k, self.shares.len()));
+        }
+        let ids: Vec<u8> = self.shares.iter().map(|share| share.id).collect();
+        let size = self.shares[0].body.len();
+        if !valid_ids(&ids) || self.shares.iter().any(|share| share.body.len() != size) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let xs: Vec<u8> = ids.iter().map(|id| self.placement.x(*id)).collect();
+        let (basis, rest) = self.shares.split_at(k);
+        for (share, x) in rest.iter().zip(&xs[k..]) {
+            let coefficients = self
+                .field
+                .lagrange_coefficients(&xs[..k], *x)
+                .ok_or(SharingError::MismatchedParameters)?;
+            let consistent = (0..size).all(|i| {
+                let expected = basis.iter().zip(&coefficients).fold(0, |acc, (s, c)| {
+                    math::add(acc, self.field.mul(s.body[i], *c))
+                });
+                expected == share.body[i]
+            });
+            if !consistent {
+                return Err(SharingError::CorruptShare(share.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every share, opened with the opening of the same id, against the dealer's
+    /// published commitments, then [`verify`](Self::verify).
+    pub fn verify_commitments(
+        &self,
+        openings: &[Opening],
+        commitments: &[Commitment],
+    ) -> Result<(), SharingError> {
+        if let Some(share) = self
+            .shares
+            .iter()
+            .find(|share| !verify_opened(share, openings, commitments))
+        {
+            return Err(SharingError::CorruptShare(share.id));
+        }
+        self.verify()
+    }
+}
+
+/// Collects shares with `k` equal to their number, at most 255, set the actual threshold
+/// with [`with_k`](ShareSet::with_k). More than 255 shares cannot have distinct ids and fail
+/// to [`verify`](ShareSet::verify).
+///
+/// ```rust
+/// use sharing::{set::ShareSet, ShamirSecretSharing, Sharing};
+///
+/// let sharer = ShamirSecretSharing::new(5, 2, rand::thread_rng());
+/// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+///
+/// let mut set: ShareSet = shares[..2].iter().cloned().collect();
+/// assert_eq!(set.k(), 2);
+/// set.extend(shares[4..].iter().cloned());
+/// assert!(set.verify().is_ok());
+/// assert_eq!(set.into_iter().map(|share| share.id).collect::<Vec<_>>(), vec![1, 2, 5]);
+///
+/// let many: ShareSet = shares.iter().cycle().take(256).cloned().collect();
+/// assert_eq!(many.k(), 255);
+/// assert!(many.verify().is_err());
+/// ```
+impl FromIterator<ShamirShare> for ShareSet {
+    fn from_iter<I: IntoIterator<Item = ShamirShare>>(iter: I) -> Self {
+        let shares: Vec<ShamirShare> = iter.into_iter().collect();
+        Self::new(u8::try_from(shares.len()).unwrap_or(u8::MAX), shares)
+    }
+}
+
+impl Extend<ShamirShare> for ShareSet {
+    fn extend<I: IntoIterator<Item = ShamirShare>>(&mut self, iter: I) {
+        self.shares.extend(iter);
+    }
+}
+
+impl IntoIterator for ShareSet {
+    type Item = ShamirShare;
+    type IntoIter = std::vec::IntoIter<ShamirShare>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.shares.into_iter()
+    }
+}
@@ -1,11 +1,79 @@
+//! Share Types
+use crate::{compression::Compression, error::SharingError};
+use std::{convert::TryFrom, error::Error, fmt, str::FromStr};
+
+const DETACHED_VERSION: u8 = 1;
+
 pub trait Share: Clone {
     fn size(&self) -> usize;
     fn with_size(size: usize) -> Self;
 }
 
-#[derive(Debug, Clone)]
+/// A single Shamir share.
+///
+/// Shares compare and hash by value and order by id first, so they can be
+/// deduplicated and sorted in standard collections:
+///
+/// ```rust
+/// use std::collections::BTreeSet;
+/// use sharing::{ShamirSecretSharing, Sharing};
+///
+/// let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+/// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+///
+/// let set: BTreeSet<_> = shares.iter().rev().chain(&shares).cloned().collect();
+/// let ids: Vec<u8> = set.iter().map(|share| share.id).collect();
+/// assert_eq!(ids, vec![1, 2, 3]);
+/// ```
+///
+/// All share types convert from and to their binary encoding with the std traits:
+///
+/// ```rust
+/// use sharing::{SharingError, ShamirShare};
+/// use std::convert::TryFrom;
+///
+/// let bytes: Vec<u8> = ShamirShare { id: 3, body: vec![1, 2] }.into();
+/// let share = ShamirShare::try_from(bytes.as_slice()).unwrap();
+/// assert_eq!(share, ShamirShare { id: 3, body: vec![1, 2] });
+///
+/// let error = ShamirShare::try_from(&[9u8][..]);
+/// assert!(matches!(error, Err(SharingError::UnsupportedVersion(9))));
+/// ```
+///
+/// With the `serde` feature they serialize with their bytes as base64 in human readable
+/// formats, and as plain bytes otherwise:
+///
+/// ```rust
+/// # #[cfg(feature = "serde")] {
+/// use sharing::ShamirShare;
+///
+/// let share = ShamirShare { id: 3, body: vec![1, 2, 255] };
+/// let json = serde_json::to_string(&share).unwrap();
+/// assert_eq!(json, r#"{"id":3,"body":"AQL/"}"#);
+/// assert_eq!(serde_json::from_str::<ShamirShare>(&json).unwrap(), share);
+/// # }
+/// ```
+///
+/// For terminals and config files they print as the hex of their binary encoding,
+/// and parse back from it:
+///
+/// ```rust
+/// use sharing::{share::ParseShareError, ShamirShare};
+///
+/// let share = ShamirShare { id: 3, body: vec![1, 2, 255] };
+/// assert_eq!(share.to_string(), "0153030102ff");
+/// assert_eq!(" 0153030102FF\n".parse::<ShamirShare>().unwrap(), share);
+///
+/// assert!(matches!("0153030102fg".parse::<ShamirShare>(), Err(ParseShareError::InvalidDigit(11))));
+/// assert!(matches!("0153030102f".parse::<ShamirShare>(), Err(ParseShareError::OddLength)));
+/// assert!(matches!("0152".parse::<ShamirShare>(), Err(ParseShareError::Decode(_))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShamirShare {
     pub id: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
     pub body: Vec<u8>,
 }
 
@@ -21,10 +89,13 @@ impl Share for ShamirShare {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RabinShare {
     pub id: u8,
     pub length: usize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
     pub body: Vec<u8>,
 }
 
@@ -41,14 +112,42 @@ impl Share for RabinShare {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KrawczykShare {
     pub id: u8,
     pub length: usize,
-    pub key: [u8; 44],
+    /// This holder's Shamir share of the encryption key.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub key: Vec<u8>,
+    /// How the data was compressed before encryption.
+    pub compression: Compression,
+    /// Authenticated with the ciphertext, see
+    /// [`share_with_associated_data`](crate::KrawczykSecretSharing::share_with_associated_data).
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub associated_data: Vec<u8>,
+    /// SHA-256 of the full ciphertext, checked before decryption.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub digest: [u8; 32],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
     pub body: Vec<u8>,
 }
 
+impl fmt::Debug for KrawczykShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KrawczykShare")
+            .field("id", &self.id)
+            .field("length", &self.length)
+            .field("key", &"<redacted>")
+            .field("compression", &self.compression)
+            .field("associated_data", &self.associated_data)
+            .field("digest", &self.digest)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
 impl Share for KrawczykShare {
     fn size(&self) -> usize {
         self.length
@@ -57,29 +156,703 @@ impl Share for KrawczykShare {
         Self {
             id: 0,
             length: 0,
-            key: [0u8; 44],
+            key: Vec::new(),
+            compression: Compression::None,
+            associated_data: Vec::new(),
+            digest: [0u8; 32],
             body: vec![0u8; size],
         }
     }
 }
 
+impl KrawczykShare {
+    /// Splits into the small key share and the bulky data share,
+    /// e.g. to keep key shares on paper or in an HSM and data shares in cloud storage.
+    ///
+    /// ```rust
+    /// use chacha20poly1305::ChaCha20Poly1305;
+    /// use sharing::{
+    ///     share::{KrawczykDataShare, KrawczykKeyShare},
+    ///     KrawczykSecretSharing, KrawczykShare, Sharing,
+    /// };
+    ///
+    /// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+    /// let shares = sharer.share(vec![1, 2, 3, 4, 5]).unwrap();
+    ///
+    /// let (key, data) = shares[0].clone().split();
+    /// let key = KrawczykKeyShare::from_bytes(&key.to_bytes()).unwrap();
+    /// let data = KrawczykDataShare::from_bytes(&data.to_bytes()).unwrap();
+    ///
+    /// let joined = KrawczykShare::join(key, data).unwrap();
+    /// let rec = sharer.reconstruct(vec![joined, shares[1].clone()]).unwrap();
+    /// assert_eq!(rec, vec![1, 2, 3, 4, 5]);
+    /// ```
+    pub fn split(self) -> (KrawczykKeyShare, KrawczykDataShare) {
+        (
+            KrawczykKeyShare {
+                id: self.id,
+                digest: self.digest,
+                key: self.key,
+            },
+            KrawczykDataShare {
+                id: self.id,
+                length: self.length,
+                compression: self.compression,
+                associated_data: self.associated_data,
+                body: self.body,
+            },
+        )
+    }
+
+    /// Reverses [`split`](Self::split), `None` if the parts belong to different holders.
+    pub fn join(key: KrawczykKeyShare, data: KrawczykDataShare) -> Option<Self> {
+        if key.id != data.id {
+            return None;
+        }
+        Some(Self {
+            id: data.id,
+            length: data.length,
+            key: key.key,
+            compression: data.compression,
+            associated_data: data.associated_data,
+            digest: key.digest,
+            body: data.body,
+        })
+    }
+}
+
+/// The key part of a [`KrawczykShare`].
+///
+/// It keeps the ciphertext digest, so data shares from untrusted storage are checked
+/// against a value held alongside the key.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KrawczykKeyShare {
+    pub id: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub digest: [u8; 32],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub key: Vec<u8>,
+}
+
+impl KrawczykKeyShare {
+    /// `version || id || digest || key`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(34 + self.key.len());
+        out.push(DETACHED_VERSION);
+        out.push(self.id);
+        out.extend_from_slice(&self.digest);
+        out.extend_from_slice(&self.key);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [DETACHED_VERSION, id, rest @ ..] if rest.len() >= 32 => {
+                let (digest, key) = rest.split_at(32);
+                let mut buf = [0u8; 32];
+                buf.copy_from_slice(digest);
+                Some(Self {
+                    id: *id,
+                    digest: buf,
+                    key: key.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Rebuilds the key part from its Shamir share and the ciphertext digest.
+    pub fn from_shamir(share: ShamirShare, digest: [u8; 32]) -> Self {
+        Self {
+            id: share.id,
+            digest,
+            key: share.body,
+        }
+    }
+}
+
+/// The Shamir share of the key, without the digest.
+///
+/// ```rust
+/// use chacha20poly1305::ChaCha20Poly1305;
+/// use sharing::{
+///     share::{KrawczykDataShare, KrawczykKeyShare},
+///     KrawczykSecretSharing, KrawczykShare, RabinShare, ShamirShare, Sharing,
+/// };
+///
+/// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+/// let shares = sharer.share(vec![1, 2, 3, 4, 5]).unwrap();
+///
+/// let (key, data) = shares[0].clone().split();
+/// let (digest, compression, associated_data) =
+///     (key.digest, data.compression, data.associated_data.clone());
+/// let key = ShamirShare::from(key);
+/// let data = RabinShare::from(data);
+///
+/// // ...sent over different channels...
+///
+/// let key = KrawczykKeyShare::from_shamir(key, digest);
+/// let data = KrawczykDataShare::from_rabin(data, compression, associated_data);
+/// let joined = KrawczykShare::join(key, data).unwrap();
+/// assert_eq!(joined, shares[0]);
+/// ```
+impl From<KrawczykKeyShare> for ShamirShare {
+    fn from(share: KrawczykKeyShare) -> Self {
+        Self {
+            id: share.id,
+            body: share.key,
+        }
+    }
+}
+
+impl fmt::Debug for KrawczykKeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KrawczykKeyShare")
+            .field("id", &self.id)
+            .field("digest", &self.digest)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// The data part of a [`KrawczykShare`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KrawczykDataShare {
+    pub id: u8,
+    pub length: usize,
+    pub compression: Compression,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub associated_data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub body: Vec<u8>,
+}
+
+impl KrawczykDataShare {
+    /// `version || id || compression || length (u64) || associated data length (u32)
+    /// || associated data || body`, integers big endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(15 + self.associated_data.len() + self.body.len());
+        out.push(DETACHED_VERSION);
+        out.push(self.id);
+        out.push(self.compression.to_byte());
+        out.extend_from_slice(&(self.length as u64).to_be_bytes());
+        out.extend_from_slice(&(self.associated_data.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.associated_data);
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [DETACHED_VERSION, id, compression, rest @ ..] if rest.len() >= 12 => {
+                let (length, rest) = rest.split_at(8);
+                let (associated_length, rest) = rest.split_at(4);
+                let mut length_buf = [0u8; 8];
+                length_buf.copy_from_slice(length);
+                let mut associated_buf = [0u8; 4];
+                associated_buf.copy_from_slice(associated_length);
+                let associated_length = usize::try_from(u32::from_be_bytes(associated_buf)).ok()?;
+                if rest.len() < associated_length {
+                    return None;
+                }
+                let (associated_data, body) = rest.split_at(associated_length);
+                Some(Self {
+                    id: *id,
+                    length: usize::try_from(u64::from_be_bytes(length_buf)).ok()?,
+                    compression: Compression::from_byte(*compression)?,
+                    associated_data: associated_data.to_vec(),
+                    body: body.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Rebuilds the data part from its Rabin share of the ciphertext and the sharing's
+    /// compression and associated data.
+    pub fn from_rabin(
+        share: RabinShare,
+        compression: Compression,
+        associated_data: Vec<u8>,
+    ) -> Self {
+        Self {
+            id: share.id,
+            length: share.length,
+            compression,
+            associated_data,
+            body: share.body,
+        }
+    }
+}
+
+/// The Rabin share of the ciphertext, without compression and associated data.
+impl From<KrawczykDataShare> for RabinShare {
+    fn from(share: KrawczykDataShare) -> Self {
+        Self {
+            id: share.id,
+            length: share.length,
+            body: share.body,
+        }
+    }
+}
+
+/// A holder's share of a [`share_batch`](crate::KrawczykSecretSharing::share_batch),
+/// one key share and a data share for every file.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KrawczykBatchShare {
+    pub id: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub key: Vec<u8>,
+    pub compression: Compression,
+    pub files: Vec<BatchFile>,
+}
+
+/// The data share of a single file in a [`KrawczykBatchShare`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchFile {
+    pub length: usize,
+    /// SHA-256 of the file's full ciphertext.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub digest: [u8; 32],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub body: Vec<u8>,
+}
+
+impl fmt::Debug for KrawczykBatchShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KrawczykBatchShare")
+            .field("id", &self.id)
+            .field("key", &"<redacted>")
+            .field("compression", &self.compression)
+            .field("files", &self.files)
+            .finish()
+    }
+}
+
+/// A holder's share of a single frame of a [`stream`](crate::stream).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KrawczykFrameShare {
+    pub id: u8,
+    /// The session that sealed the frame, 0 unless the stream was resumed.
+    pub session: u32,
+    /// The position of the frame in the stream.
+    pub index: u32,
+    /// Whether this is the final frame.
+    pub last: bool,
+    pub length: usize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
+    pub body: Vec<u8>,
+}
+
+/// A compact binary encoding of a share, tagged with a version and the scheme.
+///
+/// This is also the encoding of the share types' `TryFrom<&[u8]>`, `Into<Vec<u8>>`,
+/// `Display` and `FromStr`. A [`WireShare`](crate::wire::WireShare) converts through the
+/// [wire format](crate::wire) instead, which adds the threshold, metadata and a checksum.
+///
+/// ```rust
+/// use sharing::{share::ShareBytes, ShamirShare};
+///
+/// let share = ShamirShare { id: 3, body: vec![1, 2, 3] };
+/// let decoded = ShamirShare::from_bytes(&share.to_bytes()).unwrap();
+/// assert_eq!((decoded.id, decoded.body), (3, vec![1, 2, 3]));
+/// ```
+pub trait ShareBytes: Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// `None` if the bytes are malformed or encode another scheme.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+pub(crate) const BYTES_VERSION: u8 = 1;
+
+/// Why `bytes` did not decode with [`ShareBytes::from_bytes`].
+pub(crate) fn decode_error(bytes: &[u8]) -> SharingError {
+    match bytes.first() {
+        Some(&version) if version != BYTES_VERSION => SharingError::UnsupportedVersion(version),
+        _ => SharingError::Malformed("share"),
+    }
+}
+
+impl ShareBytes for ShamirShare {
+    /// `version || 'S' || id || body`
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(3 + self.body.len());
+        out.extend_from_slice(&[BYTES_VERSION, b'S', self.id]);
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [BYTES_VERSION, b'S', id, body @ ..] => Some(Self {
+                id: *id,
+                body: body.to_vec(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl ShareBytes for RabinShare {
+    /// `version || 'R' || id || length (u64 big endian) || body`
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(11 + self.body.len());
+        out.extend_from_slice(&[BYTES_VERSION, b'R', self.id]);
+        out.extend_from_slice(&(self.length as u64).to_be_bytes());
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [BYTES_VERSION, b'R', id, rest @ ..] => {
+                let mut reader = Reader(rest);
+                Some(Self {
+                    id: *id,
+                    length: reader.length()?,
+                    body: reader.0.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ShareBytes for KrawczykShare {
+    /// `version || 'K' || id || compression || length (u64) || digest
+    /// || key length (u32) || key || associated data length (u32) || associated data || body`,
+    /// integers big endian
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(52 + self.key.len() + self.associated_data.len() + self.body.len());
+        out.extend_from_slice(&[BYTES_VERSION, b'K', self.id, self.compression.to_byte()]);
+        out.extend_from_slice(&(self.length as u64).to_be_bytes());
+        out.extend_from_slice(&self.digest);
+        out.extend_from_slice(&(self.key.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.key);
+        out.extend_from_slice(&(self.associated_data.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.associated_data);
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [BYTES_VERSION, b'K', id, compression, rest @ ..] => {
+                let mut reader = Reader(rest);
+                let length = reader.length()?;
+                let mut digest = [0u8; 32];
+                digest.copy_from_slice(reader.take(32)?);
+                let key = reader.prefixed()?.to_vec();
+                let associated_data = reader.prefixed()?.to_vec();
+                Some(Self {
+                    id: *id,
+                    length,
+                    key,
+                    compression: Compression::from_byte(*compression)?,
+                    associated_data,
+                    digest,
+                    body: reader.0.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// `TryFrom<&[u8]>` and `Into<Vec<u8>>` through the binary encodings,
+// `Display` and `FromStr` through their hex
+macro_rules! byte_conversions {
+    ($($t:ty),*) => {$(
+        /// Decodes the bare [`ShareBytes`] encoding, not the [wire format](crate::wire).
+        impl TryFrom<&[u8]> for $t {
+            type Error = SharingError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                Self::from_bytes(bytes).ok_or_else(|| decode_error(bytes))
+            }
+        }
+
+        /// Encodes with [`ShareBytes`], without the threshold, metadata or checksum of the
+        /// [wire format](crate::wire).
+        impl From<$t> for Vec<u8> {
+            fn from(share: $t) -> Self {
+                share.to_bytes()
+            }
+        }
+
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write_hex(f, &self.to_bytes())
+            }
+        }
+
+        impl FromStr for $t {
+            type Err = ParseShareError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let bytes = parse_hex(s)?;
+                Self::try_from(bytes.as_slice()).map_err(ParseShareError::Decode)
+            }
+        }
+    )*};
+}
+
+/// Error returned when a share cannot be parsed from its hex text form.
+#[derive(Debug)]
+pub enum ParseShareError {
+    /// The character at this byte offset is not a hex digit.
+    InvalidDigit(usize),
+    /// An odd number of hex digits, e.g. the last one was not copied.
+    OddLength,
+    /// Valid hex, but not a share of this type.
+    Decode(SharingError),
+}
+
+impl fmt::Display for ParseShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseShareError::InvalidDigit(offset) => {
+                write!(f, "invalid hex digit at offset {}", offset)
+            }
+            ParseShareError::OddLength => f.write_str("odd number of hex digits"),
+            ParseShareError::Decode(error) => write!(f, "invalid share: {}", error),
+        }
+    }
+}
+
+impl Error for ParseShareError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseShareError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// CRC-32C (Castagnoli), as used by iSCSI and ext4.
+pub(crate) fn crc32c(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            (crc >> 1) ^ (0x82f6_3b78 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+pub(crate) fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    bytes.iter().try_for_each(|b| write!(f, "{:02x}", b))
+}
+
+/// Displays as lower case hex, for bytes inside a larger text form.
+#[cfg(feature = "shamir")]
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+#[cfg(feature = "shamir")]
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, self.0)
+    }
+}
+
+/// Hex in either case, surrounding whitespace is ignored.
+pub(crate) fn parse_hex(s: &str) -> Result<Vec<u8>, ParseShareError> {
+    let trimmed = s.trim_start();
+    let offset = s.len() - trimmed.len();
+    let digits = trimmed.trim_end().as_bytes();
+    if let Some(i) = digits.iter().position(|c| !c.is_ascii_hexdigit()) {
+        return Err(ParseShareError::InvalidDigit(offset + i));
+    }
+    if digits.len() % 2 != 0 {
+        return Err(ParseShareError::OddLength);
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (digit(pair[0]) << 4) | digit(pair[1]))
+        .collect())
+}
+
+fn digit(c: u8) -> u8 {
+    char::from(c).to_digit(16).map_or(0, |d| d as u8)
+}
+
+byte_conversions!(
+    ShamirShare,
+    RabinShare,
+    KrawczykShare,
+    KrawczykKeyShare,
+    KrawczykDataShare
+);
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn length(&mut self) -> Option<usize> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        usize::try_from(u64::from_be_bytes(buf)).ok()
+    }
+
+    /// A slice prefixed with its u32 length.
+    fn prefixed(&mut self) -> Option<&'a [u8]> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.take(4)?);
+        self.take(usize::try_from(u32::from_be_bytes(buf)).ok()?)
+    }
+}
+
 pub trait ShareVec {
-    fn size(&self) -> usize;
+    /// The common size of the shares, see [`validate_shares`].
+    fn size(&self) -> Result<usize, SharingError>;
 
     fn with_size(n: usize, size: usize) -> Self;
 }
 
-impl<S: Share> ShareVec for Vec<S> {
-    fn size(&self) -> usize {
-        let original_length = self[0].size();
-        if self.iter().all(|s| s.size() == original_length) {
-            original_length
-        } else {
-            panic!("size Error")
-        }
+impl<S: Share + ShareId> ShareVec for Vec<S> {
+    fn size(&self) -> Result<usize, SharingError> {
+        validate_shares(self)
     }
 
     fn with_size(n: usize, size: usize) -> Self {
         vec![S::with_size(size); n]
     }
 }
+
+/// Checks that shares from untrusted input can be combined and returns their common size.
+///
+/// Fails with [`SharingError::EmptyShareSet`] if there are none and with
+/// [`SharingError::BodyLengthMismatch`] naming the first share whose size differs from the first.
+///
+/// ```rust
+/// use sharing::{share::validate_shares, RabinShare, SharingError};
+///
+/// let share = |id, length, body: Vec<u8>| RabinShare { id, length, body };
+/// let shares = vec![share(1, 6, vec![0; 3]), share(2, 6, vec![0; 3]), share(3, 4, vec![0; 2])];
+/// assert!(matches!(
+///     validate_shares(&shares),
+///     Err(SharingError::BodyLengthMismatch { id: 3, expected: 6, found: 4 })
+/// ));
+/// assert_eq!(validate_shares(&shares[..2]).unwrap(), 6);
+/// assert!(matches!(validate_shares::<RabinShare>(&[]), Err(SharingError::EmptyShareSet)));
+/// ```
+pub fn validate_shares<S: Share + ShareId>(shares: &[S]) -> Result<usize, SharingError> {
+    let expected = shares.first().ok_or(SharingError::EmptyShareSet)?.size();
+    match shares.iter().find(|share| share.size() != expected) {
+        Some(share) => Err(SharingError::BodyLengthMismatch {
+            id: share.id(),
+            expected,
+            found: share.size(),
+        }),
+        None => Ok(expected),
+    }
+}
+
+/// Drops repeated copies of a share, keeping the first, so the same share passed twice
+/// counts once. Fails with [`SharingError::DuplicateShareId`] if two different shares
+/// have the same id, as one of them must be corrupt or belong to another dealing.
+///
+/// ```rust
+/// use sharing::{share::dedup_shares, ShamirShare, SharingError};
+///
+/// let share = |id, byte| ShamirShare { id, body: vec![byte] };
+/// let shares = dedup_shares(vec![share(1, 7), share(2, 8), share(1, 7)]).unwrap();
+/// assert_eq!(shares, vec![share(1, 7), share(2, 8)]);
+///
+/// assert!(matches!(
+///     dedup_shares(vec![share(1, 7), share(1, 9)]),
+///     Err(SharingError::DuplicateShareId(1))
+/// ));
+/// ```
+pub fn dedup_shares<S: ShareId + PartialEq>(shares: Vec<S>) -> Result<Vec<S>, SharingError> {
+    let mut unique: Vec<S> = Vec::with_capacity(shares.len());
+    for share in shares {
+        match unique.iter().find(|kept| kept.id() == share.id()) {
+            Some(kept) if *kept == share => {}
+            Some(_) => return Err(SharingError::DuplicateShareId(share.id())),
+            None => unique.push(share),
+        }
+    }
+    Ok(unique)
+}
+
+/// Whether `ids` can be used as share coordinates: nonzero, as zero is where the secret lies,
+/// and distinct.
+#[cfg(feature = "shamir")]
+pub(crate) fn valid_ids(ids: &[u8]) -> bool {
+    check_ids(ids).is_ok()
+}
+
+/// Like [`valid_ids`], but says what is wrong: a zero id is [`SharingError::InvalidParameters`],
+/// a repeated one [`SharingError::DuplicateShareId`].
+#[cfg(any(feature = "shamir", feature = "rabin"))]
+pub(crate) fn check_ids(ids: &[u8]) -> Result<(), SharingError> {
+    for (i, id) in ids.iter().enumerate() {
+        if *id == 0 {
+            return Err(SharingError::InvalidParameters);
+        }
+        if ids[..i].contains(id) {
+            return Err(SharingError::DuplicateShareId(*id));
+        }
+    }
+    Ok(())
+}
+
+/// Shares that carry the x coordinate they were evaluated at.
+pub trait ShareId {
+    fn id(&self) -> u8;
+}
+
+impl<S: ShareId + ?Sized> ShareId for &S {
+    fn id(&self) -> u8 {
+        (**self).id()
+    }
+}
+
+impl ShareId for ShamirShare {
+    fn id(&self) -> u8 {
+        self.id
+    }
+}
+
+impl ShareId for RabinShare {
+    fn id(&self) -> u8 {
+        self.id
+    }
+}
+
+impl ShareId for KrawczykShare {
+    fn id(&self) -> u8 {
+        self.id
+    }
+}
+
+impl ShareId for KrawczykFrameShare {
+    fn id(&self) -> u8 {
+        self.id
+    }
+}
+
+impl ShareId for KrawczykBatchShare {
+    fn id(&self) -> u8 {
+        self.id
+    }
+}
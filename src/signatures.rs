@@ -0,0 +1,115 @@
+//! Participant Signatures
+//!
+//! Participants document their consent to a share set by signing the [`Manifest`] digest
+//! with ed25519. The signatures are detached and travel alongside the manifest; at
+//! reconstruction time [`reconstruct_with_consent`] requires a quorum of valid signatures
+//! from registered participants before it combines any shares.
+//!
+//! ```rust
+//! use ed25519_dalek::SigningKey;
+//! use sharing::{manifest::Manifest, signatures, Parameters, Scheme};
+//! use sharing::{ShamirSecretSharing, Sharing, SharingError};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let shares = sharer.share(b"vault key".to_vec()).unwrap();
+//! let manifest = Manifest::new(Scheme::Shamir, Parameters::new(3, 2).unwrap(), &shares);
+//!
+//! let keys: Vec<SigningKey> = (1..=3).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+//! let registered: Vec<_> = (1..=3).zip(keys.iter().map(SigningKey::verifying_key)).collect();
+//!
+//! let consent = |signed: &[signatures::ManifestSignature]| {
+//!     signatures::reconstruct_with_consent(&sharer, &manifest, shares.clone(), signed, &registered)
+//! };
+//!
+//! let first = signatures::sign(&manifest, 1, &keys[0]);
+//! assert!(matches!(
+//!     consent(&[first.clone()]),
+//!     Err(SharingError::InsufficientSignatures { needed: 2, found: 1 })
+//! ));
+//!
+//! let third = signatures::sign(&manifest, 3, &keys[2]);
+//! assert_eq!(consent(&[first, third]).unwrap(), b"vault key");
+//! ```
+use crate::{
+    error::SharingError,
+    manifest::Manifest,
+    share::{ShareBytes, ShareId},
+    Sharing,
+};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+/// A participant's detached signature over a manifest digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestSignature {
+    /// The share id of the signing participant.
+    pub id: u8,
+    pub signature: Signature,
+}
+
+/// Signs the manifest as participant `id`.
+pub fn sign(manifest: &Manifest, id: u8, key: &SigningKey) -> ManifestSignature {
+    ManifestSignature {
+        id,
+        signature: key.sign(&manifest.digest()),
+    }
+}
+
+/// Checks a signature against the key registered for its participant.
+pub fn verify(manifest: &Manifest, signature: &ManifestSignature, key: &VerifyingKey) -> bool {
+    key.verify(&manifest.digest(), &signature.signature).is_ok()
+}
+
+/// The distinct participants with a valid signature from their registered key, in order.
+pub fn signers(
+    manifest: &Manifest,
+    signatures: &[ManifestSignature],
+    keys: &[(u8, VerifyingKey)],
+) -> Vec<u8> {
+    let mut signers: Vec<u8> = signatures
+        .iter()
+        .filter(|signature| {
+            keys.iter()
+                .filter(|(id, _)| *id == signature.id)
+                .any(|(_, key)| verify(manifest, signature, key))
+        })
+        .map(|signature| signature.id)
+        .collect();
+    signers.sort_unstable();
+    signers.dedup();
+    signers
+}
+
+/// Fails with [`SharingError::InsufficientSignatures`] unless `quorum` participants signed.
+pub fn verify_quorum(
+    manifest: &Manifest,
+    signatures: &[ManifestSignature],
+    keys: &[(u8, VerifyingKey)],
+    quorum: usize,
+) -> Result<(), SharingError> {
+    let found = signers(manifest, signatures, keys).len();
+    if found < quorum {
+        return Err(SharingError::InsufficientSignatures {
+            needed: quorum,
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Reconstructs once `k` registered participants signed the manifest,
+/// using only the shares that match it.
+pub fn reconstruct_with_consent<T, S>(
+    sharer: &T,
+    manifest: &Manifest,
+    shares: Vec<S>,
+    signatures: &[ManifestSignature],
+    keys: &[(u8, VerifyingKey)],
+) -> Result<Vec<u8>, SharingError>
+where
+    T: Sharing<Share = S>,
+    S: ShareBytes + ShareId,
+{
+    let k = manifest.parameters().k();
+    verify_quorum(manifest, signatures, keys, k as usize)?;
+    sharer.reconstruct(manifest.collect(shares))
+}
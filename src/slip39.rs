@@ -0,0 +1,569 @@
+//! SLIP-39
+//!
+//! [SLIP-39] shares, the mnemonic backups of Trezor and other hardware wallets. The master
+//! secret is encrypted with a passphrase, split among groups, and the share of each group
+//! split among its members. Reconstructing takes `group_threshold` groups, each with
+//! `member_threshold` of its members' shares.
+//!
+//! Shares are written as words of the 1024 word SLIP-39 list, 20 words for a 16 byte and
+//! 33 words for a 32 byte secret, and end in an RS1024 checksum that catches any three
+//! mistyped words. Both the original and the extendable backups of the current revision are read.
+//!
+//! Available with the `slip39` feature.
+//!
+//! [SLIP-39]: https://github.com/satoshilabs/slips/blob/master/slip-0039.md
+//!
+//! ```rust
+//! use sharing::{slip39::{self, Slip39SecretSharing}, Sharing};
+//!
+//! // two of three groups: the owner alone, two of three friends, or three of five family members
+//! let sharer = Slip39SecretSharing::new(2, &[(1, 1), (2, 3), (3, 5)], rand::thread_rng())
+//!     .with_passphrase("TREZOR");
+//! let shares = sharer.share(vec![7; 16]).unwrap();
+//! assert_eq!(shares.len(), 9);
+//!
+//! let owner = shares[0].to_mnemonic();
+//! assert_eq!(owner.split(' ').count(), 20);
+//! let friends = [shares[2].to_mnemonic(), shares[3].to_mnemonic()];
+//! let secret = slip39::combine_mnemonics(&[&owner, &friends[0], &friends[1]], "TREZOR");
+//! assert_eq!(secret.unwrap(), vec![7; 16]);
+//!
+//! // a wrong passphrase decrypts to another secret, by design
+//! let secret = slip39::combine_mnemonics(&[&owner, &friends[0], &friends[1]], "");
+//! assert_ne!(secret.unwrap(), vec![7; 16]);
+//! ```
+use crate::{
+    error::SharingError,
+    grouped::{self, Member},
+    secret::try_fill,
+    share::Share,
+    Sharing,
+};
+use rand::Rng;
+use sha2::Sha256;
+use std::{cell::RefCell, fmt, str::FromStr};
+
+/// The SLIP-39 word list, in order.
+const WORDS: &str = include_str!("slip39.txt");
+
+/// The shortest master secret, in bytes.
+pub const MIN_SECRET_LEN: usize = 16;
+
+const ROUNDS: u8 = 4;
+const BASE_ITERATIONS: u32 = 10000;
+const CHECKSUM_WORDS: usize = 3;
+/// identifier, flags, group and member parameters
+const HEADER_WORDS: usize = 4;
+
+/// A single member share of a SLIP-39 backup.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Slip39Share {
+    /// 15 random bits, the same for all shares of a backup.
+    pub identifier: u16,
+    /// Whether the passphrase encryption is independent of the identifier.
+    pub extendable: bool,
+    /// PBKDF2 runs 10000·2<sup>e</sup> iterations, at most 15.
+    pub iteration_exponent: u8,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Vec<u8>,
+}
+
+impl Share for Slip39Share {
+    fn size(&self) -> usize {
+        self.value.len()
+    }
+
+    fn with_size(size: usize) -> Self {
+        Self {
+            identifier: 0,
+            extendable: false,
+            iteration_exponent: 0,
+            group_index: 0,
+            group_threshold: 1,
+            group_count: 1,
+            member_index: 0,
+            member_threshold: 1,
+            value: vec![0; size],
+        }
+    }
+}
+
+impl Slip39Share {
+    pub fn to_words(&self) -> Vec<&'static str> {
+        let list: Vec<&str> = WORDS.lines().collect();
+        let mut data = vec![
+            self.identifier >> 5 & 0x3ff,
+            (self.identifier & 0x1f) << 5
+                | u16::from(self.extendable) << 4
+                | u16::from(self.iteration_exponent & 0xf),
+            u16::from(self.group_index & 0xf) << 6
+                | u16::from(self.group_threshold.wrapping_sub(1) & 0xf) << 2
+                | u16::from(self.group_count.wrapping_sub(1) & 0xf) >> 2,
+            u16::from(self.group_count.wrapping_sub(1) & 0x3) << 8
+                | u16::from(self.member_index & 0xf) << 4
+                | u16::from(self.member_threshold.wrapping_sub(1) & 0xf),
+        ];
+        // the value is left padded with zero bits to whole words
+        let padding = (10 - self.value.len() * 8 % 10) % 10;
+        let bits: Vec<bool> = std::iter::repeat_n(false, padding)
+            .chain(
+                self.value
+                    .iter()
+                    .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1)),
+            )
+            .collect();
+        data.extend(
+            bits.chunks(10)
+                .map(|word| word.iter().fold(0, |acc, bit| acc << 1 | u16::from(*bit))),
+        );
+        let checksum = checksum(customization(self.extendable), &data);
+        data.extend_from_slice(&checksum);
+        data.iter().map(|index| list[usize::from(*index)]).collect()
+    }
+
+    /// The words of the share separated by spaces.
+    pub fn to_mnemonic(&self) -> String {
+        self.to_words().join(" ")
+    }
+
+    /// Reads a share from its words, in any case and separated by any whitespace.
+    /// Fails with [`SharingError::Malformed`] on an unknown word or a wrong checksum.
+    pub fn from_mnemonic(mnemonic: &str) -> Result<Self, SharingError> {
+        let list: Vec<&str> = WORDS.lines().collect();
+        let data = mnemonic
+            .split_whitespace()
+            .map(|word| {
+                let word = word.to_lowercase();
+                list.binary_search(&word.as_str()).map(|index| index as u16)
+            })
+            .collect::<Result<Vec<u16>, _>>()
+            .map_err(|_| SharingError::Malformed("slip39 word"))?;
+
+        let value_words = data.len().saturating_sub(HEADER_WORDS + CHECKSUM_WORDS);
+        let padding = value_words * 10 % 16;
+        if value_words * 10 < MIN_SECRET_LEN * 8 || padding > 8 {
+            return Err(SharingError::Malformed("slip39 length"));
+        }
+        let extendable = data[1] >> 4 & 1 == 1;
+        if polymod(customization(extendable), &data) != 1 {
+            return Err(SharingError::Malformed("slip39 checksum"));
+        }
+
+        let mut bits = data[HEADER_WORDS..data.len() - CHECKSUM_WORDS]
+            .iter()
+            .flat_map(|word| (0..10).rev().map(move |i| word >> i & 1 == 1));
+        if bits.by_ref().take(padding).any(|bit| bit) {
+            return Err(SharingError::Malformed("slip39 padding"));
+        }
+        let value: Vec<u8> = (0..(value_words * 10 - padding) / 8)
+            .map(|_| {
+                bits.by_ref()
+                    .take(8)
+                    .fold(0, |acc, bit| acc << 1 | u8::from(bit))
+            })
+            .collect();
+        if value.len() % 2 != 0 {
+            return Err(SharingError::Malformed("slip39 length"));
+        }
+
+        let share = Slip39Share {
+            identifier: data[0] << 5 | data[1] >> 5,
+            extendable,
+            iteration_exponent: (data[1] & 0xf) as u8,
+            group_index: (data[2] >> 6) as u8,
+            group_threshold: (data[2] >> 2 & 0xf) as u8 + 1,
+            group_count: ((data[2] & 0x3) << 2 | data[3] >> 8) as u8 + 1,
+            member_index: (data[3] >> 4 & 0xf) as u8,
+            member_threshold: (data[3] & 0xf) as u8 + 1,
+            value,
+        };
+        if share.group_threshold > share.group_count {
+            return Err(SharingError::Malformed("slip39 group threshold"));
+        }
+        Ok(share)
+    }
+}
+
+impl fmt::Display for Slip39Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_mnemonic())
+    }
+}
+
+impl FromStr for Slip39Share {
+    type Err = SharingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_mnemonic(s)
+    }
+}
+
+/// SLIP-39 backups of secrets of at least 16 bytes with an even length.
+///
+/// Every group is a `(member_threshold, member_count)` pair. A group with a threshold of 1
+/// must have a single member, as SLIP-39 forbids several copies of the same share.
+pub struct Slip39SecretSharing<R: Rng> {
+    group_threshold: u8,
+    groups: Vec<(u8, u8)>,
+    passphrase: String,
+    iteration_exponent: u8,
+    extendable: bool,
+    rng: RefCell<R>,
+}
+
+impl<R: Rng> Slip39SecretSharing<R> {
+    /// The parameters are checked when sharing, see [`try_new`](Self::try_new)
+    /// to check them up front.
+    pub fn new(group_threshold: u8, groups: &[(u8, u8)], rng: R) -> Self {
+        Self {
+            group_threshold,
+            groups: groups.to_vec(),
+            passphrase: String::new(),
+            iteration_exponent: 1,
+            extendable: true,
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Like [`new`](Self::new), but fails with [`SharingError::InvalidParameters`]
+    /// unless there are 1 to 16 groups of 1 to 16 members, every threshold is reachable
+    /// and only groups with a threshold of 1 have a single member.
+    pub fn try_new(group_threshold: u8, groups: &[(u8, u8)], rng: R) -> Result<Self, SharingError> {
+        let sharer = Self::new(group_threshold, groups, rng);
+        if !sharer.parameters_valid() {
+            return Err(SharingError::InvalidParameters);
+        }
+        Ok(sharer)
+    }
+
+    /// A single group of `n` shares with threshold `k`.
+    pub fn single(n: u8, k: u8, rng: R) -> Self {
+        Self::new(1, &[(k, n)], rng)
+    }
+
+    /// Encrypt the secret with a passphrase of printable ASCII characters, empty by default.
+    pub fn with_passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = passphrase.to_string();
+        self
+    }
+
+    /// Run 10000·2<sup>e</sup> PBKDF2 iterations, `e` is 1 by default and at most 15.
+    pub fn with_iteration_exponent(mut self, exponent: u8) -> Self {
+        self.iteration_exponent = exponent;
+        self
+    }
+
+    /// Deal backups in the original format, whose encryption depends on the identifier,
+    /// for wallets that predate extendable backups.
+    pub fn with_extendable(mut self, extendable: bool) -> Self {
+        self.extendable = extendable;
+        self
+    }
+
+    fn parameters_valid(&self) -> bool {
+        let groups_valid = self.groups.iter().all(|&(threshold, count)| {
+            (1..=16).contains(&count)
+                && (1..=count).contains(&threshold)
+                && (threshold > 1 || count == 1)
+        });
+        groups_valid
+            && self.groups.len() <= 16
+            && (1..=self.groups.len()).contains(&usize::from(self.group_threshold))
+    }
+
+    fn check(&self, secret: &[u8]) -> Result<(), SharingError> {
+        if !self.parameters_valid()
+            || self.iteration_exponent > 15
+            || secret.len() < MIN_SECRET_LEN
+            || secret.len() % 2 != 0
+            || !self.passphrase.bytes().all(|c| (32..=126).contains(&c))
+        {
+            return Err(SharingError::InvalidParameters);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Rng> Sharing for Slip39SecretSharing<R> {
+    type Share = Slip39Share;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Slip39Share>, SharingError> {
+        self.check(&data)?;
+        let mut rng = self.rng.borrow_mut();
+        let mut identifier = [0u8; 2];
+        try_fill(&mut *rng, &mut identifier)?;
+        let identifier = u16::from_be_bytes(identifier) & 0x7fff;
+
+        let encrypted = crypt(
+            &data,
+            self.passphrase.as_bytes(),
+            self.iteration_exponent,
+            identifier,
+            self.extendable,
+            (0..ROUNDS).collect(),
+        );
+        let group_secrets = grouped::split(
+            self.group_threshold,
+            self.groups.len() as u8,
+            &encrypted,
+            &mut *rng,
+        )?;
+        let mut shares = Vec::new();
+        for ((member_threshold, member_count), (group_index, group_secret)) in
+            self.groups.iter().zip(group_secrets)
+        {
+            for (member_index, value) in
+                grouped::split(*member_threshold, *member_count, &group_secret, &mut *rng)?
+            {
+                shares.push(Slip39Share {
+                    identifier,
+                    extendable: self.extendable,
+                    iteration_exponent: self.iteration_exponent,
+                    group_index,
+                    group_threshold: self.group_threshold,
+                    group_count: self.groups.len() as u8,
+                    member_index,
+                    member_threshold: *member_threshold,
+                    value,
+                });
+            }
+        }
+        Ok(shares)
+    }
+
+    fn reconstruct(&self, shares: Vec<Slip39Share>) -> Result<Vec<u8>, SharingError> {
+        combine(&shares, &self.passphrase)
+    }
+}
+
+/// Recovers the master secret from the shares of a backup. Shares of incomplete groups,
+/// and groups or members beyond the thresholds, are ignored. Shares that
+/// [`from_mnemonic`](Slip39Share::from_mnemonic) would not read are rejected.
+pub fn combine(shares: &[Slip39Share], passphrase: &str) -> Result<Vec<u8>, SharingError> {
+    let first = shares.first().ok_or(SharingError::EmptyShareSet)?;
+    if first.value.len() < MIN_SECRET_LEN || first.value.len() % 2 != 0 {
+        return Err(SharingError::Malformed("slip39 length"));
+    }
+    if first.iteration_exponent > 15 {
+        return Err(SharingError::Malformed("slip39 iteration exponent"));
+    }
+    let mut members = Vec::with_capacity(shares.len());
+    for share in shares {
+        let matches = (
+            share.identifier,
+            share.extendable,
+            share.iteration_exponent,
+            share.group_threshold,
+            share.group_count,
+        ) == (
+            first.identifier,
+            first.extendable,
+            first.iteration_exponent,
+            first.group_threshold,
+            first.group_count,
+        );
+        if !matches || share.value.len() != first.value.len() {
+            return Err(SharingError::MismatchedParameters);
+        }
+        if share.member_threshold == 0 {
+            return Err(SharingError::Malformed("slip39 member threshold"));
+        }
+        members.push(Member {
+            group_index: share.group_index,
+            member_index: share.member_index,
+            member_threshold: share.member_threshold,
+            value: &share.value,
+        });
+    }
+    let encrypted = grouped::combine(first.group_threshold, &members)?;
+    Ok(crypt(
+        &encrypted,
+        passphrase.as_bytes(),
+        first.iteration_exponent,
+        first.identifier,
+        first.extendable,
+        (0..ROUNDS).rev().collect(),
+    ))
+}
+
+/// Like [`combine`], for shares written as words.
+pub fn combine_mnemonics(mnemonics: &[&str], passphrase: &str) -> Result<Vec<u8>, SharingError> {
+    let shares = mnemonics
+        .iter()
+        .map(|mnemonic| Slip39Share::from_mnemonic(mnemonic))
+        .collect::<Result<Vec<_>, _>>()?;
+    combine(&shares, passphrase)
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        b"shamir_extendable"
+    } else {
+        b"shamir"
+    }
+}
+
+fn polymod(customization: &[u8], data: &[u16]) -> u32 {
+    const GENERATOR: [u32; 10] = [
+        0x00e0_e040,
+        0x01c1_c080,
+        0x0383_8100,
+        0x0707_0200,
+        0x0e0e_0009,
+        0x1c0c_2412,
+        0x3808_6c24,
+        0x3090_fc48,
+        0x21b1_f890,
+        0x03f3_f120,
+    ];
+    customization
+        .iter()
+        .map(|c| u32::from(*c))
+        .chain(data.iter().map(|d| u32::from(*d)))
+        .fold(1, |chk, value| {
+            let top = chk >> 20;
+            let chk = (chk & 0xfffff) << 10 ^ value;
+            GENERATOR
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| top >> i & 1 == 1)
+                .fold(chk, |chk, (_, g)| chk ^ g)
+        })
+}
+
+fn checksum(customization: &[u8], data: &[u16]) -> [u16; CHECKSUM_WORDS] {
+    let mut padded = data.to_vec();
+    padded.extend_from_slice(&[0; CHECKSUM_WORDS]);
+    let chk = polymod(customization, &padded) ^ 1;
+    [
+        (chk >> 20) as u16,
+        (chk >> 10 & 0x3ff) as u16,
+        (chk & 0x3ff) as u16,
+    ]
+}
+
+/// The four round Feistel cipher of SLIP-39, encrypting with the rounds in order
+/// and decrypting with them reversed.
+fn crypt(
+    secret: &[u8],
+    passphrase: &[u8],
+    exponent: u8,
+    identifier: u16,
+    extendable: bool,
+    rounds: Vec<u8>,
+) -> Vec<u8> {
+    let (left, right) = secret.split_at(secret.len() / 2);
+    let (mut left, mut right) = (left.to_vec(), right.to_vec());
+    let mut salt = Vec::new();
+    if !extendable {
+        salt.extend_from_slice(b"shamir");
+        salt.extend_from_slice(&identifier.to_be_bytes());
+    }
+    let iterations = (BASE_ITERATIONS / u32::from(ROUNDS)) << exponent;
+    for round in rounds {
+        let mut password = vec![round];
+        password.extend_from_slice(passphrase);
+        let mut round_salt = salt.clone();
+        round_salt.extend_from_slice(&right);
+        let mut f = vec![0u8; right.len()];
+        pbkdf2::pbkdf2_hmac::<Sha256>(&password, &round_salt, iterations, &mut f);
+        for (l, f) in left.iter_mut().zip(&f) {
+            *l ^= f;
+        }
+        std::mem::swap(&mut left, &mut right);
+    }
+    right.extend_from_slice(&left);
+    right
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_new_checks_parameters() {
+        assert!(Slip39SecretSharing::try_new(2, &[(2, 3), (1, 1)], rand::thread_rng()).is_ok());
+        assert!(Slip39SecretSharing::try_new(3, &[(2, 3), (1, 1)], rand::thread_rng()).is_err());
+        assert!(Slip39SecretSharing::try_new(1, &[(4, 3)], rand::thread_rng()).is_err());
+        assert!(Slip39SecretSharing::try_new(1, &[(1, 17)], rand::thread_rng()).is_err());
+        // a single member group must have threshold 1
+        assert!(Slip39SecretSharing::try_new(1, &[(1, 2)], rand::thread_rng()).is_err());
+    }
+
+    #[test]
+    fn word_list() {
+        let list: Vec<&str> = WORDS.lines().collect();
+        assert_eq!(list.len(), 1024);
+        assert!(list.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn reference_vectors() {
+        // from the SLIP-39 test vectors, all with the passphrase "TREZOR"
+        let valid: [(&[&str], &str); 3] = [
+            (
+                &["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard"],
+                "bb54aac4b89dc868ba37d9cc21b2cece",
+            ),
+            (
+                &[
+                    "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+                    "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+                ],
+                "b43ceb7e57a0ea8766221624d01b0864",
+            ),
+            (
+                &[
+                    "eraser senior decision smug corner ruin rescue cubic angel tackle skin skunk program roster trash rumor slush angel flea amazing",
+                    "eraser senior beard romp adorn nuclear spill corner cradle style ancient family general leader ambition exchange unusual garlic promise voice",
+                    "eraser senior decision scared cargo theory device idea deliver modify curly include pancake both news skin realize vitamins away join",
+                ],
+                "7c3397a292a5941682d7a4ae2d898d11",
+            ),
+        ];
+        for (mnemonics, secret) in valid.iter() {
+            assert_eq!(
+                hex(&combine_mnemonics(mnemonics, "TREZOR").unwrap()),
+                *secret
+            );
+            for mnemonic in mnemonics.iter() {
+                assert_eq!(
+                    Slip39Share::from_mnemonic(mnemonic).unwrap().to_mnemonic(),
+                    *mnemonic
+                );
+            }
+        }
+
+        let invalid_digest = [
+            "guilt walnut academic acid deliver remove equip listen vampire tactics nylon rhythm failure husband fatigue alive blind enemy teaspoon rebound",
+            "guilt walnut academic agency brave hamster hobo declare herd taste alpha slim criminal mild arcade formal romp branch pink ambition",
+        ];
+        assert!(matches!(
+            combine_mnemonics(&invalid_digest, "TREZOR"),
+            Err(SharingError::ReconstructionFailed)
+        ));
+        let invalid_checksum = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney";
+        assert!(Slip39Share::from_mnemonic(invalid_checksum).is_err());
+    }
+
+    #[test]
+    fn original_format_round_trips() {
+        let sharer = Slip39SecretSharing::single(3, 2, rand::thread_rng())
+            .with_iteration_exponent(0)
+            .with_extendable(false);
+        let shares = sharer.share(vec![9; 32]).unwrap();
+        let mnemonics: Vec<String> = shares.iter().map(Slip39Share::to_mnemonic).collect();
+        assert_eq!(mnemonics[0].split(' ').count(), 33);
+        let shares: Vec<Slip39Share> = mnemonics[1..].iter().map(|m| m.parse().unwrap()).collect();
+        assert_eq!(sharer.reconstruct(shares).unwrap(), vec![9; 32]);
+        assert!(sharer.share(vec![9; 15]).is_err());
+    }
+}
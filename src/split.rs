@@ -0,0 +1,106 @@
+//! Dealing and Reconstructing
+//!
+//! [`Sharing`] needs both halves of a scheme, and with them the random state only dealing
+//! uses. [`Dealer`] and [`Reconstructor`] each cover one half, so a reconstruct-only
+//! program can be generic over its schemes without carrying an RNG. Every [`Sharing`]
+//! implements both.
+//!
+//! The traits are not exported from the crate root, as their methods share their names
+//! with those of [`Sharing`].
+use crate::{share::Share, Sharing, SharingError};
+
+/// The dealing half of [`Sharing`], for types that only produce shares.
+pub trait Dealer {
+    type Share: Share;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError>;
+
+    /// See [`Sharing::share_into`].
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<Self::Share>) -> Result<(), SharingError> {
+        let shares = self.share(data)?;
+        out.clear();
+        out.extend(shares);
+        Ok(())
+    }
+}
+
+/// The reconstructing half of [`Sharing`], for types that only combine shares
+/// and carry no random state, like the Shamir `Combiner` or the `KrawczykCombiner`.
+///
+/// A reconstruct-only program can be written against this trait alone:
+///
+/// ```rust
+/// # #[cfg(feature = "krawczyk")] {
+/// use chacha20poly1305::ChaCha20Poly1305;
+/// use sharing::{split::Reconstructor, KrawczykCombiner, KrawczykSecretSharing, Sharing};
+///
+/// fn recover<T: Reconstructor>(combiner: &T, shares: Vec<T::Share>) -> Vec<u8> {
+///     combiner.reconstruct(shares).unwrap()
+/// }
+///
+/// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+/// let shares = sharer.share(vec![1, 2, 3]).unwrap();
+///
+/// let combiner: KrawczykCombiner<ChaCha20Poly1305> = KrawczykCombiner::new(2);
+/// assert_eq!(recover(&combiner, shares[1..].to_vec()), vec![1, 2, 3]);
+/// assert_eq!(recover(&sharer, shares[..2].to_vec()), vec![1, 2, 3]);
+/// # }
+/// ```
+pub trait Reconstructor {
+    type Share: Share;
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError>;
+
+    /// The old, misspelled name of [`reconstruct`](Self::reconstruct).
+    #[deprecated(since = "0.2.0", note = "renamed to `reconstruct`")]
+    fn recontruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct(shares)
+    }
+
+    /// See [`Sharing::reconstruct_ref`].
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        self.reconstruct(shares.to_vec())
+    }
+
+    /// See [`Sharing::reconstruct_into`].
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        *out = self.reconstruct_ref(shares)?;
+        Ok(())
+    }
+}
+
+impl<T: Sharing> Dealer for T {
+    type Share = T::Share;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<Self::Share>, SharingError> {
+        Sharing::share(self, data)
+    }
+
+    fn share_into(&self, data: Vec<u8>, out: &mut Vec<Self::Share>) -> Result<(), SharingError> {
+        Sharing::share_into(self, data, out)
+    }
+}
+
+impl<T: Sharing> Reconstructor for T {
+    type Share = T::Share;
+
+    fn reconstruct(&self, shares: Vec<Self::Share>) -> Result<Vec<u8>, SharingError> {
+        Sharing::reconstruct(self, shares)
+    }
+
+    fn reconstruct_ref(&self, shares: &[Self::Share]) -> Result<Vec<u8>, SharingError> {
+        Sharing::reconstruct_ref(self, shares)
+    }
+
+    fn reconstruct_into(
+        &self,
+        shares: &[Self::Share],
+        out: &mut Vec<u8>,
+    ) -> Result<(), SharingError> {
+        Sharing::reconstruct_into(self, shares, out)
+    }
+}
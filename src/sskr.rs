@@ -0,0 +1,445 @@
+//! SSKR
+//!
+//! Sharded Secret Key Reconstruction of [Blockchain Commons], read and written by Gordian
+//! Seed Tool, Sparrow and other wallets. Like SLIP-39, the secret is split among groups and the
+//! share of each group among its members, but without a passphrase and with binary shares.
+//!
+//! Shares are exchanged as [UR]s such as `ur:sskr/...`, or as ByteWords, four letter words
+//! for each byte of the tagged share, both ending in a CRC-32. Shares of the older
+//! `ur:crypto-sskr` type are read too.
+//!
+//! Available with the `sskr` feature.
+//!
+//! [Blockchain Commons]: https://github.com/BlockchainCommons/Research/blob/master/papers/bcr-2020-011-sskr.md
+//! [UR]: https://github.com/BlockchainCommons/Research/blob/master/papers/bcr-2020-005-ur.md
+//!
+//! ```rust
+//! use sharing::{sskr::{self, SskrSecretSharing, SskrShare}, Sharing};
+//!
+//! let sharer = SskrSecretSharing::new(1, &[(2, 3)], rand::thread_rng());
+//! let shares = sharer.share(vec![7; 16]).unwrap();
+//!
+//! let ur = shares[0].to_ur();
+//! assert!(ur.starts_with("ur:sskr/"));
+//! let words = shares[2].to_bytewords();
+//! assert!(words.starts_with("tuna next keep"));
+//!
+//! let shares = vec![
+//!     SskrShare::from_ur(&ur).unwrap(),
+//!     SskrShare::from_bytewords(&words).unwrap(),
+//! ];
+//! assert_eq!(sskr::combine(&shares).unwrap(), vec![7; 16]);
+//! ```
+use crate::{
+    error::SharingError,
+    grouped::{self, Member},
+    secret::try_fill,
+    share::Share,
+    Sharing,
+};
+use rand::Rng;
+use std::{cell::RefCell, fmt, str::FromStr};
+
+/// The 256 ByteWords, four letters each.
+const BYTEWORDS: &[u8; 1024] = b"\
+ableacidalsoapexaquaarchatomauntawayaxisbackbaldbarnbeltbetabiasbluebodybragbrew\
+bulbbuzzcalmcashcatschefcityclawcodecolacookcostcruxcurlcuspcyandarkdatadaysdeli\
+dicedietdoordowndrawdropdrumdulldutyeacheasyechoedgeepicevenexamexiteyesfactfair\
+fernfigsfilmfishfizzflapflewfluxfoxyfreefrogfuelfundgalagamegeargemsgiftgirlglow\
+goodgraygrimgurugushgyrohalfhanghardhawkheathelphighhillholyhopehornhutsicedidea\
+idleinchinkyintoirisironitemjadejazzjoinjoltjowljudojugsjumpjunkjurykeepkenokept\
+keyskickkilnkingkitekiwiknoblamblavalazyleaflegsliarlimplionlistlogoloudloveluau\
+lucklungmainmanymathmazememomenumeowmildmintmissmonknailnavyneednewsnextnoonnote\
+numbobeyoboeomitonyxopenovalowlspaidpartpeckplaypluspoempoolposepuffpumapurrquad\
+quizraceramprealredorichroadrockroofrubyruinrunsrustsafesagascarsetssilkskewslot\
+soapsolosongstubsurfswantacotasktaxitenttiedtimetinytoiltombtoystriptunatwinugly\
+undouniturgeuservastveryvetovialvibeviewvisavoidvowswallwandwarmwaspwavewaxywebs\
+whatwhenwhizwolfworkyankyawnyellyogayurtzapszerozestzinczonezoom";
+
+/// The identifier, thresholds and indices before the value.
+pub const METADATA_LEN: usize = 5;
+/// The shortest secret, in bytes.
+pub const MIN_SECRET_LEN: usize = 16;
+/// The longest secret, in bytes.
+pub const MAX_SECRET_LEN: usize = 32;
+
+/// The CBOR tag of SSKR shares, and the tag of the older `crypto-sskr` type.
+const TAGS: [u16; 2] = [40309, 309];
+
+/// A single member share of an SSKR backup.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SskrShare {
+    /// Random, the same for all shares of a backup.
+    pub identifier: u16,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Vec<u8>,
+}
+
+impl Share for SskrShare {
+    fn size(&self) -> usize {
+        self.value.len()
+    }
+
+    fn with_size(size: usize) -> Self {
+        Self {
+            identifier: 0,
+            group_index: 0,
+            group_threshold: 1,
+            group_count: 1,
+            member_index: 0,
+            member_threshold: 1,
+            value: vec![0; size],
+        }
+    }
+}
+
+impl SskrShare {
+    /// The binary SSKR share: identifier, thresholds and indices in 5 bytes, then the value.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(METADATA_LEN + self.value.len());
+        bytes.extend_from_slice(&self.identifier.to_be_bytes());
+        bytes.push(
+            (self.group_threshold.wrapping_sub(1) & 0xf) << 4
+                | self.group_count.wrapping_sub(1) & 0xf,
+        );
+        bytes.push((self.group_index & 0xf) << 4 | self.member_threshold.wrapping_sub(1) & 0xf);
+        bytes.push(self.member_index & 0xf);
+        bytes.extend_from_slice(&self.value);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SharingError> {
+        if bytes.len() < METADATA_LEN + MIN_SECRET_LEN
+            || bytes.len() > METADATA_LEN + MAX_SECRET_LEN
+            || (bytes.len() - METADATA_LEN) % 2 != 0
+        {
+            return Err(SharingError::Malformed("sskr share length"));
+        }
+        let share = SskrShare {
+            identifier: u16::from_be_bytes([bytes[0], bytes[1]]),
+            group_threshold: (bytes[2] >> 4) + 1,
+            group_count: (bytes[2] & 0xf) + 1,
+            group_index: bytes[3] >> 4,
+            member_threshold: (bytes[3] & 0xf) + 1,
+            member_index: bytes[4] & 0xf,
+            value: bytes[METADATA_LEN..].to_vec(),
+        };
+        // the upper half of the fifth byte is reserved
+        if bytes[4] >> 4 != 0 || share.group_threshold > share.group_count {
+            return Err(SharingError::Malformed("sskr share"));
+        }
+        Ok(share)
+    }
+
+    /// The tagged share as ByteWords separated by spaces, as printed by Seed Tool.
+    pub fn to_bytewords(&self) -> String {
+        let mut cbor = vec![0xd9];
+        cbor.extend_from_slice(&TAGS[0].to_be_bytes());
+        cbor.extend_from_slice(&byte_string(&self.to_bytes()));
+        let words: Vec<&str> = checksummed(&cbor).into_iter().map(word).collect();
+        words.join(" ")
+    }
+
+    /// Reads ByteWords separated by spaces or dashes. Fails with [`SharingError::Malformed`]
+    /// on an unknown word or a wrong checksum.
+    pub fn from_bytewords(words: &str) -> Result<Self, SharingError> {
+        let words = words.trim().to_lowercase();
+        let bytes = words
+            .split(|c: char| c == '-' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                BYTEWORDS
+                    .chunks(4)
+                    .position(|w| w == word.as_bytes())
+                    .map(|i| i as u8)
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(SharingError::Malformed("bytewords word"))?;
+        Self::from_bytes(from_cbor(verify_crc(&bytes)?)?)
+    }
+
+    /// The share as a `ur:sskr` UR.
+    pub fn to_ur(&self) -> String {
+        let payload = checksummed(&byte_string(&self.to_bytes()));
+        let mut ur = String::from("ur:sskr/");
+        for byte in payload {
+            let word = word(byte).as_bytes();
+            ur.push(char::from(word[0]));
+            ur.push(char::from(word[3]));
+        }
+        ur
+    }
+
+    /// Reads a single part `ur:sskr` or `ur:crypto-sskr` UR, in either case.
+    pub fn from_ur(ur: &str) -> Result<Self, SharingError> {
+        let ur = ur.trim().to_lowercase();
+        let payload = ur
+            .strip_prefix("ur:sskr/")
+            .or_else(|| ur.strip_prefix("ur:crypto-sskr/"))
+            .ok_or(SharingError::Malformed("sskr ur type"))?;
+        if payload.len() % 2 != 0 {
+            return Err(SharingError::Malformed("bytewords"));
+        }
+        let bytes = payload
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                BYTEWORDS
+                    .chunks(4)
+                    .position(|w| w[0] == pair[0] && w[3] == pair[1])
+                    .map(|i| i as u8)
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(SharingError::Malformed("bytewords word"))?;
+        Self::from_bytes(from_cbor(verify_crc(&bytes)?)?)
+    }
+}
+
+/// Writes the share as a UR.
+impl fmt::Display for SskrShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ur())
+    }
+}
+
+/// Reads a UR or ByteWords.
+impl FromStr for SskrShare {
+    type Err = SharingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim_start().to_lowercase().starts_with("ur:") {
+            Self::from_ur(s)
+        } else {
+            Self::from_bytewords(s)
+        }
+    }
+}
+
+/// SSKR backups of secrets of 16 to 32 bytes with an even length.
+///
+/// Every group is a `(member_threshold, member_count)` pair, at most 16 groups of
+/// at most 16 members.
+pub struct SskrSecretSharing<R: Rng> {
+    group_threshold: u8,
+    groups: Vec<(u8, u8)>,
+    rng: RefCell<R>,
+}
+
+impl<R: Rng> SskrSecretSharing<R> {
+    /// The parameters are checked when sharing, see [`try_new`](Self::try_new)
+    /// to check them up front.
+    pub fn new(group_threshold: u8, groups: &[(u8, u8)], rng: R) -> Self {
+        Self {
+            group_threshold,
+            groups: groups.to_vec(),
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Like [`new`](Self::new), but fails with [`SharingError::InvalidParameters`]
+    /// unless there are 1 to 16 groups of 1 to 16 members and every threshold is reachable.
+    pub fn try_new(group_threshold: u8, groups: &[(u8, u8)], rng: R) -> Result<Self, SharingError> {
+        let sharer = Self::new(group_threshold, groups, rng);
+        if !sharer.parameters_valid() {
+            return Err(SharingError::InvalidParameters);
+        }
+        Ok(sharer)
+    }
+
+    /// A single group of `n` shares with threshold `k`.
+    pub fn single(n: u8, k: u8, rng: R) -> Self {
+        Self::new(1, &[(k, n)], rng)
+    }
+
+    fn parameters_valid(&self) -> bool {
+        let groups_valid = self.groups.iter().all(|&(threshold, count)| {
+            (1..=16).contains(&count) && (1..=count).contains(&threshold)
+        });
+        groups_valid
+            && self.groups.len() <= 16
+            && (1..=self.groups.len()).contains(&usize::from(self.group_threshold))
+    }
+}
+
+impl<R: Rng> Sharing for SskrSecretSharing<R> {
+    type Share = SskrShare;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<SskrShare>, SharingError> {
+        if !self.parameters_valid()
+            || !(MIN_SECRET_LEN..=MAX_SECRET_LEN).contains(&data.len())
+            || data.len() % 2 != 0
+        {
+            return Err(SharingError::InvalidParameters);
+        }
+        let mut rng = self.rng.borrow_mut();
+        let mut identifier = [0u8; 2];
+        try_fill(&mut *rng, &mut identifier)?;
+
+        let group_secrets = grouped::split(
+            self.group_threshold,
+            self.groups.len() as u8,
+            &data,
+            &mut *rng,
+        )?;
+        let mut shares = Vec::new();
+        for ((member_threshold, member_count), (group_index, group_secret)) in
+            self.groups.iter().zip(group_secrets)
+        {
+            for (member_index, value) in
+                grouped::split(*member_threshold, *member_count, &group_secret, &mut *rng)?
+            {
+                shares.push(SskrShare {
+                    identifier: u16::from_be_bytes(identifier),
+                    group_index,
+                    group_threshold: self.group_threshold,
+                    group_count: self.groups.len() as u8,
+                    member_index,
+                    member_threshold: *member_threshold,
+                    value,
+                });
+            }
+        }
+        Ok(shares)
+    }
+
+    fn reconstruct(&self, shares: Vec<SskrShare>) -> Result<Vec<u8>, SharingError> {
+        combine(&shares)
+    }
+}
+
+/// Recovers the secret from the shares of a backup. Shares of incomplete groups,
+/// and groups or members beyond the thresholds, are ignored.
+pub fn combine(shares: &[SskrShare]) -> Result<Vec<u8>, SharingError> {
+    let first = shares.first().ok_or(SharingError::EmptyShareSet)?;
+    let mut members = Vec::with_capacity(shares.len());
+    for share in shares {
+        let matches = (share.identifier, share.group_threshold, share.group_count)
+            == (first.identifier, first.group_threshold, first.group_count);
+        if !matches || share.value.len() != first.value.len() {
+            return Err(SharingError::MismatchedParameters);
+        }
+        members.push(Member {
+            group_index: share.group_index,
+            member_index: share.member_index,
+            member_threshold: share.member_threshold,
+            value: &share.value,
+        });
+    }
+    grouped::combine(first.group_threshold, &members)
+}
+
+fn word(byte: u8) -> &'static str {
+    let i = usize::from(byte) * 4;
+    std::str::from_utf8(&BYTEWORDS[i..i + 4]).unwrap_or_default()
+}
+
+/// The bytes followed by their CRC-32.
+fn checksummed(bytes: &[u8]) -> Vec<u8> {
+    let mut out = bytes.to_vec();
+    out.extend_from_slice(&crc32(bytes).to_be_bytes());
+    out
+}
+
+fn verify_crc(bytes: &[u8]) -> Result<&[u8], SharingError> {
+    if bytes.len() < 4 {
+        return Err(SharingError::Malformed("bytewords"));
+    }
+    let (payload, crc) = bytes.split_at(bytes.len() - 4);
+    if crc != crc32(payload).to_be_bytes() {
+        return Err(SharingError::Malformed("bytewords checksum"));
+    }
+    Ok(payload)
+}
+
+/// CRC-32 (ISO-HDLC), as used by zip and PNG.
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+/// A CBOR byte string of at most 255 bytes.
+fn byte_string(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(2 + bytes.len());
+    if bytes.len() < 24 {
+        out.push(0x40 | bytes.len() as u8);
+    } else {
+        out.extend_from_slice(&[0x58, bytes.len() as u8]);
+    }
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// The contents of a CBOR byte string, optionally tagged as an SSKR share.
+fn from_cbor(cbor: &[u8]) -> Result<&[u8], SharingError> {
+    const MALFORMED: SharingError = SharingError::Malformed("sskr cbor");
+    let cbor = match cbor {
+        [0xd9, high, low, rest @ ..] if TAGS.contains(&u16::from_be_bytes([*high, *low])) => rest,
+        _ => cbor,
+    };
+    let (length, bytes) = match cbor {
+        [0x58, length, rest @ ..] => (usize::from(*length), rest),
+        [head, rest @ ..] if (0x40..0x58).contains(head) => (usize::from(head - 0x40), rest),
+        _ => return Err(MALFORMED),
+    };
+    if bytes.len() != length {
+        return Err(MALFORMED);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_new_checks_parameters() {
+        assert!(SskrSecretSharing::try_new(2, &[(2, 3), (1, 1)], rand::thread_rng()).is_ok());
+        assert!(SskrSecretSharing::try_new(3, &[(2, 3), (1, 1)], rand::thread_rng()).is_err());
+        assert!(SskrSecretSharing::try_new(1, &[(4, 3)], rand::thread_rng()).is_err());
+        assert!(SskrSecretSharing::try_new(1, &[(1, 17)], rand::thread_rng()).is_err());
+    }
+
+    #[test]
+    fn bytewords_reference_values() {
+        // from the ByteWords specification
+        assert_eq!(crc32(b"Hello, world!"), 0xebe6_c6e6);
+        assert_eq!(
+            checksummed(&[0]).into_iter().map(word).collect::<Vec<_>>(),
+            ["able", "tied", "also", "webs", "lung"]
+        );
+        let mut minimal: Vec<(u8, u8)> = BYTEWORDS.chunks(4).map(|w| (w[0], w[3])).collect();
+        minimal.sort_unstable();
+        minimal.dedup();
+        assert_eq!(minimal.len(), 256);
+    }
+
+    #[test]
+    fn reference_shares() {
+        // dealt by the `sskr` crate of Blockchain Commons, two of two groups of (2, 3) and (1, 1)
+        let shares: Vec<SskrShare> = [
+            "ur:sskr/hdcapsahbyadadsaoepygdmeqzidpsaybdreyauomovynnbdwnkipsgubslarhpmeolrnn",
+            "tuna next keep hard cola plus arch body acid also figs limp aunt fair oboe bulb zest yurt lava away loud part onyx rich miss mild back menu good claw logo pool join jury bald road zaps wolf",
+            "ur:sskr/hdcapsahbybeaelszcmosnlpclbkidsetoeybkwersonsfsgehjssotocmiskpaybgzeiy",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        assert_eq!(shares[1].to_bytewords(), "tuna next keep hard cola plus arch body acid also figs limp aunt fair oboe bulb zest yurt lava away loud part onyx rich miss mild back menu good claw logo pool join jury bald road zaps wolf");
+        let secret: Vec<u8> = combine(&shares).unwrap();
+        assert_eq!(
+            secret,
+            [
+                0x0f, 0xf7, 0x84, 0xdf, 0x00, 0x0c, 0x43, 0x80, 0xa5, 0xed, 0x68, 0x3f, 0x7e, 0x6e,
+                0x3d, 0xcf, 0x20, 0x41, 0x88, 0xbf, 0xa6, 0xb4, 0x40, 0xa1
+            ]
+        );
+        assert!(combine(&shares[1..]).is_err());
+    }
+}
@@ -0,0 +1,540 @@
+//! ssss
+//!
+//! Shares of B. Poettering's `ssss-split` and `ssss-combine` tools, lines like
+//! `2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665`. Unlike the schemes of this crate, `ssss`
+//! treats the whole secret as one element of GF(2<sup>n</sup>), with `n` eight times the
+//! length of the secret, its security level. It also scrambles secrets of 8 bytes and more with
+//! a keyless XTEA based diffusion layer before sharing, unless it is run with `-D`.
+//!
+//! Available with the `ssss` feature.
+//!
+//! ```rust
+//! use sharing::{ssss::{self, SsssSecretSharing, SsssShare}, Sharing};
+//!
+//! // from `ssss-split -t 3 -n 5`
+//! let lines = [
+//!     "1-1c41ef496eccfbeba439714085df8437236298da8dd824",
+//!     "3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309",
+//!     "4-468de7d6eb36674c9cf008c8e8fc8c566537ad6301eb9e",
+//! ];
+//! let shares: Vec<SsssShare> = lines.iter().map(|line| line.parse().unwrap()).collect();
+//! assert_eq!(ssss::combine(&shares, true).unwrap(), b"my secret root password");
+//!
+//! // and shares for `ssss-combine -t 2`
+//! let sharer = SsssSecretSharing::new(3, 2, rand::thread_rng()).with_token("backup");
+//! let shares = sharer.share(b"hunter2".to_vec()).unwrap();
+//! assert!(shares[0].to_string().starts_with("backup-1-"));
+//! assert_eq!(sharer.reconstruct(shares[1..].to_vec()).unwrap(), b"hunter2");
+//! ```
+use crate::{error::SharingError, secret::try_fill, share::Share, Sharing};
+use rand::Rng;
+use std::{cell::RefCell, fmt, str::FromStr};
+
+/// The longest secret `ssss` shares, in bytes.
+pub const MAX_SECRET_LEN: usize = 128;
+
+/// The middle terms of the irreducible pentanomials `ssss` uses for GF(2<sup>8i</sup>),
+/// `x^8i + x^a + x^b + x^c + 1`.
+#[rustfmt::skip]
+const POLYNOMIALS: [[u8; 3]; 128] = [
+    [4, 3, 1], [5, 3, 1], [4, 3, 1], [7, 3, 2], [5, 4, 3], [5, 3, 2], [7, 4, 2], [4, 3, 1],
+    [10, 9, 3], [9, 4, 2], [7, 6, 2], [10, 9, 6], [4, 3, 1], [5, 4, 3], [4, 3, 1], [7, 2, 1],
+    [5, 3, 2], [7, 4, 2], [6, 3, 2], [5, 3, 2], [15, 3, 2], [11, 3, 2], [9, 8, 7], [7, 2, 1],
+    [5, 3, 2], [9, 3, 1], [7, 3, 1], [9, 8, 3], [9, 4, 2], [8, 5, 3], [15, 14, 10], [10, 5, 2],
+    [9, 6, 2], [9, 3, 2], [9, 5, 2], [11, 10, 1], [7, 3, 2], [11, 2, 1], [9, 7, 4], [4, 3, 1],
+    [8, 3, 1], [7, 4, 1], [7, 2, 1], [13, 11, 6], [5, 3, 2], [7, 3, 2], [8, 7, 5], [12, 3, 2],
+    [13, 10, 6], [5, 3, 2], [5, 3, 2], [9, 5, 2], [9, 7, 2], [13, 4, 3], [4, 3, 1], [11, 6, 4],
+    [18, 9, 6], [19, 18, 13], [11, 3, 2], [15, 9, 6], [4, 3, 1], [16, 5, 2], [15, 14, 6], [8, 5, 2],
+    [15, 11, 2], [11, 6, 2], [7, 5, 3], [8, 3, 1], [19, 16, 9], [11, 9, 6], [15, 7, 6], [13, 4, 3],
+    [14, 13, 3], [13, 6, 3], [9, 5, 2], [19, 13, 6], [19, 10, 3], [11, 6, 5], [9, 2, 1], [14, 3, 2],
+    [13, 3, 1], [7, 5, 4], [11, 9, 8], [11, 6, 5], [23, 16, 9], [19, 14, 6], [23, 10, 2], [8, 3, 2],
+    [5, 4, 3], [9, 6, 4], [4, 3, 2], [13, 8, 6], [13, 11, 1], [13, 10, 3], [11, 6, 5], [19, 17, 4],
+    [15, 14, 7], [13, 9, 6], [9, 7, 3], [9, 7, 1], [14, 3, 2], [11, 8, 2], [11, 6, 4], [13, 5, 2],
+    [11, 5, 1], [11, 4, 1], [19, 10, 3], [21, 10, 6], [13, 3, 1], [15, 7, 5], [19, 18, 10], [7, 5, 3],
+    [12, 7, 2], [7, 5, 1], [14, 9, 6], [10, 3, 2], [15, 13, 12], [12, 11, 9], [16, 9, 7], [12, 9, 3],
+    [9, 5, 2], [17, 10, 6], [24, 9, 3], [17, 15, 13], [5, 4, 3], [19, 17, 8], [15, 6, 3], [19, 6, 1],
+];
+
+/// A share as written by `ssss-split`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SsssShare {
+    /// The prefix given with `ssss-split -w`.
+    pub token: Option<String>,
+    pub id: u8,
+    /// A big endian number, as long as the secret.
+    pub value: Vec<u8>,
+}
+
+impl Share for SsssShare {
+    fn size(&self) -> usize {
+        self.value.len()
+    }
+
+    fn with_size(size: usize) -> Self {
+        Self {
+            token: None,
+            id: 0,
+            value: vec![0; size],
+        }
+    }
+}
+
+/// Writes the share as `ssss-split` does, without padding the id to the width of the
+/// largest one, which `ssss-combine` does not need.
+impl fmt::Display for SsssShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(token) = &self.token {
+            write!(f, "{}-", token)?;
+        }
+        write!(f, "{}-", self.id)?;
+        self.value.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl FromStr for SsssShare {
+    type Err = SharingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const MALFORMED: SharingError = SharingError::Malformed("ssss share");
+        let mut parts: Vec<&str> = s.trim().split('-').collect();
+        let hex = parts.pop().ok_or(MALFORMED)?;
+        let id = parts.pop().ok_or(MALFORMED)?;
+        let token = match parts.as_slice() {
+            [] => None,
+            [token] => Some(token.to_string()),
+            _ => return Err(MALFORMED),
+        };
+        let id = id.parse::<u8>().map_err(|_| MALFORMED)?;
+        if id == 0 || hex.is_empty() || hex.len() % 2 != 0 || hex.len() > 2 * MAX_SECRET_LEN {
+            return Err(MALFORMED);
+        }
+        let value = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(MALFORMED)?;
+        Ok(Self { token, id, value })
+    }
+}
+
+/// Shares for `ssss-combine` of secrets of up to 128 bytes, at the security level of the
+/// length of the secret, like `ssss-split` run without `-s`.
+pub struct SsssSecretSharing<R: Rng> {
+    n: u8,
+    k: u8,
+    token: Option<String>,
+    diffusion: bool,
+    rng: RefCell<R>,
+}
+
+impl<R: Rng> SsssSecretSharing<R> {
+    /// The parameters are checked when sharing, `ssss` needs `2 <= k <= n`.
+    /// See [`try_new`](Self::try_new) to check them up front.
+    pub fn new(n: u8, k: u8, rng: R) -> Self {
+        Self {
+            n,
+            k,
+            token: None,
+            diffusion: true,
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Like [`new`](Self::new), but fails with [`SharingError::InvalidParameters`]
+    /// unless `2 <= k <= n`.
+    pub fn try_new(n: u8, k: u8, rng: R) -> Result<Self, SharingError> {
+        if k < 2 || k > n {
+            return Err(SharingError::InvalidParameters);
+        }
+        Ok(Self::new(n, k, rng))
+    }
+
+    /// Prefix every share with a token, like `ssss-split -w`. It must not contain a `-`.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Share without the diffusion layer, like `ssss-split -D`.
+    pub fn without_diffusion(mut self) -> Self {
+        self.diffusion = false;
+        self
+    }
+}
+
+impl<R: Rng> Sharing for SsssSecretSharing<R> {
+    type Share = SsssShare;
+
+    fn share(&self, data: Vec<u8>) -> Result<Vec<SsssShare>, SharingError> {
+        if self.k < 2
+            || self.k > self.n
+            || data.is_empty()
+            || data.len() > MAX_SECRET_LEN
+            || self.token.as_deref().is_some_and(|t| t.contains('-'))
+        {
+            return Err(SharingError::InvalidParameters);
+        }
+        let field = GaloisField::new(data.len() * 8);
+        let mut secret = data;
+        if self.diffusion {
+            diffuse(&mut secret, true);
+        }
+        // the polynomial is monic, x^k + c[k-1]·x^(k-1) + … + c[1]·x + secret
+        let mut coefficients = vec![field.element(&secret)];
+        let mut rng = self.rng.borrow_mut();
+        for _ in 1..self.k {
+            let mut random = vec![0u8; secret.len()];
+            try_fill(&mut *rng, &mut random)?;
+            coefficients.push(field.element(&random));
+        }
+        let shares = (1..=self.n)
+            .map(|id| {
+                let x = field.small(id);
+                let mut y = x.clone();
+                for c in coefficients[1..].iter().rev() {
+                    y.xor(c);
+                    y = field.mul(&y, &x);
+                }
+                y.xor(&coefficients[0]);
+                SsssShare {
+                    token: self.token.clone(),
+                    id,
+                    value: field.to_bytes(&y),
+                }
+            })
+            .collect();
+        Ok(shares)
+    }
+
+    /// Combines the first `k` shares.
+    fn reconstruct(&self, shares: Vec<SsssShare>) -> Result<Vec<u8>, SharingError> {
+        let k = usize::from(self.k);
+        if shares.len() < k {
+            return Err(SharingError::reconstruction(self.k, shares.len()));
+        }
+        combine(&shares[..k], self.diffusion)
+    }
+}
+
+/// Recovers the secret like `ssss-combine -t <shares.len()>`, so exactly the threshold
+/// number of shares must be given. Pass `false` for shares dealt with `ssss-split -D`.
+pub fn combine(shares: &[SsssShare], diffusion: bool) -> Result<Vec<u8>, SharingError> {
+    let first = shares.first().ok_or(SharingError::EmptyShareSet)?;
+    if !(1..=MAX_SECRET_LEN).contains(&first.value.len()) {
+        return Err(SharingError::Malformed("ssss share"));
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.id == 0 {
+            return Err(SharingError::Malformed("ssss share"));
+        }
+        if share.value.len() != first.value.len() {
+            return Err(SharingError::BodyLengthMismatch {
+                id: share.id,
+                expected: first.value.len(),
+                found: share.value.len(),
+            });
+        }
+        if shares[..i].iter().any(|s| s.id == share.id) {
+            return Err(SharingError::DuplicateShareId(share.id));
+        }
+    }
+    let field = GaloisField::new(first.value.len() * 8);
+    // remove the known leading term x^k, then interpolate at 0
+    let points: Vec<(Element, Element)> = shares
+        .iter()
+        .map(|share| {
+            let x = field.small(share.id);
+            let mut y = field.element(&share.value);
+            y.xor(&(0..shares.len()).fold(field.small(1), |acc, _| field.mul(&acc, &x)));
+            (x, y)
+        })
+        .collect();
+    let mut secret = field.small(0);
+    for (j, (xj, yj)) in points.iter().enumerate() {
+        let (mut numerator, mut denominator) = (field.small(1), field.small(1));
+        for (xm, _) in points
+            .iter()
+            .enumerate()
+            .filter(|(m, _)| *m != j)
+            .map(|(_, p)| p)
+        {
+            numerator = field.mul(&numerator, xm);
+            let mut difference = xm.clone();
+            difference.xor(xj);
+            denominator = field.mul(&denominator, &difference);
+        }
+        let inverse = field
+            .inverse(&denominator)
+            .ok_or(SharingError::DuplicateShareId(first.id))?;
+        secret.xor(&field.mul(&field.mul(&numerator, &inverse), yj));
+    }
+    let mut secret = field.to_bytes(&secret);
+    if diffusion {
+        diffuse(&mut secret, false);
+    }
+    Ok(secret)
+}
+
+/// A polynomial over GF(2) as little endian 64 bit limbs.
+#[derive(Clone, PartialEq)]
+struct Element(Vec<u64>);
+
+impl Element {
+    fn bit(&self, i: usize) -> bool {
+        self.0
+            .get(i / 64)
+            .is_some_and(|limb| limb >> (i % 64) & 1 == 1)
+    }
+
+    fn set(&mut self, i: usize) {
+        if let Some(limb) = self.0.get_mut(i / 64) {
+            *limb |= 1 << (i % 64);
+        }
+    }
+
+    fn xor(&mut self, other: &Element) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a ^= b);
+    }
+
+    fn shift_left(&mut self) {
+        let mut carry = 0;
+        for limb in self.0.iter_mut() {
+            let next = *limb >> 63;
+            *limb = *limb << 1 | carry;
+            carry = next;
+        }
+    }
+
+    /// `self += other · x^shift`, dropping bits beyond the limbs.
+    fn xor_shifted(&mut self, other: &Element, shift: usize) {
+        let (words, bits) = (shift / 64, shift % 64);
+        for (i, limb) in other.0.iter().enumerate() {
+            if let Some(a) = self.0.get_mut(i + words) {
+                *a ^= limb << bits;
+            }
+            if bits > 0 {
+                if let Some(a) = self.0.get_mut(i + words + 1) {
+                    *a ^= limb >> (64 - bits);
+                }
+            }
+        }
+    }
+
+    fn degree(&self) -> Option<usize> {
+        let i = self.0.iter().rposition(|limb| *limb != 0)?;
+        Some(i * 64 + 63 - self.0[i].leading_zeros() as usize)
+    }
+}
+
+/// GF(2^n) for `n` a multiple of 8 up to 1024, with the polynomials of `ssss`.
+struct GaloisField {
+    degree: usize,
+    modulus: Element,
+}
+
+impl GaloisField {
+    fn new(degree: usize) -> Self {
+        let mut field = GaloisField {
+            degree,
+            modulus: Element(vec![0; degree / 64 + 1]),
+        };
+        field.modulus.set(degree);
+        field.modulus.set(0);
+        for term in POLYNOMIALS[degree / 8 - 1].iter() {
+            field.modulus.set(usize::from(*term));
+        }
+        field
+    }
+
+    fn small(&self, value: u8) -> Element {
+        let mut element = Element(vec![0; self.degree / 64 + 1]);
+        element.0[0] = u64::from(value);
+        element
+    }
+
+    fn element(&self, bytes: &[u8]) -> Element {
+        let mut element = self.small(0);
+        for (i, byte) in bytes.iter().rev().enumerate() {
+            element.0[i / 8] |= u64::from(*byte) << (i % 8 * 8);
+        }
+        element
+    }
+
+    fn to_bytes(&self, element: &Element) -> Vec<u8> {
+        (0..self.degree / 8)
+            .rev()
+            .map(|i| (element.0[i / 8] >> (i % 8 * 8)) as u8)
+            .collect()
+    }
+
+    fn mul(&self, a: &Element, b: &Element) -> Element {
+        let mut product = self.small(0);
+        for i in (0..self.degree).rev() {
+            product.shift_left();
+            if product.bit(self.degree) {
+                product.xor(&self.modulus);
+            }
+            if b.bit(i) {
+                product.xor(a);
+            }
+        }
+        product
+    }
+
+    /// The inverse by the extended Euclidean algorithm, `None` for zero.
+    fn inverse(&self, a: &Element) -> Option<Element> {
+        let (mut u, mut v) = (a.clone(), self.modulus.clone());
+        let (mut g1, mut g2) = (self.small(1), self.small(0));
+        loop {
+            let du = u.degree()?;
+            if du == 0 {
+                return Some(g1);
+            }
+            let dv = v.degree()?;
+            if du < dv {
+                std::mem::swap(&mut u, &mut v);
+                std::mem::swap(&mut g1, &mut g2);
+                continue;
+            }
+            u.xor_shifted(&v, du - dv);
+            g1.xor_shifted(&g2, du - dv);
+        }
+    }
+}
+
+/// The diffusion layer of `ssss`: XTEA with a zero key over overlapping 8 byte windows of the
+/// secret, in the byte order `ssss` stores it in. Secrets shorter than 8 bytes are left alone.
+fn diffuse(secret: &mut [u8], encode: bool) {
+    let len = secret.len();
+    if len < 8 {
+        return;
+    }
+    // 16 bit words from the least significant, each big endian
+    let mut v: Vec<u8> = secret
+        .rchunks(2)
+        .flat_map(|pair| pair.iter().copied())
+        .collect();
+    let rounds = (0..40 * len).step_by(2);
+    let block = |v: &mut [u8], i: usize| {
+        let mut words = [0u32; 2];
+        for (w, word) in words.iter_mut().enumerate() {
+            *word = (0..4).fold(0, |acc, b| acc << 8 | u32::from(v[(i + 4 * w + b) % len]));
+        }
+        if encode {
+            encipher(&mut words);
+        } else {
+            decipher(&mut words);
+        }
+        for (w, word) in words.iter().enumerate() {
+            for b in 0..4 {
+                v[(i + 4 * w + b) % len] = (word >> (24 - 8 * b)) as u8;
+            }
+        }
+    };
+    if encode {
+        rounds.for_each(|i| block(&mut v, i));
+    } else {
+        rounds.rev().for_each(|i| block(&mut v, i));
+    }
+    let restored: Vec<u8> = v
+        .chunks(2)
+        .rev()
+        .flat_map(|pair| pair.iter().copied())
+        .collect();
+    secret.copy_from_slice(&restored);
+}
+
+const DELTA: u32 = 0x9e37_79b9;
+
+fn encipher(v: &mut [u32; 2]) {
+    let mut sum = 0u32;
+    for _ in 0..32 {
+        v[0] = v[0].wrapping_add(((v[1] << 4 ^ v[1] >> 5).wrapping_add(v[1])) ^ sum);
+        sum = sum.wrapping_add(DELTA);
+        v[1] = v[1].wrapping_add(((v[0] << 4 ^ v[0] >> 5).wrapping_add(v[0])) ^ sum);
+    }
+}
+
+fn decipher(v: &mut [u32; 2]) {
+    let mut sum = DELTA.wrapping_mul(32);
+    for _ in 0..32 {
+        v[1] = v[1].wrapping_sub(((v[0] << 4 ^ v[0] >> 5).wrapping_add(v[0])) ^ sum);
+        sum = sum.wrapping_sub(DELTA);
+        v[0] = v[0].wrapping_sub(((v[1] << 4 ^ v[1] >> 5).wrapping_add(v[1])) ^ sum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn man_page_shares() {
+        // the example of the ssss manual, dealt with `-t 3 -n 5`
+        let shares: Vec<SsssShare> = [
+            "1-1c41ef496eccfbeba439714085df8437236298da8dd824",
+            "2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665",
+            "3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309",
+            "4-468de7d6eb36674c9cf008c8e8fc8c566537ad6301eb9e",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        for skip in 0..shares.len() {
+            let mut three = shares.clone();
+            three.remove(skip);
+            assert_eq!(combine(&three, true).unwrap(), b"my secret root password");
+        }
+        assert_ne!(combine(&shares, true).unwrap(), b"my secret root password");
+        assert_eq!(
+            shares[1].to_string(),
+            "2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        for len in [1, 7, 8, 9, 16, 33, MAX_SECRET_LEN].iter() {
+            let secret: Vec<u8> = (0..*len as u8).collect();
+            let mut diffused = secret.clone();
+            diffuse(&mut diffused, true);
+            diffuse(&mut diffused, false);
+            assert_eq!(diffused, secret);
+
+            let sharer = SsssSecretSharing::new(4, 3, rand::thread_rng()).without_diffusion();
+            let shares = sharer.share(secret.clone()).unwrap();
+            assert_eq!(sharer.reconstruct(shares[1..].to_vec()).unwrap(), secret);
+        }
+        assert!(SsssSecretSharing::try_new(4, 1, rand::thread_rng()).is_err());
+        assert!(SsssSecretSharing::try_new(2, 3, rand::thread_rng()).is_err());
+        assert!("x-y-1-00".parse::<SsssShare>().is_err());
+        assert!("0-00".parse::<SsssShare>().is_err());
+        assert!("1-0".parse::<SsssShare>().is_err());
+
+        let share = |id, len| SsssShare {
+            token: None,
+            id,
+            value: vec![1; len],
+        };
+        assert!(combine(&[share(1, 0), share(2, 0)], true).is_err());
+        assert!(combine(&[share(1, MAX_SECRET_LEN + 1)], true).is_err());
+        assert!(combine(&[share(0, 8), share(1, 8)], true).is_err());
+    }
+
+    #[test]
+    fn strict_parsing() {
+        use crate::{
+            strict::{Limits, StrictStr},
+            Parameters,
+        };
+
+        let limits = Limits::default().with_parameters(Parameters::new(5, 3).unwrap());
+        assert!(SsssShare::from_str_strict("5-00ff", &limits).is_ok());
+        assert!(matches!(
+            SsssShare::from_str_strict("6-00ff", &limits),
+            Err(SharingError::MismatchedParameters)
+        ));
+        let small = Limits::default().with_max_size(4);
+        assert!(SsssShare::from_str_strict("5-00ff", &small).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Proptest Strategies
+//!
+//! Available with the `proptest` feature.
+//!
+//! ```rust
+//! use proptest::{prelude::*, test_runner::TestRunner};
+//! use sharing::{strategies, ShamirSecretSharing, Sharing};
+//!
+//! let mut runner = TestRunner::default();
+//! runner
+//!     .run(&(strategies::parameters_up_to(10), strategies::secret(64)), |(params, data)| {
+//!         let sharer = ShamirSecretSharing::new(params.n(), params.k(), rand::thread_rng());
+//!         let shares = sharer.share(data.clone()).unwrap();
+//!         prop_assert_eq!(sharer.reconstruct(shares).unwrap(), data);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+use crate::{
+    share::{KrawczykShare, RabinShare, ShamirShare},
+    Parameters,
+};
+use proptest::{collection, prelude::*, sample};
+use std::fmt::Debug;
+
+/// Valid `(n, k)` parameters with `1 <= k <= n <= 255`.
+pub fn parameters() -> impl Strategy<Value = Parameters> {
+    parameters_up_to(255)
+}
+
+/// Valid `(n, k)` parameters with `1 <= k <= n <= max_n`.
+pub fn parameters_up_to(max_n: u8) -> impl Strategy<Value = Parameters> {
+    (1..=max_n.max(1))
+        .prop_flat_map(|n| (Just(n), 1..=n))
+        .prop_map(|(n, k)| Parameters { n, k })
+}
+
+/// Secrets of up to `max_len` bytes.
+pub fn secret(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    collection::vec(any::<u8>(), 0..=max_len)
+}
+
+/// Shuffled subsets of at least `k` of the given shares.
+pub fn share_subset<S: Clone + Debug + 'static>(
+    shares: Vec<S>,
+    k: u8,
+) -> impl Strategy<Value = Vec<S>> {
+    let len = shares.len();
+    sample::subsequence(shares, (k as usize).min(len)..=len).prop_shuffle()
+}
+
+/// Share types whose id and body can be mutated by [`corrupted`].
+pub trait Corruptible: Clone + Debug + 'static {
+    fn id_mut(&mut self) -> &mut u8;
+    fn body_mut(&mut self) -> &mut Vec<u8>;
+}
+
+impl Corruptible for ShamirShare {
+    fn id_mut(&mut self) -> &mut u8 {
+        &mut self.id
+    }
+    fn body_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.body
+    }
+}
+
+impl Corruptible for RabinShare {
+    fn id_mut(&mut self) -> &mut u8 {
+        &mut self.id
+    }
+    fn body_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.body
+    }
+}
+
+impl Corruptible for KrawczykShare {
+    fn id_mut(&mut self) -> &mut u8 {
+        &mut self.id
+    }
+    fn body_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.body
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Mutation {
+    FlipBit(usize, u8),
+    Id(u8),
+    Truncate(usize),
+    Extend(u8),
+}
+
+/// Copies of `share` with a single corruption: a flipped body bit, a changed id,
+/// a truncated body or an extra body byte. The result always differs from the input.
+pub fn corrupted<S: Corruptible>(share: S) -> impl Strategy<Value = S> {
+    let len = share.clone().body_mut().len();
+    let mut mutations = vec![
+        (1..=255u8).prop_map(Mutation::Id).boxed(),
+        any::<u8>().prop_map(Mutation::Extend).boxed(),
+    ];
+    if len > 0 {
+        mutations.push(
+            (0..len, 0..8u8)
+                .prop_map(|(i, b)| Mutation::FlipBit(i, b))
+                .boxed(),
+        );
+        mutations.push((0..len).prop_map(Mutation::Truncate).boxed());
+    }
+    sample::select(mutations)
+        .prop_flat_map(|m| m)
+        .prop_map(move |mutation| {
+            let mut share = share.clone();
+            match mutation {
+                Mutation::FlipBit(i, b) => share.body_mut()[i] ^= 1 << b,
+                Mutation::Id(delta) => {
+                    let id = share.id_mut();
+                    *id = id.wrapping_add(delta);
+                }
+                Mutation::Truncate(len) => share.body_mut().truncate(len),
+                Mutation::Extend(byte) => share.body_mut().push(byte),
+            }
+            share
+        })
+}
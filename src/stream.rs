@@ -0,0 +1,347 @@
+//! Streaming Krawczyk
+//!
+//! Arbitrarily large inputs are encrypted frame by frame with the STREAM construction
+//! and every frame is dispersed on its own, so neither side needs the whole input in memory.
+//! The frame counter and a final-frame flag are part of every nonce, so reordered, dropped
+//! or truncated frames fail to decrypt. A stream resumed with
+//! [`KrawczykSecretSharing::resume_encryptor`] seals its frames in a new session, which is
+//! part of the nonce as well, so a frame sealed again after an interruption never reuses one.
+//!
+//! Frames are not compressed, the key is shared with Shamir's scheme once per stream.
+//!
+//! ```rust
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! use sharing::KrawczykSecretSharing;
+//!
+//! let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(5, 3, rand::thread_rng());
+//! let data = vec![42u8; 10_000];
+//!
+//! let (mut encryptor, key_shares) = sharer.encryptor().unwrap();
+//! let chunks: Vec<&[u8]> = data.chunks(4096).collect();
+//! let (last, rest) = chunks.split_last().unwrap();
+//! let mut frames = Vec::new();
+//! for chunk in rest {
+//!     frames.push(encryptor.encrypt_frame(chunk.to_vec()).unwrap());
+//! }
+//! frames.push(encryptor.encrypt_last(last.to_vec()).unwrap());
+//!
+//! let mut decryptor = sharer.decryptor(key_shares[..3].to_vec()).unwrap();
+//! let mut rec = Vec::new();
+//! for frame in &frames {
+//!     rec.extend(decryptor.decrypt_frame(frame[2..].to_vec()).unwrap());
+//! }
+//! assert!(decryptor.is_complete());
+//! assert_eq!(rec, data);
+//!
+//! // a stream cut short never completes
+//! let mut decryptor = sharer.decryptor(key_shares[..3].to_vec()).unwrap();
+//! decryptor.decrypt_frame(frames[0].clone()).unwrap();
+//! assert!(!decryptor.is_complete());
+//! ```
+//!
+//! The key of a stream can be rotated frame by frame with [`KrawczykSecretSharing::rotate`],
+//! without reconstructing the whole stream anywhere.
+use crate::{
+    error::SharingError,
+    ids::RabinInformationDispersal,
+    secret::KrawczykSecretSharing,
+    share::{KrawczykFrameShare, RabinShare, ShamirShare},
+    Sharing,
+};
+use aead::{generic_array::typenum::Unsigned, AeadInPlace, KeyInit, Nonce, Tag};
+use rand::Rng;
+
+/// Encrypts and disperses the frames of a stream, created by [`KrawczykSecretSharing::encryptor`].
+pub struct Encryptor<'a, C> {
+    cipher: C,
+    rabin: &'a RabinInformationDispersal,
+    ids: Vec<u8>,
+    session: u32,
+    index: u32,
+}
+
+impl<C: AeadInPlace> Encryptor<'_, C> {
+    /// Encrypts a frame that is followed by more,
+    /// [`SharingError::InvalidParameters`] after `u32::MAX` frames.
+    pub fn encrypt_frame(
+        &mut self,
+        frame: Vec<u8>,
+    ) -> Result<Vec<KrawczykFrameShare>, SharingError> {
+        let shares = self.seal(frame, false)?;
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or(SharingError::InvalidParameters)?;
+        Ok(shares)
+    }
+
+    /// Encrypts the final frame, which may be empty.
+    pub fn encrypt_last(self, frame: Vec<u8>) -> Result<Vec<KrawczykFrameShare>, SharingError> {
+        self.seal(frame, true)
+    }
+
+    fn seal(
+        &self,
+        mut frame: Vec<u8>,
+        last: bool,
+    ) -> Result<Vec<KrawczykFrameShare>, SharingError> {
+        let tag = nonce::<C>(self.session, self.index, last)
+            .and_then(|nonce| {
+                self.cipher
+                    .encrypt_in_place_detached(&nonce, &[], &mut frame)
+                    .ok()
+            })
+            .ok_or(SharingError::MismatchedParameters)?;
+        frame.extend_from_slice(&tag);
+        Ok(self
+            .rabin
+            .share_at(frame, &self.ids)?
+            .into_iter()
+            .map(|r| KrawczykFrameShare {
+                id: r.id,
+                session: self.session,
+                index: self.index,
+                last,
+                length: r.length,
+                body: r.body,
+            })
+            .collect())
+    }
+}
+
+/// Reconstructs and decrypts the frames of a stream in order,
+/// created by [`KrawczykSecretSharing::decryptor`].
+pub struct Decryptor<'a, C> {
+    cipher: C,
+    rabin: &'a RabinInformationDispersal,
+    index: u32,
+    complete: bool,
+}
+
+impl<C: AeadInPlace> Decryptor<'_, C> {
+    /// Decrypts the next frame. Fails with [`SharingError::MismatchedParameters`] if it is out
+    /// of order or follows the last frame and [`SharingError::ReconstructionFailed`] if it is
+    /// corrupted.
+    pub fn decrypt_frame(
+        &mut self,
+        shares: Vec<KrawczykFrameShare>,
+    ) -> Result<Vec<u8>, SharingError> {
+        if self.complete {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let (session, last) = shares
+            .first()
+            .map(|s| (s.session, s.last))
+            .ok_or(SharingError::EmptyShareSet)?;
+        if shares
+            .iter()
+            .any(|s| s.session != session || s.index != self.index || s.last != last)
+        {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let mut data = self.rabin.reconstruct(
+            shares
+                .into_iter()
+                .map(|s| RabinShare {
+                    id: s.id,
+                    length: s.length,
+                    body: s.body,
+                })
+                .collect(),
+        )?;
+        let tag_start = data
+            .len()
+            .checked_sub(C::TagSize::USIZE)
+            .ok_or(SharingError::ReconstructionFailed)?;
+        let tag = Tag::<C>::clone_from_slice(&data[tag_start..]);
+        data.truncate(tag_start);
+        nonce::<C>(session, self.index, last)
+            .and_then(|nonce| {
+                self.cipher
+                    .decrypt_in_place_detached(&nonce, &[], &mut data, &tag)
+                    .ok()
+            })
+            .ok_or(SharingError::ReconstructionFailed)?;
+        if last {
+            self.complete = true;
+        } else {
+            self.index = self
+                .index
+                .checked_add(1)
+                .ok_or(SharingError::MismatchedParameters)?;
+        }
+        Ok(data)
+    }
+
+    /// Whether the final frame was decrypted, a stream that ends before is truncated.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+impl<R: Rng, C: AeadInPlace + KeyInit> KrawczykSecretSharing<C, R> {
+    /// Starts a stream under a fresh key, returning the encryptor and the key shares.
+    pub fn encryptor(&self) -> Result<(Encryptor<'_, C>, Vec<ShamirShare>), SharingError> {
+        if self.shamir.k() < 1 || self.shamir.k() > self.shamir.n() {
+            return Err(SharingError::InvalidParameters);
+        }
+        let key = self.key()?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::MismatchedParameters)?;
+        let ids = self.shamir.ids()?;
+        let key_shares = self.shamir.share_at(key, &ids)?;
+        Ok((
+            Encryptor {
+                cipher,
+                rabin: &self.rabin,
+                ids,
+                session: 0,
+                index: 0,
+            },
+            key_shares,
+        ))
+    }
+
+    /// Continues a stream at frame `index`, e.g. after an interruption.
+    ///
+    /// Needs the key shares of every holder, the stream is dispersed to exactly those.
+    /// The frame at `index` may already have been sealed before the interruption, so the
+    /// frames are sealed in `session`, which must be nonzero and differ from every earlier
+    /// session of the stream. Record it before sealing the first frame, as
+    /// [`share_file_resumable`](crate::files::share_file_resumable) does in its checkpoint.
+    ///
+    /// Fails with [`SharingError::InvalidParameters`] for session 0, which
+    /// [`encryptor`](Self::encryptor) starts in.
+    pub fn resume_encryptor(
+        &self,
+        key_shares: Vec<ShamirShare>,
+        session: u32,
+        index: u32,
+    ) -> Result<Encryptor<'_, C>, SharingError> {
+        if session == 0 {
+            return Err(SharingError::InvalidParameters);
+        }
+        let ids = key_shares.iter().map(|s| s.id).collect();
+        let mut key = self.shamir.reconstruct(key_shares)?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::ReconstructionFailed);
+        crate::secret::wipe(&mut key);
+        Ok(Encryptor {
+            cipher: cipher?,
+            rabin: &self.rabin,
+            ids,
+            session,
+            index,
+        })
+    }
+
+    /// Starts rotating a stream to a fresh key, from `k` shares of its current key.
+    ///
+    /// Returns the rotator and the shares of the new key, which replace the old key shares
+    /// once [`Rotator::is_complete`].
+    ///
+    /// ```rust
+    /// use chacha20poly1305::ChaCha20Poly1305;
+    /// use sharing::KrawczykSecretSharing;
+    ///
+    /// let sharer = KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+    /// let (mut encryptor, key_shares) = sharer.encryptor().unwrap();
+    /// let frames = vec![
+    ///     encryptor.encrypt_frame(b"first ".to_vec()).unwrap(),
+    ///     encryptor.encrypt_last(b"and last".to_vec()).unwrap(),
+    /// ];
+    ///
+    /// let (mut rotator, new_key_shares) = sharer.rotate(key_shares[1..].to_vec()).unwrap();
+    /// let rotated: Vec<_> = frames
+    ///     .into_iter()
+    ///     .map(|frame| rotator.rotate_frame(frame[..2].to_vec()).unwrap())
+    ///     .collect();
+    /// assert!(rotator.is_complete());
+    ///
+    /// // the old key no longer decrypts, the new one does
+    /// let mut old = sharer.decryptor(key_shares[..2].to_vec()).unwrap();
+    /// assert!(old.decrypt_frame(rotated[0].clone()).is_err());
+    /// let mut decryptor = sharer.decryptor(new_key_shares[..2].to_vec()).unwrap();
+    /// let mut data = Vec::new();
+    /// for frame in rotated {
+    ///     data.extend(decryptor.decrypt_frame(frame[1..].to_vec()).unwrap());
+    /// }
+    /// assert_eq!(data, b"first and last");
+    /// ```
+    pub fn rotate(
+        &self,
+        key_shares: Vec<ShamirShare>,
+    ) -> Result<(Rotator<'_, C>, Vec<ShamirShare>), SharingError> {
+        let decryptor = self.decryptor(key_shares)?;
+        let (encryptor, new_key_shares) = self.encryptor()?;
+        Ok((
+            Rotator {
+                decryptor,
+                encryptor: Some(encryptor),
+            },
+            new_key_shares,
+        ))
+    }
+
+    /// Recovers the stream key from `k` key shares.
+    pub fn decryptor(
+        &self,
+        key_shares: Vec<ShamirShare>,
+    ) -> Result<Decryptor<'_, C>, SharingError> {
+        let mut key = self.shamir.reconstruct(key_shares)?;
+        let cipher = C::new_from_slice(&key).map_err(|_| SharingError::ReconstructionFailed);
+        crate::secret::wipe(&mut key);
+        Ok(Decryptor {
+            cipher: cipher?,
+            rabin: &self.rabin,
+            index: 0,
+            complete: false,
+        })
+    }
+}
+
+/// Re-encrypts the frames of a stream under a new key, created by
+/// [`KrawczykSecretSharing::rotate`].
+///
+/// Every frame is reconstructed, decrypted, encrypted and dispersed again on its own,
+/// so the rotating node never holds more than one frame of plaintext.
+pub struct Rotator<'a, C> {
+    decryptor: Decryptor<'a, C>,
+    encryptor: Option<Encryptor<'a, C>>,
+}
+
+impl<C: AeadInPlace> Rotator<'_, C> {
+    /// Rotates the next frame from `k` of its shares into shares for every holder,
+    /// failing like [`Decryptor::decrypt_frame`].
+    pub fn rotate_frame(
+        &mut self,
+        shares: Vec<KrawczykFrameShare>,
+    ) -> Result<Vec<KrawczykFrameShare>, SharingError> {
+        let last = shares.first().ok_or(SharingError::EmptyShareSet)?.last;
+        let frame = self.decryptor.decrypt_frame(shares)?;
+        if last {
+            self.encryptor
+                .take()
+                .ok_or(SharingError::MismatchedParameters)?
+                .encrypt_last(frame)
+        } else {
+            self.encryptor
+                .as_mut()
+                .ok_or(SharingError::MismatchedParameters)?
+                .encrypt_frame(frame)
+        }
+    }
+
+    /// Whether the final frame was rotated, the old shares can be deleted only then.
+    pub fn is_complete(&self) -> bool {
+        self.decryptor.is_complete()
+    }
+}
+
+/// STREAM nonce: zeros, the big endian session and frame counter and the final-frame flag.
+fn nonce<C: AeadInPlace>(session: u32, index: u32, last: bool) -> Option<Nonce<C>> {
+    let mut nonce = Nonce::<C>::default();
+    let start = nonce.len().checked_sub(9)?;
+    nonce[start..start + 4].copy_from_slice(&session.to_be_bytes());
+    nonce[start + 4..start + 8].copy_from_slice(&index.to_be_bytes());
+    nonce[start + 8] = last as u8;
+    Some(nonce)
+}
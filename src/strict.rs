@@ -0,0 +1,346 @@
+//! Strict Decoding
+//!
+//! The plain decoders, e.g. [`ShareBytes::from_bytes`], accept anything that parses.
+//! Shares received from untrusted networks should instead be decoded with
+//! [`StrictBytes::from_bytes_strict`], which also rejects
+//!
+//! - inputs larger than [`Limits::with_max_size`], before anything is allocated,
+//! - the id zero, which would place a share on the secret,
+//! - declared lengths that do not fit the body, i.e. trailing bytes or absurd lengths,
+//! - keys and associated data beyond their caps,
+//! - and, given the expected [`Parameters`] (which cannot have `k > n`),
+//!   bodies of the wrong size for the threshold, and shares of another threshold.
+//!
+//! [`WireShare`]s carry their threshold, which must match the expected one and otherwise
+//! sizes the bodies. The other formats have strict entry points on their traits, e.g.
+//! [`Cbor::from_cbor_strict`](crate::cbor::Cbor::from_cbor_strict), and the text shares of
+//! SLIP-39, SSKR and `ssss` are read with [`StrictStr::from_str_strict`]. All of them check
+//! the decoded share with [`StrictCheck`].
+//!
+//! ```rust
+//! use sharing::{
+//!     share::ShareBytes,
+//!     strict::{Limits, StrictBytes},
+//!     wire::WireShare,
+//!     Parameters, RabinShare, SharingError,
+//! };
+//!
+//! let limits = Limits::default().with_parameters(Parameters::new(5, 3).unwrap());
+//! let share = RabinShare { id: 1, length: 8, body: vec![0; 3] };
+//! assert!(RabinShare::from_bytes_strict(&share.to_bytes(), &limits).is_ok());
+//!
+//! // a huge declared length or a trailing byte decode fine, but not strictly
+//! let absurd = RabinShare { length: 1 << 40, ..share.clone() };
+//! assert!(RabinShare::from_bytes(&absurd.to_bytes()).is_some());
+//! assert!(RabinShare::from_bytes_strict(&absurd.to_bytes(), &limits).is_err());
+//!
+//! let mut trailing = share.to_bytes();
+//! trailing.push(0);
+//! assert!(matches!(
+//!     RabinShare::from_bytes_strict(&trailing, &limits),
+//!     Err(SharingError::Malformed("share length"))
+//! ));
+//!
+//! // a wire share says how many shares it takes
+//! let wire = WireShare::new(2, share).to_bytes();
+//! assert!(matches!(
+//!     WireShare::<RabinShare>::from_bytes_strict(&wire, &limits),
+//!     Err(SharingError::MismatchedParameters)
+//! ));
+//! ```
+use crate::{
+    error::SharingError,
+    fragment::Fragment,
+    share::{
+        decode_error, KrawczykDataShare, KrawczykKeyShare, KrawczykShare, RabinShare, ShamirShare,
+        ShareBytes,
+    },
+    wire::{WireScheme, WireShare},
+    Parameters,
+};
+use std::str::FromStr;
+
+/// Caps and expectations for [`StrictBytes::from_bytes_strict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    max_size: usize,
+    max_key: usize,
+    max_associated_data: usize,
+    parameters: Option<Parameters>,
+}
+
+impl Default for Limits {
+    /// 64 MiB shares, 64 byte keys and 64 KiB of associated data, any parameters.
+    fn default() -> Self {
+        Self {
+            max_size: 64 * 1024 * 1024,
+            max_key: 64,
+            max_associated_data: 64 * 1024,
+            parameters: None,
+        }
+    }
+}
+
+impl Limits {
+    /// The largest encoded share accepted.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// The largest Krawczyk key share accepted.
+    pub fn with_max_key(mut self, max_key: usize) -> Self {
+        self.max_key = max_key;
+        self
+    }
+
+    /// The most associated data accepted on a Krawczyk share.
+    pub fn with_max_associated_data(mut self, max_associated_data: usize) -> Self {
+        self.max_associated_data = max_associated_data;
+        self
+    }
+
+    /// Only accept shares dealt with these parameters.
+    pub fn with_parameters(mut self, parameters: Parameters) -> Self {
+        self.parameters = Some(parameters);
+        self
+    }
+
+    pub fn parameters(&self) -> Option<Parameters> {
+        self.parameters
+    }
+
+    pub(crate) fn size(&self, bytes: &[u8]) -> Result<(), SharingError> {
+        if bytes.len() > self.max_size {
+            return Err(SharingError::Malformed("share size"));
+        }
+        Ok(())
+    }
+
+    /// A dispersed body holds `ceil(length / k)` bytes, for the expected `k` if known.
+    fn length(&self, length: usize, body: &[u8]) -> Result<(), SharingError> {
+        let consistent = match self.parameters {
+            Some(parameters) => length.div_ceil(parameters.k() as usize) == body.len(),
+            None => body.len() <= length && length <= body.len().saturating_mul(255),
+        };
+        if !consistent {
+            return Err(SharingError::Malformed("share length"));
+        }
+        Ok(())
+    }
+
+    fn key(&self, key: &[u8]) -> Result<(), SharingError> {
+        if key.is_empty() || key.len() > self.max_key {
+            return Err(SharingError::Malformed("key"));
+        }
+        Ok(())
+    }
+
+    fn associated_data(&self, associated_data: &[u8]) -> Result<(), SharingError> {
+        if associated_data.len() > self.max_associated_data {
+            return Err(SharingError::Malformed("associated data"));
+        }
+        Ok(())
+    }
+}
+
+fn id(id: u8) -> Result<(), SharingError> {
+    if id == 0 {
+        return Err(SharingError::Malformed("share id"));
+    }
+    Ok(())
+}
+
+/// The checks on a decoded share, shared by every strict decoder.
+pub trait StrictCheck {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError>;
+}
+
+impl StrictCheck for ShamirShare {
+    fn check_strict(&self, _limits: &Limits) -> Result<(), SharingError> {
+        id(self.id)
+    }
+}
+
+impl StrictCheck for RabinShare {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        id(self.id)?;
+        limits.length(self.length, &self.body)
+    }
+}
+
+impl StrictCheck for KrawczykShare {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        id(self.id)?;
+        limits.length(self.length, &self.body)?;
+        limits.key(&self.key)?;
+        limits.associated_data(&self.associated_data)
+    }
+}
+
+impl StrictCheck for KrawczykKeyShare {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        id(self.id)?;
+        limits.key(&self.key)
+    }
+}
+
+impl StrictCheck for KrawczykDataShare {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        id(self.id)?;
+        limits.length(self.length, &self.body)?;
+        limits.associated_data(&self.associated_data)
+    }
+}
+
+/// Fails with [`SharingError::MismatchedParameters`] if the threshold is not the expected one.
+/// Without expected parameters, the bodies are sized for the share's own threshold.
+impl<S: WireScheme + StrictCheck> StrictCheck for WireShare<S> {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        if self.k == 0 {
+            return Err(SharingError::Malformed("share threshold"));
+        }
+        if limits.parameters.is_some_and(|p| p.k() != self.k) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let limits = Limits {
+            parameters: limits.parameters.or(Parameters::new(u8::MAX, self.k)),
+            ..*limits
+        };
+        self.share.check_strict(&limits)
+    }
+}
+
+/// The expected parameters are the member threshold and count of a single group backup.
+#[cfg(feature = "slip39")]
+impl StrictCheck for crate::slip39::Slip39Share {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        member(limits, self.member_threshold, self.member_index)
+    }
+}
+
+/// The expected parameters are the member threshold and count of a single group backup.
+#[cfg(feature = "sskr")]
+impl StrictCheck for crate::sskr::SskrShare {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        member(limits, self.member_threshold, self.member_index)
+    }
+}
+
+/// `ssss` shares do not record the threshold, only ids beyond `n` are rejected.
+#[cfg(feature = "ssss")]
+impl StrictCheck for crate::ssss::SsssShare {
+    fn check_strict(&self, limits: &Limits) -> Result<(), SharingError> {
+        id(self.id)?;
+        if limits.parameters.is_some_and(|p| self.id > p.n()) {
+            return Err(SharingError::MismatchedParameters);
+        }
+        Ok(())
+    }
+}
+
+/// Member indices of SLIP-39 and SSKR count from zero.
+#[cfg(any(feature = "slip39", feature = "sskr"))]
+fn member(limits: &Limits, threshold: u8, index: u8) -> Result<(), SharingError> {
+    if limits
+        .parameters
+        .is_some_and(|p| p.k() != threshold || index >= p.n())
+    {
+        return Err(SharingError::MismatchedParameters);
+    }
+    Ok(())
+}
+
+/// Hardened decoding for shares from untrusted sources.
+pub trait StrictBytes: Sized {
+    /// Decodes like the type's plain decoder, then checks the result against `limits`.
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError>;
+}
+
+impl StrictBytes for ShamirShare {
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        let share = Self::from_bytes(bytes).ok_or_else(|| decode_error(bytes))?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl StrictBytes for RabinShare {
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        let share = Self::from_bytes(bytes).ok_or_else(|| decode_error(bytes))?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl StrictBytes for KrawczykShare {
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        let share = Self::from_bytes(bytes).ok_or_else(|| decode_error(bytes))?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl StrictBytes for KrawczykKeyShare {
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        let share = Self::from_bytes(bytes).ok_or(SharingError::Malformed("key share"))?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl StrictBytes for KrawczykDataShare {
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        let share = Self::from_bytes(bytes).ok_or(SharingError::Malformed("data share"))?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl<S: WireScheme + StrictCheck> StrictBytes for WireShare<S> {
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        let share = Self::decode(bytes)?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+/// The binary SSKR share, without the CBOR tag of the text forms.
+#[cfg(feature = "sskr")]
+impl StrictBytes for crate::sskr::SskrShare {
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        let share = Self::from_bytes(bytes)?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
+
+impl StrictBytes for Fragment {
+    /// Only checks the size, as a fragment's payload is checked once the share is reassembled.
+    fn from_bytes_strict(bytes: &[u8], limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(bytes)?;
+        Self::from_bytes(bytes).ok_or(SharingError::Malformed("fragment"))
+    }
+}
+
+/// Hardened parsing for the text shares of SLIP-39, SSKR and `ssss`.
+pub trait StrictStr: Sized {
+    /// Parses like [`FromStr`], then checks the result against `limits`.
+    fn from_str_strict(s: &str, limits: &Limits) -> Result<Self, SharingError>;
+}
+
+impl<T: FromStr<Err = SharingError> + StrictCheck> StrictStr for T {
+    fn from_str_strict(s: &str, limits: &Limits) -> Result<Self, SharingError> {
+        limits.size(s.as_bytes())?;
+        let share = s.parse::<Self>()?;
+        share.check_strict(limits)?;
+        Ok(share)
+    }
+}
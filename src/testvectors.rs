@@ -0,0 +1,444 @@
+//! Known-Answer Test Vectors
+//!
+//! Deterministic vectors for every scheme, generated from a fixed ChaCha20 RNG seed,
+//! so implementations in other languages can check that they produce identical shares,
+//! and encode them identically as [`ShareBytes`] and in the [wire format](crate::wire).
+//! Available with the `testvectors` feature. Krawczyk vectors use ChaCha20-Poly1305.
+//!
+//! Vectors are exchanged in a line based text format:
+//!
+//! ```text
+//! version = 3
+//! scheme = shamir
+//! n = 3
+//! k = 2
+//! seed = <32 bytes hex>
+//! secret = <hex>
+//! share = <id> <body hex>                                   # shamir
+//! share = <id> <length> <body hex>                          # rabin
+//! share = <id> <length> <digest hex> <key hex> <body hex>   # krawczyk
+//! bytes = <hex>                                             # per share, `ShareBytes`
+//! wire = <hex>                                              # per share, wire format
+//! ```
+//!
+//! Vectors are separated by empty lines.
+//!
+//! ```rust
+//! use sharing::{testvectors::TestVector, Scheme};
+//!
+//! assert!(TestVector::standard().iter().all(TestVector::verify));
+//!
+//! let vector = TestVector::generate(Scheme::Shamir, 5, 3, [7; 32], b"secret".to_vec()).unwrap();
+//! let parsed: TestVector = vector.to_string().parse().unwrap();
+//! assert!(parsed.verify());
+//! ```
+use crate::{
+    compression::Compression,
+    error::ParseTextError,
+    share::{parse_hex, Hex, KrawczykShare, RabinShare, ShamirShare, ShareBytes},
+    wire::{WireScheme, WireShare},
+    KrawczykSecretSharing, RabinInformationDispersal, Scheme, ShamirSecretSharing, Sharing,
+};
+use chacha20poly1305::ChaCha20Poly1305;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+const VERSION: u32 = 3;
+
+/// The shares of a test vector.
+#[derive(Debug, Clone)]
+pub enum Shares {
+    Shamir(Vec<ShamirShare>),
+    Rabin(Vec<RabinShare>),
+    Krawczyk(Vec<KrawczykShare>),
+}
+
+/// A single known-answer test vector.
+#[derive(Debug, Clone)]
+pub struct TestVector {
+    pub n: u8,
+    pub k: u8,
+    pub seed: [u8; 32],
+    pub secret: Vec<u8>,
+    pub shares: Shares,
+    /// The [`ShareBytes`] encoding of every share.
+    pub bytes: Vec<Vec<u8>>,
+    /// Every share in the wire format, with threshold `k`.
+    pub wire: Vec<Vec<u8>>,
+}
+
+impl TestVector {
+    /// Shares `secret` with a ChaCha20 RNG seeded with `seed`.
+    pub fn generate(scheme: Scheme, n: u8, k: u8, seed: [u8; 32], secret: Vec<u8>) -> Option<Self> {
+        let rng = ChaCha20Rng::from_seed(seed);
+        let shares = match scheme {
+            Scheme::Shamir => Shares::Shamir(
+                ShamirSecretSharing::new(n, k, rng)
+                    .share(secret.clone())
+                    .ok()?,
+            ),
+            Scheme::Rabin => Shares::Rabin(
+                RabinInformationDispersal::new(n, k)
+                    .share(secret.clone())
+                    .ok()?,
+            ),
+            Scheme::Krawczyk => Shares::Krawczyk(
+                KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(n, k, rng)
+                    .share(secret.clone())
+                    .ok()?,
+            ),
+        };
+        let (bytes, wire) = match &shares {
+            Shares::Shamir(shares) => encode(k, shares),
+            Shares::Rabin(shares) => encode(k, shares),
+            Shares::Krawczyk(shares) => encode(k, shares),
+        };
+        Some(Self {
+            n,
+            k,
+            seed,
+            secret,
+            shares,
+            bytes,
+            wire,
+        })
+    }
+
+    /// The vectors shipped with this crate, covering every scheme.
+    #[allow(clippy::expect_used)] // the shipped file is checked by the tests
+    pub fn standard() -> Vec<Self> {
+        parse_all(include_str!("testvectors.txt")).expect("valid standard test vectors")
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        match self.shares {
+            Shares::Shamir(_) => Scheme::Shamir,
+            Shares::Rabin(_) => Scheme::Rabin,
+            Shares::Krawczyk(_) => Scheme::Krawczyk,
+        }
+    }
+
+    /// Regenerates the shares and their encodings and checks they match, that the encodings
+    /// decode to the shares, and that the last `k` shares reconstruct the secret.
+    pub fn verify(&self) -> bool {
+        let expected = match Self::generate(
+            self.scheme(),
+            self.n,
+            self.k,
+            self.seed,
+            self.secret.clone(),
+        ) {
+            Some(expected) => expected,
+            None => return false,
+        };
+        if expected.to_string() != self.to_string() {
+            return false;
+        }
+        let decoded = match &self.shares {
+            Shares::Shamir(shares) => decodes(self.k, shares, &self.bytes, &self.wire),
+            Shares::Rabin(shares) => decodes(self.k, shares, &self.bytes, &self.wire),
+            Shares::Krawczyk(shares) => decodes(self.k, shares, &self.bytes, &self.wire),
+        };
+        if !decoded {
+            return false;
+        }
+        let rng = ChaCha20Rng::from_seed(self.seed);
+        let skip = self.n.saturating_sub(self.k) as usize;
+        let reconstructed =
+            match &self.shares {
+                Shares::Shamir(shares) => ShamirSecretSharing::new(self.n, self.k, rng)
+                    .reconstruct(shares[skip..].to_vec()),
+                Shares::Rabin(shares) => RabinInformationDispersal::new(self.n, self.k)
+                    .reconstruct(shares[skip..].to_vec()),
+                Shares::Krawczyk(shares) => {
+                    KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(self.n, self.k, rng)
+                        .reconstruct(shares[skip..].to_vec())
+                }
+            };
+        reconstructed.ok().as_ref() == Some(&self.secret)
+    }
+}
+
+impl fmt::Display for TestVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version = {}", VERSION)?;
+        writeln!(f, "scheme = {}", self.scheme())?;
+        writeln!(f, "n = {}", self.n)?;
+        writeln!(f, "k = {}", self.k)?;
+        writeln!(f, "seed = {}", Field(&self.seed))?;
+        writeln!(f, "secret = {}", Field(&self.secret))?;
+        match &self.shares {
+            Shares::Shamir(shares) => shares
+                .iter()
+                .try_for_each(|s| writeln!(f, "share = {} {}", s.id, Field(&s.body))),
+            Shares::Rabin(shares) => shares
+                .iter()
+                .try_for_each(|s| writeln!(f, "share = {} {} {}", s.id, s.length, Field(&s.body))),
+            Shares::Krawczyk(shares) => shares.iter().try_for_each(|s| {
+                writeln!(
+                    f,
+                    "share = {} {} {} {} {}",
+                    s.id,
+                    s.length,
+                    Field(&s.digest),
+                    Field(&s.key),
+                    Field(&s.body)
+                )
+            }),
+        }?;
+        for bytes in &self.bytes {
+            writeln!(f, "bytes = {}", Field(bytes))?;
+        }
+        for wire in &self.wire {
+            writeln!(f, "wire = {}", Field(wire))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TestVector {
+    type Err = ParseTextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = Vec::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| ParseTextError::new(number + 1, "line"))?
+                .trim();
+            lines.push((number + 1, key, value));
+        }
+
+        let field = |name: &'static str| {
+            lines
+                .iter()
+                .find(|(_, key, _)| *key == name)
+                .map(|(number, _, value)| (*number, *value))
+                .ok_or_else(|| ParseTextError::missing(name))
+        };
+        let number = |name: &'static str| -> Result<u8, ParseTextError> {
+            let (line, value) = field(name)?;
+            value.parse().map_err(|_| ParseTextError::new(line, name))
+        };
+
+        let (line, version) = field("version")?;
+        if version.parse() != Ok(VERSION) {
+            return Err(ParseTextError::new(line, "version"));
+        }
+        let (line, scheme) = field("scheme")?;
+        let scheme: Scheme = scheme
+            .parse()
+            .map_err(|_| ParseTextError::new(line, "scheme"))?;
+        let n = number("n")?;
+        let k = number("k")?;
+        let (line, seed) = field("seed")?;
+        let seed = from_hex(seed)
+            .and_then(|seed| <[u8; 32]>::try_from(seed).ok())
+            .ok_or_else(|| ParseTextError::new(line, "seed"))?;
+        let (line, secret) = field("secret")?;
+        let secret = from_hex(secret).ok_or_else(|| ParseTextError::new(line, "secret"))?;
+
+        let share_lines: Vec<(usize, &str)> = lines
+            .iter()
+            .filter(|(_, key, _)| *key == "share")
+            .map(|(line, _, value)| (*line, *value))
+            .collect();
+        let shares = match scheme {
+            Scheme::Shamir => Shares::Shamir(parse_shares(&share_lines, parse_shamir)?),
+            Scheme::Rabin => Shares::Rabin(parse_shares(&share_lines, parse_rabin)?),
+            Scheme::Krawczyk => Shares::Krawczyk(parse_shares(&share_lines, parse_krawczyk)?),
+        };
+        let encodings = |name: &'static str| {
+            lines
+                .iter()
+                .filter(|(_, key, _)| *key == name)
+                .map(|(line, _, value)| {
+                    from_hex(value).ok_or_else(|| ParseTextError::new(*line, name))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            n,
+            k,
+            seed,
+            secret,
+            shares,
+            bytes: encodings("bytes")?,
+            wire: encodings("wire")?,
+        })
+    }
+}
+
+/// Parses several test vectors separated by empty lines.
+pub fn parse_all(input: &str) -> Result<Vec<TestVector>, ParseTextError> {
+    let mut vectors = Vec::new();
+    let mut block = String::new();
+    let mut offset = 0;
+    for (number, line) in input.lines().chain(std::iter::once("")).enumerate() {
+        if line.trim().is_empty() {
+            if block
+                .lines()
+                .any(|l| !l.split('#').next().unwrap_or("").trim().is_empty())
+            {
+                vectors.push(
+                    block
+                        .parse()
+                        .map_err(|e: ParseTextError| e.offset(offset))?,
+                );
+            }
+            block.clear();
+            offset = number + 1;
+        } else {
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+    Ok(vectors)
+}
+
+fn parse_shares<S>(
+    lines: &[(usize, &str)],
+    parse: fn(&[&str]) -> Option<S>,
+) -> Result<Vec<S>, ParseTextError> {
+    lines
+        .iter()
+        .map(|(line, value)| {
+            let fields: Vec<&str> = value.split_whitespace().collect();
+            parse(&fields).ok_or_else(|| ParseTextError::new(*line, "share"))
+        })
+        .collect()
+}
+
+fn parse_shamir(fields: &[&str]) -> Option<ShamirShare> {
+    match fields {
+        [id, body] => Some(ShamirShare {
+            id: id.parse().ok()?,
+            body: from_hex(body)?,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_rabin(fields: &[&str]) -> Option<RabinShare> {
+    match fields {
+        [id, length, body] => Some(RabinShare {
+            id: id.parse().ok()?,
+            length: length.parse().ok()?,
+            body: from_hex(body)?,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_krawczyk(fields: &[&str]) -> Option<KrawczykShare> {
+    match fields {
+        [id, length, digest, key, body] => Some(KrawczykShare {
+            id: id.parse().ok()?,
+            length: length.parse().ok()?,
+            key: from_hex(key)?,
+            compression: Compression::None,
+            associated_data: Vec::new(),
+            digest: <[u8; 32]>::try_from(from_hex(digest)?).ok()?,
+            body: from_hex(body)?,
+        }),
+        _ => None,
+    }
+}
+
+/// The [`ShareBytes`] and wire encodings of every share.
+fn encode<S: WireScheme + Clone>(k: u8, shares: &[S]) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    shares
+        .iter()
+        .map(|share| {
+            let wire = WireShare::new(k, share.clone()).to_bytes();
+            (share.to_bytes(), wire)
+        })
+        .unzip()
+}
+
+/// Whether the encodings are those of the shares, in order.
+fn decodes<S: WireScheme + Clone + PartialEq>(
+    k: u8,
+    shares: &[S],
+    bytes: &[Vec<u8>],
+    wire: &[Vec<u8>],
+) -> bool {
+    bytes.len() == shares.len()
+        && wire.len() == shares.len()
+        && shares
+            .iter()
+            .zip(bytes)
+            .zip(wire)
+            .all(|((share, bytes), wire)| {
+                S::from_bytes(bytes).as_ref() == Some(share)
+                    && WireShare::decode(wire).ok() == Some(WireShare::new(k, share.clone()))
+            })
+}
+
+/// Lower case hex, `-` for no bytes so that every field stays visible.
+struct Field<'a>(&'a [u8]);
+
+impl fmt::Display for Field<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("-");
+        }
+        Hex(self.0).fmt(f)
+    }
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s == "-" {
+        return Some(Vec::new());
+    }
+    parse_hex(s).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_vectors() {
+        let text = include_str!("testvectors.txt");
+        let vectors = TestVector::standard();
+        for scheme in [Scheme::Shamir, Scheme::Rabin, Scheme::Krawczyk].iter() {
+            assert!(vectors.iter().any(|v| v.scheme() == *scheme));
+        }
+        for vector in &vectors {
+            assert!(vector.verify(), "{}", vector);
+            assert_eq!(vector.bytes.len(), vector.wire.len());
+            assert!(text.contains(&vector.to_string()));
+        }
+
+        // a vector whose encodings do not match its shares
+        let mut wrong = vectors[4].clone();
+        wrong.wire.swap(0, 1);
+        assert!(!wrong.verify());
+        wrong.wire.pop();
+        assert!(!wrong.verify());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let vector = TestVector::standard().remove(4).to_string();
+        let error = |text: &str| text.parse::<TestVector>().unwrap_err();
+
+        let older = error(&vector.replace("version = 3", "version = 2"));
+        assert_eq!(older.to_string(), "invalid `version` on line 1");
+        let unknown = error(&vector.replace("scheme = shamir", "scheme = blakley"));
+        assert_eq!(unknown.to_string(), "invalid `scheme` on line 2");
+        let unpaired = error(&vector.replace("\nn = 3", "\nn 3"));
+        assert_eq!(unpaired.to_string(), "invalid `line` on line 3");
+        let wire = error(&vector.replace("wire = a5", "wire = x5"));
+        assert_eq!(wire.field(), "wire");
+    }
+}
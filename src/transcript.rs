@@ -0,0 +1,325 @@
+//! Audit Transcripts
+//!
+//! A [`Transcript`] records which share ids were dealt and consumed and the outcome of every
+//! share verification, so key ceremonies can keep a compliance log. It never contains
+//! share bodies or secrets, only ids and a SHA-256 digest of the scheme parameters.
+//!
+//! Transcripts are written in the same line based text format as the test vectors:
+//!
+//! ```text
+//! version = 1
+//! scheme = shamir
+//! n = 5
+//! k = 3
+//! polynomial = 0x11d
+//! parameters = <sha256 hex>
+//! dealt = 1 2 3 4 5
+//! verified = 2 ok
+//! reconstructed = 1 2 3 ok
+//! ```
+//!
+//! Parsing fails with a [`ParseTextError`] on the `parameters` line if the digest does not
+//! match the parameters above it.
+//!
+//! [`ParseTextError`]: crate::error::ParseTextError
+//!
+//! ```rust
+//! use sharing::{math::Field, transcript::Transcript, Parameters, Scheme, ShamirSecretSharing};
+//!
+//! let sharer = ShamirSecretSharing::new(5, 3, rand::thread_rng());
+//! let mut transcript = Transcript::new(Scheme::Shamir, Parameters::new(5, 3).unwrap(), &Field::default());
+//!
+//! let (shares, openings, commitments) = sharer.share_with_commitments(vec![1, 2, 3]).unwrap();
+//! transcript.dealt(&shares);
+//! assert!(transcript.verify(&shares[1], &openings[1], &commitments));
+//! let secret = transcript.reconstruct(&sharer, shares[..3].to_vec());
+//! assert_eq!(secret.unwrap(), vec![1, 2, 3]);
+//!
+//! let log = transcript.to_string();
+//! assert_eq!(log.parse::<Transcript>().unwrap(), transcript);
+//! ```
+use crate::{
+    commitment::{verify_against_commitments, Commitment, Opening},
+    error::{ParseTextError, SharingError},
+    math::Field,
+    share::{parse_hex, Hex, ShamirShare, ShareId},
+    Parameters, Scheme, Sharing,
+};
+use sha2::{Digest, Sha256};
+use std::{fmt, str::FromStr};
+
+const VERSION: u32 = 1;
+const DOMAIN: &[u8] = b"sharing/transcript/v1";
+
+/// A single recorded step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Shares with these ids were handed out.
+    Dealt { ids: Vec<u8> },
+    /// The share with this id was checked against the published commitments.
+    Verified { id: u8, valid: bool },
+    /// The shares with these ids were combined.
+    Reconstructed { ids: Vec<u8>, success: bool },
+}
+
+/// An append-only log of a dealing and its reconstructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    scheme: Scheme,
+    parameters: Parameters,
+    polynomial: u16,
+    events: Vec<Event>,
+}
+
+impl Transcript {
+    pub fn new(scheme: Scheme, parameters: Parameters, field: &Field) -> Self {
+        Self {
+            scheme,
+            parameters,
+            polynomial: field.polynomial(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn parameters(&self) -> Parameters {
+        self.parameters
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// SHA-256 of the scheme, `n`, `k` and field polynomial, to match a log against a configuration.
+    pub fn parameters_digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(DOMAIN);
+        hasher.update(self.scheme.name());
+        hasher.update([self.parameters.n(), self.parameters.k()]);
+        hasher.update(self.polynomial.to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Records the ids of freshly dealt shares.
+    pub fn dealt<S: ShareId>(&mut self, shares: &[S]) {
+        self.events.push(Event::Dealt {
+            ids: shares.iter().map(ShareId::id).collect(),
+        });
+    }
+
+    /// Shares `data` with `sharer` and records the ids of the resulting shares.
+    pub fn deal<T: Sharing>(
+        &mut self,
+        sharer: &T,
+        data: Vec<u8>,
+    ) -> Result<Vec<T::Share>, SharingError>
+    where
+        T::Share: ShareId,
+    {
+        let shares = sharer.share(data)?;
+        self.dealt(&shares);
+        Ok(shares)
+    }
+
+    /// Checks a share against the published commitments and records the outcome.
+    pub fn verify(
+        &mut self,
+        share: &ShamirShare,
+        opening: &Opening,
+        commitments: &[Commitment],
+    ) -> bool {
+        let valid = verify_against_commitments(share, opening, commitments);
+        self.events.push(Event::Verified {
+            id: share.id,
+            valid,
+        });
+        valid
+    }
+
+    /// Reconstructs with `sharer` and records the consumed ids and whether it succeeded.
+    pub fn reconstruct<T: Sharing>(
+        &mut self,
+        sharer: &T,
+        shares: Vec<T::Share>,
+    ) -> Result<Vec<u8>, SharingError>
+    where
+        T::Share: ShareId,
+    {
+        let ids = shares.iter().map(ShareId::id).collect();
+        let secret = sharer.reconstruct(shares);
+        self.events.push(Event::Reconstructed {
+            ids,
+            success: secret.is_ok(),
+        });
+        secret
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version = {}", VERSION)?;
+        writeln!(f, "scheme = {}", self.scheme)?;
+        writeln!(f, "n = {}", self.parameters.n())?;
+        writeln!(f, "k = {}", self.parameters.k())?;
+        writeln!(f, "polynomial = {:#x}", self.polynomial)?;
+        writeln!(f, "parameters = {}", Hex(&self.parameters_digest()))?;
+        for event in &self.events {
+            match event {
+                Event::Dealt { ids } => writeln!(f, "dealt = {}", Ids(ids))?,
+                Event::Verified { id, valid } => {
+                    writeln!(f, "verified = {} {}", id, outcome(*valid))?
+                }
+                Event::Reconstructed { ids, success } => {
+                    writeln!(f, "reconstructed = {} {}", Ids(ids), outcome(*success))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = ParseTextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut version = None;
+        let mut scheme = None;
+        let mut n = None;
+        let mut k = None;
+        let mut polynomial = None;
+        let mut digest = None;
+        let mut events = Vec::new();
+
+        for (number, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |field| ParseTextError::new(number + 1, field);
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().ok_or_else(|| error("line"))?.trim();
+            match key {
+                "version" => version = Some(value.parse::<u32>().map_err(|_| error("version"))?),
+                "scheme" => scheme = Some(value.parse::<Scheme>().map_err(|_| error("scheme"))?),
+                "n" => n = Some(value.parse::<u8>().map_err(|_| error("n"))?),
+                "k" => k = Some(value.parse::<u8>().map_err(|_| error("k"))?),
+                "polynomial" => {
+                    let hex = value.trim_start_matches("0x");
+                    polynomial =
+                        Some(u16::from_str_radix(hex, 16).map_err(|_| error("polynomial"))?);
+                }
+                "parameters" => {
+                    let bytes = parse_hex(value).map_err(|_| error("parameters"))?;
+                    digest = Some((number + 1, bytes));
+                }
+                "dealt" => events.push(Event::Dealt {
+                    ids: parse_ids(value.split_whitespace()).ok_or_else(|| error("dealt"))?,
+                }),
+                "verified" => {
+                    let fields: Vec<&str> = value.split_whitespace().collect();
+                    events.push(match fields.as_slice() {
+                        [id, valid] => Event::Verified {
+                            id: id.parse().map_err(|_| error("verified"))?,
+                            valid: parse_outcome(valid).ok_or_else(|| error("verified"))?,
+                        },
+                        _ => return Err(error("verified")),
+                    });
+                }
+                "reconstructed" => {
+                    let fields: Vec<&str> = value.split_whitespace().collect();
+                    let (success, ids) =
+                        fields.split_last().ok_or_else(|| error("reconstructed"))?;
+                    events.push(Event::Reconstructed {
+                        ids: parse_ids(ids.iter().copied())
+                            .ok_or_else(|| error("reconstructed"))?,
+                        success: parse_outcome(success).ok_or_else(|| error("reconstructed"))?,
+                    });
+                }
+                _ => return Err(error("key")),
+            }
+        }
+
+        let missing = ParseTextError::missing;
+        if version.ok_or_else(|| missing("version"))? != VERSION {
+            return Err(missing("version"));
+        }
+        let parameters = Parameters::new(
+            n.ok_or_else(|| missing("n"))?,
+            k.ok_or_else(|| missing("k"))?,
+        )
+        .ok_or_else(|| missing("k"))?;
+        let polynomial = polynomial.ok_or_else(|| missing("polynomial"))?;
+        let field = Field::new(polynomial).ok_or_else(|| missing("polynomial"))?;
+        let transcript = Transcript {
+            scheme: scheme.ok_or_else(|| missing("scheme"))?,
+            parameters,
+            polynomial: field.polynomial(),
+            events,
+        };
+        let (line, digest) = digest.ok_or_else(|| missing("parameters"))?;
+        if digest[..] != transcript.parameters_digest()[..] {
+            return Err(ParseTextError::new(line, "parameters"));
+        }
+        Ok(transcript)
+    }
+}
+
+struct Ids<'a>(&'a [u8]);
+
+impl fmt::Display for Ids<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("-");
+        }
+        for (i, id) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", id)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_ids<'a>(fields: impl Iterator<Item = &'a str>) -> Option<Vec<u8>> {
+    fields
+        .filter(|f| *f != "-")
+        .map(|f| f.parse().ok())
+        .collect()
+}
+
+fn outcome(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "failed"
+    }
+}
+
+fn parse_outcome(s: &str) -> Option<bool> {
+    match s {
+        "ok" => Some(true),
+        "failed" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tampered_parameters() {
+        let transcript = Transcript::new(
+            Scheme::Rabin,
+            Parameters::new(4, 2).unwrap(),
+            &Field::default(),
+        );
+        let log = transcript.to_string().replace("k = 2", "k = 1");
+        assert_eq!(log.parse::<Transcript>().unwrap_err().field(), "parameters");
+    }
+}
@@ -0,0 +1,199 @@
+//! Vault Sharing
+//!
+//! Splits a whole map of named secrets, such as a credentials vault, among `n` holders.
+//! Every entry is shared on its own with its own [`Access`], but each holder receives
+//! a single [`VaultShare`] with their pieces of all entries, and entries can be recovered
+//! one at a time without reconstructing the rest.
+//!
+//! The entry names and lengths are not secret, every share lists them.
+//!
+//! ```rust
+//! use sharing::vault::{Access, VaultSecretSharing};
+//! use std::collections::BTreeMap;
+//!
+//! let sharer = VaultSecretSharing::new(5, 3, rand::thread_rng())
+//!     .with_access("root", Access::threshold(5))
+//!     .with_access("ops", Access::among(1, vec![4, 5]));
+//!
+//! let mut vault = BTreeMap::new();
+//! vault.insert("db".to_string(), b"hunter2".to_vec());
+//! vault.insert("root".to_string(), b"correct horse".to_vec());
+//! vault.insert("ops".to_string(), b"pager".to_vec());
+//!
+//! let shares = sharer.share_vault(&vault).unwrap();
+//!
+//! // three holders get the default entries, but not root
+//! assert_eq!(sharer.recover(&shares[..3], "db").unwrap(), b"hunter2");
+//! assert!(sharer.recover(&shares[..3], "root").is_err());
+//! assert_eq!(sharer.recover_all(&shares).unwrap(), vault);
+//!
+//! // only holders 4 and 5 have a piece of ops, and either is enough
+//! assert!(shares[0].entries.get("ops").is_none());
+//! assert_eq!(sharer.recover(&shares[4..], "ops").unwrap(), b"pager");
+//! ```
+use crate::{
+    error::SharingError,
+    share::{valid_ids, ShamirShare, ShareId},
+    ShamirSecretSharing, Sharing,
+};
+use rand::Rng;
+use std::{cell::RefCell, collections::BTreeMap};
+
+/// Who may recover an entry: any `k` of its holders.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Access {
+    k: u8,
+    holders: Option<Vec<u8>>,
+}
+
+impl Access {
+    /// Any `k` of all holders.
+    pub fn threshold(k: u8) -> Self {
+        Self { k, holders: None }
+    }
+
+    /// Any `k` of the holders with these ids, the others get no piece of the entry.
+    pub fn among(k: u8, holders: Vec<u8>) -> Self {
+        Self {
+            k,
+            holders: Some(holders),
+        }
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// The ids of the holders out of `n` that get a piece of the entry.
+    pub fn holders(&self, n: u8) -> Vec<u8> {
+        match &self.holders {
+            Some(holders) => holders.clone(),
+            None => (1..=n).collect(),
+        }
+    }
+}
+
+/// A holder's pieces of every entry they have access to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VaultShare {
+    pub id: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64::map"))]
+    pub entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl ShareId for VaultShare {
+    fn id(&self) -> u8 {
+        self.id
+    }
+}
+
+/// # Vault Secret Sharing
+///
+/// Shamir Secret Sharing of every entry of a map, by default `k` of `n`.
+pub struct VaultSecretSharing<R: Rng> {
+    n: u8,
+    k: u8,
+    access: BTreeMap<String, Access>,
+    rng: RefCell<R>,
+}
+
+impl<R: Rng> VaultSecretSharing<R> {
+    pub fn new(n: u8, k: u8, rng: R) -> Self {
+        Self {
+            n,
+            k,
+            access: BTreeMap::new(),
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Overrides the default `k` of `n` for the entry with this name.
+    pub fn with_access<S: Into<String>>(mut self, name: S, access: Access) -> Self {
+        self.access.insert(name.into(), access);
+        self
+    }
+
+    /// The access to the entry with this name.
+    pub fn access(&self, name: &str) -> Access {
+        self.access
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| Access::threshold(self.k))
+    }
+
+    pub fn n(&self) -> u8 {
+        self.n
+    }
+
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// Shares every entry, one share per holder with ids `1..=n`.
+    ///
+    /// Fails with [`SharingError::MismatchedParameters`] if an entry's access cannot be met
+    /// or names a holder that does not exist.
+    pub fn share_vault(
+        &self,
+        vault: &BTreeMap<String, Vec<u8>>,
+    ) -> Result<Vec<VaultShare>, SharingError> {
+        let mut shares: Vec<VaultShare> = (1..=self.n)
+            .map(|id| VaultShare {
+                id,
+                entries: BTreeMap::new(),
+            })
+            .collect();
+        let mut rng = self.rng.borrow_mut();
+        for (name, secret) in vault {
+            let access = self.access(name);
+            let ids = access.holders(self.n);
+            if !valid_ids(&ids) || ids.iter().any(|id| *id > self.n) {
+                return Err(SharingError::MismatchedParameters);
+            }
+            let pieces = ShamirSecretSharing::new(ids.len() as u8, access.k, &mut *rng)
+                .share_with_ids(secret.clone(), &ids)?;
+            for piece in pieces {
+                shares[piece.id as usize - 1]
+                    .entries
+                    .insert(name.clone(), piece.body);
+            }
+        }
+        Ok(shares)
+    }
+
+    /// Recovers the entry with this name from the holders' pieces of it.
+    pub fn recover(&self, shares: &[VaultShare], name: &str) -> Result<Vec<u8>, SharingError> {
+        let access = self.access(name);
+        let mut pieces: Vec<ShamirShare> = Vec::with_capacity(access.k as usize);
+        for share in shares {
+            if pieces.len() == access.k as usize {
+                break;
+            }
+            if let Some(body) = share.entries.get(name) {
+                if pieces.iter().all(|piece| piece.id != share.id) {
+                    pieces.push(ShamirShare {
+                        id: share.id,
+                        body: body.clone(),
+                    });
+                }
+            }
+        }
+        let mut rng = self.rng.borrow_mut();
+        ShamirSecretSharing::new(self.n, access.k, &mut *rng).reconstruct(pieces)
+    }
+
+    /// Recovers every entry named in the shares, failing if any of them is out of reach.
+    pub fn recover_all(
+        &self,
+        shares: &[VaultShare],
+    ) -> Result<BTreeMap<String, Vec<u8>>, SharingError> {
+        let mut names: Vec<&String> = shares.iter().flat_map(|s| s.entries.keys()).collect();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| Ok((name.clone(), self.recover(shares, name)?)))
+            .collect()
+    }
+}
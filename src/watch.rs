@@ -0,0 +1,141 @@
+//! Watch and Reshare
+//!
+//! A [`Watcher`] keeps a file continuously protected: the file is split into fixed size chunks,
+//! every chunk is shared on its own with [`KrawczykSecretSharing`], and when the file changes
+//! only the chunks whose contents differ are shared again. Holders replace the shares of
+//! the chunks in an [`Update`] and drop chunks past [`Update::chunks`].
+//!
+//! Every chunk is shared under a fresh key, so re-encoding a chunk never reuses a nonce.
+//! Changes are detected by comparing the SHA-256 of every chunk, so each poll reads the whole file.
+//!
+//! ```rust
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! use sharing::{watch::{self, Watcher}, KrawczykSecretSharing};
+//!
+//! let path = std::env::temp_dir().join(format!("sharing-watch-doc-{}", std::process::id()));
+//! std::fs::write(&path, vec![1u8; 10_000]).unwrap();
+//! let sharer = || KrawczykSecretSharing::<ChaCha20Poly1305, _>::new(3, 2, rand::thread_rng());
+//! let mut watcher = Watcher::new(sharer(), &path, 4096);
+//!
+//! // the first poll shares every chunk
+//! let update = watcher.poll().unwrap().unwrap();
+//! assert_eq!(update.changed.len(), 3);
+//! let mut held: Vec<_> = update.changed.into_iter().map(|(_, shares)| shares).collect();
+//!
+//! // unchanged files produce no update
+//! assert!(watcher.poll().unwrap().is_none());
+//!
+//! let mut data = vec![1u8; 10_000];
+//! data[5000] = 2;
+//! std::fs::write(&path, &data).unwrap();
+//! let update = watcher.poll().unwrap().unwrap();
+//! assert_eq!(update.changed.len(), 1);
+//! for (index, shares) in update.changed {
+//!     held[index as usize] = shares;
+//! }
+//!
+//! let chunks = held.into_iter().map(|shares| shares[1..].to_vec()).collect();
+//! assert_eq!(watch::reconstruct(&sharer(), chunks).unwrap(), data);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+use crate::{share::KrawczykShare, KrawczykSecretSharing, Sharing};
+use aead::{AeadInPlace, KeyInit};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::{
+    convert::TryFrom,
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+/// The chunks that changed since the previous poll.
+#[derive(Debug, Clone)]
+pub struct Update {
+    /// The number of chunks the file now has, holders drop chunks at or past this index.
+    pub chunks: u32,
+    /// The index and new shares of every changed or added chunk.
+    pub changed: Vec<(u32, Vec<KrawczykShare>)>,
+}
+
+/// Polls a file and shares the chunks that changed.
+pub struct Watcher<C: AeadInPlace + KeyInit, R: Rng> {
+    sharer: KrawczykSecretSharing<C, R>,
+    path: PathBuf,
+    chunk_size: usize,
+    digests: Option<Vec<[u8; 32]>>,
+}
+
+impl<C: AeadInPlace + KeyInit, R: Rng> Watcher<C, R> {
+    /// Watches `path` in chunks of `chunk_size` bytes, a `chunk_size` of zero is treated as one.
+    pub fn new(sharer: KrawczykSecretSharing<C, R>, path: &Path, chunk_size: usize) -> Self {
+        Self {
+            sharer,
+            path: path.to_path_buf(),
+            chunk_size: chunk_size.max(1),
+            digests: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the file and shares the chunks that differ from the previous poll,
+    /// all of them on the first poll. `None` if nothing changed.
+    pub fn poll(&mut self) -> io::Result<Option<Update>> {
+        let data = fs::read(&self.path)?;
+        let digests: Vec<[u8; 32]> = data
+            .chunks(self.chunk_size)
+            .map(|chunk| Sha256::digest(chunk).into())
+            .collect();
+        let previous = self.digests.as_deref();
+        if previous == Some(&digests[..]) {
+            return Ok(None);
+        }
+        let chunks = u32::try_from(digests.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "too many chunks"))?;
+        let mut changed = Vec::new();
+        for (index, (chunk, digest)) in data.chunks(self.chunk_size).zip(&digests).enumerate() {
+            if previous.and_then(|p| p.get(index)) == Some(digest) {
+                continue;
+            }
+            let shares = self
+                .sharer
+                .share(chunk.to_vec())
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+            changed.push((index as u32, shares));
+        }
+        self.digests = Some(digests);
+        Ok(Some(Update { chunks, changed }))
+    }
+
+    /// Polls every `interval` and passes updates to `on_update` until it returns `false`.
+    pub fn watch<F: FnMut(Update) -> bool>(
+        &mut self,
+        interval: Duration,
+        mut on_update: F,
+    ) -> io::Result<()> {
+        loop {
+            if let Some(update) = self.poll()? {
+                if !on_update(update) {
+                    return Ok(());
+                }
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// Reconstructs a watched file from `k` shares of every chunk, in chunk order.
+pub fn reconstruct<C: AeadInPlace + KeyInit, R: Rng>(
+    sharer: &KrawczykSecretSharing<C, R>,
+    chunks: Vec<Vec<KrawczykShare>>,
+) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    for shares in chunks {
+        data.extend(sharer.reconstruct(shares).ok()?);
+    }
+    Some(data)
+}
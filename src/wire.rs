@@ -0,0 +1,329 @@
+//! Wire Format
+//!
+//! [`ShareBytes`] encodes a bare share. A [`WireShare`] also records the threshold and
+//! starts with a magic byte, so a share stored today says what it is and how many
+//! shares it takes, and is still read by later versions of this crate:
+//!
+//! ```text
+//! magic (0xA5) || version || scheme || k || id || payload length (u64) || payload
+//!     || metadata || CRC-32C
+//! ```
+//!
+//! The scheme is `S`, `R` or `K`, the payload is the [`ShareBytes`] encoding of the share
+//! after its id, integers are big endian. The [`Metadata`] is the label, creation time and
+//! comment, each as its length (u32) and UTF-8, or `0xffffffff` if absent. The CRC-32C covers
+//! everything before it, so a share damaged in storage fails to parse with
+//! [`SharingError::CorruptShare`] instead of reconstructing a wrong secret.
+//!
+//! Earlier versions are still read: version 1 ends after the payload, version 2 after the
+//! metadata, and [`migrate`](crate::migrate) rewrites a share of any scheme in the current
+//! version. Use [`header`] to inspect a share without decoding it,
+//! and [`reconstruct`](crate::reconstruct) to reconstruct shares without knowing `n` and `k`.
+//! A [`WireShare`] converts from and to the wire format with `TryFrom<&[u8]>` and
+//! `Into<Vec<u8>>`, the bare share types with the same traits use their [`ShareBytes`]
+//! encoding, which a wire format decoder rejects.
+//!
+//! ```rust
+//! use sharing::{share::ShareBytes, wire::{self, WireShare}, RabinShare, Scheme, SharingError};
+//! use std::convert::TryFrom;
+//!
+//! let share = RabinShare { id: 2, length: 5, body: vec![1, 2] };
+//! let bytes = WireShare::new(3, share.clone()).to_bytes();
+//!
+//! let header = wire::header(&bytes).unwrap();
+//! assert_eq!((header.scheme, header.k, header.id), (Scheme::Rabin, 3, 2));
+//!
+//! let decoded = WireShare::<RabinShare>::decode(&bytes).unwrap();
+//! assert_eq!((decoded.k, decoded.share), (3, share));
+//! assert!(WireShare::<RabinShare>::decode(&bytes[..bytes.len() - 1]).is_err());
+//!
+//! let decoded = WireShare::<RabinShare>::try_from(bytes.as_slice()).unwrap();
+//! assert_eq!(Vec::from(decoded), bytes);
+//! // the bare share converts through its own encoding
+//! assert!(RabinShare::try_from(bytes.as_slice()).is_err());
+//!
+//! let mut flipped = bytes.clone();
+//! flipped[14] ^= 0x10;
+//! assert!(matches!(wire::header(&flipped), Err(SharingError::CorruptShare(2))));
+//! ```
+use crate::{
+    error::SharingError,
+    metadata::Metadata,
+    share::{
+        crc32c, decode_error, parse_hex, write_hex, KrawczykShare, ParseShareError, RabinShare,
+        ShamirShare, ShareBytes, ShareId, BYTES_VERSION,
+    },
+    Scheme,
+};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// The first byte of every share in the wire format.
+pub const MAGIC: u8 = 0xA5;
+
+/// The wire format version written by this build.
+pub const WIRE_VERSION: u8 = 3;
+
+/// The first version, without metadata and checksum.
+const PAYLOAD_VERSION: u8 = 1;
+
+const CRC_LEN: usize = 4;
+
+/// magic, version, scheme, k, id and the payload length
+const HEADER: usize = 13;
+
+/// Share types with a wire encoding.
+pub trait WireScheme: ShareBytes + ShareId {
+    const SCHEME: Scheme;
+}
+
+impl WireScheme for ShamirShare {
+    const SCHEME: Scheme = Scheme::Shamir;
+}
+
+impl WireScheme for RabinShare {
+    const SCHEME: Scheme = Scheme::Rabin;
+}
+
+impl WireScheme for KrawczykShare {
+    const SCHEME: Scheme = Scheme::Krawczyk;
+}
+
+fn tag(scheme: Scheme) -> u8 {
+    match scheme {
+        Scheme::Shamir => b'S',
+        Scheme::Rabin => b'R',
+        Scheme::Krawczyk => b'K',
+    }
+}
+
+fn scheme(tag: u8) -> Option<Scheme> {
+    match tag {
+        b'S' => Some(Scheme::Shamir),
+        b'R' => Some(Scheme::Rabin),
+        b'K' => Some(Scheme::Krawczyk),
+        _ => None,
+    }
+}
+
+/// The encoding of a share after its id.
+pub(crate) fn payload<S: WireScheme>(share: &S) -> Vec<u8> {
+    // the bare encoding is `version || scheme || id || payload`
+    share.to_bytes().get(3..).unwrap_or_default().to_vec()
+}
+
+pub(crate) fn from_payload<S: WireScheme>(id: u8, payload: &[u8]) -> Result<S, SharingError> {
+    let mut inner = Vec::with_capacity(3 + payload.len());
+    inner.extend_from_slice(&[BYTES_VERSION, tag(S::SCHEME), id]);
+    inner.extend_from_slice(payload);
+    S::from_bytes(&inner).ok_or_else(|| decode_error(&inner))
+}
+
+/// The fixed fields at the start of a share in the wire format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Header {
+    pub version: u8,
+    pub scheme: Scheme,
+    pub k: u8,
+    pub id: u8,
+    /// The length of the payload following the header.
+    pub length: usize,
+}
+
+/// Reads the header of a share in the wire format and checks that the payload is complete
+/// and the checksum matches.
+pub fn header(bytes: &[u8]) -> Result<Header, SharingError> {
+    const MALFORMED: SharingError = SharingError::Malformed("wire share");
+    match bytes {
+        [MAGIC, version @ PAYLOAD_VERSION..=WIRE_VERSION, scheme_tag, k, id, rest @ ..]
+            if rest.len() >= 8 =>
+        {
+            let rest = match *version {
+                WIRE_VERSION => {
+                    let (covered, crc) = bytes.split_at(bytes.len() - CRC_LEN);
+                    if covered.len() < HEADER {
+                        return Err(MALFORMED);
+                    }
+                    if crc != crc32c(covered).to_be_bytes() {
+                        return Err(SharingError::CorruptShare(*id));
+                    }
+                    &covered[5..]
+                }
+                _ => rest,
+            };
+            let mut length = [0u8; 8];
+            length.copy_from_slice(&rest[..8]);
+            let length = usize::try_from(u64::from_be_bytes(length)).map_err(|_| MALFORMED)?;
+            let complete = match *version {
+                PAYLOAD_VERSION => length == rest.len() - 8,
+                _ => length <= rest.len() - 8,
+            };
+            if !complete || *k == 0 {
+                return Err(MALFORMED);
+            }
+            Ok(Header {
+                version: *version,
+                scheme: scheme(*scheme_tag).ok_or(MALFORMED)?,
+                k: *k,
+                id: *id,
+                length,
+            })
+        }
+        [MAGIC, version, ..] if *version > WIRE_VERSION => {
+            Err(SharingError::UnsupportedVersion(*version))
+        }
+        _ => Err(MALFORMED),
+    }
+}
+
+/// A share together with the threshold it was dealt with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WireShare<S> {
+    pub k: u8,
+    pub share: S,
+    /// Carried along, but not part of the share.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub metadata: Metadata,
+}
+
+impl<S: WireScheme> WireShare<S> {
+    pub fn new(k: u8, share: S) -> Self {
+        Self {
+            k,
+            share,
+            metadata: Metadata::default(),
+        }
+    }
+
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        S::SCHEME
+    }
+
+    /// Like [`from_bytes`](ShareBytes::from_bytes), but tells why the bytes did not decode.
+    /// A share of another scheme fails with [`SharingError::MismatchedParameters`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, SharingError> {
+        let header = header(bytes)?;
+        if header.scheme != S::SCHEME {
+            return Err(SharingError::MismatchedParameters);
+        }
+        let end = match header.version {
+            WIRE_VERSION => bytes.len() - CRC_LEN,
+            _ => bytes.len(),
+        };
+        let (payload, trailer) = bytes[HEADER..end].split_at(header.length);
+        let metadata = match header.version {
+            PAYLOAD_VERSION => Metadata::default(),
+            _ => Metadata::from_bytes(trailer)
+                .ok_or(SharingError::Malformed("wire share metadata"))?,
+        };
+        Ok(Self {
+            k: header.k,
+            share: from_payload(header.id, payload)?,
+            metadata,
+        })
+    }
+}
+
+impl<S: WireScheme> ShareBytes for WireShare<S> {
+    fn to_bytes(&self) -> Vec<u8> {
+        let payload = payload(&self.share);
+        let metadata = self.metadata.to_bytes();
+        let mut out = Vec::with_capacity(HEADER + payload.len() + metadata.len() + CRC_LEN);
+        out.extend_from_slice(&[MAGIC, WIRE_VERSION, tag(S::SCHEME), self.k, self.share.id()]);
+        out.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        out.extend_from_slice(&payload);
+        out.extend_from_slice(&metadata);
+        let crc = crc32c(&out);
+        out.extend_from_slice(&crc.to_be_bytes());
+        out
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::decode(bytes).ok()
+    }
+}
+
+impl<S: WireScheme> fmt::Display for WireShare<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.to_bytes())
+    }
+}
+
+/// Decodes the wire format like [`WireShare::decode`].
+impl<S: WireScheme> TryFrom<&[u8]> for WireShare<S> {
+    type Error = SharingError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::decode(bytes)
+    }
+}
+
+/// Encodes in the current version of the wire format.
+impl<S: WireScheme> From<WireShare<S>> for Vec<u8> {
+    fn from(share: WireShare<S>) -> Self {
+        share.to_bytes()
+    }
+}
+
+impl<S: WireScheme> FromStr for WireShare<S> {
+    type Err = ParseShareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::decode(&parse_hex(s)?).map_err(ParseShareError::Decode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn earlier_versions() {
+        let share = ShamirShare {
+            id: 3,
+            body: vec![0xca, 0xfe],
+        };
+        let mut v1 = vec![
+            MAGIC,
+            PAYLOAD_VERSION,
+            b'S',
+            2,
+            3,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            2,
+            0xca,
+            0xfe,
+        ];
+        let decoded = WireShare::<ShamirShare>::decode(&v1).unwrap();
+        assert_eq!((decoded.k, &decoded.share), (2, &share));
+
+        // version 2 added the metadata
+        v1[1] = 2;
+        let metadata = Metadata::new().with_label("vault");
+        let v2 = [v1, metadata.to_bytes()].concat();
+        let decoded = WireShare::<ShamirShare>::decode(&v2).unwrap();
+        assert_eq!(decoded, WireShare::new(2, share).with_metadata(metadata));
+
+        let v3 = decoded.to_bytes();
+        assert_eq!(
+            v3[..v2.len()],
+            [&[MAGIC, WIRE_VERSION], &v2[2..]].concat()[..]
+        );
+        for i in 0..v3.len() {
+            let mut flipped = v3.clone();
+            flipped[i] ^= 1;
+            assert!(WireShare::<ShamirShare>::decode(&flipped).is_err());
+        }
+    }
+}
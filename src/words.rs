@@ -0,0 +1,88 @@
+//! Word Secrets
+//!
+//! Shares secrets given as `u16`, `u32` or `u64` words, e.g. numeric identifiers or
+//! counters, with any scheme. Words are encoded big endian, so every word maps to a fixed
+//! number of bytes and reconstruction gives back exactly the words that were shared.
+//!
+//! All schemes work in GF(256), so a word is shared as its bytes; words will map one-to-one
+//! onto field elements once wider fields are available.
+//!
+//! ```rust
+//! use sharing::{words, ShamirSecretSharing};
+//!
+//! let sharer = ShamirSecretSharing::new(3, 2, rand::thread_rng());
+//! let secret: Vec<u64> = vec![u64::MAX, 42, 1 << 40];
+//!
+//! let shares = words::share(&sharer, secret.iter().copied()).unwrap();
+//! assert_eq!(shares[0].body.len(), 24);
+//! let rec: Vec<u64> = words::reconstruct(&sharer, shares[1..].to_vec()).unwrap();
+//! assert_eq!(rec, secret);
+//!
+//! // the share length must be a whole number of words
+//! let odd = words::share(&sharer, vec![1u8]).unwrap();
+//! assert!(words::reconstruct::<_, u16>(&sharer, odd).is_err());
+//! ```
+use crate::{Sharing, SharingError};
+use std::convert::TryInto;
+
+/// An unsigned integer that can be shared as a secret word.
+pub trait Word: Copy {
+    /// The size of the word in bytes.
+    const BYTES: usize;
+
+    fn write(self, out: &mut Vec<u8>);
+
+    /// Reads a word from exactly [`BYTES`](Self::BYTES) bytes, `None` for any other length.
+    fn read(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! word {
+    ($($t:ty),*) => {$(
+        impl Word for $t {
+            const BYTES: usize = std::mem::size_of::<$t>();
+
+            fn write(self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+
+            fn read(bytes: &[u8]) -> Option<Self> {
+                bytes.try_into().ok().map(<$t>::from_be_bytes)
+            }
+        }
+    )*};
+}
+
+word!(u8, u16, u32, u64);
+
+/// Encodes words as big endian bytes.
+pub fn to_bytes<W: Word, I: IntoIterator<Item = W>>(words: I) -> Vec<u8> {
+    let mut out = Vec::new();
+    for word in words {
+        word.write(&mut out);
+    }
+    out
+}
+
+/// Decodes big endian words, `None` if the length is not a multiple of the word size.
+pub fn from_bytes<W: Word>(bytes: &[u8]) -> Option<Vec<W>> {
+    if bytes.len() % W::BYTES != 0 {
+        return None;
+    }
+    bytes.chunks(W::BYTES).map(W::read).collect()
+}
+
+/// Shares a secret made of words.
+pub fn share<T: Sharing, W: Word, I: IntoIterator<Item = W>>(
+    sharer: &T,
+    words: I,
+) -> Result<Vec<T::Share>, SharingError> {
+    sharer.share(to_bytes(words))
+}
+
+/// Reconstructs a secret made of words.
+pub fn reconstruct<T: Sharing, W: Word>(
+    sharer: &T,
+    shares: Vec<T::Share>,
+) -> Result<Vec<W>, SharingError> {
+    from_bytes(&sharer.reconstruct(shares)?).ok_or(SharingError::Malformed("words"))
+}